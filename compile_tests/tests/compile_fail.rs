@@ -0,0 +1,14 @@
+//! Checks that misuse of the `gba_save` API is rejected at compile time.
+//!
+//! These tests run on the host rather than on the GBA. They must be run from outside of the
+//! repository, with `CARGO_TARGET_DIR` also outside of it, so that the GBA target configured in
+//! `.cargo/config.toml` is not used:
+//!
+//! ``` sh
+//! cd /tmp && CARGO_TARGET_DIR=/tmp/target cargo test --manifest-path <repo>/compile_tests/Cargo.toml
+//! ```
+
+#[test]
+fn compile_fail() {
+    trybuild::TestCases::new().compile_fail("tests/compile_fail/*.rs");
+}
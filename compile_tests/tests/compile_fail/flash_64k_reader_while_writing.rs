@@ -0,0 +1,11 @@
+use gba_save::flash::Flash;
+
+fn main() {
+    let Flash::Flash64K(mut flash_64k) = unsafe { Flash::new() }.unwrap() else {
+        panic!("unsupported flash type");
+    };
+    let reader = flash_64k.reader(..);
+    let writer = flash_64k.writer(..);
+    drop(reader);
+    drop(writer);
+}
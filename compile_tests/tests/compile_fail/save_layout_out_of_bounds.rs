@@ -0,0 +1,9 @@
+use gba_save::{range::SramRegion, save_layout};
+
+save_layout! {
+    SramRegion {
+        WORLD: 1024..32769,
+    }
+}
+
+fn main() {}
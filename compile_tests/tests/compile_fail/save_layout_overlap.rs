@@ -0,0 +1,10 @@
+use gba_save::{range::SramRegion, save_layout};
+
+save_layout! {
+    SramRegion {
+        SETTINGS: 0..64,
+        INVENTORY: 63..1024,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use gba_save::sram::Sram;
+
+fn main() {
+    let sram = unsafe { Sram::new() };
+    let reader = sram.reader(..);
+    drop(sram);
+    drop(reader);
+}
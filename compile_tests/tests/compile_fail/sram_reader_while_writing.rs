@@ -0,0 +1,9 @@
+use gba_save::sram::Sram;
+
+fn main() {
+    let mut sram = unsafe { Sram::new() };
+    let reader = sram.reader(..);
+    let writer = sram.writer(..);
+    drop(reader);
+    drop(writer);
+}
@@ -0,0 +1,128 @@
+//! A link-time check that reading and writing backup memory cannot panic.
+//!
+//! The panic handler calls a function that is never defined. If every panic is optimized out of
+//! the functions called from `main()`, the handler is never referenced and the example links.
+//! Otherwise, linking fails with an undefined reference to `panic_is_reachable`.
+//!
+//! This is run in CI. To run it locally, build it in release mode, without a linker script:
+//!
+//! ``` sh
+//! RUSTFLAGS="-Clinker=arm-none-eabi-ld -Clink-arg=--entry=main -Ztrap-unreachable=no" \
+//!     cargo build --release --example panic_audit --features panic-audit
+//! ```
+//!
+//! Every function called here is documented as not panicking. Calls made through `black_box()`
+//! prevent the optimizer from removing panics based on known inputs.
+
+#![no_std]
+#![no_main]
+
+use core::{hint::black_box, panic::PanicInfo};
+use embedded_io::{Read, Write};
+use gba_save::{flash::Flash, sram::Sram};
+
+extern "C" {
+    /// Never defined, so that linking fails if a panic is reachable.
+    fn panic_is_reachable() -> !;
+}
+
+#[panic_handler]
+fn panic(_: &PanicInfo) -> ! {
+    unsafe { panic_is_reachable() }
+}
+
+/// An allocator that always fails.
+///
+/// `agb_save` depends on `alloc`, which requires a global allocator to be defined. Nothing here
+/// allocates.
+#[cfg(feature = "agb")]
+struct NoAllocator;
+
+#[cfg(feature = "agb")]
+unsafe impl core::alloc::GlobalAlloc for NoAllocator {
+    unsafe fn alloc(&self, _layout: core::alloc::Layout) -> *mut u8 {
+        core::ptr::null_mut()
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: core::alloc::Layout) {}
+}
+
+#[cfg(feature = "agb")]
+#[global_allocator]
+static ALLOCATOR: NoAllocator = NoAllocator;
+
+/// Reads through `reader` using every reading method.
+macro_rules! read {
+    ($reader:expr, $buf:expr) => {{
+        let mut reader = $reader;
+        let _ = reader.read(black_box(&mut $buf[..]));
+        let _ = reader.read_exact_into(black_box(&mut $buf[..]));
+    }};
+}
+
+/// Writes through `writer` using every writing method.
+macro_rules! write {
+    ($writer:expr, $buf:expr) => {{
+        let mut writer = $writer;
+        let _ = writer.write(black_box(&$buf[..]));
+        let _ = writer.write_all_strict(black_box(&$buf[..]));
+        let _ = writer.commit(black_box(&$buf[..]));
+        let _ = writer.write_verified(black_box(&$buf[..]));
+        let _ = writer.flush();
+    }};
+}
+
+/// The entry point. This is only linked, never run.
+#[no_mangle]
+pub extern "C" fn main() {
+    let mut buf = [0; 256];
+
+    let mut sram = unsafe { Sram::new() };
+    read!(sram.reader(..), buf);
+    if let Ok(reader) = sram.try_reader(black_box(0..256)) {
+        read!(reader, buf);
+    }
+    write!(sram.writer(..), buf);
+    if let Ok(writer) = sram.try_writer(black_box(0..256)) {
+        write!(writer, buf);
+    }
+
+    if let Ok(mut flash) = unsafe { Flash::new() } {
+        match &mut flash {
+            Flash::Flash64K(flash_64k) => {
+                let _ = flash_64k.erase_sectors(..);
+                read!(flash_64k.reader(..), buf);
+                if let Ok(reader) = flash_64k.try_reader(black_box(0..256)) {
+                    read!(reader, buf);
+                }
+                write!(flash_64k.writer(..), buf);
+                if let Ok(writer) = flash_64k.try_writer(black_box(0..256)) {
+                    write!(writer, buf);
+                }
+            }
+            Flash::Flash64KAtmel(flash_64k_atmel) => {
+                let _ = flash_64k_atmel.erase_chip();
+                read!(flash_64k_atmel.reader(..), buf);
+                if let Ok(reader) = flash_64k_atmel.try_reader(black_box(0..256)) {
+                    read!(reader, buf);
+                }
+                write!(flash_64k_atmel.writer(..), buf);
+                if let Ok(writer) = flash_64k_atmel.try_writer(black_box(0..256)) {
+                    write!(writer, buf);
+                }
+            }
+            Flash::Flash128K(flash_128k) => {
+                let _ = flash_128k.erase_sectors(..);
+                read!(flash_128k.reader(..), buf);
+                if let Ok(reader) = flash_128k.try_reader(black_box(0..256)) {
+                    read!(reader, buf);
+                }
+                write!(flash_128k.writer(..), buf);
+                if let Ok(writer) = flash_128k.try_writer(black_box(0..256)) {
+                    write!(writer, buf);
+                }
+            }
+        }
+        let _ = flash.reset();
+    }
+}
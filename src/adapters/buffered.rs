@@ -0,0 +1,180 @@
+use core::ops;
+use embedded_io::{Read, ReadExactError, Write};
+
+/// A region of backup memory loaded into RAM.
+///
+/// Parsing libraries often require their input as a `&[u8]`, which backup memory cannot provide
+/// directly. This type reads a region of up to `N` bytes into an internal buffer using any
+/// reader, exposes it as a slice, and tracks which bytes have been modified so that only the
+/// modified part of the region needs to be written back.
+///
+/// # Example
+/// ``` no_run
+/// use gba_save::{adapters::BufferedRegion, sram::Sram};
+/// use gba_save::deranged::RangedUsize;
+///
+/// let mut sram = unsafe { Sram::new() };
+/// let range = RangedUsize::new_static::<0>()..RangedUsize::new_static::<64>();
+/// let mut region = BufferedRegion::<64>::load(&mut sram.reader(range.clone()))
+///     .expect("could not load region");
+///
+/// // Parse `region.as_slice()`, then modify part of it.
+/// region.slice_mut(8..12).copy_from_slice(&[1, 2, 3, 4]);
+///
+/// region
+///     .write_back(&mut sram.writer(range))
+///     .expect("could not save region");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BufferedRegion<const N: usize> {
+    buf: [u8; N],
+    dirty: Option<ops::Range<usize>>,
+}
+
+impl<const N: usize> BufferedRegion<N> {
+    /// Reads `N` bytes from `reader` into a new buffered region.
+    pub fn load<R>(reader: &mut R) -> Result<Self, ReadExactError<R::Error>>
+    where
+        R: Read,
+    {
+        let mut buf = [0; N];
+        reader.read_exact(&mut buf)?;
+        Ok(Self { buf, dirty: None })
+    }
+
+    /// Returns the buffered data.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns the buffered data for modification, marking all of it as modified.
+    ///
+    /// To mark only part of the data as modified, use [`slice_mut()`](Self::slice_mut()).
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        self.slice_mut(0..N)
+    }
+
+    /// Returns part of the buffered data for modification, marking only that part as modified.
+    ///
+    /// # Panics
+    /// Panics if `range` is not within the buffer.
+    pub fn slice_mut(&mut self, range: ops::Range<usize>) -> &mut [u8] {
+        let slice = &mut self.buf[range.clone()];
+        if !range.is_empty() {
+            self.dirty = Some(match self.dirty.take() {
+                Some(dirty) => dirty.start.min(range.start)..dirty.end.max(range.end),
+                None => range,
+            });
+        }
+        slice
+    }
+
+    /// Returns the range of bytes that have been modified since loading or the last write-back.
+    ///
+    /// If several parts have been modified, this is the smallest range containing all of them.
+    pub fn dirty_range(&self) -> Option<ops::Range<usize>> {
+        self.dirty.clone()
+    }
+
+    /// Returns whether any bytes have been modified since loading or the last write-back.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_some()
+    }
+
+    /// Writes modified data back through `writer`, then flushes it.
+    ///
+    /// `writer` must cover the region the data was loaded from. Nothing is written if no bytes
+    /// have been modified. Otherwise, data is written up to the end of the
+    /// [dirty range](Self::dirty_range()); unmodified bytes before it are rewritten with their
+    /// existing values, which flash writers skip without reprogramming. Bytes after the dirty
+    /// range are not written.
+    pub fn write_back<W>(&mut self, writer: &mut W) -> Result<(), W::Error>
+    where
+        W: Write,
+    {
+        let Some(dirty) = self.dirty.clone() else {
+            return Ok(());
+        };
+        writer.write_all(&self.buf[..dirty.end])?;
+        writer.flush()?;
+        self.dirty = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BufferedRegion;
+    use claims::{assert_err_eq, assert_none, assert_ok, assert_some_eq};
+    use embedded_io::ReadExactError;
+    use gba_test::test;
+
+    #[test]
+    fn load() {
+        let region = assert_ok!(BufferedRegion::<4>::load(&mut &[1, 2, 3, 4, 5][..]));
+
+        assert_eq!(region.as_slice(), [1, 2, 3, 4]);
+        assert!(!region.is_dirty());
+    }
+
+    #[test]
+    fn load_unexpected_eof() {
+        assert_err_eq!(
+            BufferedRegion::<4>::load(&mut &[1, 2][..]),
+            ReadExactError::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn as_mut_slice_marks_all_dirty() {
+        let mut region = assert_ok!(BufferedRegion::<4>::load(&mut &[1, 2, 3, 4][..]));
+        region.as_mut_slice()[1] = 42;
+
+        assert_eq!(region.as_slice(), [1, 42, 3, 4]);
+        assert_some_eq!(region.dirty_range(), 0..4);
+    }
+
+    #[test]
+    fn slice_mut_merges_dirty_ranges() {
+        let mut region = assert_ok!(BufferedRegion::<8>::load(&mut &[0; 8][..]));
+        region.slice_mut(5..6)[0] = 1;
+        region.slice_mut(2..3)[0] = 1;
+
+        assert_some_eq!(region.dirty_range(), 2..6);
+    }
+
+    #[test]
+    fn slice_mut_empty_is_not_dirty() {
+        let mut region = assert_ok!(BufferedRegion::<8>::load(&mut &[0; 8][..]));
+        region.slice_mut(3..3);
+
+        assert_none!(region.dirty_range());
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 9 out of range for slice of length 8")]
+    fn slice_mut_out_of_bounds() {
+        let mut region = assert_ok!(BufferedRegion::<8>::load(&mut &[0; 8][..]));
+        region.slice_mut(4..9);
+    }
+
+    #[test]
+    fn write_back_clean() {
+        let mut region = assert_ok!(BufferedRegion::<4>::load(&mut &[1, 2, 3, 4][..]));
+        let mut stored = [0; 4];
+
+        assert_ok!(region.write_back(&mut &mut stored[..]));
+        assert_eq!(stored, [0; 4]);
+    }
+
+    #[test]
+    fn write_back_through_dirty_end() {
+        let mut region = assert_ok!(BufferedRegion::<8>::load(&mut &[1; 8][..]));
+        region.slice_mut(2..4).copy_from_slice(&[5, 6]);
+        let mut stored = [0; 8];
+
+        assert_ok!(region.write_back(&mut &mut stored[..]));
+        assert_eq!(stored, [1, 1, 5, 6, 0, 0, 0, 0]);
+        assert!(!region.is_dirty());
+    }
+}
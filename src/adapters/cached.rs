@@ -0,0 +1,254 @@
+use core::num::NonZeroU32;
+use embedded_io::{Read, ReadExactError, Write};
+
+/// A frequently updated value kept in RAM and only written to backup memory when requested.
+///
+/// Values such as step counters and play time change far more often than they should be written
+/// to backup memory, especially flash, which wears out with every erase. This type holds the
+/// current value as `N` bytes in RAM and tracks whether it has changed since it was last
+/// persisted. [`persist()`](Self::persist()) writes it only if it has, so any number of calls to
+/// [`set()`](Self::set()) cost at most one write.
+///
+/// The value is written using whichever writer is given when persisting. On flash, the writer
+/// must cover erased memory, so persisting repeatedly should write to successive locations, such
+/// as alternating slots identified by a [`SeqNum`](crate::seq::SeqNum), rather than erasing the
+/// same sector each time.
+///
+/// # Example
+/// ``` no_run
+/// use core::num::NonZeroU32;
+/// use gba_save::{adapters::Cached, deranged::RangedUsize, sram::Sram};
+///
+/// let mut sram = unsafe { Sram::new() };
+/// let range = RangedUsize::new_static::<0>()..RangedUsize::new_static::<4>();
+/// let mut steps = Cached::<4>::load(&mut sram.reader(range.clone())).expect("could not load");
+///
+/// // Each frame:
+/// let count = u32::from_le_bytes(*steps.get()) + 1;
+/// steps.set(count.to_le_bytes());
+/// steps
+///     .persist_every(NonZeroU32::new(300).unwrap(), || sram.writer(range.clone()))
+///     .expect("could not save");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cached<const N: usize> {
+    value: [u8; N],
+    dirty: bool,
+    frames: u32,
+}
+
+impl<const N: usize> Cached<N> {
+    /// Creates a cached value that has not yet been persisted.
+    ///
+    /// The value is considered modified, so the next call to [`persist()`](Self::persist())
+    /// writes it.
+    pub fn new(value: [u8; N]) -> Self {
+        Self {
+            value,
+            dirty: true,
+            frames: 0,
+        }
+    }
+
+    /// Reads `N` bytes from `reader` as the current value.
+    pub fn load<R>(reader: &mut R) -> Result<Self, ReadExactError<R::Error>>
+    where
+        R: Read,
+    {
+        let mut value = [0; N];
+        reader.read_exact(&mut value)?;
+        Ok(Self {
+            value,
+            dirty: false,
+            frames: 0,
+        })
+    }
+
+    /// Returns the current value.
+    pub fn get(&self) -> &[u8; N] {
+        &self.value
+    }
+
+    /// Sets the current value.
+    ///
+    /// The value is only marked as modified if it differs from the current value.
+    pub fn set(&mut self, value: [u8; N]) {
+        if value != self.value {
+            self.value = value;
+            self.dirty = true;
+        }
+    }
+
+    /// Returns whether the value has been modified since it was loaded or last persisted.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Writes the value through `writer` and flushes it, if the value has been modified.
+    ///
+    /// Returns whether anything was written. If writing fails, the value remains modified, so the
+    /// next call tries again.
+    pub fn persist<W>(&mut self, writer: &mut W) -> Result<bool, W::Error>
+    where
+        W: Write,
+    {
+        if !self.dirty {
+            return Ok(false);
+        }
+        writer.write_all(&self.value)?;
+        writer.flush()?;
+        self.dirty = false;
+        Ok(true)
+    }
+
+    /// Persists the value once every `frames` calls.
+    ///
+    /// This is intended to be called unconditionally once per frame. On every `frames`th call,
+    /// the writer is created by calling `writer` and the value is
+    /// [persisted](Self::persist()) through it. Otherwise, nothing is done. The writer is only
+    /// created when it is needed, so no device is accessed on other frames.
+    ///
+    /// Returns whether anything was written.
+    pub fn persist_every<W, F>(&mut self, frames: NonZeroU32, writer: F) -> Result<bool, W::Error>
+    where
+        W: Write,
+        F: FnOnce() -> W,
+    {
+        self.frames += 1;
+        if self.frames < frames.get() {
+            return Ok(false);
+        }
+        self.frames = 0;
+        if !self.dirty {
+            return Ok(false);
+        }
+        self.persist(&mut writer())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cached;
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use core::{convert::Infallible, num::NonZeroU32};
+    use embedded_io::{ErrorType, ReadExactError, SliceWriteError, Write};
+    use gba_test::test;
+
+    /// A writer that counts the calls made to it.
+    #[derive(Default)]
+    struct CountingWriter {
+        data: [u8; 4],
+        writes: usize,
+        flushes: usize,
+    }
+
+    impl ErrorType for CountingWriter {
+        type Error = Infallible;
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.writes += 1;
+            self.data.copy_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn load() {
+        let cached = assert_ok!(Cached::<4>::load(&mut &[1, 2, 3, 4, 5][..]));
+
+        assert_eq!(cached.get(), &[1, 2, 3, 4]);
+        assert!(!cached.is_dirty());
+    }
+
+    #[test]
+    fn load_unexpected_eof() {
+        assert_err_eq!(
+            Cached::<4>::load(&mut &[1, 2][..]),
+            ReadExactError::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn new_is_dirty() {
+        assert!(Cached::new([0; 4]).is_dirty());
+    }
+
+    #[test]
+    fn set_same_value_is_not_dirty() {
+        let mut cached = assert_ok!(Cached::<4>::load(&mut &[1, 2, 3, 4][..]));
+        cached.set([1, 2, 3, 4]);
+
+        assert!(!cached.is_dirty());
+    }
+
+    #[test]
+    fn many_sets_one_write() {
+        let mut cached = assert_ok!(Cached::<4>::load(&mut &[0; 4][..]));
+        let mut writer = CountingWriter::default();
+
+        for count in 1..=100u32 {
+            cached.set(count.to_le_bytes());
+        }
+
+        assert_ok_eq!(cached.persist(&mut writer), true);
+        assert_eq!(writer.writes, 1);
+        assert_eq!(writer.flushes, 1);
+        assert_eq!(writer.data, 100u32.to_le_bytes());
+        assert!(!cached.is_dirty());
+    }
+
+    #[test]
+    fn persist_clean_does_not_write() {
+        let mut cached = assert_ok!(Cached::<4>::load(&mut &[0; 4][..]));
+        let mut writer = CountingWriter::default();
+
+        assert_ok_eq!(cached.persist(&mut writer), false);
+        assert_eq!(writer.writes, 0);
+        assert_eq!(writer.flushes, 0);
+    }
+
+    #[test]
+    fn persist_failure_stays_dirty() {
+        let mut cached = Cached::new([1, 2, 3, 4]);
+        let mut stored = [0; 2];
+
+        assert_err_eq!(cached.persist(&mut &mut stored[..]), SliceWriteError::Full);
+        assert!(cached.is_dirty());
+    }
+
+    #[test]
+    fn persist_every() {
+        let mut cached = assert_ok!(Cached::<4>::load(&mut &[0; 4][..]));
+        let mut writer = CountingWriter::default();
+        let frames = NonZeroU32::new(60).unwrap();
+
+        for frame in 1..=180u32 {
+            cached.set(frame.to_le_bytes());
+            assert_ok!(cached.persist_every(frames, || &mut writer));
+        }
+
+        assert_eq!(writer.writes, 3);
+        assert_eq!(writer.data, 180u32.to_le_bytes());
+    }
+
+    #[test]
+    fn persist_every_does_not_create_writer_when_clean() {
+        let mut cached = assert_ok!(Cached::<4>::load(&mut &[0; 4][..]));
+
+        for _ in 0..10 {
+            assert_ok_eq!(
+                cached.persist_every(NonZeroU32::new(2).unwrap(), || -> CountingWriter {
+                    panic!("writer created")
+                }),
+                false
+            );
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{
+    flash::{Reader128K, Reader64K, Writer128K, Writer64K, Writer64KAtmel},
+    sram, Error,
+};
+use core::fmt::{self, Debug, Formatter};
+use embedded_io::{ErrorType, Read, Write};
+
+enum Reader<'a> {
+    Sram(sram::Reader<'a>),
+    Flash64K(Reader64K<'a>),
+    Flash128K(Reader128K<'a>),
+}
+
+/// A reader on any of this crate's backup devices.
+///
+/// The readers for each device have distinct types, which prevents choosing between them at
+/// runtime. Any of them can be converted into an `ErasedReader` using [`From`], which reads
+/// through the unified [`Error`] type. This allows it to be used as a
+/// `&mut dyn Read<Error = gba_save::Error>`.
+pub struct ErasedReader<'a> {
+    reader: Reader<'a>,
+}
+
+impl<'a> From<sram::Reader<'a>> for ErasedReader<'a> {
+    fn from(reader: sram::Reader<'a>) -> Self {
+        Self {
+            reader: Reader::Sram(reader),
+        }
+    }
+}
+
+impl<'a> From<Reader64K<'a>> for ErasedReader<'a> {
+    fn from(reader: Reader64K<'a>) -> Self {
+        Self {
+            reader: Reader::Flash64K(reader),
+        }
+    }
+}
+
+impl<'a> From<Reader128K<'a>> for ErasedReader<'a> {
+    fn from(reader: Reader128K<'a>) -> Self {
+        Self {
+            reader: Reader::Flash128K(reader),
+        }
+    }
+}
+
+impl ErrorType for ErasedReader<'_> {
+    type Error = Error;
+}
+
+impl Read for ErasedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        Ok(match &mut self.reader {
+            Reader::Sram(reader) => reader.read(buf)?,
+            Reader::Flash64K(reader) => reader.read(buf)?,
+            Reader::Flash128K(reader) => reader.read(buf)?,
+        })
+    }
+}
+
+impl Debug for ErasedReader<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let mut tuple = formatter.debug_tuple("ErasedReader");
+        match &self.reader {
+            Reader::Sram(reader) => tuple.field(reader),
+            Reader::Flash64K(reader) => tuple.field(reader),
+            Reader::Flash128K(reader) => tuple.field(reader),
+        }
+        .finish()
+    }
+}
+
+enum Writer<'a> {
+    Sram(sram::Writer<'a>),
+    Flash64K(Writer64K<'a>),
+    Flash64KAtmel(Writer64KAtmel<'a>),
+    Flash128K(Writer128K<'a>),
+}
+
+/// A writer on any of this crate's backup devices.
+///
+/// The writers for each device have distinct types and errors, which prevents choosing between
+/// them at runtime. Any of them can be converted into an `ErasedWriter` using [`From`], which
+/// writes through the unified [`Error`] type. This allows it to be used as a
+/// `&mut dyn Write<Error = gba_save::Error>`.
+///
+/// # Example
+/// ``` no_run
+/// use embedded_io::Write;
+/// use gba_save::{adapters::ErasedWriter, sram::Sram, Error};
+///
+/// let mut sram = unsafe { Sram::new() };
+/// let mut writer = ErasedWriter::from(sram.writer(..));
+/// let destination: &mut dyn Write<Error = Error> = &mut writer;
+///
+/// destination.write_all(b"hello, world!").expect("could not write save");
+/// ```
+pub struct ErasedWriter<'a> {
+    writer: Writer<'a>,
+}
+
+impl<'a> From<sram::Writer<'a>> for ErasedWriter<'a> {
+    fn from(writer: sram::Writer<'a>) -> Self {
+        Self {
+            writer: Writer::Sram(writer),
+        }
+    }
+}
+
+impl<'a> From<Writer64K<'a>> for ErasedWriter<'a> {
+    fn from(writer: Writer64K<'a>) -> Self {
+        Self {
+            writer: Writer::Flash64K(writer),
+        }
+    }
+}
+
+impl<'a> From<Writer64KAtmel<'a>> for ErasedWriter<'a> {
+    fn from(writer: Writer64KAtmel<'a>) -> Self {
+        Self {
+            writer: Writer::Flash64KAtmel(writer),
+        }
+    }
+}
+
+impl<'a> From<Writer128K<'a>> for ErasedWriter<'a> {
+    fn from(writer: Writer128K<'a>) -> Self {
+        Self {
+            writer: Writer::Flash128K(writer),
+        }
+    }
+}
+
+impl ErrorType for ErasedWriter<'_> {
+    type Error = Error;
+}
+
+impl Write for ErasedWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        Ok(match &mut self.writer {
+            Writer::Sram(writer) => writer.write(buf)?,
+            Writer::Flash64K(writer) => writer.write(buf)?,
+            Writer::Flash64KAtmel(writer) => writer.write(buf)?,
+            Writer::Flash128K(writer) => writer.write(buf)?,
+        })
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        match &mut self.writer {
+            Writer::Sram(writer) => writer.flush()?,
+            Writer::Flash64K(writer) => writer.flush()?,
+            Writer::Flash64KAtmel(writer) => writer.flush()?,
+            Writer::Flash128K(writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
+impl Debug for ErasedWriter<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let mut tuple = formatter.debug_tuple("ErasedWriter");
+        match &self.writer {
+            Writer::Sram(writer) => tuple.field(writer),
+            Writer::Flash64K(writer) => tuple.field(writer),
+            Writer::Flash64KAtmel(writer) => tuple.field(writer),
+            Writer::Flash128K(writer) => tuple.field(writer),
+        }
+        .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErasedReader, ErasedWriter};
+    use crate::{flash::Flash, sram::Sram, Error};
+    use claims::assert_ok;
+    use deranged::RangedU8;
+    use embedded_io::{Read, Write};
+    use gba_test::test;
+
+    const PAYLOAD: &[u8] = b"hello, world!";
+
+    /// Pushes the payload through a dynamically-dispatched writer.
+    ///
+    /// Taking trait objects here ensures the erased adapters stay dyn-compatible.
+    fn write_payload(writer: &mut dyn Write<Error = Error>) {
+        assert_ok!(writer.write_all(PAYLOAD));
+        assert_ok!(writer.flush());
+    }
+
+    /// Reads the payload back through a dynamically-dispatched reader.
+    fn assert_payload(reader: &mut dyn Read<Error = Error>) {
+        let mut buf = [0; PAYLOAD.len()];
+        assert_ok!(reader.read_exact(&mut buf));
+        assert_eq!(buf, PAYLOAD);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn dyn_sram() {
+        let mut sram = unsafe { Sram::new() };
+
+        write_payload(&mut ErasedWriter::from(assert_ok!(sram.try_writer(42..55))));
+
+        assert_payload(&mut ErasedReader::from(assert_ok!(sram.try_reader(42..55))));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn dyn_flash_64k() {
+        let Flash::Flash64K(mut flash_64k) = assert_ok!(unsafe { Flash::new() }) else {
+            panic!("not a Flash 64KiB chip");
+        };
+        assert_ok!(flash_64k.erase_sectors(..=RangedU8::new_static::<0>()));
+
+        write_payload(&mut ErasedWriter::from(assert_ok!(
+            flash_64k.try_writer(42..55)
+        )));
+
+        assert_payload(&mut ErasedReader::from(assert_ok!(
+            flash_64k.try_reader(42..55)
+        )));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn dyn_flash_64k_atmel() {
+        let Flash::Flash64KAtmel(mut flash_64k_atmel) = assert_ok!(unsafe { Flash::new() }) else {
+            panic!("not a Flash 64KiB Atmel chip");
+        };
+
+        write_payload(&mut ErasedWriter::from(assert_ok!(
+            flash_64k_atmel.try_writer(42..55)
+        )));
+
+        assert_payload(&mut ErasedReader::from(assert_ok!(
+            flash_64k_atmel.try_reader(42..55)
+        )));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn dyn_flash_128k() {
+        let Flash::Flash128K(mut flash_128k) = assert_ok!(unsafe { Flash::new() }) else {
+            panic!("not a Flash 128KiB chip");
+        };
+        assert_ok!(flash_128k.erase_sectors(..=RangedU8::new_static::<0>()));
+
+        write_payload(&mut ErasedWriter::from(assert_ok!(
+            flash_128k.try_writer(42..55)
+        )));
+
+        assert_payload(&mut ErasedReader::from(assert_ok!(
+            flash_128k.try_reader(42..55)
+        )));
+    }
+}
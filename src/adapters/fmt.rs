@@ -0,0 +1,124 @@
+use core::fmt;
+use embedded_io::{ErrorType, Write};
+
+/// An adapter allowing formatted text to be written to any writer.
+///
+/// The readers and writers in this crate implement [`embedded_io`] traits, which cannot be used
+/// with [`write!`] and other formatting machinery directly. This type implements
+/// [`core::fmt::Write`] by writing the formatted text to the wrapped writer.
+///
+/// [`core::fmt::Write`] can only report that an error occurred, so any error returned by the
+/// wrapped writer is kept and can be retrieved with [`last_error()`](Self::last_error()). If the
+/// wrapped writer runs out of space by returning `Ok(0)`, formatting also fails, but no error is
+/// kept.
+///
+/// # Example
+/// ``` no_run
+/// use core::fmt::Write as _;
+/// use gba_save::{adapters::FmtWriter, sram::Sram};
+///
+/// let mut sram = unsafe { Sram::new() };
+/// let mut writer = FmtWriter::new(sram.writer(..));
+///
+/// if write!(writer, "level {}", 3).is_err() {
+///     panic!("could not write: {:?}", writer.last_error());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FmtWriter<W>
+where
+    W: ErrorType,
+{
+    writer: W,
+    error: Option<W::Error>,
+}
+
+impl<W> FmtWriter<W>
+where
+    W: ErrorType,
+{
+    /// Wraps `writer`, allowing formatted text to be written to it.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Returns the error most recently returned by the wrapped writer, if any.
+    pub fn last_error(&self) -> Option<&W::Error> {
+        self.error.as_ref()
+    }
+
+    /// Removes and returns the error most recently returned by the wrapped writer, if any.
+    pub fn take_error(&mut self) -> Option<W::Error> {
+        self.error.take()
+    }
+
+    /// Returns the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> fmt::Write for FmtWriter<W>
+where
+    W: Write,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut buf = s.as_bytes();
+        while !buf.is_empty() {
+            match self.writer.write(buf) {
+                Ok(0) => return Err(fmt::Error),
+                Ok(count) => buf = buf.get(count..).unwrap_or(&[]),
+                Err(error) => {
+                    self.error = Some(error);
+                    return Err(fmt::Error);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FmtWriter;
+    use claims::{assert_err, assert_none, assert_ok, assert_some_eq};
+    use core::fmt::Write as _;
+    use embedded_io::SliceWriteError;
+    use gba_test::test;
+
+    #[test]
+    fn write_formatted() {
+        let mut buf = [0; 16];
+        let mut writer = FmtWriter::new(&mut buf[..]);
+
+        assert_ok!(write!(writer, "level {}: {}", 3, "ok"));
+
+        assert_none!(writer.last_error());
+        assert_eq!(writer.into_inner().len(), 5);
+        assert_eq!(&buf[..11], b"level 3: ok");
+    }
+
+    #[test]
+    fn write_error() {
+        let mut buf = [0; 4];
+        let mut writer = FmtWriter::new(&mut buf[..]);
+
+        assert_err!(write!(writer, "hello, world!"));
+
+        assert_some_eq!(writer.last_error(), &SliceWriteError::Full);
+        assert_eq!(&buf, b"hell");
+    }
+
+    #[test]
+    fn take_error() {
+        let mut buf = [0; 0];
+        let mut writer = FmtWriter::new(&mut buf[..]);
+        assert_err!(write!(writer, "a"));
+
+        assert_some_eq!(writer.take_error(), SliceWriteError::Full);
+        assert_none!(writer.last_error());
+    }
+}
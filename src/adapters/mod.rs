@@ -0,0 +1,24 @@
+//! Adapters over readers and writers.
+//!
+//! These types wrap any [`embedded_io`] reader or writer, including the readers and writers
+//! provided by this crate, to provide additional behavior.
+
+mod buffered;
+mod cached;
+mod erased;
+mod fmt;
+mod retry;
+#[cfg(feature = "compress")]
+mod rle;
+mod throttled;
+mod verifying;
+
+pub use buffered::BufferedRegion;
+pub use cached::Cached;
+pub use erased::{ErasedReader, ErasedWriter};
+pub use fmt::FmtWriter;
+pub use retry::{RetryError, RetryWriter};
+#[cfg(feature = "compress")]
+pub use rle::{RleError, RleReader, RleWriter};
+pub use throttled::Throttled;
+pub use verifying::{VerifyError, VerifyingWriter};
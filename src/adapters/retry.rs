@@ -0,0 +1,306 @@
+use embedded_io::{ErrorKind, ErrorType, Write};
+
+/// An error returned by a [`RetryWriter`] after all retries have been exhausted.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RetryError<E> {
+    /// The error returned by the final attempt.
+    pub error: E,
+    /// The total number of attempts made, including the initial attempt.
+    pub attempts: u16,
+}
+
+impl<E> embedded_io::Error for RetryError<E>
+where
+    E: embedded_io::Error,
+{
+    fn kind(&self) -> ErrorKind {
+        self.error.kind()
+    }
+}
+
+/// A writer that retries failed operations on the wrapped writer.
+///
+/// When a call to [`write()`] or [`flush()`] fails, the same call is made again, up to the
+/// configured maximum number of retries. This is only appropriate for writers that do not advance
+/// when returning an error, as is the case for all writers provided by this crate. A
+/// [`Writer64KAtmel`] whose page fails to program returns to the start of that page's data before
+/// returning the error, so retrying rewrites the same page.
+///
+/// Note that this retries entire calls. For retrying individual bytes, see
+/// [`Sram::writer_with_retries()`].
+///
+/// [`write()`]: Write::write()
+/// [`flush()`]: Write::flush()
+/// [`Sram::writer_with_retries()`]: crate::sram::Sram::writer_with_retries()
+/// [`Writer64KAtmel`]: crate::flash::Writer64KAtmel
+#[derive(Debug)]
+pub struct RetryWriter<W> {
+    writer: W,
+    max_retries: u8,
+}
+
+impl<W> RetryWriter<W> {
+    /// Wraps `writer`, retrying each failed operation up to `max_retries` times.
+    pub fn new(writer: W, max_retries: u8) -> Self {
+        Self {
+            writer,
+            max_retries,
+        }
+    }
+
+    /// Returns the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> ErrorType for RetryWriter<W>
+where
+    W: ErrorType,
+{
+    type Error = RetryError<W::Error>;
+}
+
+impl<W> Write for RetryWriter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let mut retries = 0;
+        loop {
+            match self.writer.write(buf) {
+                Ok(count) => return Ok(count),
+                Err(error) => {
+                    if retries == self.max_retries {
+                        return Err(RetryError {
+                            error,
+                            attempts: u16::from(retries) + 1,
+                        });
+                    }
+                    retries += 1;
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        let mut retries = 0;
+        loop {
+            match self.writer.flush() {
+                Ok(()) => return Ok(()),
+                Err(error) => {
+                    if retries == self.max_retries {
+                        return Err(RetryError {
+                            error,
+                            attempts: u16::from(retries) + 1,
+                        });
+                    }
+                    retries += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RetryError, RetryWriter};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use embedded_io::{ErrorKind, ErrorType, Write};
+    use gba_test::test;
+
+    /// A writer that fails a set number of times before succeeding.
+    struct FlakyWriter {
+        failures: u16,
+        calls: u16,
+    }
+
+    impl ErrorType for FlakyWriter {
+        type Error = ErrorKind;
+    }
+
+    impl Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.calls += 1;
+            if self.failures > 0 {
+                self.failures -= 1;
+                Err(ErrorKind::Other)
+            } else {
+                Ok(buf.len())
+            }
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.calls += 1;
+            if self.failures > 0 {
+                self.failures -= 1;
+                Err(ErrorKind::Other)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn write_no_failures() {
+        let mut writer = RetryWriter::new(
+            FlakyWriter {
+                failures: 0,
+                calls: 0,
+            },
+            3,
+        );
+
+        assert_ok_eq!(writer.write(&[1, 2, 3]), 3);
+        assert_eq!(writer.into_inner().calls, 1);
+    }
+
+    #[test]
+    fn write_succeeds_after_retries() {
+        let mut writer = RetryWriter::new(
+            FlakyWriter {
+                failures: 3,
+                calls: 0,
+            },
+            3,
+        );
+
+        assert_ok_eq!(writer.write(&[1, 2, 3]), 3);
+        assert_eq!(writer.into_inner().calls, 4);
+    }
+
+    #[test]
+    fn write_retries_exhausted() {
+        let mut writer = RetryWriter::new(
+            FlakyWriter {
+                failures: 4,
+                calls: 0,
+            },
+            3,
+        );
+
+        assert_err_eq!(
+            writer.write(&[1, 2, 3]),
+            RetryError {
+                error: ErrorKind::Other,
+                attempts: 4
+            }
+        );
+    }
+
+    #[test]
+    fn write_no_retries() {
+        let mut writer = RetryWriter::new(
+            FlakyWriter {
+                failures: 1,
+                calls: 0,
+            },
+            0,
+        );
+
+        assert_err_eq!(
+            writer.write(&[1, 2, 3]),
+            RetryError {
+                error: ErrorKind::Other,
+                attempts: 1
+            }
+        );
+    }
+
+    #[test]
+    fn write_max_retries_exhausted() {
+        let mut writer = RetryWriter::new(
+            FlakyWriter {
+                failures: u16::MAX,
+                calls: 0,
+            },
+            u8::MAX,
+        );
+
+        assert_err_eq!(
+            writer.write(&[1, 2, 3]),
+            RetryError {
+                error: ErrorKind::Other,
+                attempts: 256
+            }
+        );
+        assert_eq!(writer.into_inner().calls, 256);
+    }
+
+    #[test]
+    fn write_succeeds_on_final_retry() {
+        let mut writer = RetryWriter::new(
+            FlakyWriter {
+                failures: 255,
+                calls: 0,
+            },
+            u8::MAX,
+        );
+
+        assert_ok_eq!(writer.write(&[1, 2, 3]), 3);
+        assert_eq!(writer.into_inner().calls, 256);
+    }
+
+    #[test]
+    fn flush_succeeds_after_retries() {
+        let mut writer = RetryWriter::new(
+            FlakyWriter {
+                failures: 2,
+                calls: 0,
+            },
+            2,
+        );
+
+        assert_ok!(writer.flush());
+        assert_eq!(writer.into_inner().calls, 3);
+    }
+
+    #[test]
+    fn flush_retries_exhausted() {
+        let mut writer = RetryWriter::new(
+            FlakyWriter {
+                failures: 3,
+                calls: 0,
+            },
+            2,
+        );
+
+        assert_err_eq!(
+            writer.flush(),
+            RetryError {
+                error: ErrorKind::Other,
+                attempts: 3
+            }
+        );
+    }
+
+    #[test]
+    fn flush_max_retries_exhausted() {
+        let mut writer = RetryWriter::new(
+            FlakyWriter {
+                failures: u16::MAX,
+                calls: 0,
+            },
+            u8::MAX,
+        );
+
+        assert_err_eq!(
+            writer.flush(),
+            RetryError {
+                error: ErrorKind::Other,
+                attempts: 256
+            }
+        );
+    }
+
+    #[test]
+    fn error_kind() {
+        assert_eq!(
+            embedded_io::Error::kind(&RetryError {
+                error: ErrorKind::TimedOut,
+                attempts: 1
+            }),
+            ErrorKind::TimedOut
+        );
+    }
+}
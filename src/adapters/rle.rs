@@ -0,0 +1,493 @@
+use crate::error_kind::Class;
+use embedded_io::{ErrorKind, ErrorType, Read, ReadExactError, Write};
+
+/// The largest number of bytes in a single literal block.
+const MAX_LITERAL: usize = 128;
+/// The smallest number of repeated bytes encoded as a run.
+const MIN_RUN: usize = 3;
+/// The largest number of repeated bytes in a single run block.
+const MAX_RUN: usize = 129;
+/// The control byte marking the end of the stream.
+const END: u8 = 0xff;
+
+/// An error returned by an [`RleReader`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RleError<E> {
+    /// The wrapped reader returned an error.
+    Read(E),
+
+    /// The wrapped reader ran out of data before the end of the stream.
+    ///
+    /// This indicates that the stream was never [finished](RleWriter::finish()), or that the
+    /// region being read is too small to contain it.
+    Truncated,
+}
+
+impl<E> embedded_io::Error for RleError<E>
+where
+    E: embedded_io::Error,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Read(error) => error.kind(),
+            Self::Truncated => Class::Malformed.kind(),
+        }
+    }
+}
+
+/// A writer that run-length encodes data as it is written.
+///
+/// Repeated bytes are common in save data, such as unused inventory slots or untouched map tiles,
+/// and run-length encoding them can let a save fit on a smaller backup device. Data is encoded on
+/// the fly into the wrapped writer using a fixed 128-byte buffer. Nothing is allocated.
+///
+/// The stream must be completed by calling [`finish()`](Self::finish()), which writes the end
+/// marker. Data still buffered when the writer is dropped is lost. Use an [`RleReader`] to decode
+/// the stream.
+///
+/// If the wrapped writer returns an error, the encoded stream is incomplete and should be written
+/// again from the start.
+///
+/// This type is only available with the `compress` feature enabled.
+///
+/// # Format
+/// The encoded stream is a sequence of blocks, each starting with a control byte:
+///
+/// | Control byte  | Block                                                           |
+/// | ------------- | --------------------------------------------------------------- |
+/// | `0x00..=0x7f` | A literal block: the next `control + 1` bytes are copied as-is. |
+/// | `0x80..=0xfe` | A run: the next byte is repeated `control - 0x7d` times.        |
+/// | `0xff`        | The end of the stream.                                          |
+///
+/// Literal blocks therefore hold 1 to 128 bytes, and runs hold 3 to 129 bytes. This format is
+/// stable and will not change. Because the end marker is `0xff`, erased flash memory decodes as
+/// an empty stream.
+///
+/// Runs are only encoded when they save space, so the encoded size of `n` bytes is at most
+/// `n + n.div_ceil(128) + 1`, plus one byte for each call to [`flush()`](Write::flush())
+/// before the stream is finished.
+///
+/// # Example
+/// ``` no_run
+/// use embedded_io::Write;
+/// use gba_save::{adapters::RleWriter, sram::Sram};
+///
+/// let mut sram = unsafe { Sram::new() };
+/// let mut writer = RleWriter::new(sram.writer(..));
+///
+/// writer.write_all(&[0; 1024]).expect("could not write");
+/// // Encoded as 17 bytes.
+/// writer.finish().expect("could not finish");
+/// ```
+#[derive(Debug)]
+pub struct RleWriter<W> {
+    writer: W,
+    literals: [u8; MAX_LITERAL],
+    literal_len: usize,
+    run_byte: u8,
+    run_len: usize,
+}
+
+impl<W> RleWriter<W>
+where
+    W: Write,
+{
+    /// Wraps `writer`, encoding all data written to it.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            literals: [0; MAX_LITERAL],
+            literal_len: 0,
+            run_byte: 0,
+            run_len: 0,
+        }
+    }
+
+    /// Writes all buffered data and the end marker, then flushes and returns the wrapped writer.
+    pub fn finish(mut self) -> Result<W, W::Error> {
+        self.write_pending()?;
+        self.writer.write_all(&[END])?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn push(&mut self, byte: u8) -> Result<(), W::Error> {
+        if self.run_len > 0 && byte == self.run_byte && self.run_len < MAX_RUN {
+            self.run_len += 1;
+            return Ok(());
+        }
+        self.end_run()?;
+        self.run_byte = byte;
+        self.run_len = 1;
+        Ok(())
+    }
+
+    /// Encodes the current run, either as a run block or by appending it to the literals.
+    fn end_run(&mut self) -> Result<(), W::Error> {
+        if self.run_len >= MIN_RUN {
+            self.write_literals()?;
+            self.writer
+                .write_all(&[(self.run_len + 0x7d) as u8, self.run_byte])?;
+        } else {
+            for _ in 0..self.run_len {
+                if self.literal_len == MAX_LITERAL {
+                    self.write_literals()?;
+                }
+                self.literals[self.literal_len] = self.run_byte;
+                self.literal_len += 1;
+            }
+        }
+        self.run_len = 0;
+        Ok(())
+    }
+
+    fn write_literals(&mut self) -> Result<(), W::Error> {
+        if self.literal_len > 0 {
+            self.writer.write_all(&[(self.literal_len - 1) as u8])?;
+            self.writer.write_all(&self.literals[..self.literal_len])?;
+            self.literal_len = 0;
+        }
+        Ok(())
+    }
+
+    fn write_pending(&mut self) -> Result<(), W::Error> {
+        self.end_run()?;
+        self.write_literals()
+    }
+}
+
+impl<W> ErrorType for RleWriter<W>
+where
+    W: ErrorType,
+{
+    type Error = W::Error;
+}
+
+impl<W> Write for RleWriter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        for &byte in buf {
+            self.push(byte)?;
+        }
+        Ok(buf.len())
+    }
+
+    /// Writes all buffered data to the wrapped writer and flushes it.
+    ///
+    /// This does not finish the stream, and more data may be written afterward.
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.write_pending()?;
+        self.writer.flush()
+    }
+}
+
+/// The block currently being decoded by an [`RleReader`].
+#[derive(Clone, Copy, Debug)]
+enum Block {
+    Literal { remaining: usize },
+    Run { byte: u8, remaining: usize },
+    End,
+}
+
+/// A reader that decodes data written by an [`RleWriter`].
+///
+/// Data is decoded on the fly from the wrapped reader. Literal bytes are read directly into the
+/// caller's buffer, so no buffer is needed. See [`RleWriter`] for a description of the format.
+///
+/// Once the end of the stream is reached, reads return `Ok(0)`. Nothing past the end marker is
+/// read from the wrapped reader.
+///
+/// This type is only available with the `compress` feature enabled.
+///
+/// # Example
+/// ``` no_run
+/// use embedded_io::Read;
+/// use gba_save::{adapters::RleReader, sram::Sram};
+///
+/// let mut sram = unsafe { Sram::new() };
+/// let mut reader = RleReader::new(sram.reader(..));
+///
+/// let mut buf = [0; 1024];
+/// reader.read_exact(&mut buf).expect("could not read");
+/// ```
+#[derive(Debug)]
+pub struct RleReader<R> {
+    reader: R,
+    block: Block,
+}
+
+impl<R> RleReader<R>
+where
+    R: Read,
+{
+    /// Wraps `reader`, decoding all data read from it.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            block: Block::Literal { remaining: 0 },
+        }
+    }
+
+    /// Returns the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_byte(&mut self) -> Result<u8, RleError<R::Error>> {
+        let mut byte = [0];
+        self.reader
+            .read_exact(&mut byte)
+            .map_err(|error| match error {
+                ReadExactError::UnexpectedEof => RleError::Truncated,
+                ReadExactError::Other(error) => RleError::Read(error),
+            })?;
+        Ok(byte[0])
+    }
+}
+
+impl<R> ErrorType for RleReader<R>
+where
+    R: ErrorType,
+{
+    type Error = RleError<R::Error>;
+}
+
+impl<R> Read for RleReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            match self.block {
+                Block::Literal { remaining: 0 } | Block::Run { remaining: 0, .. } => {
+                    self.block = match self.read_byte()? {
+                        END => Block::End,
+                        control @ 0x00..=0x7f => Block::Literal {
+                            remaining: control as usize + 1,
+                        },
+                        control => Block::Run {
+                            byte: self.read_byte()?,
+                            remaining: control as usize - 0x7d,
+                        },
+                    };
+                }
+                Block::Literal { ref mut remaining } => {
+                    let len = buf.len().min(*remaining);
+                    let read_count = self.reader.read(&mut buf[..len]).map_err(RleError::Read)?;
+                    if read_count == 0 {
+                        return Err(RleError::Truncated);
+                    }
+                    *remaining -= read_count;
+                    return Ok(read_count);
+                }
+                Block::Run {
+                    byte,
+                    ref mut remaining,
+                } => {
+                    let len = buf.len().min(*remaining);
+                    buf[..len].fill(byte);
+                    *remaining -= len;
+                    return Ok(len);
+                }
+                Block::End => return Ok(0),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RleError, RleReader, RleWriter};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use embedded_io::{ErrorKind, Read, Write};
+    use gba_test::test;
+
+    /// Encodes `data` into `encoded`, returning the encoded length.
+    fn encode(data: &[u8], encoded: &mut [u8]) -> usize {
+        let capacity = encoded.len();
+        let mut writer = RleWriter::new(encoded);
+        assert_ok!(writer.write_all(data));
+        capacity - assert_ok!(writer.finish()).len()
+    }
+
+    /// Asserts that `data` decodes from `encoded` exactly, with nothing following it.
+    fn assert_decodes(encoded: &[u8], data: &[u8]) {
+        let mut reader = RleReader::new(encoded);
+        let mut decoded = [0; 512];
+        assert_ok!(reader.read_exact(&mut decoded[..data.len()]));
+        assert_eq!(&decoded[..data.len()], data);
+        assert_ok_eq!(reader.read(&mut decoded), 0);
+    }
+
+    #[test]
+    fn encode_empty() {
+        let mut encoded = [0; 4];
+
+        assert_eq!(encode(&[], &mut encoded), 1);
+        assert_eq!(encoded[0], 0xff);
+    }
+
+    #[test]
+    fn encode_format() {
+        let mut encoded = [0; 16];
+
+        let len = encode(b"abccccd", &mut encoded);
+
+        assert_eq!(
+            &encoded[..len],
+            &[0x01, b'a', b'b', 0x81, b'c', 0x00, b'd', 0xff]
+        );
+    }
+
+    #[test]
+    fn encode_short_runs_as_literals() {
+        let mut encoded = [0; 16];
+
+        let len = encode(b"aabbc", &mut encoded);
+
+        assert_eq!(&encoded[..len], &[0x04, b'a', b'a', b'b', b'b', b'c', 0xff]);
+    }
+
+    #[test]
+    fn encode_long_run() {
+        let mut encoded = [0; 32];
+
+        let len = encode(&[0; 512], &mut encoded);
+
+        // Three full runs of 129 bytes and one run of 125 bytes.
+        assert_eq!(&encoded[..len], &[0xfe, 0, 0xfe, 0, 0xfe, 0, 0xfa, 0, 0xff]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut data = [0; 300];
+        for (index, byte) in data.iter_mut().enumerate() {
+            *byte = match index % 50 {
+                0..=9 => index as u8,
+                10..=29 => 0,
+                _ => 0xff,
+            };
+        }
+        let mut encoded = [0; 512];
+
+        let len = encode(&data, &mut encoded);
+
+        assert!(len < data.len());
+        assert_decodes(&encoded[..len], &data);
+    }
+
+    #[test]
+    fn round_trip_incompressible() {
+        let mut data = [0; 512];
+        for (index, byte) in data.iter_mut().enumerate() {
+            *byte = (index * 7 + index / 256) as u8;
+        }
+        let mut encoded = [0; 600];
+
+        let len = encode(&data, &mut encoded);
+
+        assert_eq!(len, data.len() + data.len().div_ceil(128) + 1);
+        assert_decodes(&encoded[..len], &data);
+    }
+
+    #[test]
+    fn round_trip_alternating_runs_bounded() {
+        // Single literals separated by minimal runs are the worst case for splitting blocks.
+        let mut data = [0; 401];
+        for (index, byte) in data.iter_mut().enumerate() {
+            *byte = if index % 4 == 0 { 1 } else { 2 };
+        }
+        let mut encoded = [0; 600];
+
+        let len = encode(&data, &mut encoded);
+
+        assert!(len <= data.len() + data.len().div_ceil(128) + 1);
+        assert_decodes(&encoded[..len], &data);
+    }
+
+    #[test]
+    fn round_trip_with_flush() {
+        let mut encoded = [0; 32];
+        let mut writer = RleWriter::new(&mut encoded[..]);
+
+        assert_ok!(writer.write_all(b"aaaa"));
+        assert_ok!(writer.flush());
+        assert_ok!(writer.write_all(b"aaab"));
+        let remaining = assert_ok!(writer.finish()).len();
+
+        assert_decodes(&encoded[..32 - remaining], b"aaaaaaab");
+    }
+
+    #[test]
+    fn read_small_chunks() {
+        let mut encoded = [0; 16];
+        let len = encode(b"abccccd", &mut encoded);
+        let mut reader = RleReader::new(&encoded[..len]);
+        let mut decoded = [0; 7];
+
+        for chunk in decoded.chunks_mut(2) {
+            assert_ok!(reader.read_exact(chunk));
+        }
+
+        assert_eq!(&decoded, b"abccccd");
+    }
+
+    #[test]
+    fn read_stops_at_end() {
+        let encoded = [0x00, b'a', 0xff, 0x00, b'b'];
+        let mut reader = RleReader::new(&encoded[..]);
+        let mut decoded = [0; 4];
+
+        assert_ok_eq!(reader.read(&mut decoded), 1);
+        assert_ok_eq!(reader.read(&mut decoded), 0);
+        assert_eq!(reader.into_inner(), &[0x00, b'b']);
+    }
+
+    #[test]
+    fn read_erased() {
+        let mut reader = RleReader::new(&[0xff; 16][..]);
+
+        assert_ok_eq!(reader.read(&mut [0; 4]), 0);
+    }
+
+    #[test]
+    fn read_truncated_literal() {
+        let mut reader = RleReader::new(&[0x03, b'a'][..]);
+        let mut decoded = [0; 4];
+
+        assert_ok_eq!(reader.read(&mut decoded), 1);
+        assert_err_eq!(reader.read(&mut decoded), RleError::Truncated);
+    }
+
+    #[test]
+    fn read_truncated_run() {
+        let mut reader = RleReader::new(&[0x80][..]);
+
+        assert_err_eq!(reader.read(&mut [0; 4]), RleError::Truncated);
+    }
+
+    #[test]
+    fn read_missing_end() {
+        let mut reader = RleReader::new(&[0x00, b'a'][..]);
+        let mut decoded = [0; 4];
+
+        assert_ok_eq!(reader.read(&mut decoded), 1);
+        assert_err_eq!(reader.read(&mut decoded), RleError::Truncated);
+    }
+
+    #[test]
+    fn truncated_kind() {
+        use embedded_io::Error;
+
+        assert_eq!(
+            RleError::<ErrorKind>::Truncated.kind(),
+            ErrorKind::InvalidData
+        );
+    }
+}
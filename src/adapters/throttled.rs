@@ -0,0 +1,210 @@
+use core::{cmp::min, num::NonZeroUsize};
+use embedded_io::{ErrorType, Write};
+
+/// A writer that limits how many bytes are written by each call.
+///
+/// Each call to [`write()`] passes at most the configured number of bytes to the wrapped writer
+/// and returns the number actually written, so a save can be spread across frames by calling
+/// `write()` once per frame and resuming with the rest of the buffer on the next frame. This bounds
+/// the time spent writing in any one frame.
+///
+/// Note that [`write_all()`](Write::write_all()) calls `write()` repeatedly until the whole buffer
+/// is written, so it is not throttled.
+///
+/// # Blocks
+/// Some writers program the device a block at a time, such as [`Writer64KAtmel`], which programs a
+/// whole 128-byte page when the last byte of the page is written. Splitting such a block across
+/// calls does not split the work of programming it. When created with
+/// [`with_blocks()`](Self::with_blocks()), each call instead ends on a block boundary, so the
+/// budget is spent on whole blocks. If a single block is larger than the budget, one block is
+/// written per call. [`Writer64KAtmel::throttled()`] configures this automatically.
+///
+/// # Example
+/// ``` no_run
+/// use core::num::NonZeroUsize;
+/// use embedded_io::Write;
+/// use gba_save::{adapters::Throttled, sram::Sram};
+///
+/// let mut sram = unsafe { Sram::new() };
+/// let mut writer = Throttled::new(sram.writer(..), NonZeroUsize::new(256).unwrap());
+/// let mut remaining: &[u8] = &[0; 1024];
+///
+/// // Once per frame:
+/// if !remaining.is_empty() {
+///     let count = writer.write(remaining).expect("could not save");
+///     remaining = &remaining[count..];
+/// }
+/// ```
+///
+/// [`write()`]: Write::write()
+/// [`Writer64KAtmel`]: crate::flash::Writer64KAtmel
+/// [`Writer64KAtmel::throttled()`]: crate::flash::Writer64KAtmel::throttled()
+#[derive(Debug)]
+pub struct Throttled<W> {
+    writer: W,
+    bytes_per_call: NonZeroUsize,
+    block_size: NonZeroUsize,
+    block_offset: usize,
+}
+
+impl<W> Throttled<W> {
+    /// Wraps `writer`, writing at most `bytes_per_call` bytes on each call.
+    pub fn new(writer: W, bytes_per_call: NonZeroUsize) -> Self {
+        Self::with_blocks(writer, bytes_per_call, NonZeroUsize::MIN, 0)
+    }
+
+    /// Wraps `writer`, which programs the device in blocks of `block_size` bytes.
+    ///
+    /// `block_offset` is the position of the writer within its current block. Each call writes
+    /// up to a block boundary, writing as many whole blocks as fit within `bytes_per_call`, or a
+    /// single block if none fit. See [Blocks](Self#blocks) for details.
+    pub fn with_blocks(
+        writer: W,
+        bytes_per_call: NonZeroUsize,
+        block_size: NonZeroUsize,
+        block_offset: usize,
+    ) -> Self {
+        Self {
+            writer,
+            bytes_per_call,
+            block_size,
+            block_offset: block_offset % block_size,
+        }
+    }
+
+    /// Returns the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Returns the number of bytes that may be written by the next call.
+    fn limit(&self) -> usize {
+        let block_size = self.block_size.get();
+        let to_boundary = block_size - self.block_offset;
+        match self.bytes_per_call.get().checked_sub(to_boundary) {
+            Some(extra) => to_boundary + extra / block_size * block_size,
+            None => to_boundary,
+        }
+    }
+}
+
+impl<W> ErrorType for Throttled<W>
+where
+    W: ErrorType,
+{
+    type Error = W::Error;
+}
+
+impl<W> Write for Throttled<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let limit = self.limit();
+        let count = self
+            .writer
+            .write(buf.get(..min(buf.len(), limit)).unwrap_or(buf))?;
+        self.block_offset = (self.block_offset + count) % self.block_size;
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Throttled;
+    use claims::{assert_ok, assert_ok_eq};
+    use core::num::NonZeroUsize;
+    use embedded_io::{ErrorType, SliceWriteError, Write};
+    use gba_test::test;
+
+    /// A writer that accepts everything, counting the calls made to it.
+    struct CountingWriter {
+        written: usize,
+        calls: usize,
+    }
+
+    impl ErrorType for CountingWriter {
+        type Error = SliceWriteError;
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.written += buf.len();
+            self.calls += 1;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    const fn counting() -> CountingWriter {
+        CountingWriter {
+            written: 0,
+            calls: 0,
+        }
+    }
+
+    fn nonzero(value: usize) -> NonZeroUsize {
+        NonZeroUsize::new(value).unwrap()
+    }
+
+    #[test]
+    fn write_limited() {
+        let mut writer = Throttled::new(counting(), nonzero(4));
+
+        assert_ok_eq!(writer.write(&[0; 10]), 4);
+        assert_ok_eq!(writer.write(&[0; 6]), 4);
+        assert_ok_eq!(writer.write(&[0; 2]), 2);
+        assert_eq!(writer.into_inner().written, 10);
+    }
+
+    #[test]
+    fn write_all_not_throttled() {
+        let mut writer = Throttled::new(counting(), nonzero(4));
+
+        assert_ok!(writer.write_all(&[0; 10]));
+
+        let inner = writer.into_inner();
+        assert_eq!(inner.written, 10);
+        assert_eq!(inner.calls, 3);
+    }
+
+    #[test]
+    fn blocks_end_on_boundary() {
+        let mut writer = Throttled::with_blocks(counting(), nonzero(300), nonzero(128), 0);
+
+        assert_ok_eq!(writer.write(&[0; 1024]), 256);
+        assert_ok_eq!(writer.write(&[0; 768]), 256);
+    }
+
+    #[test]
+    fn blocks_from_offset() {
+        let mut writer = Throttled::with_blocks(counting(), nonzero(300), nonzero(128), 100);
+
+        assert_ok_eq!(writer.write(&[0; 1024]), 284);
+        assert_ok_eq!(writer.write(&[0; 740]), 256);
+    }
+
+    #[test]
+    fn block_larger_than_budget() {
+        let mut writer = Throttled::with_blocks(counting(), nonzero(64), nonzero(128), 0);
+
+        assert_ok_eq!(writer.write(&[0; 1024]), 128);
+        assert_ok_eq!(writer.write(&[0; 896]), 128);
+    }
+
+    #[test]
+    fn short_write_keeps_block_position() {
+        let mut writer = Throttled::with_blocks(counting(), nonzero(128), nonzero(128), 0);
+
+        assert_ok_eq!(writer.write(&[0; 28]), 28);
+        assert_ok_eq!(writer.write(&[0; 1024]), 100);
+        assert_ok_eq!(writer.write(&[0; 924]), 128);
+    }
+}
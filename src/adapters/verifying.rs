@@ -0,0 +1,232 @@
+use crate::error_kind::Class;
+use embedded_io::{ErrorKind, ErrorType, Read, Write};
+
+/// An error returned by a [`VerifyingWriter`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum VerifyError<E> {
+    /// The written data did not match the stored data.
+    Mismatch {
+        /// The offset of the first mismatched byte, relative to the start of the writer.
+        offset: usize,
+    },
+
+    /// The wrapped reader returned an error.
+    Read(E),
+
+    /// The writer has exhausted all of its space.
+    ///
+    /// This indicates that the wrapped reader has no more data to compare against.
+    EndOfWriter,
+}
+
+impl<E> embedded_io::Error for VerifyError<E>
+where
+    E: embedded_io::Error,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Mismatch { .. } => Class::Verification.kind(),
+            Self::Read(error) => error.kind(),
+            Self::EndOfWriter => Class::EndOfWriter.kind(),
+        }
+    }
+}
+
+/// A writer that compares against stored data instead of writing.
+///
+/// Each byte written is compared against the next byte read from the wrapped reader. Nothing is
+/// ever written to the backup memory. This allows running save routines against a known-good save
+/// to confirm that they produce byte-identical output.
+///
+/// # Example
+/// ``` no_run
+/// use gba_save::{adapters::VerifyingWriter, sram::Sram};
+/// use embedded_io::Write;
+///
+/// let sram = unsafe { Sram::new() };
+/// let mut writer = VerifyingWriter::new(sram.reader(..));
+///
+/// // Fails with `VerifyError::Mismatch` if the stored data differs.
+/// writer.write_all(b"hello, world!").expect("save data differs");
+/// ```
+#[derive(Debug)]
+pub struct VerifyingWriter<R> {
+    reader: R,
+    offset: usize,
+}
+
+impl<R> VerifyingWriter<R> {
+    /// Wraps `reader`, comparing all written data against the data it reads.
+    pub fn new(reader: R) -> Self {
+        Self { reader, offset: 0 }
+    }
+
+    /// Returns the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> ErrorType for VerifyingWriter<R>
+where
+    R: ErrorType,
+{
+    type Error = VerifyError<R::Error>;
+}
+
+impl<R> Write for VerifyingWriter<R>
+where
+    R: Read,
+{
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut stored = [0; 32];
+        let mut write_count = 0;
+        while write_count < buf.len() {
+            let chunk = &buf[write_count..];
+            let chunk_len = chunk.len().min(stored.len());
+            let read_count = self
+                .reader
+                .read(&mut stored[..chunk_len])
+                .map_err(VerifyError::Read)?;
+            if read_count == 0 {
+                break;
+            }
+            for (index, (&byte, &stored_byte)) in
+                chunk.iter().zip(&stored[..read_count]).enumerate()
+            {
+                if byte != stored_byte {
+                    return Err(VerifyError::Mismatch {
+                        offset: self.offset + index,
+                    });
+                }
+            }
+            self.offset += read_count;
+            write_count += read_count;
+        }
+
+        if write_count == 0 {
+            Err(VerifyError::EndOfWriter)
+        } else {
+            Ok(write_count)
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VerifyError, VerifyingWriter};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use embedded_io::{ErrorKind, Write};
+    use gba_test::test;
+
+    #[test]
+    fn write_matching() {
+        let stored: &[u8] = b"hello, world!";
+        let mut writer = VerifyingWriter::new(stored);
+
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+    }
+
+    #[test]
+    fn write_empty() {
+        let stored: &[u8] = b"hello, world!";
+        let mut writer = VerifyingWriter::new(stored);
+
+        assert_ok_eq!(writer.write(&[]), 0);
+    }
+
+    #[test]
+    fn write_mismatch() {
+        let stored: &[u8] = b"hello, world!";
+        let mut writer = VerifyingWriter::new(stored);
+
+        assert_err_eq!(
+            writer.write(b"hello, there!"),
+            VerifyError::Mismatch { offset: 7 }
+        );
+    }
+
+    #[test]
+    fn write_mismatch_offset_across_writes() {
+        let stored: &[u8] = b"hello, world!";
+        let mut writer = VerifyingWriter::new(stored);
+
+        assert_ok_eq!(writer.write(b"hello, "), 7);
+        assert_err_eq!(writer.write(b"there!"), VerifyError::Mismatch { offset: 7 });
+    }
+
+    #[test]
+    fn write_larger_than_chunk() {
+        let stored = [0xab; 100];
+        let mut writer = VerifyingWriter::new(&stored[..]);
+
+        assert_ok_eq!(writer.write(&[0xab; 100]), 100);
+    }
+
+    #[test]
+    fn write_mismatch_after_chunk() {
+        let stored = [0xab; 100];
+        let mut buf = [0xab; 100];
+        buf[70] = 0;
+        let mut writer = VerifyingWriter::new(&stored[..]);
+
+        assert_err_eq!(writer.write(&buf), VerifyError::Mismatch { offset: 70 });
+    }
+
+    #[test]
+    fn write_partial() {
+        let stored: &[u8] = b"hello";
+        let mut writer = VerifyingWriter::new(stored);
+
+        assert_ok_eq!(writer.write(b"hello, world!"), 5);
+    }
+
+    #[test]
+    fn write_end_of_writer() {
+        let stored: &[u8] = b"hello";
+        let mut writer = VerifyingWriter::new(stored);
+
+        assert_ok_eq!(writer.write(b"hello"), 5);
+        assert_err_eq!(writer.write(b"!"), VerifyError::EndOfWriter);
+    }
+
+    #[test]
+    fn flush() {
+        let stored: &[u8] = b"hello";
+        let mut writer = VerifyingWriter::new(stored);
+
+        assert_ok!(writer.flush());
+    }
+
+    #[test]
+    fn mismatch_kind() {
+        assert_eq!(
+            embedded_io::Error::kind(&VerifyError::<ErrorKind>::Mismatch { offset: 0 }),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn read_kind() {
+        assert_eq!(
+            embedded_io::Error::kind(&VerifyError::Read(ErrorKind::TimedOut)),
+            ErrorKind::TimedOut
+        );
+    }
+
+    #[test]
+    fn end_of_writer_kind() {
+        assert_eq!(
+            embedded_io::Error::kind(&VerifyError::<ErrorKind>::EndOfWriter),
+            ErrorKind::WriteZero
+        );
+    }
+}
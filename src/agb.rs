@@ -0,0 +1,320 @@
+//! Adapters for the `agb` crate's save media.
+//!
+//! The `agb` crate initializes and manages backup memory through its own save API, exposing the
+//! initialized media through the [`StorageMedium`] trait. The [`Reader`] and [`Writer`] types
+//! defined here implement [`embedded_io`]'s [`Read`] and [`Write`] traits over a range of any
+//! [`StorageMedium`], allowing this crate's adapters (such as [`VerifyingWriter`]) and any other
+//! [`embedded_io`]-based code to be used with media that `agb` has already initialized. This
+//! avoids initializing the backup hardware a second time.
+//!
+//! ``` ignore
+//! use embedded_io::Write;
+//! use gba_save::agb::Writer;
+//!
+//! let mut save_data = gba.save.access()?;
+//! let mut writer = Writer::new(&mut save_data, 0..64)?;
+//! writer.write_all(b"hello, world!")?;
+//! ```
+//!
+//! [`StorageMedium`]: agb_save::StorageMedium
+//! [`VerifyingWriter`]: crate::adapters::VerifyingWriter
+
+use crate::error_kind::Class;
+use agb_save::StorageMedium;
+use core::{cmp::min, fmt::Debug, ops};
+use embedded_io::{ErrorKind, ErrorType, Read, Write};
+
+/// An error that can occur when accessing an `agb` storage medium.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Error<E> {
+    /// The underlying storage medium returned an error.
+    Medium(E),
+
+    /// The requested range is not within the storage medium.
+    OutOfBounds,
+
+    /// The write is not aligned to the storage medium's write size.
+    Unaligned,
+
+    /// The writer has exhausted all of its space.
+    ///
+    /// This indicates that the range provided when creating the writer has been completely
+    /// exhausted.
+    EndOfWriter,
+}
+
+impl<E> embedded_io::Error for Error<E>
+where
+    E: Debug,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Medium(_) => ErrorKind::Other,
+            Self::OutOfBounds => Class::InvalidInput.kind(),
+            Self::Unaligned => Class::InvalidInput.kind(),
+            Self::EndOfWriter => Class::EndOfWriter.kind(),
+        }
+    }
+}
+
+fn check_range<S>(medium: &S, range: &ops::Range<usize>) -> Result<(), Error<S::Error>>
+where
+    S: StorageMedium,
+{
+    if range.start > range.end || range.end > medium.info().size {
+        Err(Error::OutOfBounds)
+    } else {
+        Ok(())
+    }
+}
+
+/// A reader over a range of an `agb` storage medium.
+#[derive(Debug)]
+pub struct Reader<'a, S> {
+    medium: &'a mut S,
+    offset: usize,
+    len: usize,
+}
+
+impl<'a, S> Reader<'a, S>
+where
+    S: StorageMedium,
+{
+    /// Returns a reader over the given range of `medium`.
+    pub fn new(medium: &'a mut S, range: ops::Range<usize>) -> Result<Self, Error<S::Error>> {
+        check_range(medium, &range)?;
+        Ok(Self {
+            medium,
+            offset: range.start,
+            len: range.end - range.start,
+        })
+    }
+}
+
+impl<S> ErrorType for Reader<'_, S>
+where
+    S: StorageMedium,
+    S::Error: Debug,
+{
+    type Error = Error<S::Error>;
+}
+
+impl<S> Read for Reader<'_, S>
+where
+    S: StorageMedium,
+    S::Error: Debug,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let read_count = min(buf.len(), self.len);
+        if read_count == 0 {
+            return Ok(0);
+        }
+        self.medium
+            .read(self.offset, &mut buf[..read_count])
+            .map_err(Error::Medium)?;
+        self.offset += read_count;
+        self.len -= read_count;
+        Ok(read_count)
+    }
+}
+
+/// A writer over a range of an `agb` storage medium.
+///
+/// Each call to [`write()`] is passed directly to the storage medium. For media that require
+/// erasing, the range must be erased before writing. For media with a write size larger than one
+/// byte, every write must begin and end on a multiple of the write size, or else
+/// [`Error::Unaligned`] is returned.
+///
+/// [`write()`]: Write::write()
+#[derive(Debug)]
+pub struct Writer<'a, S> {
+    medium: &'a mut S,
+    offset: usize,
+    len: usize,
+}
+
+impl<'a, S> Writer<'a, S>
+where
+    S: StorageMedium,
+{
+    /// Returns a writer over the given range of `medium`.
+    pub fn new(medium: &'a mut S, range: ops::Range<usize>) -> Result<Self, Error<S::Error>> {
+        check_range(medium, &range)?;
+        Ok(Self {
+            medium,
+            offset: range.start,
+            len: range.end - range.start,
+        })
+    }
+}
+
+impl<S> ErrorType for Writer<'_, S>
+where
+    S: StorageMedium,
+    S::Error: Debug,
+{
+    type Error = Error<S::Error>;
+}
+
+impl<S> Write for Writer<'_, S>
+where
+    S: StorageMedium,
+    S::Error: Debug,
+{
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.len == 0 {
+            return Err(Error::EndOfWriter);
+        }
+        let write_count = min(buf.len(), self.len);
+        let write_size = self.medium.info().write_size.get();
+        if !self.offset.is_multiple_of(write_size) || !write_count.is_multiple_of(write_size) {
+            return Err(Error::Unaligned);
+        }
+        self.medium
+            .write(self.offset, &buf[..write_count])
+            .map_err(Error::Medium)?;
+        self.offset += write_count;
+        self.len -= write_count;
+        Ok(write_count)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Reader, Writer};
+    use agb_save::{StorageInfo, StorageMedium};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use core::num::NonZeroUsize;
+    use embedded_io::{Read, Write};
+    use gba_test::test;
+
+    /// A storage medium backed by an in-memory buffer.
+    struct Buffer {
+        data: [u8; 64],
+        write_size: usize,
+    }
+
+    impl Buffer {
+        fn new(write_size: usize) -> Self {
+            Self {
+                data: [0xff; 64],
+                write_size,
+            }
+        }
+    }
+
+    impl StorageMedium for Buffer {
+        type Error = ();
+
+        fn info(&self) -> StorageInfo {
+            StorageInfo {
+                size: self.data.len(),
+                erase_size: None,
+                write_size: NonZeroUsize::new(self.write_size).unwrap(),
+            }
+        }
+
+        fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), Self::Error> {
+            buf.copy_from_slice(self.data.get(offset..offset + buf.len()).ok_or(())?);
+            Ok(())
+        }
+
+        fn erase(&mut self, _offset: usize, _len: usize) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Self::Error> {
+            self.data
+                .get_mut(offset..offset + data.len())
+                .ok_or(())?
+                .copy_from_slice(data);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reader_out_of_bounds() {
+        let mut buffer = Buffer::new(1);
+
+        assert_err_eq!(
+            Reader::new(&mut buffer, 0..65).map(|_| ()),
+            Error::OutOfBounds
+        );
+    }
+
+    #[test]
+    fn writer_out_of_bounds() {
+        let mut buffer = Buffer::new(1);
+
+        assert_err_eq!(
+            Writer::new(&mut buffer, 60..65).map(|_| ()),
+            Error::OutOfBounds
+        );
+    }
+
+    #[test]
+    fn read() {
+        let mut buffer = Buffer::new(1);
+        buffer.data[10..15].copy_from_slice(b"hello");
+        let mut reader = assert_ok!(Reader::new(&mut buffer, 10..14));
+        let mut buf = [0; 8];
+
+        assert_ok_eq!(reader.read(&mut buf), 4);
+        assert_eq!(buf, [b'h', b'e', b'l', b'l', 0, 0, 0, 0]);
+        assert_ok_eq!(reader.read(&mut buf), 0);
+    }
+
+    #[test]
+    fn write() {
+        let mut buffer = Buffer::new(1);
+        let mut writer = assert_ok!(Writer::new(&mut buffer, 10..14));
+
+        assert_ok_eq!(writer.write(b"hello"), 4);
+        assert_err_eq!(writer.write(b"o"), Error::EndOfWriter);
+        assert_eq!(&buffer.data[9..15], &[0xff, b'h', b'e', b'l', b'l', 0xff]);
+    }
+
+    #[test]
+    fn write_empty() {
+        let mut buffer = Buffer::new(1);
+        let mut writer = assert_ok!(Writer::new(&mut buffer, 10..10));
+
+        assert_ok_eq!(writer.write(&[]), 0);
+    }
+
+    #[test]
+    fn write_aligned() {
+        let mut buffer = Buffer::new(8);
+        let mut writer = assert_ok!(Writer::new(&mut buffer, 8..24));
+
+        assert_ok_eq!(writer.write(&[1; 8]), 8);
+        assert_ok_eq!(writer.write(&[2; 8]), 8);
+        assert_eq!(
+            &buffer.data[8..24],
+            &[1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2]
+        );
+    }
+
+    #[test]
+    fn write_unaligned_offset() {
+        let mut buffer = Buffer::new(8);
+        let mut writer = assert_ok!(Writer::new(&mut buffer, 4..24));
+
+        assert_err_eq!(writer.write(&[1; 8]), Error::Unaligned);
+    }
+
+    #[test]
+    fn write_unaligned_len() {
+        let mut buffer = Buffer::new(8);
+        let mut writer = assert_ok!(Writer::new(&mut buffer, 8..24));
+
+        assert_err_eq!(writer.write(&[1; 4]), Error::Unaligned);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{error_kind::Class, flash::UnknownDeviceID};
+use embedded_io::ErrorKind;
+
+/// The backup device selected by the `backend-*` features.
+///
+/// See [`default_backup()`] for details.
+#[cfg(all(feature = "backend-sram", not(feature = "backend-flash-128k")))]
+pub type DefaultBackup = crate::sram::Sram;
+
+/// The backup device selected by the `backend-*` features.
+///
+/// See [`default_backup()`] for details.
+#[cfg(all(feature = "backend-flash-128k", not(feature = "backend-sram")))]
+pub type DefaultBackup = crate::flash::Flash128K;
+
+/// An error returned by `default_backup()`.
+///
+/// `default_backup()` is only available when one of the `backend-*` features is enabled.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DefaultBackupError {
+    /// No known flash device was detected.
+    UnknownDevice(UnknownDeviceID),
+
+    /// A different type of backup device was detected than the one selected.
+    WrongDevice {
+        /// The name of the device that was detected.
+        name: &'static str,
+    },
+}
+
+impl embedded_io::Error for DefaultBackupError {
+    fn kind(&self) -> ErrorKind {
+        Class::NoDevice.kind()
+    }
+}
+
+impl From<UnknownDeviceID> for DefaultBackupError {
+    fn from(error: UnknownDeviceID) -> Self {
+        Self::UnknownDevice(error)
+    }
+}
+
+/// Returns the backup device selected by the `backend-*` features.
+///
+/// Enabling exactly one of the following features defines this function, along with the
+/// [`DefaultBackup`] alias for the type it returns:
+///
+/// | Feature              | Device                                 |
+/// | -------------------- | -------------------------------------- |
+/// | `backend-sram`       | [`Sram`](crate::sram::Sram)            |
+/// | `backend-flash-128k` | [`Flash128K`](crate::flash::Flash128K) |
+///
+/// If none or more than one of these features is enabled, neither is defined.
+///
+/// Code written against [`DefaultBackup`] can be moved between devices by changing which feature
+/// is enabled. This is intended for development carts that provide both SRAM and flash, where
+/// SRAM is used during development and flash is used for release. Only the API shared by both
+/// devices, such as `reader()`, `writer()`, `try_reader()`, `try_writer()`, `name()`, and
+/// `capacity()`, should be relied upon. Note that flash must still be erased before it is written.
+///
+/// SRAM cannot be detected, so with `backend-sram` this always succeeds. With
+/// `backend-flash-128k`, an error is returned if the flash device is not recognized or is not a
+/// 128KiB device.
+///
+/// # Safety
+/// Must have exclusive ownership of both backup memory and WAITCNT's SRAM wait control setting
+/// for the duration of the returned device's lifetime.
+#[cfg(any(
+    all(feature = "backend-sram", not(feature = "backend-flash-128k")),
+    all(feature = "backend-flash-128k", not(feature = "backend-sram")),
+))]
+pub unsafe fn default_backup() -> Result<DefaultBackup, DefaultBackupError> {
+    #[cfg(feature = "backend-sram")]
+    {
+        Ok(unsafe { crate::sram::Sram::new() })
+    }
+    #[cfg(feature = "backend-flash-128k")]
+    {
+        use crate::flash::Flash;
+
+        match unsafe { Flash::new() }? {
+            Flash::Flash128K(flash_128k) => Ok(flash_128k),
+            flash => Err(DefaultBackupError::WrongDevice { name: flash.name() }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DefaultBackupError, UnknownDeviceID};
+    use embedded_io::{Error, ErrorKind};
+    use gba_test::test;
+
+    #[test]
+    fn error_kinds() {
+        assert_eq!(
+            DefaultBackupError::from(UnknownDeviceID(0xffff)).kind(),
+            ErrorKind::NotConnected
+        );
+        assert_eq!(
+            DefaultBackupError::WrongDevice {
+                name: "Macronix MX29L512"
+            }
+            .kind(),
+            ErrorKind::NotConnected
+        );
+    }
+
+    #[cfg(any(
+        all(feature = "backend-sram", not(feature = "backend-flash-128k")),
+        all(feature = "backend-flash-128k", not(feature = "backend-sram")),
+    ))]
+    mod selected {
+        use crate::{default_backup, DefaultBackup};
+        use claims::{assert_ok, assert_ok_eq};
+        use embedded_io::{Read, Write};
+        use gba_test::test;
+
+        /// Exercises the API shared by every backend.
+        fn round_trip(backup: &mut DefaultBackup) {
+            assert!(!backup.name().is_empty());
+            assert!(backup.capacity() >= 32768);
+
+            let mut writer = assert_ok!(backup.try_writer(0..4));
+            assert_ok!(writer.write_all(&[1, 2, 3, 4]));
+            assert_ok!(writer.flush());
+
+            let mut reader = assert_ok!(backup.try_reader(0..4));
+            let mut buf = [0; 4];
+            assert_ok_eq!(reader.read(&mut buf), 4);
+            assert_eq!(buf, [1, 2, 3, 4]);
+        }
+
+        #[test]
+        #[cfg(feature = "backend-sram")]
+        #[cfg_attr(
+            not(sram),
+            ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+        )]
+        fn default_backup_sram() {
+            let mut backup = assert_ok!(unsafe { default_backup() });
+
+            round_trip(&mut backup);
+        }
+
+        #[test]
+        #[cfg(feature = "backend-flash-128k")]
+        #[cfg_attr(
+            not(flash_128k),
+            ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+        )]
+        fn default_backup_flash_128k() {
+            let mut backup = assert_ok!(unsafe { default_backup() });
+            assert_ok!(backup.erase_sectors(..=deranged::RangedU8::new_static::<0>()));
+
+            round_trip(&mut backup);
+        }
+    }
+}
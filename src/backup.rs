@@ -0,0 +1,742 @@
+use crate::{
+    error_kind::Class,
+    flash::{self, Flash, Flash128K, Flash64K, SECTOR_SIZE},
+    range::{FlashSector128K, FlashSector64K, RangeError},
+    sram::Sram,
+    Error, Progress,
+};
+use core::{cmp::min, ops};
+use embedded_io::{ErrorKind, Read, ReadExactError, Write};
+
+/// The number of bytes transferred at a time by [`dump()`] and [`restore()`].
+pub const TRANSFER_CHUNK_SIZE: usize = 1024;
+
+/// Any of the backup devices supported by this crate.
+///
+/// This is used by [`save_all()`] and [`load_all()`] to save and load data in the same way
+/// regardless of which device is present.
+pub enum Backup {
+    /// SRAM.
+    Sram(Sram),
+    /// Flash memory.
+    Flash(Flash),
+}
+
+impl Backup {
+    /// Returns a human-readable name for the backup device.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sram(sram) => sram.name(),
+            Self::Flash(flash) => flash.name(),
+        }
+    }
+
+    /// Returns the number of bytes of backup memory.
+    pub fn capacity(&self) -> usize {
+        match self {
+            Self::Sram(sram) => sram.capacity(),
+            Self::Flash(flash) => flash.capacity(),
+        }
+    }
+}
+
+impl From<Sram> for Backup {
+    fn from(sram: Sram) -> Self {
+        Self::Sram(sram)
+    }
+}
+
+impl From<Flash> for Backup {
+    fn from(flash: Flash) -> Self {
+        Self::Flash(flash)
+    }
+}
+
+/// A flash device that must be erased a sector at a time.
+trait SectorErase {
+    /// Erases a single sector.
+    fn erase_sector(&mut self, sector: u8) -> Result<(), flash::Error>;
+
+    /// Fills `buf` with the data at `range`.
+    fn read_into(&self, range: ops::Range<usize>, buf: &mut [u8]) -> Result<(), RangeError>;
+
+    /// Writes `buf` to `range` and reads it back to check it.
+    fn write_verified(&mut self, range: ops::Range<usize>, buf: &[u8]) -> Result<(), Error>;
+}
+
+impl SectorErase for Flash64K {
+    fn erase_sector(&mut self, sector: u8) -> Result<(), flash::Error> {
+        let sector = FlashSector64K::new_saturating(sector);
+        self.erase_sectors(sector..=sector).map(|_| ())
+    }
+
+    fn read_into(&self, range: ops::Range<usize>, buf: &mut [u8]) -> Result<(), RangeError> {
+        let Ok(_) = self.try_reader(range)?.read(buf);
+        Ok(())
+    }
+
+    fn write_verified(&mut self, range: ops::Range<usize>, buf: &[u8]) -> Result<(), Error> {
+        Ok(self.try_writer(range)?.write_verified(buf)?)
+    }
+}
+
+impl SectorErase for Flash128K {
+    fn erase_sector(&mut self, sector: u8) -> Result<(), flash::Error> {
+        let sector = FlashSector128K::new_saturating(sector);
+        self.erase_sectors(sector..=sector).map(|_| ())
+    }
+
+    fn read_into(&self, range: ops::Range<usize>, buf: &mut [u8]) -> Result<(), RangeError> {
+        let Ok(_) = self.try_reader(range)?.read(buf);
+        Ok(())
+    }
+
+    fn write_verified(&mut self, range: ops::Range<usize>, buf: &[u8]) -> Result<(), Error> {
+        Ok(self.try_writer(range)?.write_verified(buf)?)
+    }
+}
+
+/// Saves `data` to a flash device that must be erased before writing.
+///
+/// Each sector covered by `range` is erased and reprogrammed in turn. Sectors only partially
+/// covered are first read into a buffer, so the bytes outside of `range` are preserved.
+fn save_sectors<F>(flash: &mut F, range: ops::Range<usize>, data: &[u8]) -> Result<(), Error>
+where
+    F: SectorErase,
+{
+    // Check the range up front, so nothing is erased if it is out of bounds.
+    flash.read_into(range.clone(), &mut [])?;
+    if data.is_empty() {
+        return Ok(());
+    }
+
+    let mut buf = [0; SECTOR_SIZE];
+    for sector in range.start / SECTOR_SIZE..range.end.div_ceil(SECTOR_SIZE) {
+        let sector_range = sector * SECTOR_SIZE..(sector + 1) * SECTOR_SIZE;
+        let start = range.start.max(sector_range.start);
+        let end = range.end.min(sector_range.end);
+        let chunk = data
+            .get(start - range.start..end - range.start)
+            .unwrap_or(&[]);
+        if start == sector_range.start && end == sector_range.end {
+            flash.erase_sector(sector as u8)?;
+            flash.write_verified(sector_range, chunk)?;
+        } else {
+            flash.read_into(sector_range.clone(), &mut buf)?;
+            if let Some(overlay) =
+                buf.get_mut(start % SECTOR_SIZE..(start % SECTOR_SIZE) + chunk.len())
+            {
+                overlay.copy_from_slice(chunk);
+            }
+            flash.erase_sector(sector as u8)?;
+            flash.write_verified(sector_range, &buf)?;
+        }
+    }
+    Ok(())
+}
+
+/// Saves `data` at `offset` within the backup memory, whatever the type of backup device.
+///
+/// This performs every step needed to store `data` correctly on the device, and then reads it back
+/// to check it:
+///
+/// - On SRAM, `data` is written directly.
+/// - On 64KiB Atmel flash, `data` is written through a writer that preserves the rest of each
+///   128-byte page it programs.
+/// - On other flash devices, each 4KiB sector covering `data` is erased and reprogrammed. Bytes in
+///   those sectors that are outside of `data` are read first and written back, so only the bytes
+///   at `offset..offset + data.len()` change.
+///
+/// If the range is beyond the end of the backup memory, [`Error::Range`] is returned before
+/// anything is written. If reading the data back finds a mismatch, a `VerifyMismatch` error is
+/// returned.
+///
+/// Note that this is not atomic: if power is lost partway through, the affected range, and on
+/// flash the rest of the sectors covering it, may be left partially written. Saving to
+/// alternating slots, and loading whichever was saved most recently according to its
+/// [`SeqNum`](crate::seq::SeqNum), protects against this.
+///
+/// # Stack Usage
+/// Saving to 64KiB and 128KiB flash devices other than the Atmel device uses a 4KiB buffer on the
+/// stack to preserve the parts of partially covered sectors.
+///
+/// # Example
+/// ``` no_run
+/// use gba_save::{load_all, save_all, sram::Sram, Backup};
+///
+/// let mut backup = Backup::from(unsafe { Sram::new() });
+/// save_all(&mut backup, 100, b"hello, world!").expect("could not save");
+///
+/// let mut buf = [0; 13];
+/// load_all(&backup, 100, &mut buf).expect("could not load");
+/// ```
+pub fn save_all(backup: &mut Backup, offset: usize, data: &[u8]) -> Result<(), Error> {
+    let range = offset..offset.saturating_add(data.len());
+    match backup {
+        Backup::Sram(sram) => Ok(sram.try_writer(range)?.write_verified(data)?),
+        Backup::Flash(Flash::Flash64KAtmel(flash_64k_atmel)) => {
+            Ok(flash_64k_atmel.try_writer(range)?.write_verified(data)?)
+        }
+        Backup::Flash(Flash::Flash64K(flash_64k)) => save_sectors(flash_64k, range, data),
+        Backup::Flash(Flash::Flash128K(flash_128k)) => save_sectors(flash_128k, range, data),
+    }
+}
+
+/// Loads the data at `offset` within the backup memory into `buf`, whatever the type of backup
+/// device.
+///
+/// This is the counterpart to [`save_all()`]. If the range is beyond the end of the backup
+/// memory, [`Error::Range`] is returned and `buf` is left unchanged.
+pub fn load_all(backup: &Backup, offset: usize, buf: &mut [u8]) -> Result<(), Error> {
+    let range = offset..offset.saturating_add(buf.len());
+    let Ok(_) = match backup {
+        Backup::Sram(sram) => sram.try_reader(range)?.read(buf),
+        Backup::Flash(Flash::Flash64K(flash_64k)) => flash_64k.try_reader(range)?.read(buf),
+        Backup::Flash(Flash::Flash64KAtmel(flash_64k_atmel)) => {
+            flash_64k_atmel.try_reader(range)?.read(buf)
+        }
+        Backup::Flash(Flash::Flash128K(flash_128k)) => flash_128k.try_reader(range)?.read(buf),
+    };
+    Ok(())
+}
+
+/// An error returned by [`dump()`] or [`restore()`].
+///
+/// Every variant records the offset up to which the transfer was confirmed. Passing it as
+/// `resume_from` to another call continues the transfer from where it stopped.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TransferError<E> {
+    /// The stream returned an error.
+    Stream {
+        /// The offset up to which the transfer was confirmed.
+        offset: usize,
+        /// The error returned by the stream.
+        error: E,
+    },
+
+    /// The source ended before the rest of the backup memory was restored.
+    UnexpectedEof {
+        /// The offset up to which the transfer was confirmed.
+        offset: usize,
+    },
+
+    /// The backup device returned an error.
+    Device {
+        /// The offset up to which the transfer was confirmed.
+        offset: usize,
+        /// The error returned by the device.
+        error: Error,
+    },
+}
+
+impl<E> TransferError<E> {
+    /// Returns the offset up to which the transfer was confirmed.
+    ///
+    /// This is the `resume_from` offset to continue the transfer with.
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::Stream { offset, .. }
+            | Self::UnexpectedEof { offset }
+            | Self::Device { offset, .. } => *offset,
+        }
+    }
+}
+
+impl<E> embedded_io::Error for TransferError<E>
+where
+    E: embedded_io::Error,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Stream { error, .. } => error.kind(),
+            Self::UnexpectedEof { .. } => Class::Malformed.kind(),
+            Self::Device { error, .. } => error.kind(),
+        }
+    }
+}
+
+/// Returns the chunks from `resume_from` to `capacity`, each ending on a multiple of
+/// [`TRANSFER_CHUNK_SIZE`].
+fn chunks(resume_from: usize, capacity: usize) -> impl Iterator<Item = ops::Range<usize>> {
+    (resume_from / TRANSFER_CHUNK_SIZE..capacity.div_ceil(TRANSFER_CHUNK_SIZE)).map(move |chunk| {
+        resume_from.max(chunk * TRANSFER_CHUNK_SIZE)
+            ..min((chunk + 1) * TRANSFER_CHUNK_SIZE, capacity)
+    })
+}
+
+/// Checks that `resume_from` is within the backup memory.
+fn check_resume<E>(backup: &Backup, resume_from: usize) -> Result<(), TransferError<E>> {
+    if resume_from > backup.capacity() {
+        return Err(TransferError::Device {
+            offset: resume_from,
+            error: Error::Range(RangeError::OutOfBounds {
+                value: resume_from,
+                max: backup.capacity(),
+            }),
+        });
+    }
+    Ok(())
+}
+
+/// Writes the entire backup memory from `resume_from` onward to `sink`.
+///
+/// The memory is read and written in chunks of [`TRANSFER_CHUNK_SIZE`] bytes, with `progress` reported in
+/// bytes after each chunk is written. On success, the capacity of the backup memory is returned.
+///
+/// Passing a nonzero `resume_from` continues a previous dump, such as after a link error, without
+/// resending the data already confirmed. `sink` should then continue from the same point. On
+/// failure, the returned error records the offset to resume from. See [`TransferError`].
+///
+/// # Example
+/// ``` no_run
+/// use gba_save::{dump, sram::Sram, Backup};
+///
+/// let backup = Backup::from(unsafe { Sram::new() });
+/// let mut image = [0; 32768];
+/// dump(&backup, &mut &mut image[..], 0, |_| {}).expect("could not dump");
+/// ```
+pub fn dump<W, F>(
+    backup: &Backup,
+    sink: &mut W,
+    resume_from: usize,
+    mut progress: F,
+) -> Result<usize, TransferError<W::Error>>
+where
+    W: Write,
+    F: FnMut(Progress),
+{
+    check_resume(backup, resume_from)?;
+    let total = backup.capacity();
+    let mut buf = [0; TRANSFER_CHUNK_SIZE];
+    for chunk in chunks(resume_from, total) {
+        let offset = chunk.start;
+        let buf = buf.get_mut(..chunk.len()).unwrap_or_default();
+        load_all(backup, offset, buf).map_err(|error| TransferError::Device { offset, error })?;
+        sink.write_all(buf)
+            .map_err(|error| TransferError::Stream { offset, error })?;
+        progress(Progress {
+            completed: chunk.end,
+            total,
+        });
+    }
+    Ok(total)
+}
+
+/// Erases each sector starting within `range`, and then writes `chunk` to `range`.
+///
+/// Only sectors starting within `range` are erased, so a sector is erased when restoring reaches
+/// its start. This avoids erasing the part of a sector already restored before resuming.
+fn restore_chunk<F>(flash: &mut F, range: ops::Range<usize>, chunk: &[u8]) -> Result<(), Error>
+where
+    F: SectorErase,
+{
+    for sector in range.start.div_ceil(SECTOR_SIZE)..range.end.div_ceil(SECTOR_SIZE) {
+        flash.erase_sector(sector as u8)?;
+    }
+    flash.write_verified(range, chunk)
+}
+
+/// Restores the entire backup memory from `resume_from` onward by reading from `source`.
+///
+/// The memory is written in chunks of [`TRANSFER_CHUNK_SIZE`] bytes, each of which is read back to check
+/// it, with `progress` reported in bytes after each chunk is confirmed. On success, the capacity
+/// of the backup memory is returned.
+///
+/// On flash devices that must be erased before writing, each sector is erased when restoring
+/// reaches its start, rather than all at once beforehand.
+///
+/// Passing a nonzero `resume_from` continues a previous restore, such as after a link error. It
+/// should be the offset recorded in the error that stopped the previous restore, and `source`
+/// should continue from the same point. See [`TransferError`].
+pub fn restore<R, F>(
+    backup: &mut Backup,
+    source: &mut R,
+    resume_from: usize,
+    mut progress: F,
+) -> Result<usize, TransferError<R::Error>>
+where
+    R: Read,
+    F: FnMut(Progress),
+{
+    check_resume(backup, resume_from)?;
+    let total = backup.capacity();
+    let mut buf = [0; TRANSFER_CHUNK_SIZE];
+    for range in chunks(resume_from, total) {
+        let offset = range.start;
+        let buf = buf.get_mut(..range.len()).unwrap_or_default();
+        source.read_exact(buf).map_err(|error| match error {
+            ReadExactError::UnexpectedEof => TransferError::UnexpectedEof { offset },
+            ReadExactError::Other(error) => TransferError::Stream { offset, error },
+        })?;
+        match backup {
+            Backup::Sram(sram) => sram
+                .try_writer(range.clone())
+                .map_err(Error::from)
+                .and_then(|mut writer| Ok(writer.write_verified(buf)?)),
+            Backup::Flash(Flash::Flash64KAtmel(flash_64k_atmel)) => flash_64k_atmel
+                .try_writer(range.clone())
+                .map_err(Error::from)
+                .and_then(|mut writer| Ok(writer.write_verified(buf)?)),
+            Backup::Flash(Flash::Flash64K(flash_64k)) => {
+                restore_chunk(flash_64k, range.clone(), buf)
+            }
+            Backup::Flash(Flash::Flash128K(flash_128k)) => {
+                restore_chunk(flash_128k, range.clone(), buf)
+            }
+        }
+        .map_err(|error| TransferError::Device { offset, error })?;
+        progress(Progress {
+            completed: range.end,
+            total,
+        });
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dump, load_all, restore, save_all, Backup, TransferError};
+    use crate::{
+        flash::Flash,
+        range::{FlashSector128K, FlashSector64K, RangeError},
+        sram::Sram,
+        Error, Progress,
+    };
+    use claims::{assert_err, assert_err_eq, assert_ok, assert_ok_eq};
+    use core::cmp::min;
+    use embedded_io::{ErrorKind, ErrorType, Read, Write};
+    use gba_test::test;
+
+    /// The offset of the test data, which is not aligned to a sector or page.
+    const OFFSET: usize = 4000;
+    /// The length of the test data, which covers part of a sector, a whole sector, and part of
+    /// another sector.
+    const LEN: usize = 5000;
+
+    fn data() -> [u8; LEN] {
+        let mut data = [0; LEN];
+        for (index, byte) in data.iter_mut().enumerate() {
+            *byte = index as u8 ^ 0x5a;
+        }
+        data
+    }
+
+    /// Writes markers on either side of the test data, saves the test data, and checks that the
+    /// markers and the data read back intact.
+    fn round_trip(backup: &mut Backup, offset: usize) {
+        assert_ok!(save_all(backup, offset - 8, b"before!!"));
+        assert_ok!(save_all(backup, offset + LEN, b"after!!!"));
+
+        let data = data();
+        assert_ok!(save_all(backup, offset, &data));
+
+        let mut buf = [0; LEN];
+        assert_ok!(load_all(backup, offset, &mut buf));
+        assert_eq!(buf, data);
+        let mut marker = [0; 8];
+        assert_ok!(load_all(backup, offset - 8, &mut marker));
+        assert_eq!(&marker, b"before!!");
+        assert_ok!(load_all(backup, offset + LEN, &mut marker));
+        assert_eq!(&marker, b"after!!!");
+    }
+
+    /// Returns the byte of the test image at `offset`.
+    fn pattern(offset: usize) -> u8 {
+        (offset ^ (offset >> 8)) as u8
+    }
+
+    /// A stream of the test image, which fails once it reaches `fail_at`.
+    struct PatternStream {
+        position: usize,
+        fail_at: usize,
+        mismatches: usize,
+    }
+
+    impl PatternStream {
+        fn new(position: usize, fail_at: usize) -> Self {
+            Self {
+                position,
+                fail_at,
+                mismatches: 0,
+            }
+        }
+
+        fn remaining(&self) -> Result<usize, ErrorKind> {
+            match self.fail_at - self.position {
+                0 => Err(ErrorKind::Other),
+                remaining => Ok(remaining),
+            }
+        }
+    }
+
+    impl ErrorType for PatternStream {
+        type Error = ErrorKind;
+    }
+
+    impl Read for PatternStream {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let count = min(buf.len(), self.remaining()?);
+            for (index, byte) in buf[..count].iter_mut().enumerate() {
+                *byte = pattern(self.position + index);
+            }
+            self.position += count;
+            Ok(count)
+        }
+    }
+
+    impl Write for PatternStream {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            let count = min(buf.len(), self.remaining()?);
+            for (index, &byte) in buf[..count].iter().enumerate() {
+                if byte != pattern(self.position + index) {
+                    self.mismatches += 1;
+                }
+            }
+            self.position += count;
+            Ok(count)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    /// Restores the test image with a failure partway through the second sector, resumes the
+    /// restore, and checks that the whole image dumps intact.
+    fn restore_with_resume(backup: &mut Backup) {
+        let capacity = backup.capacity();
+
+        let error = assert_err!(restore(backup, &mut PatternStream::new(0, 5000), 0, |_| {}));
+        assert_eq!(
+            error,
+            TransferError::Stream {
+                offset: 4096,
+                error: ErrorKind::Other
+            }
+        );
+        let mut last = None;
+        assert_ok_eq!(
+            restore(
+                backup,
+                &mut PatternStream::new(error.offset(), usize::MAX),
+                error.offset(),
+                |progress| last = Some(progress)
+            ),
+            capacity
+        );
+        assert_eq!(
+            last,
+            Some(Progress {
+                completed: capacity,
+                total: capacity
+            })
+        );
+
+        let mut sink = PatternStream::new(0, usize::MAX);
+        assert_ok_eq!(dump(backup, &mut sink, 0, |_| {}), capacity);
+        assert_eq!(sink.position, capacity);
+        assert_eq!(sink.mismatches, 0);
+    }
+
+    #[test]
+    fn save_out_of_bounds() {
+        let mut backup = Backup::from(unsafe { Sram::new() });
+
+        assert_err_eq!(
+            save_all(&mut backup, 32000, &[0; 1000]),
+            Error::Range(RangeError::OutOfBounds {
+                value: 33000,
+                max: 32768
+            })
+        );
+    }
+
+    #[test]
+    fn load_out_of_bounds() {
+        let backup = Backup::from(unsafe { Sram::new() });
+
+        assert_err_eq!(
+            load_all(&backup, 32000, &mut [0; 1000]),
+            Error::Range(RangeError::OutOfBounds {
+                value: 33000,
+                max: 32768
+            })
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn round_trip_sram() {
+        let mut backup = Backup::from(unsafe { Sram::new() });
+
+        round_trip(&mut backup, OFFSET);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn round_trip_flash_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        let Flash::Flash64K(flash_64k) = &mut flash else {
+            panic!("not a Flash 64KiB chip");
+        };
+        assert_ok!(flash_64k
+            .erase_sectors(FlashSector64K::new_static::<0>()..=FlashSector64K::new_static::<2>()));
+        let mut backup = Backup::from(flash);
+
+        round_trip(&mut backup, OFFSET);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn round_trip_flash_64k_atmel() {
+        let mut backup = Backup::from(assert_ok!(unsafe { Flash::new() }));
+
+        round_trip(&mut backup, OFFSET);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn round_trip_flash_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        let Flash::Flash128K(flash_128k) = &mut flash else {
+            panic!("not a Flash 128KiB chip");
+        };
+        assert_ok!(flash_128k.erase_sectors(
+            FlashSector128K::new_static::<15>()..=FlashSector128K::new_static::<16>()
+        ));
+        let mut backup = Backup::from(flash);
+
+        // Cross the boundary between the two banks.
+        round_trip(&mut backup, 65536 - 2000);
+    }
+
+    #[test]
+    fn dump_out_of_bounds() {
+        let backup = Backup::from(unsafe { Sram::new() });
+
+        assert_err_eq!(
+            dump(
+                &backup,
+                &mut PatternStream::new(0, usize::MAX),
+                40000,
+                |_| {}
+            ),
+            TransferError::Device {
+                offset: 40000,
+                error: Error::Range(RangeError::OutOfBounds {
+                    value: 40000,
+                    max: 32768
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn transfer_error_offset() {
+        assert_eq!(
+            TransferError::Stream {
+                offset: 1024,
+                error: ErrorKind::Other
+            }
+            .offset(),
+            1024
+        );
+        assert_eq!(
+            TransferError::<ErrorKind>::UnexpectedEof { offset: 2048 }.offset(),
+            2048
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn dump_with_resume_sram() {
+        let mut backup = Backup::from(unsafe { Sram::new() });
+        assert_ok!(restore(
+            &mut backup,
+            &mut PatternStream::new(0, usize::MAX),
+            0,
+            |_| {}
+        ));
+
+        let error = assert_err!(dump(&backup, &mut PatternStream::new(0, 5000), 0, |_| {}));
+        assert_eq!(error.offset(), 4096);
+        let mut sink = PatternStream::new(error.offset(), usize::MAX);
+
+        assert_ok_eq!(dump(&backup, &mut sink, error.offset(), |_| {}), 32768);
+        assert_eq!(sink.position, 32768);
+        assert_eq!(sink.mismatches, 0);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn restore_unexpected_eof_sram() {
+        let mut backup = Backup::from(unsafe { Sram::new() });
+
+        assert_err_eq!(
+            restore(&mut backup, &mut &[0; 3000][..], 0, |_| {}),
+            TransferError::UnexpectedEof { offset: 2048 }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn restore_with_resume_sram() {
+        let mut backup = Backup::from(unsafe { Sram::new() });
+
+        restore_with_resume(&mut backup);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn restore_with_resume_flash_64k() {
+        let mut backup = Backup::from(assert_ok!(unsafe { Flash::new() }));
+
+        restore_with_resume(&mut backup);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn restore_with_resume_flash_64k_atmel() {
+        let mut backup = Backup::from(assert_ok!(unsafe { Flash::new() }));
+
+        restore_with_resume(&mut backup);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn restore_with_resume_flash_128k() {
+        let mut backup = Backup::from(assert_ok!(unsafe { Flash::new() }));
+
+        restore_with_resume(&mut backup);
+    }
+}
@@ -0,0 +1,443 @@
+//! Checking for an existing save at boot.
+//!
+//! Most games start by deciding whether to offer "Continue" or "New Game". [`probe_save()`] reads
+//! a [`SaveHeader`] from the start of the save and reports whether a save exists and whether it
+//! can be loaded. [`initialize_save()`] writes a new header and payload for the "New Game" path.
+//!
+//! ``` no_run
+//! use gba_save::{
+//!     boot::{self, Medium, SaveStatus},
+//!     sram::Sram,
+//! };
+//!
+//! const MAGIC: [u8; 4] = *b"MYGM";
+//!
+//! let mut sram = unsafe { Sram::new() };
+//! match boot::probe_save(&mut sram.reader(..), Medium::Sram, MAGIC, 1..=2) {
+//!     Ok(SaveStatus::Valid(_header)) => {
+//!         // Load the save, skipping the header.
+//!     }
+//!     _ => {
+//!         boot::initialize_save(&mut sram.writer(..), MAGIC, 2, &[0; 64])
+//!             .expect("could not create save");
+//!     }
+//! }
+//! ```
+//!
+//! A save consists of a [`SaveHeader::SIZE`]-byte header followed directly by its payload. The
+//! header identifies the game using a 4-byte magic value, records the layout version and the
+//! length of the payload, and holds a Fletcher-16 checksum of the payload.
+
+use core::ops::RangeBounds;
+use embedded_io::{Read, ReadExactError, Write};
+
+/// The type of backup memory a save is stored on.
+///
+/// This determines what backup memory that has never been saved to looks like.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Medium {
+    /// SRAM, which may be filled with either `0x00` or `0xff` from the factory.
+    Sram,
+    /// Flash, which is filled with `0xff` when erased.
+    Flash,
+}
+
+impl Medium {
+    /// Returns whether `bytes` look like memory that has never been saved to.
+    fn is_blank(self, bytes: &[u8]) -> bool {
+        let all = |value| bytes.iter().all(|&byte| byte == value);
+        match self {
+            Self::Sram => all(0x00) || all(0xff),
+            Self::Flash => all(0xff),
+        }
+    }
+}
+
+/// The header stored at the start of a save.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SaveHeader {
+    /// A value identifying the game the save belongs to.
+    pub magic: [u8; 4],
+    /// The version of the save's layout.
+    pub version: u16,
+    /// The number of bytes in the payload following the header.
+    pub len: u16,
+    /// The Fletcher-16 checksum of the payload.
+    pub checksum: u16,
+}
+
+impl SaveHeader {
+    /// The number of bytes used to store a header.
+    pub const SIZE: usize = 10;
+
+    /// Creates a header describing `payload`.
+    ///
+    /// # Panics
+    /// Panics if `payload` is longer than `u16::MAX` bytes.
+    pub fn new(magic: [u8; 4], version: u16, payload: &[u8]) -> Self {
+        let mut checksum = Fletcher16::new();
+        checksum.update(payload);
+        Self {
+            magic,
+            version,
+            len: payload
+                .len()
+                .try_into()
+                .expect("payload is too long for a save header"),
+            checksum: checksum.finish(),
+        }
+    }
+
+    fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[..4].copy_from_slice(&self.magic);
+        bytes[4..6].copy_from_slice(&self.version.to_le_bytes());
+        bytes[6..8].copy_from_slice(&self.len.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.checksum.to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        Self {
+            magic: [bytes[0], bytes[1], bytes[2], bytes[3]],
+            version: u16::from_le_bytes([bytes[4], bytes[5]]),
+            len: u16::from_le_bytes([bytes[6], bytes[7]]),
+            checksum: u16::from_le_bytes([bytes[8], bytes[9]]),
+        }
+    }
+}
+
+/// The state of the save found by [`probe_save()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SaveStatus {
+    /// Nothing has been saved.
+    Absent,
+    /// A save with the expected magic value and a supported version, whose payload matches its
+    /// checksum.
+    Valid(SaveHeader),
+    /// A save with the expected magic value and a supported version, whose payload does not match
+    /// its checksum.
+    ///
+    /// This is also returned if the payload extends beyond the end of the reader.
+    CorruptChecksum(SaveHeader),
+    /// A save with the expected magic value, but an unsupported version.
+    WrongVersion(SaveHeader),
+    /// Data that is neither blank nor a save with the expected magic value.
+    Unrecognized,
+}
+
+/// The Fletcher-16 checksum.
+pub(crate) struct Fletcher16 {
+    sum: u16,
+    sum_of_sums: u16,
+}
+
+impl Fletcher16 {
+    pub(crate) fn new() -> Self {
+        Self {
+            sum: 0,
+            sum_of_sums: 0,
+        }
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.sum = (self.sum + byte as u16) % 255;
+            self.sum_of_sums = (self.sum_of_sums + self.sum) % 255;
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u16 {
+        self.sum_of_sums << 8 | self.sum
+    }
+}
+
+/// Reads the save at the start of `reader` and reports its status.
+///
+/// The header is read first. If it is blank for the given `medium`, the save is
+/// [`Absent`](SaveStatus::Absent). Otherwise, its magic value and version are checked, and then
+/// its payload is read and checked against its checksum. The reader is left positioned after
+/// whatever was read.
+///
+/// An error is returned if the reader fails, or if it ends before the end of the header.
+pub fn probe_save<R, Versions>(
+    reader: &mut R,
+    medium: Medium,
+    expected_magic: [u8; 4],
+    expected_versions: Versions,
+) -> Result<SaveStatus, ReadExactError<R::Error>>
+where
+    R: Read,
+    Versions: RangeBounds<u16>,
+{
+    let mut bytes = [0; SaveHeader::SIZE];
+    reader.read_exact(&mut bytes)?;
+    if medium.is_blank(&bytes) {
+        return Ok(SaveStatus::Absent);
+    }
+    let header = SaveHeader::from_bytes(bytes);
+    if header.magic != expected_magic {
+        return Ok(SaveStatus::Unrecognized);
+    }
+    if !expected_versions.contains(&header.version) {
+        return Ok(SaveStatus::WrongVersion(header));
+    }
+
+    let mut checksum = Fletcher16::new();
+    let mut chunk = [0; 32];
+    let mut remaining = header.len as usize;
+    while remaining > 0 {
+        let len = remaining.min(chunk.len());
+        match reader.read_exact(&mut chunk[..len]) {
+            Ok(()) => {}
+            Err(ReadExactError::UnexpectedEof) => return Ok(SaveStatus::CorruptChecksum(header)),
+            Err(error) => return Err(error),
+        }
+        checksum.update(&chunk[..len]);
+        remaining -= len;
+    }
+    if checksum.finish() == header.checksum {
+        Ok(SaveStatus::Valid(header))
+    } else {
+        Ok(SaveStatus::CorruptChecksum(header))
+    }
+}
+
+/// Writes a new save consisting of a header and `payload`, then flushes the writer.
+///
+/// Returns the header that was written. The writer must start at the same offset that
+/// [`probe_save()`] reads from, and on flash it must cover erased memory.
+///
+/// # Panics
+/// Panics if `payload` is longer than `u16::MAX` bytes.
+pub fn initialize_save<W>(
+    writer: &mut W,
+    magic: [u8; 4],
+    version: u16,
+    payload: &[u8],
+) -> Result<SaveHeader, W::Error>
+where
+    W: Write,
+{
+    let header = SaveHeader::new(magic, version, payload);
+    writer.write_all(&header.to_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()?;
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{initialize_save, probe_save, Fletcher16, Medium, SaveHeader, SaveStatus};
+    use crate::{
+        flash::Flash,
+        range::{FlashRegion64K, SramRegion},
+        sram::Sram,
+    };
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use embedded_io::{ReadExactError, Write};
+    use gba_test::test;
+
+    const MAGIC: [u8; 4] = *b"TEST";
+
+    /// Returns a buffer holding a save with the given version and payload.
+    fn save(version: u16, payload: &[u8]) -> [u8; 64] {
+        let mut buf = [0xff; 64];
+        assert_ok!(initialize_save(&mut &mut buf[..], MAGIC, version, payload));
+        buf
+    }
+
+    #[test]
+    fn fletcher16() {
+        let mut checksum = Fletcher16::new();
+        checksum.update(b"abcde");
+
+        assert_eq!(checksum.finish(), 0xc8f0);
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let header = SaveHeader::new(MAGIC, 3, b"hello");
+
+        assert_eq!(SaveHeader::from_bytes(header.to_bytes()), header);
+    }
+
+    #[test]
+    fn probe_absent_flash() {
+        assert_ok_eq!(
+            probe_save(&mut &[0xff; 64][..], Medium::Flash, MAGIC, ..),
+            SaveStatus::Absent
+        );
+    }
+
+    #[test]
+    fn probe_zeroed_flash_is_unrecognized() {
+        assert_ok_eq!(
+            probe_save(&mut &[0x00; 64][..], Medium::Flash, MAGIC, ..),
+            SaveStatus::Unrecognized
+        );
+    }
+
+    #[test]
+    fn probe_absent_sram() {
+        assert_ok_eq!(
+            probe_save(&mut &[0x00; 64][..], Medium::Sram, MAGIC, ..),
+            SaveStatus::Absent
+        );
+        assert_ok_eq!(
+            probe_save(&mut &[0xff; 64][..], Medium::Sram, MAGIC, ..),
+            SaveStatus::Absent
+        );
+    }
+
+    #[test]
+    fn probe_valid() {
+        let buf = save(2, b"hello");
+
+        assert_ok_eq!(
+            probe_save(&mut &buf[..], Medium::Flash, MAGIC, 1..=2),
+            SaveStatus::Valid(SaveHeader::new(MAGIC, 2, b"hello"))
+        );
+    }
+
+    #[test]
+    fn probe_valid_empty_payload() {
+        let buf = save(1, &[]);
+
+        assert_ok_eq!(
+            probe_save(&mut &buf[..], Medium::Sram, MAGIC, ..),
+            SaveStatus::Valid(SaveHeader::new(MAGIC, 1, &[]))
+        );
+    }
+
+    #[test]
+    fn probe_corrupt_checksum() {
+        let mut buf = save(2, b"hello");
+        buf[SaveHeader::SIZE + 1] ^= 1;
+
+        assert_ok_eq!(
+            probe_save(&mut &buf[..], Medium::Flash, MAGIC, 1..=2),
+            SaveStatus::CorruptChecksum(SaveHeader::new(MAGIC, 2, b"hello"))
+        );
+    }
+
+    #[test]
+    fn probe_payload_beyond_reader() {
+        let buf = save(2, &[1; 40]);
+
+        assert_ok_eq!(
+            probe_save(&mut &buf[..30], Medium::Flash, MAGIC, ..),
+            SaveStatus::CorruptChecksum(SaveHeader::new(MAGIC, 2, &[1; 40]))
+        );
+    }
+
+    #[test]
+    fn probe_wrong_version() {
+        let buf = save(3, b"hello");
+
+        assert_ok_eq!(
+            probe_save(&mut &buf[..], Medium::Flash, MAGIC, 1..=2),
+            SaveStatus::WrongVersion(SaveHeader::new(MAGIC, 3, b"hello"))
+        );
+    }
+
+    #[test]
+    fn probe_unrecognized() {
+        let buf = save(2, b"hello");
+
+        assert_ok_eq!(
+            probe_save(&mut &buf[..], Medium::Flash, *b"OTHR", ..),
+            SaveStatus::Unrecognized
+        );
+    }
+
+    #[test]
+    fn probe_reader_too_short() {
+        assert_err_eq!(
+            probe_save(&mut &[0xff; 4][..], Medium::Flash, MAGIC, ..),
+            ReadExactError::UnexpectedEof
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn sram_statuses() {
+        let mut sram = unsafe { Sram::new() };
+        assert_ok!(sram.writer(..).write_all(&[0; 64]));
+        assert_ok_eq!(
+            probe_save(&mut sram.reader(..), Medium::Sram, MAGIC, ..),
+            SaveStatus::Absent
+        );
+
+        let header = assert_ok!(initialize_save(&mut sram.writer(..), MAGIC, 2, b"hello"));
+        assert_ok_eq!(
+            probe_save(&mut sram.reader(..), Medium::Sram, MAGIC, 1..=2),
+            SaveStatus::Valid(header)
+        );
+        assert_ok_eq!(
+            probe_save(&mut sram.reader(..), Medium::Sram, MAGIC, 3..),
+            SaveStatus::WrongVersion(header)
+        );
+        assert_ok_eq!(
+            probe_save(&mut sram.reader(..), Medium::Sram, *b"OTHR", ..),
+            SaveStatus::Unrecognized
+        );
+
+        assert_ok!(sram
+            .writer(SramRegion::new(SaveHeader::SIZE, 1))
+            .write_all(b"j"));
+        assert_ok_eq!(
+            probe_save(&mut sram.reader(..), Medium::Sram, MAGIC, 1..=2),
+            SaveStatus::CorruptChecksum(header)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn flash_statuses() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let Flash::Flash64K(mut flash_64k) = flash else {
+            panic!("expected a Flash 64KiB device");
+        };
+        assert_ok_eq!(
+            probe_save(&mut flash_64k.reader(..), Medium::Flash, MAGIC, ..),
+            SaveStatus::Absent
+        );
+
+        let header = assert_ok!(initialize_save(
+            &mut flash_64k.writer(..),
+            MAGIC,
+            2,
+            b"hello"
+        ));
+        assert_ok_eq!(
+            probe_save(&mut flash_64k.reader(..), Medium::Flash, MAGIC, 1..=2),
+            SaveStatus::Valid(header)
+        );
+        assert_ok_eq!(
+            probe_save(&mut flash_64k.reader(..), Medium::Flash, MAGIC, 3..),
+            SaveStatus::WrongVersion(header)
+        );
+        assert_ok_eq!(
+            probe_save(&mut flash_64k.reader(..), Medium::Flash, *b"OTHR", ..),
+            SaveStatus::Unrecognized
+        );
+
+        // Programming can only clear bits, so this changes the first byte of the payload.
+        assert_ok!(flash_64k
+            .writer(FlashRegion64K::new(SaveHeader::SIZE, 1))
+            .write_all(&[0]));
+        assert_ok_eq!(
+            probe_save(&mut flash_64k.reader(..), Medium::Flash, MAGIC, 1..=2),
+            SaveStatus::CorruptChecksum(header)
+        );
+    }
+}
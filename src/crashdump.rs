@@ -0,0 +1,295 @@
+//! Recording panics to SRAM.
+//!
+//! [`record_panic()`] writes a compact crash record to a region of SRAM reserved by the
+//! application, and [`read_last_crash()`] reads it back on the next boot. This allows crashes in
+//! the field to leave evidence in the save file.
+//!
+//! Recording is designed to be called from a panic handler: it does not allocate, writes each byte
+//! exactly once without verification or retries, and takes time bounded by the size of the region.
+//!
+//! ``` no_run
+//! use core::panic::PanicInfo;
+//! use gba_save::{crashdump, range::SramRegion};
+//!
+//! const CRASH: SramRegion = SramRegion::new(32512, 256);
+//!
+//! // Registered with `#[panic_handler]`.
+//! fn panic(info: &PanicInfo) -> ! {
+//!     unsafe { crashdump::record_panic(CRASH, info, 0, &[]) };
+//!     loop {}
+//! }
+//! ```
+//!
+//! A record consists of a 10-byte header, up to [`MAX_CONTEXT_LEN`] bytes of context supplied by
+//! the application, and the panic message, truncated to fit within the region and to at most 255
+//! bytes.
+
+use crate::{
+    range::SramRegion,
+    sram::{Error, Sram, SRAM_MEMORY},
+};
+use core::{
+    cmp::min,
+    fmt::{self, Display, Write as _},
+    panic::PanicInfo,
+    str,
+};
+use embedded_io::{Read, Write};
+
+/// Marks the start of a complete crash record.
+const MAGIC: [u8; 4] = *b"CRSH";
+/// The magic value, frame counter, context length, and message length.
+const HEADER_LEN: usize = 10;
+/// The maximum number of context bytes stored in a crash record.
+pub const MAX_CONTEXT_LEN: usize = 16;
+
+/// A crash record read back from SRAM.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CrashRecord<'a> {
+    /// The frame counter, or other timestamp, provided when the crash was recorded.
+    pub frame: u32,
+    /// The context bytes provided when the crash was recorded.
+    pub context: &'a [u8],
+    /// The panic message, possibly truncated.
+    pub message: &'a str,
+}
+
+/// Writes formatted text directly to SRAM, truncating on a character boundary once full.
+struct MessageWriter {
+    address: *mut u8,
+    remaining: usize,
+    len: usize,
+}
+
+impl fmt::Write for MessageWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut end = min(s.len(), self.remaining);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        for &byte in &s.as_bytes()[..end] {
+            unsafe {
+                self.address.add(self.len).write_volatile(byte);
+            }
+            self.len += 1;
+        }
+        self.remaining -= end;
+        if end < s.len() {
+            // Stop formatting once the region is full.
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Records a panic to `region`.
+///
+/// The panic's location and message are stored along with `frame` and up to [`MAX_CONTEXT_LEN`]
+/// bytes of `context`. See [`record()`] for details.
+///
+/// # Safety
+/// Must have exclusive access to `region` of SRAM, and WAITCNT's SRAM wait control setting must
+/// be set to 8 cycles, such as by a previous call to [`Sram::new()`].
+pub unsafe fn record_panic(region: SramRegion, info: &PanicInfo, frame: u32, context: &[u8]) {
+    unsafe { record(region, frame, context, info) }
+}
+
+/// Records a crash with the given message to `region`.
+///
+/// Any previous record in `region` is overwritten. The record is only marked as complete once it
+/// has been fully written, so a record interrupted partway through will not be read back. If
+/// `region` is too small to hold the record header, nothing is written.
+///
+/// # Safety
+/// Must have exclusive access to `region` of SRAM, and WAITCNT's SRAM wait control setting must
+/// be set to 8 cycles, such as by a previous call to [`Sram::new()`].
+pub unsafe fn record<Message>(region: SramRegion, frame: u32, context: &[u8], message: Message)
+where
+    Message: Display,
+{
+    if region.len() < HEADER_LEN {
+        return;
+    }
+    let address = unsafe { SRAM_MEMORY.add(region.offset()) };
+    let write = |offset: usize, byte: u8| unsafe { address.add(offset).write_volatile(byte) };
+
+    // Invalidate any previous record before overwriting it.
+    write(0, 0);
+
+    for (i, byte) in frame.to_le_bytes().into_iter().enumerate() {
+        write(4 + i, byte);
+    }
+    let context = &context[..min(
+        context.len(),
+        min(MAX_CONTEXT_LEN, region.len() - HEADER_LEN),
+    )];
+    for (i, &byte) in context.iter().enumerate() {
+        write(HEADER_LEN + i, byte);
+    }
+    let mut message_writer = MessageWriter {
+        address: unsafe { address.add(HEADER_LEN + context.len()) },
+        remaining: min(region.len() - HEADER_LEN - context.len(), u8::MAX as usize),
+        len: 0,
+    };
+    // A formatting error only indicates that the message was truncated.
+    let _ = write!(message_writer, "{message}");
+    write(8, context.len() as u8);
+    write(9, message_writer.len as u8);
+
+    for (i, &byte) in MAGIC.iter().enumerate().skip(1) {
+        write(i, byte);
+    }
+    write(0, MAGIC[0]);
+}
+
+/// Reads the crash record stored in `region`, if there is one.
+///
+/// The record's context and message are read into `buf`. If there is no complete record in
+/// `region`, or if `buf` is too small to hold the record's context and message, `None` is
+/// returned.
+pub fn read_last_crash<'buf>(
+    sram: &Sram,
+    region: SramRegion,
+    buf: &'buf mut [u8],
+) -> Option<CrashRecord<'buf>> {
+    if region.len() < HEADER_LEN {
+        return None;
+    }
+    let mut reader = sram.reader(region);
+    let mut header = [0; HEADER_LEN];
+    let Ok(_) = reader.read(&mut header);
+    if header[..4] != MAGIC {
+        return None;
+    }
+    let frame = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    let context_len = header[8] as usize;
+    let message_len = header[9] as usize;
+    let len = context_len + message_len;
+    if context_len > MAX_CONTEXT_LEN || len > region.len() - HEADER_LEN || len > buf.len() {
+        return None;
+    }
+
+    let Ok(_) = reader.read(&mut buf[..len]);
+    let (context, message) = buf[..len].split_at(context_len);
+    Some(CrashRecord {
+        frame,
+        context,
+        message: str::from_utf8(message).ok()?,
+    })
+}
+
+/// Clears the crash record stored in `region`, if there is one.
+pub fn clear(sram: &mut Sram, region: SramRegion) -> Result<(), Error> {
+    if region.is_empty() {
+        return Ok(());
+    }
+    sram.writer(region).write_all(&[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clear, read_last_crash, record, CrashRecord};
+    use crate::{range::SramRegion, sram::Sram};
+    use claims::{assert_none, assert_ok, assert_some_eq};
+    use gba_test::test;
+
+    const REGION: SramRegion = SramRegion::new(32512, 256);
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn record_and_read() {
+        let sram = unsafe { Sram::new() };
+        unsafe { record(REGION, 1234, &[1, 2, 3], "index out of bounds") };
+        let mut buf = [0; 256];
+
+        assert_some_eq!(
+            read_last_crash(&sram, REGION, &mut buf),
+            CrashRecord {
+                frame: 1234,
+                context: &[1, 2, 3],
+                message: "index out of bounds",
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn record_truncated() {
+        const SMALL_REGION: SramRegion = SramRegion::new(32512, 16);
+        let sram = unsafe { Sram::new() };
+        unsafe { record(SMALL_REGION, 42, &[1, 2], "αβγδ") };
+        let mut buf = [0; 256];
+
+        // Only 4 bytes of message fit, which is two characters.
+        assert_some_eq!(
+            read_last_crash(&sram, SMALL_REGION, &mut buf),
+            CrashRecord {
+                frame: 42,
+                context: &[1, 2],
+                message: "αβ",
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn record_context_truncated() {
+        let sram = unsafe { Sram::new() };
+        unsafe { record(REGION, 0, &[0xab; 20], "") };
+        let mut buf = [0; 256];
+
+        assert_some_eq!(
+            read_last_crash(&sram, REGION, &mut buf),
+            CrashRecord {
+                frame: 0,
+                context: &[0xab; 16],
+                message: "",
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn read_buffer_too_small() {
+        let sram = unsafe { Sram::new() };
+        unsafe { record(REGION, 1234, &[1, 2, 3], "index out of bounds") };
+        let mut buf = [0; 8];
+
+        assert_none!(read_last_crash(&sram, REGION, &mut buf));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn clear_record() {
+        let mut sram = unsafe { Sram::new() };
+        unsafe { record(REGION, 1234, &[1, 2, 3], "index out of bounds") };
+        assert_ok!(clear(&mut sram, REGION));
+        let mut buf = [0; 256];
+
+        assert_none!(read_last_crash(&sram, REGION, &mut buf));
+    }
+
+    #[test]
+    fn region_too_small() {
+        let sram = unsafe { Sram::new() };
+        let mut buf = [0; 256];
+
+        assert_none!(read_last_crash(&sram, SramRegion::new(0, 9), &mut buf));
+    }
+}
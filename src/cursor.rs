@@ -0,0 +1,83 @@
+//! Formatting of readers and writers.
+
+use core::fmt::{self, Formatter};
+
+/// The position of a reader or writer within its device.
+///
+/// All offsets are measured from the start of the device.
+pub(crate) struct Cursor {
+    /// The offset of the start of the range the reader or writer was created over.
+    pub(crate) start: usize,
+    /// The offset of the next byte to be read or written.
+    pub(crate) position: usize,
+    /// The offset of the end of the range.
+    pub(crate) end: usize,
+}
+
+impl Cursor {
+    /// Formats the cursor as a struct with the given type name.
+    pub(crate) fn debug(&self, formatter: &mut Formatter, name: &str) -> fmt::Result {
+        formatter
+            .debug_struct(name)
+            .field("range", &(self.start..self.end))
+            .field("position", &self.position)
+            .field("remaining", &(self.end - self.position))
+            .finish()
+    }
+
+    /// Formats the cursor as a short human-readable summary for the named device.
+    pub(crate) fn display(&self, formatter: &mut Formatter, device: &str) -> fmt::Result {
+        write!(
+            formatter,
+            "{} {}..{} at {}",
+            device, self.start, self.end, self.position
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cursor;
+    use crate::test_util::Buffer;
+    use core::fmt::{self, Display, Formatter, Write};
+    use gba_test::test;
+
+    struct Formatted(Cursor);
+
+    impl fmt::Debug for Formatted {
+        fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+            self.0.debug(formatter, "Sram::Writer")
+        }
+    }
+
+    impl Display for Formatted {
+        fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+            self.0.display(formatter, "SRAM")
+        }
+    }
+
+    const CURSOR: Formatted = Formatted(Cursor {
+        start: 42,
+        position: 58,
+        end: 100,
+    });
+
+    #[test]
+    fn debug() {
+        let mut buffer = Buffer::<128>::new();
+        write!(buffer, "{CURSOR:?}").unwrap();
+
+        assert_eq!(
+            buffer.as_str(),
+            "Sram::Writer { range: 42..100, position: 58, remaining: 42 }"
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut buffer = Buffer::<128>::new();
+        write!(buffer, "{CURSOR}").unwrap();
+
+        assert_eq!(buffer.as_str(), "SRAM 42..100 at 58");
+    }
+}
@@ -0,0 +1,83 @@
+//! Self-tests of backup memory.
+//!
+//! Each backup device provides a `self_test()` method, which writes a test pattern to a region of
+//! the device and reads it back, producing a [`SelfTestReport`]. This allows an application to
+//! report the health of the save chip, such as displaying "save chip OK" on boot.
+//!
+//! The region tested is designated by the application and is overwritten by the test, so it must
+//! be reserved for diagnostics and not used to store data.
+//!
+//! ``` no_run
+//! use gba_save::{range::SramAddress, sram::Sram};
+//!
+//! const DIAGNOSTICS: core::ops::RangeFrom<SramAddress> = SramAddress::new_static::<32704>()..;
+//!
+//! let mut sram = unsafe { Sram::new() };
+//! if !sram.self_test(DIAGNOSTICS).is_ok() {
+//!     // Warn the user that saving may not work.
+//! }
+//! ```
+
+/// The pattern written during a self-test.
+///
+/// This covers alternating bits, nibbles, and each individual bit being set.
+pub(crate) const PATTERN: [u8; 16] = [
+    0x00, 0xff, 0x55, 0xaa, 0x0f, 0xf0, 0x33, 0xcc, 0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80,
+];
+
+/// The results of a self-test.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SelfTestReport {
+    /// Whether the test pattern was written without the device reporting an error.
+    pub write_ok: bool,
+    /// Whether the test pattern was read back correctly after the entire pattern was written.
+    pub verify_ok: bool,
+    /// The number of times the device was polled while waiting for the pattern to be programmed.
+    ///
+    /// The device is polled roughly 1000 times per millisecond. SRAM writes complete immediately
+    /// and are never polled, so this is always zero for SRAM rather than a measured value.
+    pub latency: u32,
+}
+
+impl SelfTestReport {
+    /// Returns whether the test pattern was both written and verified successfully.
+    pub fn is_ok(&self) -> bool {
+        self.write_ok && self.verify_ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SelfTestReport;
+    use gba_test::test;
+
+    #[test]
+    fn is_ok() {
+        assert!(SelfTestReport {
+            write_ok: true,
+            verify_ok: true,
+            latency: 0
+        }
+        .is_ok());
+    }
+
+    #[test]
+    fn is_ok_write_failed() {
+        assert!(!SelfTestReport {
+            write_ok: false,
+            verify_ok: true,
+            latency: 0
+        }
+        .is_ok());
+    }
+
+    #[test]
+    fn is_ok_verify_failed() {
+        assert!(!SelfTestReport {
+            write_ok: true,
+            verify_ok: false,
+            latency: 42
+        }
+        .is_ok());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{error_kind::Class, flash, range::RangeError, sram};
+use core::convert::Infallible;
+use embedded_io::ErrorKind;
+
+/// An error from any of this crate's backup devices.
+///
+/// This allows readers and writers on different devices to be used through the same error type,
+/// such as through [`ErasedReader`](crate::adapters::ErasedReader) and
+/// [`ErasedWriter`](crate::adapters::ErasedWriter).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Error {
+    /// An error from SRAM.
+    Sram(sram::Error),
+
+    /// An error from flash memory.
+    Flash(flash::Error),
+
+    /// The requested range is not within the backup memory.
+    Range(RangeError),
+}
+
+impl embedded_io::Error for Error {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Sram(error) => error.kind(),
+            Self::Flash(error) => error.kind(),
+            Self::Range(_) => Class::InvalidInput.kind(),
+        }
+    }
+}
+
+impl From<sram::Error> for Error {
+    fn from(error: sram::Error) -> Self {
+        Self::Sram(error)
+    }
+}
+
+impl From<flash::Error> for Error {
+    fn from(error: flash::Error) -> Self {
+        Self::Flash(error)
+    }
+}
+
+impl From<RangeError> for Error {
+    fn from(error: RangeError) -> Self {
+        Self::Range(error)
+    }
+}
+
+impl From<Infallible> for Error {
+    fn from(error: Infallible) -> Self {
+        match error {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use crate::{flash, range::RangeError, sram, test_util::hash};
+    use embedded_io::{Error as _, ErrorKind};
+    use gba_test::test;
+
+    #[test]
+    fn sram_kind() {
+        let error = Error::from(sram::Error::EndOfWriter);
+
+        assert_eq!(error.kind(), sram::Error::EndOfWriter.kind());
+        assert_eq!(error, Error::Sram(sram::Error::EndOfWriter));
+    }
+
+    #[test]
+    fn flash_kind() {
+        let error = Error::from(flash::Error::OperationTimedOut);
+
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+        assert_eq!(error, Error::Flash(flash::Error::OperationTimedOut));
+    }
+
+    #[test]
+    fn range_kind() {
+        let error = Error::from(RangeError::OutOfBounds {
+            value: 33000,
+            max: 32768,
+        });
+
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn copy_eq_hash() {
+        let error = Error::Flash(flash::Error::NotErased { address: 42 });
+        let copy = error;
+
+        assert_eq!(copy, error);
+        assert_eq!(hash(&copy), hash(&error));
+        assert_ne!(
+            hash(&error),
+            hash(&Error::Flash(flash::Error::NotErased { address: 43 }))
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! The policy for mapping this crate's errors to [`ErrorKind`]s.
+//!
+//! Generic code using `embedded_io` matches on [`ErrorKind`], so every error type in this crate
+//! reports its kind through a shared [`Class`]. Each variant is classified by what went wrong,
+//! and each class maps to exactly one kind, so the same failure reports the same kind on every
+//! device.
+
+use embedded_io::ErrorKind;
+
+/// A class of backup memory error.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Class {
+    /// The device did not complete an operation in time.
+    TimedOut,
+    /// No device responded, or a different device responded than was expected.
+    NoDevice,
+    /// The request was out of range or otherwise invalid.
+    InvalidInput,
+    /// Data read back did not match the data written.
+    Verification,
+    /// A writer has no space remaining.
+    EndOfWriter,
+    /// Stored data is not in the expected format.
+    Malformed,
+}
+
+impl Class {
+    /// Returns the kind reported for errors of this class.
+    pub(crate) fn kind(self) -> ErrorKind {
+        match self {
+            Self::TimedOut => ErrorKind::TimedOut,
+            Self::NoDevice => ErrorKind::NotConnected,
+            Self::InvalidInput => ErrorKind::InvalidInput,
+            Self::Verification => ErrorKind::InvalidData,
+            Self::EndOfWriter => ErrorKind::WriteZero,
+            Self::Malformed => ErrorKind::InvalidData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        adapters::VerifyError,
+        flash::{self, UnknownDeviceID},
+        sram, InsufficientData,
+    };
+    use embedded_io::{Error, ErrorKind};
+    use gba_test::test;
+
+    #[test]
+    fn kinds() {
+        let kinds = [
+            (sram::Error::WriteFailure.kind(), ErrorKind::InvalidData),
+            (sram::Error::EndOfWriter.kind(), ErrorKind::WriteZero),
+            (
+                sram::Error::RetriesExhausted { attempts: 3 }.kind(),
+                ErrorKind::InvalidData,
+            ),
+            (
+                sram::Error::BufferTooLarge {
+                    needed: 43,
+                    available: 42,
+                }
+                .kind(),
+                ErrorKind::InvalidInput,
+            ),
+            (
+                sram::Error::VerifyMismatch { address: 42 }.kind(),
+                ErrorKind::InvalidData,
+            ),
+            (
+                sram::Error::Truncated {
+                    written: 42,
+                    requested: 43,
+                }
+                .kind(),
+                ErrorKind::WriteZero,
+            ),
+            (flash::Error::OperationTimedOut.kind(), ErrorKind::TimedOut),
+            (flash::Error::EndOfWriter.kind(), ErrorKind::WriteZero),
+            (
+                flash::Error::NotErased { address: 42 }.kind(),
+                ErrorKind::InvalidInput,
+            ),
+            (
+                flash::Error::BufferTooLarge {
+                    needed: 43,
+                    available: 42,
+                }
+                .kind(),
+                ErrorKind::InvalidInput,
+            ),
+            (
+                flash::Error::ProgramFailed { address: 42 }.kind(),
+                ErrorKind::InvalidData,
+            ),
+            (
+                flash::Error::VerifyMismatch { address: 42 }.kind(),
+                ErrorKind::InvalidData,
+            ),
+            (
+                flash::Error::Truncated {
+                    written: 42,
+                    requested: 43,
+                }
+                .kind(),
+                ErrorKind::WriteZero,
+            ),
+            (flash::Error::NotPresent.kind(), ErrorKind::NotConnected),
+            (
+                flash::Error::DuplicateLogSector { sector: 15 }.kind(),
+                ErrorKind::InvalidInput,
+            ),
+            (UnknownDeviceID(0xffff).kind(), ErrorKind::NotConnected),
+            (
+                InsufficientData {
+                    needed: 43,
+                    available: 42,
+                }
+                .kind(),
+                ErrorKind::InvalidInput,
+            ),
+            (
+                VerifyError::<sram::Error>::Mismatch { offset: 42 }.kind(),
+                ErrorKind::InvalidData,
+            ),
+            (
+                VerifyError::<sram::Error>::EndOfWriter.kind(),
+                ErrorKind::WriteZero,
+            ),
+        ];
+
+        for (index, (kind, expected)) in kinds.into_iter().enumerate() {
+            assert_eq!(kind, expected, "unexpected kind for case {index}");
+        }
+    }
+}
@@ -0,0 +1,455 @@
+//! Command sequences sent to flash devices.
+//!
+//! Every command begins with the same unlock sequence, followed by a command byte and any data the
+//! command requires. Sequences are assembled using [`CommandSequence`], whose type only allows
+//! valid orderings to be built: a sector erase, for example, can only follow the generic erase
+//! command. A completed sequence is sent using [`CommandSequence::execute()`], which writes the
+//! whole sequence with interrupts disabled, as some devices abort a command if the cartridge bus is
+//! accessed partway through it.
+
+use super::{Bank, Device, FLASH_MEMORY, SECTOR_SIZE};
+use crate::mmio::without_interrupts;
+
+/// The offset that command bytes are written to.
+const COMMAND: usize = 0x5555;
+/// The offset that the second byte of the unlock sequence is written to.
+const COMMAND_ENABLE: usize = 0x2aaa;
+/// The offset that the bank is written to when switching banks.
+const BANK_SWITCH: usize = 0x0000;
+
+#[derive(Clone, Copy, Debug)]
+#[repr(u8)]
+enum Command {
+    EraseChip = 0x10,
+    EraseSector = 0x30,
+    Erase = 0x80,
+    EnterIDMode = 0x90,
+    Write = 0xa0,
+    SwitchBank = 0xb0,
+    TerminateMode = 0xf0,
+    Enable = 0xaa,
+}
+
+/// The second byte of the unlock sequence.
+const ENABLE: u8 = 0x55;
+
+/// A destination for the writes making up a command sequence.
+pub(super) trait Bus {
+    /// Writes `byte` at `offset` from the start of the flash memory region.
+    fn write(&mut self, offset: usize, byte: u8);
+}
+
+/// The flash memory region itself.
+pub(super) struct Memory;
+
+impl Bus for Memory {
+    fn write(&mut self, offset: usize, byte: u8) {
+        unsafe { FLASH_MEMORY.add(offset).write_volatile(byte) }
+    }
+}
+
+/// How a device differs from the standard command set.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct Quirks {
+    /// The number of times the terminate command must be sent to leave ID mode.
+    pub(super) terminate_repeats: u8,
+    /// Whether data is programmed a 128-byte page at a time, erasing the page as part of
+    /// programming, rather than a byte at a time into erased sectors.
+    pub(super) page_program: bool,
+}
+
+impl Device {
+    /// Returns how the device differs from the standard command set.
+    pub(super) fn quirks(self) -> Quirks {
+        match self {
+            // Sanyo's device only leaves ID mode once the terminate command is sent twice.
+            Self::LE26FV10N1TS => Quirks {
+                terminate_repeats: 2,
+                page_program: false,
+            },
+            Self::AT29LV512 => Quirks {
+                terminate_repeats: 1,
+                page_program: true,
+            },
+            Self::MX29L010 | Self::MN63F805MNP | Self::MX29L512 | Self::LE39FW512 => Quirks {
+                terminate_repeats: 1,
+                page_program: false,
+            },
+        }
+    }
+}
+
+/// A sequence that has only been unlocked.
+#[derive(Debug)]
+pub(super) struct Unlocked;
+
+/// A sequence that has sent the generic erase command, and must be followed by the specific
+/// erase command.
+#[derive(Debug)]
+pub(super) struct Erase;
+
+/// A complete sequence, ready to be sent.
+#[derive(Debug)]
+pub(super) enum Ready<'a> {
+    EnterIdMode,
+    TerminateMode,
+    SwitchBank(Bank),
+    Program { offset: usize, byte: u8 },
+    ProgramPage { offset: usize, page: &'a [u8; 128] },
+    EraseSector(u8),
+    EraseChip,
+}
+
+/// A command sequence being built.
+///
+/// `State` tracks how much of the sequence has been built, so that only valid sequences can be
+/// executed.
+#[derive(Debug)]
+pub(super) struct CommandSequence<State> {
+    state: State,
+}
+
+impl CommandSequence<Unlocked> {
+    /// Begins a sequence with the unlock sequence.
+    pub(super) fn begin() -> Self {
+        Self { state: Unlocked }
+    }
+
+    fn ready(operation: Ready<'_>) -> CommandSequence<Ready<'_>> {
+        CommandSequence { state: operation }
+    }
+
+    /// Enters ID mode, in which the device ID can be read from the start of the memory region.
+    pub(super) fn enter_id_mode(self) -> CommandSequence<Ready<'static>> {
+        Self::ready(Ready::EnterIdMode)
+    }
+
+    /// Leaves ID mode.
+    ///
+    /// Some devices require this to be sent more than once. See [`exit_id_mode()`].
+    pub(super) fn terminate_mode(self) -> CommandSequence<Ready<'static>> {
+        Self::ready(Ready::TerminateMode)
+    }
+
+    /// Switches to `bank` on a 128KiB device.
+    pub(super) fn switch_bank(self, bank: Bank) -> CommandSequence<Ready<'static>> {
+        Self::ready(Ready::SwitchBank(bank))
+    }
+
+    /// Programs a single byte at `offset`, which must be erased.
+    pub(super) fn program(self, offset: usize, byte: u8) -> CommandSequence<Ready<'static>> {
+        Self::ready(Ready::Program { offset, byte })
+    }
+
+    /// Programs the 128-byte page starting at `offset` on a device that programs whole pages.
+    pub(super) fn program_page(
+        self,
+        offset: usize,
+        page: &[u8; 128],
+    ) -> CommandSequence<Ready<'_>> {
+        Self::ready(Ready::ProgramPage { offset, page })
+    }
+
+    /// Begins an erase, which must be followed by the part of the device to erase.
+    pub(super) fn erase(self) -> CommandSequence<Erase> {
+        CommandSequence { state: Erase }
+    }
+}
+
+impl CommandSequence<Erase> {
+    /// Erases the sector at index `sector` within the current bank.
+    pub(super) fn sector(self, sector: u8) -> CommandSequence<Ready<'static>> {
+        CommandSequence {
+            state: Ready::EraseSector(sector),
+        }
+    }
+
+    /// Erases the entire device, or the current chip on a dual-die device.
+    pub(super) fn chip(self) -> CommandSequence<Ready<'static>> {
+        CommandSequence {
+            state: Ready::EraseChip,
+        }
+    }
+}
+
+fn unlock<B>(bus: &mut B)
+where
+    B: Bus,
+{
+    bus.write(COMMAND, Command::Enable as u8);
+    bus.write(COMMAND_ENABLE, ENABLE);
+}
+
+fn send<B>(bus: &mut B, command: Command)
+where
+    B: Bus,
+{
+    unlock(bus);
+    bus.write(COMMAND, command as u8);
+}
+
+impl CommandSequence<Ready<'_>> {
+    /// Sends the sequence to the device.
+    pub(super) fn execute(self) {
+        self.execute_on(&mut Memory);
+    }
+
+    /// Writes the sequence to `bus`, with interrupts disabled.
+    pub(super) fn execute_on<B>(self, bus: &mut B)
+    where
+        B: Bus,
+    {
+        without_interrupts(|| match self.state {
+            Ready::EnterIdMode => send(bus, Command::EnterIDMode),
+            Ready::TerminateMode => send(bus, Command::TerminateMode),
+            Ready::SwitchBank(bank) => {
+                send(bus, Command::SwitchBank);
+                bus.write(BANK_SWITCH, bank as u8);
+            }
+            Ready::Program { offset, byte } => {
+                send(bus, Command::Write);
+                bus.write(offset, byte);
+            }
+            Ready::ProgramPage { offset, page } => {
+                send(bus, Command::Write);
+                for (index, &byte) in page.iter().enumerate() {
+                    bus.write(offset + index, byte);
+                }
+            }
+            Ready::EraseSector(sector) => {
+                send(bus, Command::Erase);
+                unlock(bus);
+                bus.write(sector as usize * SECTOR_SIZE, Command::EraseSector as u8);
+            }
+            Ready::EraseChip => {
+                send(bus, Command::Erase);
+                send(bus, Command::EraseChip);
+            }
+        });
+    }
+}
+
+/// Leaves ID mode on `device`, calling `wait` after each terminate command.
+pub(super) fn exit_id_mode<B>(device: Device, bus: &mut B, mut wait: impl FnMut())
+where
+    B: Bus,
+{
+    for _ in 0..device.quirks().terminate_repeats {
+        CommandSequence::begin().terminate_mode().execute_on(bus);
+        wait();
+    }
+}
+
+/// Records writes instead of sending them to the device.
+#[cfg(test)]
+#[derive(Debug)]
+pub(super) struct Recorder {
+    writes: [(usize, u8); 140],
+    len: usize,
+}
+
+#[cfg(test)]
+impl Recorder {
+    pub(super) fn new() -> Self {
+        Self {
+            writes: [(0, 0); 140],
+            len: 0,
+        }
+    }
+
+    pub(super) fn writes(&self) -> &[(usize, u8)] {
+        &self.writes[..self.len]
+    }
+}
+
+#[cfg(test)]
+impl Bus for Recorder {
+    fn write(&mut self, offset: usize, byte: u8) {
+        self.writes[self.len] = (offset, byte);
+        self.len += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exit_id_mode, CommandSequence, Quirks, Recorder};
+    use crate::flash::{Bank, Device};
+    use gba_test::test;
+
+    const UNLOCK: [(usize, u8); 2] = [(0x5555, 0xaa), (0x2aaa, 0x55)];
+
+    #[test]
+    fn enter_id_mode() {
+        let mut recorder = Recorder::new();
+
+        CommandSequence::begin()
+            .enter_id_mode()
+            .execute_on(&mut recorder);
+
+        assert_eq!(recorder.writes(), [UNLOCK[0], UNLOCK[1], (0x5555, 0x90)]);
+    }
+
+    #[test]
+    fn terminate_mode() {
+        let mut recorder = Recorder::new();
+
+        CommandSequence::begin()
+            .terminate_mode()
+            .execute_on(&mut recorder);
+
+        assert_eq!(recorder.writes(), [UNLOCK[0], UNLOCK[1], (0x5555, 0xf0)]);
+    }
+
+    #[test]
+    fn switch_bank() {
+        let mut recorder = Recorder::new();
+
+        CommandSequence::begin()
+            .switch_bank(Bank::_1)
+            .execute_on(&mut recorder);
+
+        assert_eq!(
+            recorder.writes(),
+            [UNLOCK[0], UNLOCK[1], (0x5555, 0xb0), (0x0000, 1)]
+        );
+    }
+
+    #[test]
+    fn program() {
+        let mut recorder = Recorder::new();
+
+        CommandSequence::begin()
+            .program(0x1234, 42)
+            .execute_on(&mut recorder);
+
+        assert_eq!(
+            recorder.writes(),
+            [UNLOCK[0], UNLOCK[1], (0x5555, 0xa0), (0x1234, 42)]
+        );
+    }
+
+    #[test]
+    fn program_page() {
+        let mut recorder = Recorder::new();
+        let mut page = [0; 128];
+        for (index, byte) in page.iter_mut().enumerate() {
+            *byte = index as u8;
+        }
+
+        CommandSequence::begin()
+            .program_page(0x0180, &page)
+            .execute_on(&mut recorder);
+
+        let writes = recorder.writes();
+        assert_eq!(writes.len(), 131);
+        assert_eq!(writes[..3], [UNLOCK[0], UNLOCK[1], (0x5555, 0xa0)]);
+        for (index, &write) in writes[3..].iter().enumerate() {
+            assert_eq!(write, (0x0180 + index, index as u8));
+        }
+    }
+
+    #[test]
+    fn erase_sector() {
+        let mut recorder = Recorder::new();
+
+        CommandSequence::begin()
+            .erase()
+            .sector(3)
+            .execute_on(&mut recorder);
+
+        assert_eq!(
+            recorder.writes(),
+            [
+                UNLOCK[0],
+                UNLOCK[1],
+                (0x5555, 0x80),
+                UNLOCK[0],
+                UNLOCK[1],
+                (0x3000, 0x30)
+            ]
+        );
+    }
+
+    #[test]
+    fn erase_chip() {
+        let mut recorder = Recorder::new();
+
+        CommandSequence::begin()
+            .erase()
+            .chip()
+            .execute_on(&mut recorder);
+
+        assert_eq!(
+            recorder.writes(),
+            [
+                UNLOCK[0],
+                UNLOCK[1],
+                (0x5555, 0x80),
+                UNLOCK[0],
+                UNLOCK[1],
+                (0x5555, 0x10)
+            ]
+        );
+    }
+
+    /// Returns the writes and number of waits made when leaving ID mode on `device`.
+    fn exit_id_mode_writes(device: Device) -> (Recorder, usize) {
+        let mut recorder = Recorder::new();
+        let mut waits = 0;
+
+        exit_id_mode(device, &mut recorder, || waits += 1);
+
+        (recorder, waits)
+    }
+
+    #[test]
+    fn exit_id_mode_standard() {
+        for device in [
+            Device::MX29L010,
+            Device::MN63F805MNP,
+            Device::MX29L512,
+            Device::AT29LV512,
+            Device::LE39FW512,
+        ] {
+            let (recorder, waits) = exit_id_mode_writes(device);
+
+            assert_eq!(
+                recorder.writes(),
+                [UNLOCK[0], UNLOCK[1], (0x5555, 0xf0)],
+                "{device:?}"
+            );
+            assert_eq!(waits, 1, "{device:?}");
+        }
+    }
+
+    #[test]
+    fn exit_id_mode_sanyo() {
+        let (recorder, waits) = exit_id_mode_writes(Device::LE26FV10N1TS);
+
+        assert_eq!(
+            recorder.writes(),
+            [
+                UNLOCK[0],
+                UNLOCK[1],
+                (0x5555, 0xf0),
+                UNLOCK[0],
+                UNLOCK[1],
+                (0x5555, 0xf0)
+            ]
+        );
+        assert_eq!(waits, 2);
+    }
+
+    #[test]
+    fn quirks_page_program() {
+        assert_eq!(
+            Device::AT29LV512.quirks(),
+            Quirks {
+                terminate_repeats: 1,
+                page_program: true,
+            }
+        );
+        assert!(!Device::MX29L512.quirks().page_program);
+        assert!(!Device::MX29L010.quirks().page_program);
+    }
+}
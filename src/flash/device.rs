@@ -1,3 +1,50 @@
+use super::ERASE_TIMEOUT_MARGIN;
+use crate::error_kind::Class;
+use core::time::Duration;
+use embedded_io::ErrorKind;
+
+/// Information about a flash device's characteristics.
+///
+/// Timings and endurance are typical values taken from the manufacturer's datasheet, and should be
+/// treated as estimates. They can be used to decide how often it is reasonable to save, such as
+/// by using a [`WearEstimator`].
+///
+/// [`WearEstimator`]: crate::flash::WearEstimator
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DeviceInfo {
+    /// The manufacturer and part number of the device.
+    pub name: &'static str,
+    /// The raw ID reported by the device in ID mode.
+    ///
+    /// The low byte is the manufacturer ID and the high byte is the chip ID, unless `id_swapped`
+    /// is set.
+    pub id: u16,
+    /// Whether the device reported its ID with the manufacturer and chip ID bytes swapped.
+    ///
+    /// Some reproduction carts report their IDs this way. Such devices are still detected, but
+    /// this can be used to report the oddity.
+    pub id_swapped: bool,
+    /// The number of bytes erased at once.
+    ///
+    /// This is 4KiB for most devices. Atmel devices erase and program a 128-byte page at once.
+    pub erase_block_size: usize,
+    /// The number of erase blocks on the device.
+    pub erase_blocks: u32,
+    /// The number of times each erase block can be erased before it may wear out.
+    pub endurance: u32,
+    /// The time taken to program a single byte.
+    ///
+    /// For Atmel devices, this is the time taken to program an entire page.
+    pub byte_program_time: Duration,
+    /// The time taken to erase a single erase block.
+    ///
+    /// For Atmel devices, pages are erased as part of programming, so this is the time taken to
+    /// program an entire page.
+    pub sector_erase_time: Duration,
+    /// The time taken to erase the entire device.
+    pub chip_erase_time: Duration,
+}
+
 /// An unknown device ID.
 ///
 /// There are several different common devices used in GBA cartridges for flash data. These devices
@@ -8,9 +55,30 @@
 /// and therefore cannot know how to interact with it.
 ///
 /// [`Flash`]: gba_save::flash::Flash
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct UnknownDeviceID(pub u16);
 
+impl embedded_io::Error for UnknownDeviceID {
+    fn kind(&self) -> ErrorKind {
+        Class::NoDevice.kind()
+    }
+}
+
+impl DeviceInfo {
+    /// Returns this info as reported by a device whose ID bytes may be swapped.
+    pub(crate) fn reported(self, id_swapped: bool) -> Self {
+        if id_swapped {
+            Self {
+                id: self.id.swap_bytes(),
+                id_swapped,
+                ..self
+            }
+        } else {
+            self
+        }
+    }
+}
+
 /// Different flash chip devices, by ID code.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) enum Device {
@@ -28,6 +96,126 @@ pub(crate) enum Device {
     LE39FW512,
 }
 
+impl Device {
+    /// Returns the manufacturer and part number of the device.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::MX29L010 => "Macronix MX29L010",
+            Self::LE26FV10N1TS => "Sanyo LE26FV10N1TS",
+            Self::MN63F805MNP => "Panasonic MN63F805MNP",
+            Self::MX29L512 => "Macronix MX29L512",
+            Self::AT29LV512 => "Atmel AT29LV512",
+            Self::LE39FW512 => "SST LE39FW512",
+        }
+    }
+
+    /// Returns whether the device toggles bit 6 of every read while programming a byte.
+    ///
+    /// Such devices signal completion as soon as programming finishes, rather than only once the
+    /// programmed value can be read back.
+    pub(crate) fn has_toggle_bit(self) -> bool {
+        matches!(
+            self,
+            Self::MX29L010 | Self::MX29L512 | Self::LE26FV10N1TS | Self::LE39FW512
+        )
+    }
+
+    /// Returns the characteristics of the device.
+    pub(crate) fn info(self) -> DeviceInfo {
+        let (erase_block_size, erase_blocks) = match self {
+            Self::MX29L010 | Self::LE26FV10N1TS => (4096, 32),
+            Self::AT29LV512 => (128, 512),
+            Self::MN63F805MNP | Self::MX29L512 | Self::LE39FW512 => (4096, 16),
+        };
+        let (endurance, byte_program_time, sector_erase_time, chip_erase_time) = match self {
+            Self::MX29L010 | Self::MX29L512 => (
+                100_000,
+                Duration::from_micros(30),
+                Duration::from_millis(1000),
+                Duration::from_millis(3000),
+            ),
+            Self::LE26FV10N1TS | Self::MN63F805MNP => (
+                100_000,
+                Duration::from_micros(30),
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+            ),
+            Self::AT29LV512 => (
+                10_000,
+                Duration::from_millis(10),
+                Duration::from_millis(10),
+                Duration::from_millis(20),
+            ),
+            Self::LE39FW512 => (
+                100_000,
+                Duration::from_micros(14),
+                Duration::from_millis(18),
+                Duration::from_millis(70),
+            ),
+        };
+        DeviceInfo {
+            name: self.name(),
+            id: self.id(),
+            id_swapped: false,
+            erase_block_size,
+            erase_blocks,
+            endurance,
+            byte_program_time,
+            sector_erase_time,
+            chip_erase_time,
+        }
+    }
+
+    /// Returns the ID reported by the device in ID mode.
+    ///
+    /// The low byte is the manufacturer ID, and the high byte is the chip ID.
+    pub(crate) fn id(self) -> u16 {
+        match self {
+            Self::MX29L010 => 0x09c2,
+            Self::LE26FV10N1TS => 0x1362,
+            Self::MN63F805MNP => 0x1b32,
+            Self::MX29L512 => 0x1cc2,
+            Self::AT29LV512 => 0x3d1f,
+            Self::LE39FW512 => 0xd4bf,
+        }
+    }
+}
+
+impl Device {
+    /// Returns the longest an erase of a single sector waits for the device to read as erased.
+    pub(crate) fn sector_erase_timeout(self) -> Duration {
+        self.info().sector_erase_time * ERASE_TIMEOUT_MARGIN
+    }
+
+    /// Returns the longest an erase of the whole chip waits for the device to read as erased.
+    pub(crate) fn chip_erase_timeout(self) -> Duration {
+        self.info().chip_erase_time * ERASE_TIMEOUT_MARGIN
+    }
+
+    /// Detects the device reporting `id` in ID mode.
+    ///
+    /// If `id` is not recognized, the ID with its bytes swapped is tried, as some reproduction
+    /// carts report their IDs that way. Along with the device, whether the swapped ID was matched
+    /// is returned. If neither is recognized, the original ID is returned as an error.
+    pub(crate) fn detect(id: u16) -> Result<(Self, bool), UnknownDeviceID> {
+        match Self::try_from(id) {
+            Ok(device) => Ok((device, false)),
+            Err(error) => match Self::try_from(id.swap_bytes()) {
+                Ok(device) => {
+                    #[cfg(feature = "log")]
+                    log::warn!(
+                        "flash reported ID {:#06x}, detected as {} with its ID bytes swapped",
+                        id,
+                        device.name()
+                    );
+                    Ok((device, true))
+                }
+                Err(_) => Err(error),
+            },
+        }
+    }
+}
+
 impl TryFrom<u16> for Device {
     type Error = UnknownDeviceID;
 
@@ -38,7 +226,7 @@ impl TryFrom<u16> for Device {
             0x1b32 => Ok(Device::MN63F805MNP),
             0x1cc2 => Ok(Device::MX29L512),
             0x3d1f => Ok(Device::AT29LV512),
-            0xd4b4 => Ok(Device::LE39FW512),
+            0xd4bf => Ok(Device::LE39FW512),
             _ => Err(UnknownDeviceID(id)),
         }
     }
@@ -49,9 +237,20 @@ mod tests {
     #![allow(non_snake_case)]
 
     use super::{Device, UnknownDeviceID};
+    use crate::test_util::hash;
     use claims::{assert_err_eq, assert_ok_eq};
     use gba_test::test;
 
+    #[test]
+    fn unknown_device_id_copy_eq_hash() {
+        let id = UnknownDeviceID(0xffff);
+        let copy = id;
+
+        assert_eq!(copy, id);
+        assert_eq!(hash(&copy), hash(&id));
+        assert_ne!(hash(&id), hash(&UnknownDeviceID(0xfffe)));
+    }
+
     #[test]
     fn device_from_MX29L010() {
         assert_ok_eq!(Device::try_from(0x09c2), Device::MX29L010);
@@ -79,11 +278,183 @@ mod tests {
 
     #[test]
     fn device_from_LE39FW512() {
-        assert_ok_eq!(Device::try_from(0xd4b4), Device::LE39FW512);
+        assert_ok_eq!(Device::try_from(0xd4bf), Device::LE39FW512);
+    }
+
+    #[test]
+    fn name_MX29L010() {
+        assert_eq!(Device::MX29L010.name(), "Macronix MX29L010");
+    }
+
+    #[test]
+    fn name_LE26FV10N1TS() {
+        assert_eq!(Device::LE26FV10N1TS.name(), "Sanyo LE26FV10N1TS");
+    }
+
+    #[test]
+    fn name_MN63F805MNP() {
+        assert_eq!(Device::MN63F805MNP.name(), "Panasonic MN63F805MNP");
+    }
+
+    #[test]
+    fn name_MX29L512() {
+        assert_eq!(Device::MX29L512.name(), "Macronix MX29L512");
+    }
+
+    #[test]
+    fn name_AT29LV512() {
+        assert_eq!(Device::AT29LV512.name(), "Atmel AT29LV512");
+    }
+
+    #[test]
+    fn name_LE39FW512() {
+        assert_eq!(Device::LE39FW512.name(), "SST LE39FW512");
+    }
+
+    #[test]
+    fn id_round_trip_MX29L010() {
+        assert_ok_eq!(Device::try_from(Device::MX29L010.id()), Device::MX29L010);
+    }
+
+    #[test]
+    fn id_round_trip_LE26FV10N1TS() {
+        assert_ok_eq!(
+            Device::try_from(Device::LE26FV10N1TS.id()),
+            Device::LE26FV10N1TS
+        );
+    }
+
+    #[test]
+    fn id_round_trip_MN63F805MNP() {
+        assert_ok_eq!(
+            Device::try_from(Device::MN63F805MNP.id()),
+            Device::MN63F805MNP
+        );
+    }
+
+    #[test]
+    fn id_round_trip_MX29L512() {
+        assert_ok_eq!(Device::try_from(Device::MX29L512.id()), Device::MX29L512);
+    }
+
+    #[test]
+    fn id_round_trip_AT29LV512() {
+        assert_ok_eq!(Device::try_from(Device::AT29LV512.id()), Device::AT29LV512);
+    }
+
+    #[test]
+    fn id_round_trip_LE39FW512() {
+        assert_ok_eq!(Device::try_from(Device::LE39FW512.id()), Device::LE39FW512);
+    }
+
+    #[test]
+    fn info_MX29L010() {
+        let info = Device::MX29L010.info();
+
+        assert_eq!(info.name, "Macronix MX29L010");
+        assert_eq!(info.id, 0x09c2);
+        assert_eq!(info.erase_block_size * info.erase_blocks as usize, 131072);
+    }
+
+    #[test]
+    fn info_AT29LV512() {
+        let info = Device::AT29LV512.info();
+
+        assert_eq!(info.name, "Atmel AT29LV512");
+        assert_eq!(info.id, 0x3d1f);
+        assert_eq!(info.erase_block_size * info.erase_blocks as usize, 65536);
+        assert_eq!(info.endurance, 10_000);
+    }
+
+    #[test]
+    fn info_LE39FW512() {
+        let info = Device::LE39FW512.info();
+
+        assert_eq!(info.name, "SST LE39FW512");
+        assert_eq!(info.id, 0xd4bf);
+        assert_eq!(info.erase_block_size * info.erase_blocks as usize, 65536);
+    }
+
+    #[test]
+    fn has_toggle_bit_MX29L512() {
+        assert!(Device::MX29L512.has_toggle_bit());
+    }
+
+    #[test]
+    fn has_toggle_bit_AT29LV512() {
+        assert!(!Device::AT29LV512.has_toggle_bit());
+    }
+
+    #[test]
+    fn has_toggle_bit_MN63F805MNP() {
+        assert!(!Device::MN63F805MNP.has_toggle_bit());
+    }
+
+    #[test]
+    fn device_from_incorrect_SST_id() {
+        // SST's manufacturer ID is 0xbf. 0xd4b4 does not identify a known device.
+        assert_err_eq!(Device::try_from(0xd4b4), UnknownDeviceID(0xd4b4));
     }
 
     #[test]
     fn device_from_unknown() {
         assert_err_eq!(Device::try_from(0xffff), UnknownDeviceID(0xffff));
     }
+
+    #[test]
+    fn detect() {
+        assert_ok_eq!(Device::detect(0x09c2), (Device::MX29L010, false));
+        assert_ok_eq!(Device::detect(0x1362), (Device::LE26FV10N1TS, false));
+        assert_ok_eq!(Device::detect(0x1b32), (Device::MN63F805MNP, false));
+        assert_ok_eq!(Device::detect(0x1cc2), (Device::MX29L512, false));
+        assert_ok_eq!(Device::detect(0x3d1f), (Device::AT29LV512, false));
+        assert_ok_eq!(Device::detect(0xd4bf), (Device::LE39FW512, false));
+    }
+
+    #[test]
+    fn detect_swapped() {
+        assert_ok_eq!(Device::detect(0xc209), (Device::MX29L010, true));
+        assert_ok_eq!(Device::detect(0x6213), (Device::LE26FV10N1TS, true));
+        assert_ok_eq!(Device::detect(0x321b), (Device::MN63F805MNP, true));
+        assert_ok_eq!(Device::detect(0xc21c), (Device::MX29L512, true));
+        assert_ok_eq!(Device::detect(0x1f3d), (Device::AT29LV512, true));
+        assert_ok_eq!(Device::detect(0xbfd4), (Device::LE39FW512, true));
+    }
+
+    #[test]
+    fn detect_unknown() {
+        assert_err_eq!(Device::detect(0xd4b4), UnknownDeviceID(0xd4b4));
+    }
+
+    #[test]
+    fn info_reported_swapped() {
+        let info = Device::MX29L010.info().reported(true);
+
+        assert_eq!(info.id, 0xc209);
+        assert!(info.id_swapped);
+        assert_eq!(info.name, "Macronix MX29L010");
+    }
+
+    #[test]
+    fn info_reported_not_swapped() {
+        assert_eq!(
+            Device::MX29L010.info().reported(false),
+            Device::MX29L010.info()
+        );
+    }
+
+    #[test]
+    fn erase_timeouts_exceed_typical() {
+        for device in [
+            Device::MX29L010,
+            Device::LE26FV10N1TS,
+            Device::MN63F805MNP,
+            Device::MX29L512,
+            Device::AT29LV512,
+            Device::LE39FW512,
+        ] {
+            assert!(device.sector_erase_timeout() > device.info().sector_erase_time);
+            assert!(device.chip_erase_timeout() > device.info().chip_erase_time);
+        }
+    }
 }
@@ -0,0 +1,265 @@
+use super::{erase_iter::SectorAccess, Error, ERASED, SECTOR_SIZE};
+use crate::boot::Fletcher16;
+use core::{cmp::min, iter::once};
+
+/// The largest number of sectors on any supported device.
+const MAX_SECTORS: usize = 32;
+/// The size of the largest record, holding a count for every sector of a 128KiB device.
+const MAX_RECORD_SIZE: usize = record_size(MAX_SECTORS);
+
+/// Returns the size of a record holding `sectors` counts.
+///
+/// Each record is a little-endian sequence number, followed by the count for each sector, followed
+/// by a Fletcher-16 checksum of everything before it.
+const fn record_size(sectors: usize) -> usize {
+    4 + sectors * 4 + 2
+}
+
+/// Encodes a record into the start of `buf`, returning its size.
+fn encode(sequence: u32, counts: &[u32], buf: &mut [u8; MAX_RECORD_SIZE]) -> usize {
+    let mut size = 0;
+    let bytes = once(sequence)
+        .chain(counts.iter().copied())
+        .flat_map(u32::to_le_bytes);
+    for (slot, byte) in buf.iter_mut().take(MAX_RECORD_SIZE - 2).zip(bytes) {
+        *slot = byte;
+        size += 1;
+    }
+    let mut checksum = Fletcher16::new();
+    checksum.update(buf.get(..size).unwrap_or_default());
+    for (slot, byte) in buf
+        .iter_mut()
+        .skip(size)
+        .zip(checksum.finish().to_le_bytes())
+    {
+        *slot = byte;
+    }
+    size + 2
+}
+
+/// Decodes a record holding `counts.len()` counts, returning its sequence number.
+///
+/// Returns `None` if the record is blank or its checksum does not match, such as when power was
+/// lost while it was being written.
+fn decode(bytes: &[u8], counts: &mut [u32]) -> Option<u32> {
+    let size = record_size(counts.len());
+    let bytes = bytes.get(..size)?;
+    let sequence = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    if sequence == u32::MAX {
+        return None;
+    }
+    let mut checksum = Fletcher16::new();
+    checksum.update(&bytes[..size - 2]);
+    if checksum.finish() != u16::from_le_bytes([bytes[size - 2], bytes[size - 1]]) {
+        return None;
+    }
+    for (count, chunk) in counts.iter_mut().zip(bytes[4..].chunks_exact(4)) {
+        *count = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    Some(sequence)
+}
+
+/// Erase counts for each sector of a flash device, persisted in a log on the device itself.
+///
+/// The log alternates between two reserved sectors. Each update appends a new record holding every
+/// count to the active sector, writing its checksum last, so a record that was only partially
+/// written when power was lost is ignored in favor of the previous one. Once the active sector is
+/// full, the other sector is erased and becomes active, so the latest complete record is never
+/// erased.
+#[derive(Debug)]
+pub(crate) struct EraseCounts {
+    counts: [u32; MAX_SECTORS],
+    sectors: u8,
+    /// The sectors holding the log, with the active sector first.
+    log: [u8; 2],
+    next: usize,
+    sequence: u32,
+}
+
+impl EraseCounts {
+    /// Loads the counts for a device with `sectors` sectors from the log in the `log` sectors.
+    ///
+    /// If no record is found, the counts start at zero and an initial record is written.
+    pub(crate) fn open(
+        access: &mut SectorAccess,
+        sectors: u8,
+        log: [u8; 2],
+    ) -> Result<Self, Error> {
+        let mut erase_counts = Self {
+            counts: [0; MAX_SECTORS],
+            sectors,
+            log,
+            next: 0,
+            sequence: 0,
+        };
+        let mut latest = None;
+        let size = erase_counts.record_size();
+        let mut buf = [0; MAX_RECORD_SIZE];
+        let mut counts = [0; MAX_SECTORS];
+        for sector in log {
+            for slot in 0..erase_counts.slots() {
+                access.read(erase_counts.slot_offset(sector, slot), &mut buf[..size]);
+                if let Some(sequence) = decode(&buf, &mut counts[..sectors as usize]) {
+                    if latest.is_none_or(|latest| sequence > latest) {
+                        latest = Some(sequence);
+                        erase_counts.counts = counts;
+                        erase_counts.log = if sector == log[0] {
+                            log
+                        } else {
+                            [log[1], log[0]]
+                        };
+                        erase_counts.next = slot + 1;
+                        erase_counts.sequence = sequence;
+                    }
+                }
+            }
+        }
+
+        if latest.is_none() {
+            // Start a new log, erasing whatever was previously stored in the sector.
+            let start = log[0] as usize * SECTOR_SIZE;
+            let programmed = (start..start + SECTOR_SIZE)
+                .step_by(MAX_RECORD_SIZE)
+                .any(|offset| {
+                    let chunk = &mut buf[..min(MAX_RECORD_SIZE, start + SECTOR_SIZE - offset)];
+                    access.read(offset, chunk);
+                    chunk.iter().any(|&byte| byte != ERASED)
+                });
+            if programmed {
+                access.erase(log[0])?;
+                erase_counts.increment(log[0]);
+            }
+            erase_counts.commit(access)?;
+        } else {
+            // Skip any records that were only partially written.
+            while erase_counts.next < erase_counts.slots() {
+                access.read(
+                    erase_counts.slot_offset(erase_counts.log[0], erase_counts.next),
+                    &mut buf[..size],
+                );
+                if buf[..size].iter().all(|&byte| byte == ERASED) {
+                    break;
+                }
+                erase_counts.next += 1;
+            }
+        }
+        Ok(erase_counts)
+    }
+
+    /// Returns the first `N` counts.
+    pub(crate) fn counts<const N: usize>(&self) -> [u32; N] {
+        let mut counts = [0; N];
+        counts.copy_from_slice(&self.counts[..N]);
+        counts
+    }
+
+    /// Records that `sector` was erased.
+    pub(crate) fn record_sector(
+        &mut self,
+        access: &mut SectorAccess,
+        sector: u8,
+    ) -> Result<(), Error> {
+        self.increment(sector);
+        if sector == self.log[0] {
+            // The log was erased, so it starts again from the beginning.
+            self.next = 0;
+        }
+        self.commit(access)
+    }
+
+    /// Records that every sector was erased.
+    pub(crate) fn record_all(&mut self, access: &mut SectorAccess) -> Result<(), Error> {
+        for sector in 0..self.sectors {
+            self.increment(sector);
+        }
+        self.next = 0;
+        self.commit(access)
+    }
+
+    fn increment(&mut self, sector: u8) {
+        if let Some(count) = self.counts.get_mut(sector as usize) {
+            *count = count.saturating_add(1);
+        }
+    }
+
+    fn record_size(&self) -> usize {
+        record_size(self.sectors as usize)
+    }
+
+    fn slots(&self) -> usize {
+        SECTOR_SIZE / self.record_size()
+    }
+
+    fn slot_offset(&self, sector: u8, slot: usize) -> usize {
+        sector as usize * SECTOR_SIZE + slot * self.record_size()
+    }
+
+    /// Appends a record of the current counts to the log.
+    fn commit(&mut self, access: &mut SectorAccess) -> Result<(), Error> {
+        if self.next >= self.slots() {
+            access.erase(self.log[1])?;
+            self.increment(self.log[1]);
+            self.log.swap(0, 1);
+            self.next = 0;
+        }
+
+        self.sequence = self.sequence.wrapping_add(1);
+        let mut buf = [0; MAX_RECORD_SIZE];
+        let counts = self
+            .counts
+            .get(..self.sectors as usize)
+            .unwrap_or(&self.counts);
+        let size = encode(self.sequence, counts, &mut buf);
+        let offset = self.slot_offset(self.log[0], self.next);
+        // Move past the slot even if programming fails, since it may now be partially written.
+        self.next += 1;
+        access.program(offset, buf.get(..size).unwrap_or(&buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, record_size, MAX_RECORD_SIZE};
+    use claims::{assert_none, assert_some_eq};
+    use gba_test::test;
+
+    #[test]
+    fn encode_decode() {
+        let mut buf = [0; MAX_RECORD_SIZE];
+        let size = encode(42, &[1, 2, 3, 4], &mut buf);
+        let mut counts = [0; 4];
+
+        assert_eq!(size, record_size(4));
+        assert_some_eq!(decode(&buf, &mut counts), 42);
+        assert_eq!(counts, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn decode_blank() {
+        let mut counts = [0; 16];
+
+        assert_none!(decode(&[0xff; MAX_RECORD_SIZE], &mut counts));
+    }
+
+    #[test]
+    fn decode_partially_written() {
+        let mut buf = [0; MAX_RECORD_SIZE];
+        let size = encode(42, &[1, 2, 3, 4], &mut buf);
+        // Power was lost before the checksum was written.
+        buf[size - 2..size].fill(0xff);
+        let mut counts = [0; 4];
+
+        assert_none!(decode(&buf, &mut counts));
+        assert_eq!(counts, [0; 4]);
+    }
+
+    #[test]
+    fn decode_corrupted() {
+        let mut buf = [0; MAX_RECORD_SIZE];
+        encode(42, &[1, 2, 3, 4], &mut buf);
+        buf[8] ^= 0x10;
+        let mut counts = [0; 4];
+
+        assert_none!(decode(&buf, &mut counts));
+    }
+}
@@ -0,0 +1,213 @@
+use super::{
+    erase_counts::EraseCounts,
+    erase_sector, switch_bank,
+    writer::{program_byte, BATCH_SIZE, BATCH_TIMEOUT},
+    Bank, Device, ErasedSectors, Error, FLASH_MEMORY, SIZE_64KB,
+};
+use crate::{halt::Halt, verify::PollBudget};
+use core::{iter::FusedIterator, marker::PhantomData, num::NonZeroU8, ops};
+use deranged::RangedU8;
+
+/// An iterator that erases one sector each time it is advanced.
+///
+/// This is returned by `erase_iter()` on [`Flash64K`] and [`Flash128K`]. Each call to
+/// [`next()`](Iterator::next()) erases exactly one sector and returns its index, so erasing can
+/// be spread across frames instead of blocking in a single call to `erase_sectors()`.
+///
+/// The iterator borrows the device exclusively, so the device cannot be read or written until it
+/// is dropped. Dropping it early leaves the remaining sectors untouched. Once a sector fails to
+/// erase, the error is returned and the iterator yields nothing further.
+///
+/// # Example
+/// ``` no_run
+/// use gba_save::flash::Flash;
+///
+/// let Flash::Flash128K(mut flash_128k) = unsafe { Flash::new() }.unwrap() else {
+///     panic!("unsupported flash type");
+/// };
+/// let mut erase = flash_128k.erase_iter(..);
+///
+/// // Once per frame:
+/// match erase.next() {
+///     Some(Ok(sector)) => { /* Sector `sector` is now erased. */ }
+///     Some(Err(error)) => panic!("could not erase: {error:?}"),
+///     None => { /* All sectors are erased. */ }
+/// }
+/// ```
+///
+/// [`Flash64K`]: crate::flash::Flash64K
+/// [`Flash128K`]: crate::flash::Flash128K
+#[derive(Debug)]
+pub struct EraseIter<'a, const MAX: u8> {
+    start: u8,
+    sectors: ops::Range<u8>,
+    access: SectorAccess,
+    erase_counts: Option<&'a mut EraseCounts>,
+    lifetime: PhantomData<&'a mut ()>,
+}
+
+impl<'a, const MAX: u8> EraseIter<'a, MAX> {
+    /// Creates an iterator erasing `sectors`.
+    ///
+    /// If `erase_counts` is given, each erase is recorded in it.
+    pub(crate) fn new(
+        sectors: ops::Range<u8>,
+        access: SectorAccess,
+        erase_counts: Option<&'a mut EraseCounts>,
+    ) -> Self {
+        Self {
+            start: sectors.start,
+            sectors,
+            access,
+            erase_counts,
+            lifetime: PhantomData,
+        }
+    }
+
+    /// Returns a record of the sectors erased so far.
+    ///
+    /// This can be used with `writer_into()` once the iterator is dropped.
+    pub fn erased(&self) -> ErasedSectors<MAX> {
+        ErasedSectors::new(self.start..self.sectors.start)
+    }
+
+    fn erase(&mut self, sector: u8) -> Result<(), Error> {
+        self.access.erase(sector)?;
+        match &mut self.erase_counts {
+            Some(erase_counts) => erase_counts.record_sector(&mut self.access, sector),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Access to the sectors of a flash device, switching banks as needed.
+#[derive(Debug)]
+pub(crate) struct SectorAccess {
+    device: Device,
+    confirmations: NonZeroU8,
+    halt: Option<Halt>,
+    banked: bool,
+    dual_die: bool,
+    bank: Option<Bank>,
+    fixed_bank: Option<Bank>,
+}
+
+impl SectorAccess {
+    /// Creates access to a device's sectors.
+    ///
+    /// If `banked` is set, each sector index is split into a bank and an index within the bank,
+    /// and the bank is switched as needed.
+    pub(crate) fn new(
+        device: Device,
+        confirmations: NonZeroU8,
+        halt: Option<Halt>,
+        banked: bool,
+        dual_die: bool,
+    ) -> Self {
+        Self {
+            device,
+            confirmations,
+            halt,
+            banked,
+            dual_die,
+            bank: None,
+            fixed_bank: None,
+        }
+    }
+
+    /// Restricts access to the sectors of `bank`, which is selected before every access.
+    ///
+    /// This is used when the bank selection is shared with another owner, so the currently
+    /// selected bank cannot be relied upon. Sector indices and offsets are measured from the start
+    /// of `bank`.
+    pub(super) fn in_bank(mut self, bank: Bank) -> Self {
+        self.fixed_bank = Some(bank);
+        self
+    }
+
+    /// Selects the bank containing `offset`, returning the address of `offset` within the bank.
+    fn select(&mut self, offset: usize) -> *mut u8 {
+        if let Some(bank) = self.fixed_bank {
+            switch_bank(bank);
+        } else if self.banked {
+            let bank = if offset < SIZE_64KB {
+                Bank::_0
+            } else {
+                Bank::_1
+            };
+            if !matches!(
+                (self.bank, bank),
+                (Some(Bank::_0), Bank::_0) | (Some(Bank::_1), Bank::_1)
+            ) {
+                switch_bank(bank);
+                self.bank = Some(bank);
+            }
+        }
+        unsafe { FLASH_MEMORY.add(offset % SIZE_64KB) }
+    }
+
+    /// Erases a single sector.
+    pub(crate) fn erase(&mut self, sector: u8) -> Result<(), Error> {
+        let offset = sector as usize * super::SECTOR_SIZE;
+        self.select(offset);
+        erase_sector(sector % 16, self.device, self.confirmations, self.halt)?;
+        if self.banked && self.dual_die {
+            // Dual-die carts lose the bank selection after an erase.
+            self.bank = None;
+            self.select(offset);
+        }
+        Ok(())
+    }
+
+    /// Fills `buf` with the bytes starting at `offset`, which must all be within one bank.
+    pub(crate) fn read(&mut self, offset: usize, buf: &mut [u8]) {
+        let address = self.select(offset);
+        for (index, byte) in buf.iter_mut().enumerate() {
+            *byte = unsafe { address.add(index).read_volatile() };
+        }
+    }
+
+    /// Programs `bytes` starting at `offset`, which must all be erased and within one bank.
+    pub(crate) fn program(&mut self, offset: usize, bytes: &[u8]) -> Result<(), Error> {
+        let address = self.select(offset);
+        let mut budget = PollBudget::new(BATCH_TIMEOUT);
+        for (index, &byte) in bytes.iter().enumerate() {
+            if index > 0 && index.is_multiple_of(BATCH_SIZE) {
+                budget = PollBudget::new(BATCH_TIMEOUT);
+            }
+            program_byte(
+                unsafe { address.add(index) },
+                byte,
+                offset + index,
+                self.device,
+                &mut budget,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<const MAX: u8> Iterator for EraseIter<'_, MAX> {
+    type Item = Result<RangedU8<0, MAX>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sector = self.sectors.next()?;
+        Some(match self.erase(sector) {
+            // SAFETY: The sectors were translated from a range of `RangedU8<0, MAX>`.
+            Ok(()) => Ok(unsafe { RangedU8::new_unchecked(sector) }),
+            Err(error) => {
+                // Stop iterating, without counting the failed sector as erased.
+                self.sectors = sector..sector;
+                Err(error)
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.sectors.size_hint()
+    }
+}
+
+impl<const MAX: u8> ExactSizeIterator for EraseIter<'_, MAX> {}
+
+impl<const MAX: u8> FusedIterator for EraseIter<'_, MAX> {}
@@ -0,0 +1,192 @@
+use crate::flash::Error;
+use core::ops;
+
+/// The number of bytes in a single flash sector.
+pub(crate) const SECTOR_SIZE: usize = 0x1000;
+
+/// A record of sectors that have been erased.
+///
+/// This is returned by `erase_sectors()` and `erase_sectors_with_progress()` on [`Flash64K`] and
+/// [`Flash128K`]. It can be passed to `writer_into()` to obtain a writer that is only allowed to
+/// write within the erased sectors, helping to avoid writing to memory that has not been erased.
+///
+/// `MAX` is the index of the last sector on the device, so a record from one type of device
+/// cannot be used with another.
+///
+/// Note that this only records that the sectors were erased. Writing to the same memory twice
+/// using writers created from the same record will still fail.
+///
+/// [`Flash64K`]: crate::flash::Flash64K
+/// [`Flash128K`]: crate::flash::Flash128K
+#[derive(Debug, Eq, PartialEq)]
+pub struct ErasedSectors<const MAX: u8> {
+    sectors: ops::Range<u8>,
+}
+
+impl<const MAX: u8> ErasedSectors<MAX> {
+    pub(crate) fn new(sectors: ops::Range<u8>) -> Self {
+        Self { sectors }
+    }
+
+    /// Returns the range of sector indices that were erased.
+    pub fn sectors(&self) -> ops::Range<u8> {
+        self.sectors.clone()
+    }
+
+    /// Checks that the `len` bytes starting at `offset` are all within the erased sectors.
+    ///
+    /// If they are not, the offset of the first byte outside of the erased sectors is returned
+    /// within [`Error::NotErased`].
+    pub(crate) fn check_covers(&self, offset: usize, len: usize) -> Result<(), Error> {
+        if len == 0 {
+            return Ok(());
+        }
+        let start = self.sectors.start as usize * SECTOR_SIZE;
+        let end = self.sectors.end as usize * SECTOR_SIZE;
+        if offset < start || offset >= end {
+            Err(Error::NotErased { address: offset })
+        } else if offset + len > end {
+            Err(Error::NotErased { address: end })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Whether a single sector contains data.
+///
+/// This is returned by `sector_summary()` on [`Flash64K`] and [`Flash128K`].
+///
+/// [`Flash64K`]: crate::flash::Flash64K
+/// [`Flash128K`]: crate::flash::Flash128K
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SectorState {
+    /// The index of the sector on the device.
+    pub index: u8,
+    /// Whether every byte in the sector is erased.
+    pub erased: bool,
+    /// The offset of the first byte in the sector that is not erased, measured from the start of
+    /// the sector.
+    pub first_used_offset: Option<u16>,
+}
+
+impl SectorState {
+    /// Creates the state of a sector from the offset of its first programmed byte, measured from
+    /// the start of the device.
+    pub(crate) fn new(index: u8, first_programmed_byte: Option<usize>) -> Self {
+        let first_used_offset =
+            first_programmed_byte.map(|offset| (offset - index as usize * SECTOR_SIZE) as u16);
+        Self {
+            index,
+            erased: first_used_offset.is_none(),
+            first_used_offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErasedSectors, SectorState};
+    use crate::flash::Error;
+    use claims::{assert_err_eq, assert_ok};
+    use gba_test::test;
+
+    #[test]
+    fn sectors() {
+        assert_eq!(ErasedSectors::<15>::new(2..5).sectors(), 2..5);
+    }
+
+    #[test]
+    fn check_covers_single_sector() {
+        assert_ok!(ErasedSectors::<15>::new(0..1).check_covers(0, 0x1000));
+    }
+
+    #[test]
+    fn check_covers_within_sectors() {
+        assert_ok!(ErasedSectors::<15>::new(2..5).check_covers(0x2042, 0x2000));
+    }
+
+    #[test]
+    fn check_covers_all_sectors_64k() {
+        assert_ok!(ErasedSectors::<15>::new(0..16).check_covers(0, 0x10000));
+    }
+
+    #[test]
+    fn check_covers_all_sectors_128k() {
+        assert_ok!(ErasedSectors::<31>::new(0..32).check_covers(0, 0x20000));
+    }
+
+    #[test]
+    fn check_covers_across_banks_128k() {
+        assert_ok!(ErasedSectors::<31>::new(15..17).check_covers(0xf000, 0x2000));
+    }
+
+    #[test]
+    fn check_covers_empty() {
+        assert_ok!(ErasedSectors::<15>::new(0..0).check_covers(0x4000, 0));
+    }
+
+    #[test]
+    fn check_covers_before_sectors() {
+        assert_err_eq!(
+            ErasedSectors::<15>::new(2..5).check_covers(0x1fff, 2),
+            Error::NotErased { address: 0x1fff }
+        );
+    }
+
+    #[test]
+    fn check_covers_after_sectors() {
+        assert_err_eq!(
+            ErasedSectors::<15>::new(2..5).check_covers(0x5000, 1),
+            Error::NotErased { address: 0x5000 }
+        );
+    }
+
+    #[test]
+    fn check_covers_past_end() {
+        assert_err_eq!(
+            ErasedSectors::<15>::new(2..5).check_covers(0x4fff, 2),
+            Error::NotErased { address: 0x5000 }
+        );
+    }
+
+    #[test]
+    fn check_covers_past_end_128k() {
+        assert_err_eq!(
+            ErasedSectors::<31>::new(16..32).check_covers(0x1ffff, 2),
+            Error::NotErased { address: 0x20000 }
+        );
+    }
+
+    #[test]
+    fn check_covers_no_sectors() {
+        assert_err_eq!(
+            ErasedSectors::<15>::new(0..0).check_covers(0, 1),
+            Error::NotErased { address: 0 }
+        );
+    }
+
+    #[test]
+    fn sector_state_erased() {
+        assert_eq!(
+            SectorState::new(3, None),
+            SectorState {
+                index: 3,
+                erased: true,
+                first_used_offset: None,
+            }
+        );
+    }
+
+    #[test]
+    fn sector_state_used() {
+        assert_eq!(
+            SectorState::new(3, Some(0x3042)),
+            SectorState {
+                index: 3,
+                erased: false,
+                first_used_offset: Some(0x42),
+            }
+        );
+    }
+}
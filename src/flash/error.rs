@@ -1,9 +1,14 @@
+use crate::error_kind::Class;
 use embedded_io::ErrorKind;
 
 /// An error that can occur when writing to flash memory.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Error {
     /// The write operation did not complete successfully within the device's timeout window.
+    ///
+    /// Before this is returned, the terminate command is sent to return the device to read-array
+    /// mode, so that reads return the stored data rather than status bits. Whatever was being
+    /// programmed or erased when the operation timed out should be treated as corrupt.
     OperationTimedOut,
 
     /// The writer has exhausted all of its space.
@@ -11,14 +16,82 @@ pub enum Error {
     /// This indicates that the range provided when creating the writer has been completely
     /// exhausted.
     EndOfWriter,
+
+    /// The memory being written to has not been erased.
+    ///
+    /// Programming a byte can only clear bits, so a byte can only be written if every bit that
+    /// must be set is already set. This usually means the sector containing the byte was not
+    /// erased before writing.
+    NotErased {
+        /// The offset of the byte from the start of the device.
+        address: usize,
+    },
+
+    /// The buffer passed to `commit()` is larger than the space remaining in the writer.
+    BufferTooLarge {
+        /// The number of bytes in the buffer.
+        needed: usize,
+        /// The number of bytes remaining in the writer.
+        available: usize,
+    },
+
+    /// The device reported that programming a byte completed, but the byte does not hold the
+    /// value written.
+    ProgramFailed {
+        /// The offset of the byte from the start of the device.
+        address: usize,
+    },
+
+    /// Data read back by `write_verified()` did not match the data written.
+    VerifyMismatch {
+        /// The offset of the first mismatching byte from the start of the device.
+        address: usize,
+    },
+
+    /// The buffer passed to `write_all_strict()` did not fit in the space remaining in the writer.
+    ///
+    /// As much of the buffer as fit was written.
+    Truncated {
+        /// The number of bytes of the buffer that were written.
+        written: usize,
+        /// The number of bytes in the buffer.
+        requested: usize,
+    },
+
+    /// Reads from the device did not behave like reads from a flash chip.
+    ///
+    /// This is returned by [`Reader64K::validate_presence()`] and
+    /// [`Reader128K::validate_presence()`], and usually means the cartridge has been removed or has
+    /// no flash chip, so reads return whatever value was left on the bus.
+    ///
+    /// [`Reader64K::validate_presence()`]: crate::flash::Reader64K::validate_presence()
+    /// [`Reader128K::validate_presence()`]: crate::flash::Reader128K::validate_presence()
+    NotPresent,
+
+    /// The same sector was passed as both sectors of the erase count log.
+    ///
+    /// The log alternates between two sectors, so that the previous record is never erased before
+    /// the next one is committed.
+    DuplicateLogSector {
+        /// The index of the sector.
+        sector: u8,
+    },
 }
 
 impl embedded_io::Error for Error {
     fn kind(&self) -> ErrorKind {
         match self {
-            Self::OperationTimedOut => ErrorKind::TimedOut,
-            Self::EndOfWriter => ErrorKind::WriteZero,
+            Self::OperationTimedOut => Class::TimedOut,
+            Self::EndOfWriter => Class::EndOfWriter,
+            Self::NotErased { .. } => Class::InvalidInput,
+            Self::BufferTooLarge { .. } => Class::InvalidInput,
+            Self::ProgramFailed { .. } => Class::Verification,
+            Self::VerifyMismatch { .. } => Class::Verification,
+            Self::Truncated { .. } => Class::EndOfWriter,
+            Self::NotPresent => Class::NoDevice,
+            Self::DuplicateLogSector { .. } => Class::InvalidInput,
         }
+        .kind()
     }
 }
 
@@ -37,4 +110,53 @@ mod tests {
     fn end_of_writer_kind() {
         assert_eq!(Error::EndOfWriter.kind(), ErrorKind::WriteZero);
     }
+
+    #[test]
+    fn not_erased_kind() {
+        assert_eq!(
+            Error::NotErased { address: 42 }.kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn buffer_too_large_kind() {
+        assert_eq!(
+            Error::BufferTooLarge {
+                needed: 43,
+                available: 42
+            }
+            .kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn program_failed_kind() {
+        assert_eq!(
+            Error::ProgramFailed { address: 42 }.kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn verify_mismatch_kind() {
+        assert_eq!(
+            Error::VerifyMismatch { address: 42 }.kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn not_present_kind() {
+        assert_eq!(Error::NotPresent.kind(), ErrorKind::NotConnected);
+    }
+
+    #[test]
+    fn duplicate_log_sector_kind() {
+        assert_eq!(
+            Error::DuplicateLogSector { sector: 15 }.kind(),
+            ErrorKind::InvalidInput
+        );
+    }
 }
@@ -44,63 +44,113 @@
 //! }
 //! ```
 //!
+//! # Interrupts
+//! Interrupts may remain enabled while using flash. Each command sequence sent to the device is
+//! only a few bus accesses long, and interrupts are briefly disabled while it is sent so that an
+//! interrupt handler accessing the cartridge bus cannot abort it. Erases are confirmed by
+//! requiring several consecutive erased reads, since some carts return a spurious value when an
+//! interrupt handler runs a DMA transfer from ROM while the device is being polled. See
+//! [`Flash::set_erase_confirmations()`] for details.
+//!
+//! # Latency
+//! Writing and erasing block until the device has finished. The [`typical`] module lists how long
+//! each operation usually takes, while [`PROGRAM_TIMEOUT`] and [`ERASE_TIMEOUT_MARGIN`] bound how
+//! long a single operation can block before failing.
+//!
+//! # Panics
+//! Reading and writing never panic, so a save cannot be cut short partway through by a panic.
+//! This covers [`Flash::new()`] and [`Flash::reset()`], along with the following on each device
+//! type:
+//! - `reader()`, `try_reader()`, and the `read()` and `read_exact_into()` methods of the
+//!   returned readers.
+//! - `writer()`, `try_writer()`, and the `write()`, `flush()`, `write_all_strict()`, `commit()`,
+//!   and `write_verified()` methods of the returned writers.
+//! - `erase_sectors()` and `erase_chip()`.
+//!
+//! This is checked in CI by the `panic_audit` example, which fails to link if any of these can
+//! reach a panic in a release build. The only exception is dropping a [`Writer64KAtmel`] whose
+//! flush fails with the `strict` feature enabled, which panics deliberately.
+//!
 //! [`Flash::new()`]: Flash::new()
 
+mod command;
 mod device;
+mod erase_counts;
+mod erase_iter;
+mod erased;
 mod error;
+pub mod raw;
 mod reader;
+pub mod typical;
+mod wear;
 mod writer;
 
-pub use device::UnknownDeviceID;
+pub use device::{DeviceInfo, UnknownDeviceID};
+pub use erase_iter::EraseIter;
+pub use erased::{ErasedSectors, SectorState};
 pub use error::Error;
 pub use reader::{Reader128K, Reader64K};
-pub use writer::{Writer128K, Writer64K, Writer64KAtmel};
+pub use wear::WearEstimator;
+pub use writer::{TailPolicy, Writer128K, Writer64K, Writer64KAtmel};
 
-use crate::mmio::{Cycles, WAITCNT};
+use crate::{
+    diagnostics::{SelfTestReport, PATTERN},
+    halt::Halt,
+    plan::{SaveBackend, SavePlan},
+    range::{
+        bounded_range, FlashPage64KAtmel, FlashRegion128K, FlashRegion64K, FlashSector128K,
+        FlashSector64K, IntoRegion, RangeError, Region,
+    },
+    time,
+    verify::{self, PollBudget},
+    waitstate::{self, Cycles, WaitstateControl},
+    Progress,
+};
+use command::CommandSequence;
 use core::{
-    hint::black_box,
+    cmp::min,
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    num::NonZeroU8,
     ops,
     ops::{Bound, RangeBounds},
     time::Duration,
 };
-use deranged::{RangedU8, RangedUsize};
+use deranged::RangedU8;
 use device::Device;
+use embedded_io::{Read, Write};
+use erase_counts::EraseCounts;
+use erase_iter::SectorAccess;
+pub(crate) use erased::SECTOR_SIZE;
+use writer::{program_byte, program_page};
 
 const FLASH_MEMORY: *mut u8 = 0x0e00_0000 as *mut u8;
-const BANK_SWITCH: *mut Bank = 0x0e00_0000 as *mut Bank;
-const COMMAND: *mut Command = 0x0e00_5555 as *mut Command;
-const COMMAND_ENABLE: *mut u8 = 0x0e00_2aaa as *mut u8;
-const SECTOR_COMMAND: *mut Command = 0x0e00_0000 as *mut Command;
-const ENABLE: u8 = 0x55;
 const ERASED: u8 = 0xff;
 const SIZE_64KB: usize = 0x10000;
+const ATMEL_PAGE_SIZE: usize = 128;
 
-#[derive(Debug)]
-#[repr(u8)]
-enum Command {
-    EraseChip = 0x10,
-    EraseSector = 0x30,
-    Erase = 0x80,
-    EnterIDMode = 0x90,
-    Write = 0xa0,
-    SwitchBank = 0xb0,
-    TerminateMode = 0xf0,
-    Enable = 0xaa,
-}
+/// The default number of consecutive erased reads required to confirm an erase.
+///
+/// See [`Flash::set_erase_confirmations()`] for details.
+pub const DEFAULT_ERASE_CONFIRMATIONS: NonZeroU8 = NonZeroU8::new(4).unwrap();
 
-fn begin_send_command() {
-    unsafe {
-        COMMAND.write_volatile(Command::Enable);
-        COMMAND_ENABLE.write_volatile(ENABLE);
-    }
-}
+/// The longest a write waits for programming to complete before failing.
+///
+/// Writes are programmed in batches of up to 32 bytes, each of which must complete within this
+/// time. Devices that signal completion using a toggle bit report a failed byte as soon as
+/// programming stops, but on other devices a byte that never reads back correctly stalls the write
+/// for this long before [`Error::OperationTimedOut`] is returned. Atmel devices wait this long for
+/// each page.
+pub const PROGRAM_TIMEOUT: Duration = Duration::from_millis(20);
 
-fn send_command(command: Command) {
-    begin_send_command();
-    unsafe {
-        COMMAND.write_volatile(command);
-    }
-}
+/// How many times a device's typical erase time an erase waits for the device to read as erased
+/// before failing.
+///
+/// Erasing a sector waits this many times the device's typical sector erase time, and erasing the
+/// whole chip this many times its typical chip erase time. Datasheets allow an erase to take
+/// several times longer than typical, particularly once the device has worn. The typical times for
+/// each device are listed in its [`DeviceInfo`].
+pub const ERASE_TIMEOUT_MARGIN: u32 = 4;
 
 #[derive(Clone, Copy, Debug)]
 enum Bank {
@@ -108,88 +158,201 @@ enum Bank {
     _1,
 }
 
-fn switch_bank(bank: Bank) {
-    send_command(Command::SwitchBank);
-    unsafe {
-        BANK_SWITCH.write_volatile(bank);
-    }
+/// A 64KiB bank of a 128KiB flash device.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FlashBank {
+    /// The lower bank, holding offsets `0` through `65535`.
+    _0,
+    /// The upper bank, holding offsets `65536` through `131071`.
+    _1,
 }
 
-fn wait(amount: Duration) {
-    for _ in 0..amount.as_millis() * 1000 {
-        black_box(());
+impl From<FlashBank> for Bank {
+    fn from(bank: FlashBank) -> Self {
+        match bank {
+            FlashBank::_0 => Self::_0,
+            FlashBank::_1 => Self::_1,
+        }
     }
 }
 
-fn verify_byte(address: *const u8, byte: u8, timeout: Duration) -> Result<(), Error> {
-    let mut i = 0;
-    loop {
-        if unsafe { address.read_volatile() } == byte {
-            return Ok(());
-        }
-        if i >= timeout.as_millis() * 1000 {
-            return Err(Error::OperationTimedOut);
+fn switch_bank(bank: Bank) {
+    CommandSequence::begin().switch_bank(bank).execute();
+}
+
+fn wait(amount: Duration, halt: Option<Halt>) {
+    match halt {
+        Some(halt) => {
+            for _ in 0..halt.count(amount) {
+                halt.halt();
+            }
         }
+        None => time::spin_for(amount),
+    }
+}
+
+fn enter_id_mode(halt: Option<Halt>) -> u16 {
+    CommandSequence::begin().enter_id_mode().execute();
+    wait(Duration::from_millis(20), halt);
+
+    // Read u16 from memory.
+    u16::from_ne_bytes(unsafe {
+        [
+            FLASH_MEMORY.read_volatile(),
+            FLASH_MEMORY.add(1).read_volatile(),
+        ]
+    })
+}
+
+fn exit_id_mode(device: Device, halt: Option<Halt>) {
+    command::exit_id_mode(device, &mut command::Memory, || {
+        wait(Duration::from_millis(20), halt)
+    });
+}
 
-        i += 1;
+fn verify_device_present(
+    device: Device,
+    expected: u16,
+    halt: Option<Halt>,
+) -> Result<(), UnknownDeviceID> {
+    let id = enter_id_mode(halt);
+    exit_id_mode(device, halt);
+    if id == expected {
+        Ok(())
+    } else {
+        Err(UnknownDeviceID(id))
     }
 }
 
-fn verify_bytes(address: *const u8, bytes: &[u8], timeout: Duration) -> Result<(), Error> {
-    let mut i = 0;
+fn timed_out(_: verify::TimedOut) -> Error {
+    record_stat!(verification_failures, 1);
+    recover_from_timeout()
+}
+
+/// Returns the device to read-array mode after an operation times out.
+///
+/// A device that is still busy, or that is stuck partway through a command, returns status bits
+/// instead of stored data on every read. Sending the terminate command returns it to reading the
+/// stored data, so readers created before the timeout remain usable.
+fn recover_from_timeout() -> Error {
+    CommandSequence::begin().terminate_mode().execute();
+    Error::OperationTimedOut
+}
+
+/// Returns the offset of the first byte read from `reader` that is not erased, counting from
+/// `offset`.
+fn find_programmed<R>(mut reader: R, offset: usize) -> Option<usize>
+where
+    R: Read<Error = Infallible>,
+{
+    let mut chunk = [0; 32];
+    let mut position = offset;
     loop {
-        let mut verified = true;
-        for (i, &byte) in bytes.iter().enumerate() {
-            if unsafe { address.add(i).read_volatile() } != byte {
-                verified = false;
-                break;
-            }
-        }
-        if verified {
-            return Ok(());
+        let Ok(count) = reader.read(&mut chunk);
+        if count == 0 {
+            return None;
         }
-        if i >= timeout.as_millis() * 1000 {
-            return Err(Error::OperationTimedOut);
+        if let Some(index) = chunk[..count].iter().position(|&byte| byte != ERASED) {
+            return Some(position + index);
         }
-
-        i += 1;
+        position += count;
     }
 }
 
-fn erase_sector(sector: u8) -> Result<(), Error> {
-    // Generic erase command.
-    send_command(Command::Erase);
+/// Polls until the memory at `address` reads as erased on `confirmations` consecutive reads.
+fn verify_erased(
+    address: *const u8,
+    timeout: Duration,
+    confirmations: NonZeroU8,
+    halt: Option<Halt>,
+) -> Result<(), Error> {
+    PollBudget::with_halt(timeout, halt)
+        .verify_byte_confirmed(address, ERASED, confirmations)
+        .map_err(timed_out)
+}
 
-    // Specific erase command for sector.
-    begin_send_command();
-    let sector_command = unsafe { SECTOR_COMMAND.add(sector as usize * 0x1000) };
-    unsafe {
-        sector_command.write_volatile(Command::EraseSector);
-    }
+/// Polls the device until it is no longer busy with a program or erase operation.
+///
+/// While an operation is in progress, bit 6 of every read toggles between successive reads. The
+/// device is idle once two successive reads return the same value.
+fn poll_ready(timeout: Duration, halt: Option<Halt>) -> Result<(), Error> {
+    PollBudget::with_halt(timeout, halt)
+        .poll(|| {
+            let first = unsafe { FLASH_MEMORY.read_volatile() };
+            let second = unsafe { FLASH_MEMORY.read_volatile() };
+            first == second
+        })
+        .map_err(|_| recover_from_timeout())
+}
+
+fn erase_sector(
+    sector: u8,
+    device: Device,
+    confirmations: NonZeroU8,
+    halt: Option<Halt>,
+) -> Result<(), Error> {
+    CommandSequence::begin().erase().sector(sector).execute();
+
+    verify_erased(
+        unsafe { FLASH_MEMORY.add(sector as usize * SECTOR_SIZE) },
+        device.sector_erase_timeout(),
+        confirmations,
+        halt,
+    )?;
+    record_stat!(sectors_erased, 1);
+    Ok(())
+}
+
+fn erase_chip(device: Device, confirmations: NonZeroU8, halt: Option<Halt>) -> Result<(), Error> {
+    CommandSequence::begin().erase().chip().execute();
 
-    verify_byte(
-        sector_command as *const u8,
-        ERASED,
-        Duration::from_millis(20),
+    // Verify.
+    verify_erased(
+        FLASH_MEMORY,
+        device.chip_erase_timeout(),
+        confirmations,
+        halt,
     )
 }
 
-fn translate_range_to_buffer<const MAX: usize, Range>(range: Range) -> (*mut u8, usize)
-where
-    Range: RangeBounds<RangedUsize<0, MAX>>,
-{
-    let offset = match range.start_bound() {
-        Bound::Included(start) => start.get(),
-        Bound::Excluded(start) => start.get() + 1,
-        Bound::Unbounded => 0,
-    };
-    let address = unsafe { FLASH_MEMORY.add(offset) };
-    let len = match range.end_bound() {
-        Bound::Included(end) => end.get() + 1,
-        Bound::Excluded(end) => end.get(),
-        Bound::Unbounded => MAX + 1,
-    } - offset;
-    (address, len)
+/// Erases a sector and programs the self-test pattern at its start.
+///
+/// `sector` is the index of the sector within the current bank, while `offset` is the offset of
+/// the sector from the start of the device.
+fn self_test_sector(
+    sector: u8,
+    offset: usize,
+    device: Device,
+    confirmations: NonZeroU8,
+    halt: Option<Halt>,
+) -> SelfTestReport {
+    let address = unsafe { FLASH_MEMORY.add(sector as usize * SECTOR_SIZE) };
+    let mut budget = PollBudget::new(PROGRAM_TIMEOUT);
+    let write_ok = erase_sector(sector, device, confirmations, halt).is_ok()
+        && PATTERN.iter().enumerate().all(|(i, &byte)| {
+            program_byte(
+                unsafe { address.add(i) },
+                byte,
+                offset + i,
+                device,
+                &mut budget,
+            )
+            .is_ok()
+        });
+    let verify_ok = PATTERN
+        .iter()
+        .enumerate()
+        .all(|(i, &byte)| unsafe { address.add(i).read_volatile() } == byte);
+
+    SelfTestReport {
+        write_ok,
+        verify_ok,
+        latency: budget.spent(),
+    }
+}
+
+const fn translate_range_to_buffer<const MAX: usize>(region: Region<MAX>) -> (*mut u8, usize) {
+    (unsafe { FLASH_MEMORY.add(region.offset()) }, region.len())
 }
 
 fn translate_range_to_sectors<const MAX: u8, Range>(range: Range) -> ops::Range<u8>
@@ -212,42 +375,346 @@ where
 ///
 /// This storage type is divided into 16 4KiB sectors. Each sector must be erased before it can be
 /// written to. Failing to erase a sector will result in invalid data.
+///
+/// Readers and writers borrow the device they are created from. A writer borrows the device
+/// exclusively, so no other reader or writer can exist while it is alive.
+///
+/// Readers and writers are neither `Send` nor `Sync`, as they access the device through raw
+/// pointers and depend on global device state such as the selected bank. They therefore cannot be
+/// moved into an interrupt handler.
+///
+/// See [`Sram`](crate::sram::Sram) for a pattern for saving in response to an interrupt.
 #[derive(Debug)]
-pub struct Flash64K;
+pub struct Flash64K {
+    device: Device,
+    id_swapped: bool,
+    halt: Option<Halt>,
+    erase_confirmations: NonZeroU8,
+    erase_counts: Option<EraseCounts>,
+}
 
 impl Flash64K {
+    /// Returns a human-readable name for this type of backup memory.
+    pub fn name(&self) -> &'static str {
+        "Flash 64KiB"
+    }
+
+    /// Returns the number of bytes of storage available on this device.
+    pub fn capacity(&self) -> usize {
+        SIZE_64KB
+    }
+
+    /// Returns information about the device's characteristics.
+    pub fn info(&self) -> DeviceInfo {
+        self.device.info().reported(self.id_swapped)
+    }
+
+    /// Returns the raw ID reported by the device when it was detected.
+    ///
+    /// The low byte is the manufacturer ID and the high byte is the chip ID, unless the device
+    /// reported its ID with the bytes swapped, as indicated by [`DeviceInfo::id_swapped`]. For
+    /// such devices the bytes are returned as reported, with the chip ID in the low byte and the
+    /// manufacturer ID in the high byte. [`manufacturer_id()`](Self::manufacturer_id()) and
+    /// [`chip_id()`](Self::chip_id()) return the correct bytes either way.
+    pub fn device_id(&self) -> u16 {
+        self.info().id
+    }
+
+    /// Returns the manufacturer ID reported by the device when it was detected.
+    pub fn manufacturer_id(&self) -> u8 {
+        self.device.id() as u8
+    }
+
+    /// Returns the chip ID reported by the device when it was detected.
+    pub fn chip_id(&self) -> u8 {
+        (self.device.id() >> 8) as u8
+    }
+
+    /// Checks that the device detected at initialization is still present.
+    ///
+    /// This re-enters ID mode and compares the reported ID against the ID reported when the device
+    /// was detected. If the cartridge has been removed or replaced, the ID reported is returned as
+    /// an error. No stored data is modified.
+    pub fn verify_present(&mut self) -> Result<(), UnknownDeviceID> {
+        verify_device_present(self.device, self.device_id(), self.halt)
+    }
+
+    /// Waits until the device has finished any in-progress program or erase operation.
+    ///
+    /// This can be used to synchronize with the device before reading back written data or
+    /// powering down. If the device is still busy after `timeout`, [`Error::OperationTimedOut`]
+    /// is returned.
+    pub fn wait_ready(&mut self, timeout: Duration) -> Result<(), Error> {
+        poll_ready(timeout, self.halt)
+    }
+
+    /// Returns the number of consecutive erased reads required to confirm an erase.
+    pub fn erase_confirmations(&self) -> NonZeroU8 {
+        self.erase_confirmations
+    }
+
+    /// Sets the number of consecutive erased reads required to confirm an erase.
+    ///
+    /// See [`Flash::set_erase_confirmations()`] for details.
+    pub fn set_erase_confirmations(&mut self, confirmations: NonZeroU8) {
+        self.erase_confirmations = confirmations;
+    }
+
     /// Returns a reader over the given range.
-    pub fn reader<'a, 'b, Range>(&'a mut self, range: Range) -> Reader64K<'b>
+    pub fn reader<Range>(&self, range: Range) -> Reader64K<'_>
     where
-        'a: 'b,
-        Range: RangeBounds<RangedUsize<0, 65535>>,
+        Range: IntoRegion<65535>,
     {
-        let (address, len) = translate_range_to_buffer(range);
-        unsafe { Reader64K::new_unchecked(address, len) }
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Reader64K::new_unchecked(address, len) }.logged("Flash 64KiB")
+    }
+
+    /// Returns a reader over the given range of offsets, if it is within the device.
+    pub fn try_reader(&self, range: ops::Range<usize>) -> Result<Reader64K<'_>, RangeError> {
+        Ok(self.reader(bounded_range(range)?))
+    }
+
+    /// Returns whether every byte in the given range is erased.
+    ///
+    /// This can be used to check whether data can be appended to a range without erasing it
+    /// first.
+    pub fn is_erased<Range>(&self, range: Range) -> bool
+    where
+        Range: IntoRegion<65535>,
+    {
+        self.first_programmed_byte(range).is_none()
+    }
+
+    /// Returns the offset of the first byte in the given range that is not erased.
+    ///
+    /// The offset is measured from the start of the device. If every byte in the range is
+    /// erased, `None` is returned.
+    pub fn first_programmed_byte<Range>(&self, range: Range) -> Option<usize>
+    where
+        Range: IntoRegion<65535>,
+    {
+        let region = range.into_region();
+        find_programmed(self.reader(region), region.offset())
+    }
+
+    /// Plans writing `data_len` bytes to the start of the given range, for use with
+    /// [`estimate_duration()`](Self::estimate_duration()).
+    ///
+    /// At most the length of the range is written. Each sector covered by the write that is
+    /// not already erased over the written bytes is counted as requiring an erase, so this scans the
+    /// device.
+    pub fn plan_write<Range>(&self, range: Range, data_len: usize) -> SavePlan
+    where
+        Range: IntoRegion<65535>,
+    {
+        let region = range.into_region();
+        let bytes = min(data_len, region.len());
+        SavePlan::flash(
+            SaveBackend::Flash64K,
+            region.offset(),
+            bytes,
+            |start, end| !self.is_erased(Region::from_offsets(start, end)),
+        )
+    }
+
+    /// Estimates how long performing `plan` takes on this device.
+    ///
+    /// The estimate uses the typical timings from [`info()`](Self::info()), so it should only be
+    /// used to judge the scale of a save, such as to decide whether to show a progress bar.
+    pub fn estimate_duration(&self, plan: &SavePlan) -> Duration {
+        plan.flash_duration(self.info())
+    }
+
+    /// Returns the state of each sector on the device, in order.
+    ///
+    /// Each sector is scanned when the iterator reaches it, stopping at the first byte that is not
+    /// erased.
+    pub fn sector_summary(&self) -> impl Iterator<Item = SectorState> + '_ {
+        (0..16).map(|index| {
+            SectorState::new(
+                index,
+                self.first_programmed_byte(FlashRegion64K::new(
+                    index as usize * SECTOR_SIZE,
+                    SECTOR_SIZE,
+                )),
+            )
+        })
     }
 
     /// Erases the specified sectors.
     ///
     /// This should be called before attempting to write to these sectors. Memory that has already
     /// been written to cannot be written to again without first being erased.
-    pub fn erase_sectors<Range>(&mut self, sectors: Range) -> Result<(), Error>
+    ///
+    /// On success, a record of the erased sectors is returned, which can be used with
+    /// [`writer_into()`](Self::writer_into()).
+    pub fn erase_sectors<Range>(&mut self, sectors: Range) -> Result<ErasedSectors<15>, Error>
+    where
+        Range: RangeBounds<FlashSector64K>,
+    {
+        self.erase_sectors_with_progress(sectors, |_| {})
+    }
+
+    /// Erases the specified sectors, reporting progress after each sector is erased.
+    ///
+    /// Progress is reported in units of sectors. The callback is invoked between sector erase
+    /// operations, so it is safe to perform rendering work within it.
+    pub fn erase_sectors_with_progress<Range, F>(
+        &mut self,
+        sectors: Range,
+        mut progress: F,
+    ) -> Result<ErasedSectors<15>, Error>
+    where
+        Range: RangeBounds<FlashSector64K>,
+        F: FnMut(Progress),
+    {
+        let mut erase = self.erase_iter(sectors);
+        let total = erase.len();
+        for (completed, result) in (&mut erase).enumerate() {
+            result?;
+            progress(Progress {
+                completed: completed + 1,
+                total,
+            });
+        }
+        Ok(erase.erased())
+    }
+
+    /// Returns an iterator that erases one of the specified sectors each time it is advanced.
+    ///
+    /// This allows erasing to be interleaved with other work, such as rendering one frame per
+    /// sector. See [`EraseIter`] for details.
+    pub fn erase_iter<Range>(&mut self, sectors: Range) -> EraseIter<'_, 15>
     where
-        Range: RangeBounds<RangedU8<0, 15>>,
+        Range: RangeBounds<FlashSector64K>,
     {
-        for sector in translate_range_to_sectors(sectors) {
-            erase_sector(sector)?;
+        EraseIter::new(
+            translate_range_to_sectors(sectors),
+            self.sector_access(),
+            self.erase_counts.as_mut(),
+        )
+    }
+
+    /// Begins tracking how many times each sector is erased, persisting the counts on the device.
+    ///
+    /// The counts are stored in a log that alternates between the two `log` sectors, which must
+    /// be reserved for this purpose and not otherwise written. Any counts previously stored in
+    /// these sectors are loaded; if there are none, tracking starts from zero.
+    ///
+    /// Once enabled, every sector erased by [`erase_sectors()`](Self::erase_sectors()),
+    /// [`erase_sectors_with_progress()`](Self::erase_sectors_with_progress()),
+    /// [`erase_iter()`](Self::erase_iter()), and [`Flash::reset()`] is counted, and a new record
+    /// is appended to the log after each one. Counts saturate at `u32::MAX`. Erases performed by
+    /// [`self_test()`](Self::self_test()) are not counted.
+    ///
+    /// Each record is written in full before it is committed by its checksum, and the previous
+    /// record is never erased before the next is committed, so losing power partway through an
+    /// update loses at most that update. Tracking must be enabled again each time the device is
+    /// created.
+    ///
+    /// If both `log` sectors are the same, [`Error::DuplicateLogSector`] is returned and tracking
+    /// is not enabled.
+    ///
+    /// # Example
+    /// ``` no_run
+    /// use gba_save::{flash::Flash, range::FlashSector64K};
+    ///
+    /// let Flash::Flash64K(mut flash_64k) = unsafe { Flash::new() }.unwrap() else {
+    ///     panic!("unsupported flash type");
+    /// };
+    /// flash_64k
+    ///     .track_erase_counts([
+    ///         FlashSector64K::new_static::<14>(),
+    ///         FlashSector64K::new_static::<15>(),
+    ///     ])
+    ///     .expect("could not load erase counts");
+    ///
+    /// // Display on a health screen.
+    /// let counts = flash_64k.sector_erase_counts();
+    /// ```
+    pub fn track_erase_counts(&mut self, log: [FlashSector64K; 2]) -> Result<(), Error> {
+        if log[0] == log[1] {
+            return Err(Error::DuplicateLogSector {
+                sector: log[0].get(),
+            });
         }
+        self.erase_counts = Some(EraseCounts::open(
+            &mut self.sector_access(),
+            16,
+            [log[0].get(), log[1].get()],
+        )?);
         Ok(())
     }
 
+    /// Returns the number of times each sector has been erased, if tracking is enabled.
+    ///
+    /// See [`track_erase_counts()`](Self::track_erase_counts()) for details.
+    pub fn sector_erase_counts(&self) -> Option<[u32; 16]> {
+        self.erase_counts
+            .as_ref()
+            .map(|erase_counts| erase_counts.counts())
+    }
+
+    fn sector_access(&self) -> SectorAccess {
+        SectorAccess::new(
+            self.device,
+            self.erase_confirmations,
+            self.halt,
+            false,
+            false,
+        )
+    }
+
+    /// Tests that data can be written to and read from the device.
+    ///
+    /// The given sector is erased and a test pattern is written to it and read back. The contents
+    /// of the sector are overwritten, so it should be reserved for diagnostics. See the
+    /// [`diagnostics`](crate::diagnostics) module for details.
+    pub fn self_test(&mut self, sector: FlashSector64K) -> SelfTestReport {
+        self_test_sector(
+            sector.get(),
+            sector.get() as usize * SECTOR_SIZE,
+            self.device,
+            self.erase_confirmations,
+            self.halt,
+        )
+    }
+
     /// Returns a writer over the given range.
-    pub fn writer<'a, 'b, Range>(&'a mut self, range: Range) -> Writer64K<'b>
+    pub fn writer<Range>(&mut self, range: Range) -> Writer64K<'_>
+    where
+        Range: IntoRegion<65535>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Writer64K::new_unchecked(address, len, self.device) }
+    }
+
+    /// Returns a writer over the given range of offsets, if it is within the device.
+    pub fn try_writer(&mut self, range: ops::Range<usize>) -> Result<Writer64K<'_>, RangeError> {
+        Ok(self.writer(bounded_range(range)?))
+    }
+
+    /// Returns a writer over the given range, if the range is within the erased sectors.
+    ///
+    /// If any part of the range lies outside of `erased`, [`Error::NotErased`] is returned with
+    /// the offset of the first byte outside of the erased sectors.
+    pub fn writer_into<Range>(
+        &mut self,
+        erased: &ErasedSectors<15>,
+        range: Range,
+    ) -> Result<Writer64K<'_>, Error>
     where
-        'a: 'b,
-        Range: RangeBounds<RangedUsize<0, 65535>>,
+        Range: IntoRegion<65535>,
     {
-        let (address, len) = translate_range_to_buffer(range);
-        unsafe { Writer64K::new_unchecked(address, len) }
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        erased.check_covers(unsafe { address.offset_from(FLASH_MEMORY) as usize }, len)?;
+        Ok(unsafe { Writer64K::new_unchecked(address, len, self.device) })
+    }
+}
+
+impl Display for Flash64K {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{} ({})", self.name(), self.device.name())
     }
 }
 
@@ -257,110 +724,877 @@ impl Flash64K {
 /// sectors. Instead, they can be written to directly, as the sector size is small enough to fit
 /// into an internal buffer.
 #[derive(Debug)]
-pub struct Flash64KAtmel;
+pub struct Flash64KAtmel {
+    device: Device,
+    id_swapped: bool,
+    halt: Option<Halt>,
+    erase_confirmations: NonZeroU8,
+}
 
 impl Flash64KAtmel {
+    /// Returns a human-readable name for this type of backup memory.
+    pub fn name(&self) -> &'static str {
+        "Flash 64KiB (Atmel)"
+    }
+
+    /// Returns the number of bytes of storage available on this device.
+    pub fn capacity(&self) -> usize {
+        SIZE_64KB
+    }
+
+    /// Returns information about the device's characteristics.
+    pub fn info(&self) -> DeviceInfo {
+        self.device.info().reported(self.id_swapped)
+    }
+
+    /// Returns the raw ID reported by the device when it was detected.
+    ///
+    /// The low byte is the manufacturer ID and the high byte is the chip ID, unless the device
+    /// reported its ID with the bytes swapped, as indicated by [`DeviceInfo::id_swapped`]. For
+    /// such devices the bytes are returned as reported, with the chip ID in the low byte and the
+    /// manufacturer ID in the high byte. [`manufacturer_id()`](Self::manufacturer_id()) and
+    /// [`chip_id()`](Self::chip_id()) return the correct bytes either way.
+    pub fn device_id(&self) -> u16 {
+        self.info().id
+    }
+
+    /// Returns the manufacturer ID reported by the device when it was detected.
+    pub fn manufacturer_id(&self) -> u8 {
+        self.device.id() as u8
+    }
+
+    /// Returns the chip ID reported by the device when it was detected.
+    pub fn chip_id(&self) -> u8 {
+        (self.device.id() >> 8) as u8
+    }
+
+    /// Checks that the device detected at initialization is still present.
+    ///
+    /// This re-enters ID mode and compares the reported ID against the ID reported when the device
+    /// was detected. If the cartridge has been removed or replaced, the ID reported is returned as
+    /// an error. No stored data is modified.
+    pub fn verify_present(&mut self) -> Result<(), UnknownDeviceID> {
+        verify_device_present(self.device, self.device_id(), self.halt)
+    }
+
+    /// Waits until the device has finished any in-progress program or erase operation.
+    ///
+    /// This can be used to synchronize with the device before reading back written data or
+    /// powering down. If the device is still busy after `timeout`, [`Error::OperationTimedOut`]
+    /// is returned.
+    pub fn wait_ready(&mut self, timeout: Duration) -> Result<(), Error> {
+        poll_ready(timeout, self.halt)
+    }
+
+    /// Returns the number of consecutive erased reads required to confirm an erase.
+    pub fn erase_confirmations(&self) -> NonZeroU8 {
+        self.erase_confirmations
+    }
+
+    /// Sets the number of consecutive erased reads required to confirm an erase.
+    ///
+    /// See [`Flash::set_erase_confirmations()`] for details.
+    pub fn set_erase_confirmations(&mut self, confirmations: NonZeroU8) {
+        self.erase_confirmations = confirmations;
+    }
+
     /// Returns a reader over the given range.
-    pub fn reader<'a, 'b, Range>(&'a mut self, range: Range) -> Reader64K<'b>
+    pub fn reader<Range>(&self, range: Range) -> Reader64K<'_>
     where
-        'a: 'b,
-        Range: RangeBounds<RangedUsize<0, 65535>>,
+        Range: IntoRegion<65535>,
     {
-        let (address, len) = translate_range_to_buffer(range);
-        unsafe { Reader64K::new_unchecked(address, len) }
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Reader64K::new_unchecked(address, len) }.logged(self.name())
     }
 
-    /// Returns a writer over the given range.
-    pub fn writer<'a, 'b, Range>(&'a mut self, range: Range) -> Writer64KAtmel<'b>
+    /// Returns a reader over the given range of offsets, if it is within the device.
+    pub fn try_reader(&self, range: ops::Range<usize>) -> Result<Reader64K<'_>, RangeError> {
+        Ok(self.reader(bounded_range(range)?))
+    }
+
+    /// Returns whether every byte in the given range is erased.
+    ///
+    /// This can be used to check whether data can be appended to a range without erasing it
+    /// first.
+    pub fn is_erased<Range>(&self, range: Range) -> bool
     where
-        'a: 'b,
-        Range: RangeBounds<RangedUsize<0, 65535>>,
+        Range: IntoRegion<65535>,
     {
-        let (address, len) = translate_range_to_buffer(range);
-        unsafe { Writer64KAtmel::new_unchecked(address, len) }
+        self.first_programmed_byte(range).is_none()
     }
-}
 
-/// A flash device with 128KiB of storage.
-///
-/// This storage type is divided into 32 4KiB sectors. Each sector must be erased before it can be
-/// written to. Failing to erase a sector will result in invalid data.
-#[derive(Debug)]
-pub struct Flash128K;
+    /// Returns the offset of the first byte in the given range that is not erased.
+    ///
+    /// The offset is measured from the start of the device. If every byte in the range is
+    /// erased, `None` is returned.
+    pub fn first_programmed_byte<Range>(&self, range: Range) -> Option<usize>
+    where
+        Range: IntoRegion<65535>,
+    {
+        let region = range.into_region();
+        find_programmed(self.reader(region), region.offset())
+    }
 
-impl Flash128K {
-    /// Returns a reader over the given range.
-    pub fn reader<'a, 'b, Range>(&'a mut self, range: Range) -> Reader128K<'b>
+    /// Plans writing `data_len` bytes to the start of the given range, for use with
+    /// [`estimate_duration()`](Self::estimate_duration()).
+    ///
+    /// At most the length of the range is written. Every 128-byte page covered by the write is
+    /// erased and programmed, so no erases are needed beforehand.
+    pub fn plan_write<Range>(&self, range: Range, data_len: usize) -> SavePlan
     where
-        'a: 'b,
-        Range: RangeBounds<RangedUsize<0, 131071>>,
+        Range: IntoRegion<65535>,
     {
-        let (address, len) = translate_range_to_buffer(range);
-        unsafe { Reader128K::new_unchecked(address, len) }
+        let region = range.into_region();
+        let bytes = min(data_len, region.len());
+        SavePlan::atmel(region.offset(), bytes, ATMEL_PAGE_SIZE)
     }
 
-    /// Erases the specified sectors.
+    /// Estimates how long performing `plan` takes on this device.
     ///
-    /// This should be called before attempting to write to these sectors. Memory that has already
-    /// been written to cannot be written to again without first being erased.
-    pub fn erase_sectors<Range>(&mut self, sectors: Range) -> Result<(), Error>
+    /// The estimate uses the typical timings from [`info()`](Self::info()), so it should only be
+    /// used to judge the scale of a save, such as to decide whether to show a progress bar.
+    pub fn estimate_duration(&self, plan: &SavePlan) -> Duration {
+        plan.atmel_duration(self.info())
+    }
+
+    /// Tests that data can be written to and read from the device.
+    ///
+    /// A test pattern is written to the given 128-byte page and read back. The contents of the
+    /// page are overwritten, so it should be reserved for diagnostics. See the
+    /// [`diagnostics`](crate::diagnostics) module for details.
+    pub fn self_test(&mut self, page: FlashPage64KAtmel) -> SelfTestReport {
+        let address = unsafe { FLASH_MEMORY.add(page.get() as usize * ATMEL_PAGE_SIZE) };
+        let mut buf = [0; ATMEL_PAGE_SIZE];
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = PATTERN[i % PATTERN.len()];
+        }
+        let mut budget = PollBudget::new(PROGRAM_TIMEOUT);
+        let write_ok = program_page(address, &buf, &mut budget).is_ok();
+        let verify_ok = buf
+            .iter()
+            .enumerate()
+            .all(|(i, &byte)| unsafe { address.add(i).read_volatile() } == byte);
+
+        SelfTestReport {
+            write_ok,
+            verify_ok,
+            latency: budget.spent(),
+        }
+    }
+
+    /// Returns a writer over the given range.
+    pub fn writer<Range>(&mut self, range: Range) -> Writer64KAtmel<'_>
     where
-        Range: RangeBounds<RangedU8<0, 31>>,
+        Range: IntoRegion<65535>,
     {
-        let sectors_range = translate_range_to_sectors(sectors);
-        let mut bank = if sectors_range.start < 16 {
-            Bank::_0
-        } else {
-            Bank::_1
-        };
-        switch_bank(bank);
-        for mut sector in sectors_range {
-            if matches!(bank, Bank::_0) {
-                if sector >= 16 {
-                    bank = Bank::_1;
-                    switch_bank(bank);
-                }
-            }
-            if matches!(bank, Bank::_1) {
-                sector %= 16;
-            }
-            erase_sector(sector)?;
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Writer64KAtmel::new_unchecked(address, len) }
+    }
+
+    /// Erases the entire device.
+    ///
+    /// After the erase is issued, the first byte of every 128-byte page is checked to confirm the
+    /// erase completed.
+    ///
+    /// The erase fails with [`Error::OperationTimedOut`] if the device does not read as erased
+    /// within [`ERASE_TIMEOUT_MARGIN`] times its typical chip erase time.
+    pub fn erase_chip(&mut self) -> Result<(), Error> {
+        erase_chip(self.device, self.erase_confirmations, self.halt)?;
+        for page in 0..(SIZE_64KB / ATMEL_PAGE_SIZE) {
+            verify_erased(
+                unsafe { FLASH_MEMORY.add(page * ATMEL_PAGE_SIZE) },
+                self.device.sector_erase_timeout(),
+                self.erase_confirmations,
+                None,
+            )?;
         }
+        record_stat!(sectors_erased, 16);
         Ok(())
     }
 
-    /// Returns a writer over the given range.
-    pub fn writer<'a, 'b, Range>(&'a mut self, range: Range) -> Writer128K<'b>
+    /// Fills the given range with `byte`.
+    ///
+    /// Each page touched by the range is rewritten in full, preserving any data in the page that
+    /// lies outside of the range.
+    pub fn fill<Range>(&mut self, range: Range, byte: u8) -> Result<(), Error>
     where
-        'a: 'b,
-        Range: RangeBounds<RangedUsize<0, 131071>>,
+        Range: IntoRegion<65535>,
     {
-        let (address, len) = translate_range_to_buffer(range);
-        unsafe { Writer128K::new_unchecked(address, len) }
+        let (address, mut len) = translate_range_to_buffer(range.into_region());
+        let mut writer = unsafe { Writer64KAtmel::new_unchecked(address, len) };
+        let buf = [byte; ATMEL_PAGE_SIZE];
+        while len > 0 {
+            len -= writer.write(&buf[..min(len, ATMEL_PAGE_SIZE)])?;
+        }
+        writer.flush()
+    }
+
+    /// Returns a writer over the given range of offsets, if it is within the device.
+    pub fn try_writer(
+        &mut self,
+        range: ops::Range<usize>,
+    ) -> Result<Writer64KAtmel<'_>, RangeError> {
+        Ok(self.writer(bounded_range(range)?))
     }
 }
 
-/// The currently available flash backup device.
-///
-/// The GBA has three different variants of flash backup:
-/// - 64KiB
-/// - 64KiB Atmel
-/// - 128KiB
-///
-/// Each of these backup types are interacted with in slightly different ways. Therefore, they are
-/// treated separately by this library. This type contains the variant of the currently available
-/// flash device. Users should match on the variants of this type and provide specific behavior for
-/// each supported variant.
+impl Display for Flash64KAtmel {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        // The device name already identifies the chip as an Atmel one.
+        write!(formatter, "Flash 64KiB ({})", self.device.name())
+    }
+}
+
+/// A flash device with 128KiB of storage.
 ///
-/// # Example
-/// ``` no_run
-/// use gba_save::flash::Flash;
+/// This storage type is divided into 32 4KiB sectors. Each sector must be erased before it can be
+/// written to. Failing to erase a sector will result in invalid data.
 ///
-/// let flash = unsafe { Flash::new() }.expect("flash not available");
-/// match flash {
-///     Flash::Flash64K(flash_64k) => {
-///         // Read, write, etc.
-///     }
+/// Some carts implement 128KiB of flash using two separate 64KiB chips, one for each bank. These
+/// report the ID of a 64KiB device and are not detected by [`Flash::new()`]. Such carts can
+/// instead be accessed using [`Flash::assume_128k_dual_die()`].
+#[derive(Debug)]
+pub struct Flash128K {
+    device: Device,
+    id_swapped: bool,
+    halt: Option<Halt>,
+    erase_confirmations: NonZeroU8,
+    dual_die: bool,
+    erase_counts: Option<EraseCounts>,
+}
+
+impl Flash128K {
+    /// Returns a human-readable name for this type of backup memory.
+    pub fn name(&self) -> &'static str {
+        "Flash 128KiB"
+    }
+
+    /// Returns whether this device was created using [`Flash::assume_128k_dual_die()`].
+    pub fn is_dual_die(&self) -> bool {
+        self.dual_die
+    }
+
+    /// Returns the number of bytes of storage available on this device.
+    pub fn capacity(&self) -> usize {
+        SIZE_64KB * 2
+    }
+
+    /// Returns information about the device's characteristics.
+    pub fn info(&self) -> DeviceInfo {
+        // Dual-die carts report the ID of a 64KiB device.
+        DeviceInfo {
+            erase_blocks: 32,
+            ..self.device.info().reported(self.id_swapped)
+        }
+    }
+
+    /// Returns the raw ID reported by the device when it was detected.
+    ///
+    /// The low byte is the manufacturer ID and the high byte is the chip ID, unless the device
+    /// reported its ID with the bytes swapped, as indicated by [`DeviceInfo::id_swapped`]. For
+    /// such devices the bytes are returned as reported, with the chip ID in the low byte and the
+    /// manufacturer ID in the high byte. [`manufacturer_id()`](Self::manufacturer_id()) and
+    /// [`chip_id()`](Self::chip_id()) return the correct bytes either way.
+    pub fn device_id(&self) -> u16 {
+        self.info().id
+    }
+
+    /// Returns the manufacturer ID reported by the device when it was detected.
+    pub fn manufacturer_id(&self) -> u8 {
+        self.device.id() as u8
+    }
+
+    /// Returns the chip ID reported by the device when it was detected.
+    pub fn chip_id(&self) -> u8 {
+        (self.device.id() >> 8) as u8
+    }
+
+    /// Checks that the device detected at initialization is still present.
+    ///
+    /// This re-enters ID mode and compares the reported ID against the ID reported when the device
+    /// was detected. If the cartridge has been removed or replaced, the ID reported is returned as
+    /// an error. No stored data is modified.
+    pub fn verify_present(&mut self) -> Result<(), UnknownDeviceID> {
+        verify_device_present(self.device, self.device_id(), self.halt)
+    }
+
+    /// Waits until the device has finished any in-progress program or erase operation.
+    ///
+    /// This can be used to synchronize with the device before reading back written data or
+    /// powering down. If the device is still busy after `timeout`, [`Error::OperationTimedOut`]
+    /// is returned.
+    pub fn wait_ready(&mut self, timeout: Duration) -> Result<(), Error> {
+        poll_ready(timeout, self.halt)
+    }
+
+    /// Returns the number of consecutive erased reads required to confirm an erase.
+    pub fn erase_confirmations(&self) -> NonZeroU8 {
+        self.erase_confirmations
+    }
+
+    /// Sets the number of consecutive erased reads required to confirm an erase.
+    ///
+    /// See [`Flash::set_erase_confirmations()`] for details.
+    pub fn set_erase_confirmations(&mut self, confirmations: NonZeroU8) {
+        self.erase_confirmations = confirmations;
+    }
+
+    /// Returns a reader over the given range.
+    ///
+    /// Multiple readers may exist at once. Since reading switches the device's active bank, each
+    /// reader re-selects the bank it needs at the start of every read.
+    pub fn reader<Range>(&self, range: Range) -> Reader128K<'_>
+    where
+        Range: IntoRegion<131071>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Reader128K::new_unchecked(address, len) }.logged("Flash 128KiB")
+    }
+
+    /// Returns a reader over the given range of offsets, if it is within the device.
+    pub fn try_reader(&self, range: ops::Range<usize>) -> Result<Reader128K<'_>, RangeError> {
+        Ok(self.reader(bounded_range(range)?))
+    }
+
+    /// Returns a reader over the given range within a single bank.
+    ///
+    /// The range is measured from the start of `bank`. The bank is selected once when the reader
+    /// is created, so reads skip the bank check performed by [`Reader128K`], making this faster
+    /// for data that lives entirely in one bank.
+    ///
+    /// This borrows the device mutably, since any other reader or writer could select a different
+    /// bank while this reader is alive.
+    pub fn bank_reader<Range>(&mut self, bank: FlashBank, range: Range) -> Reader64K<'_>
+    where
+        Range: IntoRegion<65535>,
+    {
+        switch_bank(bank.into());
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Reader64K::new_unchecked(address, len) }.logged(match bank {
+            FlashBank::_0 => "Flash 128KiB bank 0",
+            FlashBank::_1 => "Flash 128KiB bank 1",
+        })
+    }
+
+    /// Returns whether every byte in the given range is erased.
+    ///
+    /// This can be used to check whether data can be appended to a range without erasing it
+    /// first.
+    ///
+    /// The range may span both banks.
+    pub fn is_erased<Range>(&self, range: Range) -> bool
+    where
+        Range: IntoRegion<131071>,
+    {
+        self.first_programmed_byte(range).is_none()
+    }
+
+    /// Returns the offset of the first byte in the given range that is not erased.
+    ///
+    /// The offset is measured from the start of the device. If every byte in the range is
+    /// erased, `None` is returned.
+    ///
+    /// The range may span both banks.
+    pub fn first_programmed_byte<Range>(&self, range: Range) -> Option<usize>
+    where
+        Range: IntoRegion<131071>,
+    {
+        let region = range.into_region();
+        find_programmed(self.reader(region), region.offset())
+    }
+
+    /// Plans writing `data_len` bytes to the start of the given range, for use with
+    /// [`estimate_duration()`](Self::estimate_duration()).
+    ///
+    /// At most the length of the range is written. Each sector covered by the write that is
+    /// not already erased over the written bytes is counted as requiring an erase, so this scans the
+    /// device.
+    pub fn plan_write<Range>(&self, range: Range, data_len: usize) -> SavePlan
+    where
+        Range: IntoRegion<131071>,
+    {
+        let region = range.into_region();
+        let bytes = min(data_len, region.len());
+        SavePlan::flash(
+            SaveBackend::Flash128K,
+            region.offset(),
+            bytes,
+            |start, end| !self.is_erased(Region::from_offsets(start, end)),
+        )
+    }
+
+    /// Estimates how long performing `plan` takes on this device.
+    ///
+    /// The estimate uses the typical timings from [`info()`](Self::info()), so it should only be
+    /// used to judge the scale of a save, such as to decide whether to show a progress bar.
+    pub fn estimate_duration(&self, plan: &SavePlan) -> Duration {
+        plan.flash_duration(self.info())
+    }
+
+    /// Returns the state of each sector on the device, in order.
+    ///
+    /// Each sector is scanned when the iterator reaches it, stopping at the first byte that is not
+    /// erased.
+    ///
+    /// Sectors 16 and above are in the second bank.
+    pub fn sector_summary(&self) -> impl Iterator<Item = SectorState> + '_ {
+        (0..32).map(|index| {
+            SectorState::new(
+                index,
+                self.first_programmed_byte(FlashRegion128K::new(
+                    index as usize * SECTOR_SIZE,
+                    SECTOR_SIZE,
+                )),
+            )
+        })
+    }
+
+    /// Erases the specified sectors.
+    ///
+    /// This should be called before attempting to write to these sectors. Memory that has already
+    /// been written to cannot be written to again without first being erased.
+    ///
+    /// On success, a record of the erased sectors is returned, which can be used with
+    /// [`writer_into()`](Self::writer_into()).
+    pub fn erase_sectors<Range>(&mut self, sectors: Range) -> Result<ErasedSectors<31>, Error>
+    where
+        Range: RangeBounds<FlashSector128K>,
+    {
+        self.erase_sectors_with_progress(sectors, |_| {})
+    }
+
+    /// Erases the specified sectors, reporting progress after each sector is erased.
+    ///
+    /// Progress is reported in units of sectors. The callback is invoked between sector erase
+    /// operations, so it is safe to perform rendering work within it.
+    pub fn erase_sectors_with_progress<Range, F>(
+        &mut self,
+        sectors: Range,
+        mut progress: F,
+    ) -> Result<ErasedSectors<31>, Error>
+    where
+        Range: RangeBounds<FlashSector128K>,
+        F: FnMut(Progress),
+    {
+        let mut erase = self.erase_iter(sectors);
+        let total = erase.len();
+        for (completed, result) in (&mut erase).enumerate() {
+            result?;
+            progress(Progress {
+                completed: completed + 1,
+                total,
+            });
+        }
+        Ok(erase.erased())
+    }
+
+    /// Returns an iterator that erases one of the specified sectors each time it is advanced.
+    ///
+    /// This allows erasing to be interleaved with other work, such as rendering one frame per
+    /// sector. See [`EraseIter`] for details.
+    ///
+    /// The bank is switched as needed between sectors, just as in
+    /// [`erase_sectors()`](Self::erase_sectors()).
+    pub fn erase_iter<Range>(&mut self, sectors: Range) -> EraseIter<'_, 31>
+    where
+        Range: RangeBounds<FlashSector128K>,
+    {
+        EraseIter::new(
+            translate_range_to_sectors(sectors),
+            self.sector_access(),
+            self.erase_counts.as_mut(),
+        )
+    }
+
+    /// Begins tracking how many times each sector is erased, persisting the counts on the device.
+    ///
+    /// The counts are stored in a log that alternates between the two `log` sectors, which must
+    /// be reserved for this purpose and not otherwise written. See
+    /// [`Flash64K::track_erase_counts()`] for details.
+    pub fn track_erase_counts(&mut self, log: [FlashSector128K; 2]) -> Result<(), Error> {
+        if log[0] == log[1] {
+            return Err(Error::DuplicateLogSector {
+                sector: log[0].get(),
+            });
+        }
+        self.erase_counts = Some(EraseCounts::open(
+            &mut self.sector_access(),
+            32,
+            [log[0].get(), log[1].get()],
+        )?);
+        Ok(())
+    }
+
+    /// Returns the number of times each sector has been erased, if tracking is enabled.
+    ///
+    /// See [`track_erase_counts()`](Self::track_erase_counts()) for details.
+    pub fn sector_erase_counts(&self) -> Option<[u32; 32]> {
+        self.erase_counts
+            .as_ref()
+            .map(|erase_counts| erase_counts.counts())
+    }
+
+    fn sector_access(&self) -> SectorAccess {
+        SectorAccess::new(
+            self.device,
+            self.erase_confirmations,
+            self.halt,
+            true,
+            self.dual_die,
+        )
+    }
+
+    /// Tests that data can be written to and read from the device.
+    ///
+    /// The given sector is erased and a test pattern is written to it and read back. The contents
+    /// of the sector are overwritten, so it should be reserved for diagnostics. See the
+    /// [`diagnostics`](crate::diagnostics) module for details.
+    pub fn self_test(&mut self, sector: FlashSector128K) -> SelfTestReport {
+        let offset = sector.get() as usize * SECTOR_SIZE;
+        if sector.get() < 16 {
+            switch_bank(Bank::_0);
+            self_test_sector(
+                sector.get(),
+                offset,
+                self.device,
+                self.erase_confirmations,
+                self.halt,
+            )
+        } else {
+            switch_bank(Bank::_1);
+            self_test_sector(
+                sector.get() - 16,
+                offset,
+                self.device,
+                self.erase_confirmations,
+                self.halt,
+            )
+        }
+    }
+
+    /// Returns a writer over the given range.
+    ///
+    /// The writer selects the bank containing the start of the range when it is created, so the
+    /// bank is always reasserted after any previous erase.
+    pub fn writer<Range>(&mut self, range: Range) -> Writer128K<'_>
+    where
+        Range: IntoRegion<131071>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Writer128K::new_unchecked(address, len, self.device) }
+    }
+
+    /// Returns a writer over the given range of offsets, if it is within the device.
+    pub fn try_writer(&mut self, range: ops::Range<usize>) -> Result<Writer128K<'_>, RangeError> {
+        Ok(self.writer(bounded_range(range)?))
+    }
+
+    /// Returns a writer over the given range, if the range is within the erased sectors.
+    ///
+    /// If any part of the range lies outside of `erased`, [`Error::NotErased`] is returned with
+    /// the offset of the first byte outside of the erased sectors.
+    pub fn writer_into<Range>(
+        &mut self,
+        erased: &ErasedSectors<31>,
+        range: Range,
+    ) -> Result<Writer128K<'_>, Error>
+    where
+        Range: IntoRegion<131071>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        erased.check_covers(unsafe { address.offset_from(FLASH_MEMORY) as usize }, len)?;
+        Ok(unsafe { Writer128K::new_unchecked(address, len, self.device) })
+    }
+
+    /// Splits the device into its two 64KiB banks, each of which can be used independently.
+    ///
+    /// This allows the type system to keep separate data in separate banks, such as save data in
+    /// one bank and recordings in the other. See [`FlashBank64K`] for details.
+    ///
+    /// Erase count tracking stops when the device is split. The counts remain in their log on the
+    /// device, so calling [`track_erase_counts()`](Self::track_erase_counts()) again after
+    /// [`unsplit()`](Self::unsplit()) resumes them.
+    pub fn split(self) -> (FlashBank64K, FlashBank64K) {
+        let bank = |bank| FlashBank64K {
+            device: self.device,
+            id_swapped: self.id_swapped,
+            halt: self.halt,
+            erase_confirmations: self.erase_confirmations,
+            dual_die: self.dual_die,
+            bank,
+        };
+        (bank(FlashBank::_0), bank(FlashBank::_1))
+    }
+
+    /// Recombines the two banks returned by [`split()`](Self::split()).
+    ///
+    /// # Panics
+    /// Panics if `bank_0` is not bank 0 or `bank_1` is not bank 1.
+    pub fn unsplit(bank_0: FlashBank64K, bank_1: FlashBank64K) -> Self {
+        assert!(
+            bank_0.bank == FlashBank::_0 && bank_1.bank == FlashBank::_1,
+            "banks must be bank 0 followed by bank 1"
+        );
+        Self {
+            device: bank_0.device,
+            id_swapped: bank_0.id_swapped,
+            halt: bank_0.halt,
+            erase_confirmations: bank_0.erase_confirmations,
+            dual_die: bank_0.dual_die,
+            erase_counts: None,
+        }
+    }
+}
+
+impl Display for Flash128K {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{} ({})", self.name(), self.device.name())
+    }
+}
+
+/// One 64KiB bank of a 128KiB flash device.
+///
+/// This is created by [`Flash128K::split()`], and provides the same API as [`Flash64K`] over the
+/// bank, with offsets and sectors measured from the start of the bank. The two banks of a device
+/// can be used independently, such as by keeping them in separate parts of a program.
+///
+/// Both banks share the device's bank selection, so using one bank switches the bank seen by the
+/// other. Each bank therefore selects itself before every operation, including at the start of
+/// every read or write through its readers and writers, so readers and writers on both banks can
+/// be used in any interleaving.
+#[derive(Debug)]
+pub struct FlashBank64K {
+    device: Device,
+    id_swapped: bool,
+    halt: Option<Halt>,
+    erase_confirmations: NonZeroU8,
+    dual_die: bool,
+    bank: FlashBank,
+}
+
+impl FlashBank64K {
+    /// Returns a human-readable name for this bank.
+    pub fn name(&self) -> &'static str {
+        match self.bank {
+            FlashBank::_0 => "Flash 128KiB bank 0",
+            FlashBank::_1 => "Flash 128KiB bank 1",
+        }
+    }
+
+    /// Returns which bank of the device this is.
+    pub fn bank(&self) -> FlashBank {
+        self.bank
+    }
+
+    /// Returns the number of bytes of storage available in this bank.
+    pub fn capacity(&self) -> usize {
+        SIZE_64KB
+    }
+
+    /// Returns information about the device's characteristics.
+    ///
+    /// This describes the whole device, not just this bank.
+    pub fn info(&self) -> DeviceInfo {
+        DeviceInfo {
+            erase_blocks: 32,
+            ..self.device.info().reported(self.id_swapped)
+        }
+    }
+
+    /// Returns the number of consecutive erased reads required to confirm an erase.
+    pub fn erase_confirmations(&self) -> NonZeroU8 {
+        self.erase_confirmations
+    }
+
+    /// Sets the number of consecutive erased reads required to confirm an erase.
+    ///
+    /// See [`Flash::set_erase_confirmations()`] for details.
+    pub fn set_erase_confirmations(&mut self, confirmations: NonZeroU8) {
+        self.erase_confirmations = confirmations;
+    }
+
+    /// Returns a reader over the given range.
+    pub fn reader<Range>(&self, range: Range) -> Reader64K<'_>
+    where
+        Range: IntoRegion<65535>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Reader64K::new_unchecked(address, len) }
+            .in_bank(self.bank.into())
+            .logged(self.name())
+    }
+
+    /// Returns a reader over the given range of offsets, if it is within the bank.
+    pub fn try_reader(&self, range: ops::Range<usize>) -> Result<Reader64K<'_>, RangeError> {
+        Ok(self.reader(bounded_range(range)?))
+    }
+
+    /// Erases the specified sectors of this bank.
+    ///
+    /// This should be called before attempting to write to these sectors. Memory that has already
+    /// been written to cannot be written to again without first being erased.
+    ///
+    /// On success, a record of the erased sectors is returned, which can be used with
+    /// [`writer_into()`](Self::writer_into()).
+    pub fn erase_sectors<Range>(&mut self, sectors: Range) -> Result<ErasedSectors<15>, Error>
+    where
+        Range: RangeBounds<FlashSector64K>,
+    {
+        let mut erase = self.erase_iter(sectors);
+        for result in &mut erase {
+            result?;
+        }
+        Ok(erase.erased())
+    }
+
+    /// Returns an iterator that erases one of the specified sectors of this bank each time it is
+    /// advanced.
+    ///
+    /// See [`EraseIter`] for details.
+    pub fn erase_iter<Range>(&mut self, sectors: Range) -> EraseIter<'_, 15>
+    where
+        Range: RangeBounds<FlashSector64K>,
+    {
+        EraseIter::new(
+            translate_range_to_sectors(sectors),
+            SectorAccess::new(
+                self.device,
+                self.erase_confirmations,
+                self.halt,
+                false,
+                self.dual_die,
+            )
+            .in_bank(self.bank.into()),
+            None,
+        )
+    }
+
+    /// Returns a writer over the given range.
+    pub fn writer<Range>(&mut self, range: Range) -> Writer64K<'_>
+    where
+        Range: IntoRegion<65535>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Writer64K::new_unchecked(address, len, self.device) }.in_bank(self.bank.into())
+    }
+
+    /// Returns a writer over the given range of offsets, if it is within the bank.
+    pub fn try_writer(&mut self, range: ops::Range<usize>) -> Result<Writer64K<'_>, RangeError> {
+        Ok(self.writer(bounded_range(range)?))
+    }
+
+    /// Returns a writer over the given range, if the range is within the erased sectors.
+    ///
+    /// If any part of the range lies outside of `erased`, [`Error::NotErased`] is returned with
+    /// the offset of the first byte outside of the erased sectors.
+    pub fn writer_into<Range>(
+        &mut self,
+        erased: &ErasedSectors<15>,
+        range: Range,
+    ) -> Result<Writer64K<'_>, Error>
+    where
+        Range: IntoRegion<65535>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        erased.check_covers(unsafe { address.offset_from(FLASH_MEMORY) as usize }, len)?;
+        Ok(
+            unsafe { Writer64K::new_unchecked(address, len, self.device) }
+                .in_bank(self.bank.into()),
+        )
+    }
+}
+
+impl Display for FlashBank64K {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{} ({})", self.name(), self.device.name())
+    }
+}
+
+/// Read-only access to the first 64KiB of a flash device.
+///
+/// Unlike [`Flash`], creating this type has no side effects: the device is not identified, no
+/// commands are sent to it, and WAITCNT is not modified. This makes it suitable for crash handlers
+/// and integrity checks that should disturb as little global state as possible. Only readers can
+/// be created.
+///
+/// This can read any 64KiB device, including Atmel devices. On a 128KiB device, it reads whichever
+/// bank is currently selected, as switching banks requires sending a command.
+#[derive(Debug)]
+pub struct Flash64KReadOnly {
+    /// As this struct relies on WAITCNT already being configured, we want to make sure it can only
+    /// be constructed through its `unsafe` `new()` associated function.
+    _private: (),
+}
+
+impl Flash64KReadOnly {
+    /// Creates a read-only accessor to the flash backup.
+    ///
+    /// # Safety
+    /// WAITCNT's SRAM wait control setting (bits 0-1) must already be set to 8 cycles, and the
+    /// device must not be written to, erased, or sent any commands for the lifetime of this value.
+    pub unsafe fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Returns a reader over the given range.
+    pub fn reader<Range>(&self, range: Range) -> Reader64K<'_>
+    where
+        Range: IntoRegion<65535>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Reader64K::new_unchecked(address, len) }.logged("Flash 64KiB")
+    }
+
+    /// Returns a reader over the given range of offsets, if it is within the device.
+    pub fn try_reader(&self, range: ops::Range<usize>) -> Result<Reader64K<'_>, RangeError> {
+        Ok(self.reader(bounded_range(range)?))
+    }
+
+    /// Returns whether every byte in the given range is erased.
+    ///
+    /// This can be used to check whether data can be appended to a range without erasing it
+    /// first.
+    pub fn is_erased<Range>(&self, range: Range) -> bool
+    where
+        Range: IntoRegion<65535>,
+    {
+        self.first_programmed_byte(range).is_none()
+    }
+
+    /// Returns the offset of the first byte in the given range that is not erased.
+    ///
+    /// The offset is measured from the start of the device. If every byte in the range is
+    /// erased, `None` is returned.
+    pub fn first_programmed_byte<Range>(&self, range: Range) -> Option<usize>
+    where
+        Range: IntoRegion<65535>,
+    {
+        let region = range.into_region();
+        find_programmed(self.reader(region), region.offset())
+    }
+}
+
+/// The currently available flash backup device.
+///
+/// The GBA has three different variants of flash backup:
+/// - 64KiB
+/// - 64KiB Atmel
+/// - 128KiB
+///
+/// Each of these backup types are interacted with in slightly different ways. Therefore, they are
+/// treated separately by this library. This type contains the variant of the currently available
+/// flash device. Users should match on the variants of this type and provide specific behavior for
+/// each supported variant.
+///
+/// # Example
+/// ``` no_run
+/// use gba_save::flash::Flash;
+///
+/// let flash = unsafe { Flash::new() }.expect("flash not available");
+/// match flash {
+///     Flash::Flash64K(flash_64k) => {
+///         // Read, write, etc.
+///     }
 ///     Flash::Flash64KAtmel(flash_64k_atmel) => {
 ///         // Read, write, etc.
 ///     }
@@ -387,231 +1621,3261 @@ impl Flash {
     ///
     /// This is the starting point for interacting with the flash backup.
     ///
+    /// Some reproduction carts report their device ID with its bytes swapped. If the reported ID
+    /// is not recognized but its swapped form is, that device is used, and
+    /// [`DeviceInfo::id_swapped`] is set in its [`info()`](Self::info()). A warning is logged when
+    /// the `log` feature is enabled.
+    ///
+    /// This sets WAITCNT's SRAM wait control setting (bits 0-1) to 8 cycles. No other bits of
+    /// WAITCNT are modified.
+    ///
     /// # Safety
     /// Must have exclusive ownership of both flash RAM memory and WAITCNT's SRAM wait control
     /// setting for the duration of its lifetime.
     pub unsafe fn new() -> Result<Self, UnknownDeviceID> {
-        let mut waitstate_control = unsafe { WAITCNT.read_volatile() };
-        waitstate_control.set_backup_waitstate(Cycles::_8);
-        unsafe { WAITCNT.write_volatile(waitstate_control) };
+        unsafe { Self::new_with(None, None) }.map(|(flash, _)| flash)
+    }
+
+    /// Returns the variant of the currently available flash device, using a known value of
+    /// WAITCNT.
+    ///
+    /// Rather than reading WAITCNT, the SRAM wait control setting is applied to
+    /// `waitstate_control`, which is then written to WAITCNT and returned alongside the flash
+    /// device. This allows callers that keep their own copy of WAITCNT to keep that copy
+    /// coherent. WAITCNT is written even if no known device is found.
+    ///
+    /// # Safety
+    /// Must have exclusive ownership of both flash RAM memory and WAITCNT's SRAM wait control
+    /// setting for the duration of its lifetime. `waitstate_control` must be the current value
+    /// of WAITCNT, or else the other settings in WAITCNT will be overwritten.
+    pub unsafe fn new_with_waitstate(
+        waitstate_control: WaitstateControl,
+    ) -> Result<(Self, WaitstateControl), UnknownDeviceID> {
+        unsafe { Self::new_with(Some(waitstate_control), None) }
+    }
+
+    /// Returns the variant of the currently available flash device, halting the CPU during long
+    /// waits.
+    ///
+    /// Detecting the device, erasing, and waiting for the device to become ready will halt the
+    /// CPU between polls of the device rather than spinning, which saves power. See the
+    /// [`halt`](crate::halt) module for details.
+    ///
+    /// This sets WAITCNT's SRAM wait control setting (bits 0-1) to 8 cycles. No other bits of
+    /// WAITCNT are modified.
+    ///
+    /// # Safety
+    /// Must have exclusive ownership of both flash RAM memory and WAITCNT's SRAM wait control
+    /// setting for the duration of its lifetime.
+    pub unsafe fn new_with_halt(halt: Halt) -> Result<Self, UnknownDeviceID> {
+        unsafe { Self::new_with(None, Some(halt)) }.map(|(flash, _)| flash)
+    }
+
+    /// Detects the flash device after setting the SRAM wait control setting.
+    ///
+    /// If `waitstate_control` is given, the setting is applied to it and the whole value is
+    /// written. Otherwise, only the setting is modified in the current value of WAITCNT.
+    unsafe fn new_with(
+        waitstate_control: Option<WaitstateControl>,
+        halt: Option<Halt>,
+    ) -> Result<(Self, WaitstateControl), UnknownDeviceID> {
+        let waitstate_control = match waitstate_control {
+            Some(mut waitstate_control) => {
+                waitstate_control.set_backup_waitstate(Cycles::_8);
+                unsafe { waitstate::write(waitstate_control) };
+                waitstate_control
+            }
+            None => unsafe { waitstate::set_backup_waitstate_8() },
+        };
+
+        let (device, id_swapped) = Device::detect(enter_id_mode(halt))?;
+        exit_id_mode(device, halt);
+
+        let flash = match device {
+            _ if device.quirks().page_program => Self::Flash64KAtmel(Flash64KAtmel {
+                device,
+                id_swapped,
+                halt,
+                erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            }),
+            Device::MX29L010 | Device::LE26FV10N1TS => Self::Flash128K(Flash128K {
+                device,
+                id_swapped,
+                halt,
+                erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+                dual_die: false,
+                erase_counts: None,
+            }),
+            _ => Self::Flash64K(Flash64K {
+                device,
+                id_swapped,
+                halt,
+                erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+                erase_counts: None,
+            }),
+        };
+        Ok((flash, waitstate_control))
+    }
+
+    /// Returns a 128KiB flash device made up of two 64KiB chips.
+    ///
+    /// Some carts implement 128KiB of flash using a separate 64KiB chip for each bank. In ID mode,
+    /// these report the ID of the 64KiB part, so [`Flash::new()`] detects them as
+    /// [`Flash64K`] and the second bank is unreachable. This constructor instead treats the
+    /// device as 128KiB, as long as a known device ID is reported.
+    ///
+    /// The bank is reasserted after every sector erase and whenever a writer is created, since
+    /// these carts do not reliably retain the selected bank. Erasing the whole device with
+    /// [`Flash::reset()`] erases each chip in turn.
+    ///
+    /// These carts cannot be detected, so this should only be used when the cart is known to be
+    /// of this type.
+    ///
+    /// This sets WAITCNT's SRAM wait control setting (bits 0-1) to 8 cycles. No other bits of
+    /// WAITCNT are modified.
+    ///
+    /// # Safety
+    /// Must have exclusive ownership of both flash RAM memory and WAITCNT's SRAM wait control
+    /// setting for the duration of its lifetime.
+    pub unsafe fn assume_128k_dual_die() -> Result<Flash128K, UnknownDeviceID> {
+        unsafe { waitstate::set_backup_waitstate_8() };
+
+        let (device, id_swapped) = Device::detect(enter_id_mode(None))?;
+        exit_id_mode(device, None);
+
+        Ok(Flash128K {
+            device,
+            id_swapped,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: true,
+            erase_counts: None,
+        })
+    }
+
+    /// Returns a human-readable name for the type of backup memory.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k.name(),
+            Self::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel.name(),
+            Self::Flash128K(flash_128k) => flash_128k.name(),
+        }
+    }
+
+    /// Returns the number of bytes of storage available on the device.
+    pub fn capacity(&self) -> usize {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k.capacity(),
+            Self::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel.capacity(),
+            Self::Flash128K(flash_128k) => flash_128k.capacity(),
+        }
+    }
+
+    /// Returns information about the device's characteristics.
+    pub fn info(&self) -> DeviceInfo {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k.info(),
+            Self::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel.info(),
+            Self::Flash128K(flash_128k) => flash_128k.info(),
+        }
+    }
+
+    /// Returns the raw ID reported by the device when it was detected.
+    ///
+    /// The low byte is the manufacturer ID and the high byte is the chip ID, unless the device
+    /// reported its ID with the bytes swapped, as indicated by [`DeviceInfo::id_swapped`]. For
+    /// such devices the bytes are returned as reported, with the chip ID in the low byte and the
+    /// manufacturer ID in the high byte. [`manufacturer_id()`](Self::manufacturer_id()) and
+    /// [`chip_id()`](Self::chip_id()) return the correct bytes either way.
+    ///
+    /// This is cached when the device is detected, so calling this does not re-enter ID mode.
+    pub fn device_id(&self) -> u16 {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k.device_id(),
+            Self::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel.device_id(),
+            Self::Flash128K(flash_128k) => flash_128k.device_id(),
+        }
+    }
+
+    /// Returns the manufacturer ID reported by the device when it was detected.
+    pub fn manufacturer_id(&self) -> u8 {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k.manufacturer_id(),
+            Self::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel.manufacturer_id(),
+            Self::Flash128K(flash_128k) => flash_128k.manufacturer_id(),
+        }
+    }
+
+    /// Returns the chip ID reported by the device when it was detected.
+    pub fn chip_id(&self) -> u8 {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k.chip_id(),
+            Self::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel.chip_id(),
+            Self::Flash128K(flash_128k) => flash_128k.chip_id(),
+        }
+    }
+
+    /// Checks that the detected flash device is still present.
+    ///
+    /// This re-enters ID mode and compares the reported ID against the ID reported when the device
+    /// was detected, allowing removal of the cartridge to be detected before attempting to save.
+    /// If the IDs do not match, the ID reported is returned as an error. No stored data is
+    /// modified.
+    pub fn verify_present(&mut self) -> Result<(), UnknownDeviceID> {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k.verify_present(),
+            Self::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel.verify_present(),
+            Self::Flash128K(flash_128k) => flash_128k.verify_present(),
+        }
+    }
+
+    /// Waits until the device has finished any in-progress program or erase operation.
+    ///
+    /// If the device is still busy after `timeout`, [`Error::OperationTimedOut`] is returned.
+    pub fn wait_ready(&mut self, timeout: Duration) -> Result<(), Error> {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k.wait_ready(timeout),
+            Self::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel.wait_ready(timeout),
+            Self::Flash128K(flash_128k) => flash_128k.wait_ready(timeout),
+        }
+    }
+
+    /// Returns the number of consecutive erased reads required to confirm an erase.
+    pub fn erase_confirmations(&self) -> NonZeroU8 {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k.erase_confirmations(),
+            Self::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel.erase_confirmations(),
+            Self::Flash128K(flash_128k) => flash_128k.erase_confirmations(),
+        }
+    }
+
+    /// Sets the number of consecutive erased reads required to confirm an erase.
+    ///
+    /// Erases are confirmed by polling the device until it reads as erased. If an interrupt
+    /// handler accesses the cartridge bus during this polling, such as by running a DMA transfer
+    /// from ROM to stream audio, some carts return garbage for a single read. If that read
+    /// happens to look erased, the erase could be reported as complete while it is still in
+    /// progress. Requiring several consecutive erased reads makes this vanishingly unlikely.
+    ///
+    /// Defaults to [`DEFAULT_ERASE_CONFIRMATIONS`]. Applications that never access the cartridge
+    /// bus from interrupts can lower this to one.
+    pub fn set_erase_confirmations(&mut self, confirmations: NonZeroU8) {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k.set_erase_confirmations(confirmations),
+            Self::Flash64KAtmel(flash_64k_atmel) => {
+                flash_64k_atmel.set_erase_confirmations(confirmations)
+            }
+            Self::Flash128K(flash_128k) => flash_128k.set_erase_confirmations(confirmations),
+        }
+    }
+
+    /// Erase the entirety of the flash backup memory.
+    pub fn reset(&mut self) -> Result<(), Error> {
+        self.reset_with_progress(|_| {})
+    }
+
+    /// Erase the entirety of the flash backup memory, reporting progress.
+    ///
+    /// Erasing the chip is a single device operation, so progress is reported once before the
+    /// erase begins and once after it has been verified. For finer-grained progress, erase
+    /// individual sectors using `erase_sectors_with_progress()` on the specific device instead.
+    pub fn reset_with_progress<F>(&mut self, mut progress: F) -> Result<(), Error>
+    where
+        F: FnMut(Progress),
+    {
+        progress(Progress {
+            completed: 0,
+            total: 1,
+        });
+
+        match self {
+            Self::Flash64K(flash_64k) => erase_chip(
+                flash_64k.device,
+                flash_64k.erase_confirmations,
+                flash_64k.halt,
+            )?,
+            Self::Flash64KAtmel(flash_64k_atmel) => erase_chip(
+                flash_64k_atmel.device,
+                flash_64k_atmel.erase_confirmations,
+                flash_64k_atmel.halt,
+            )?,
+            Self::Flash128K(flash_128k) if flash_128k.dual_die => {
+                // Each chip only erases itself.
+                switch_bank(Bank::_0);
+                erase_chip(
+                    flash_128k.device,
+                    flash_128k.erase_confirmations,
+                    flash_128k.halt,
+                )?;
+                switch_bank(Bank::_1);
+                erase_chip(
+                    flash_128k.device,
+                    flash_128k.erase_confirmations,
+                    flash_128k.halt,
+                )?;
+            }
+            Self::Flash128K(flash_128k) => erase_chip(
+                flash_128k.device,
+                flash_128k.erase_confirmations,
+                flash_128k.halt,
+            )?,
+        }
+        record_stat!(
+            sectors_erased,
+            match self {
+                Self::Flash64K(_) | Self::Flash64KAtmel(_) => 16,
+                Self::Flash128K(_) => 32,
+            }
+        );
+        match self {
+            Self::Flash64K(flash_64k) => {
+                let mut access = flash_64k.sector_access();
+                if let Some(erase_counts) = &mut flash_64k.erase_counts {
+                    erase_counts.record_all(&mut access)?;
+                }
+            }
+            Self::Flash64KAtmel(_) => {}
+            Self::Flash128K(flash_128k) => {
+                let mut access = flash_128k.sector_access();
+                if let Some(erase_counts) = &mut flash_128k.erase_counts {
+                    erase_counts.record_all(&mut access)?;
+                }
+            }
+        }
+
+        progress(Progress {
+            completed: 1,
+            total: 1,
+        });
+        Ok(())
+    }
+}
+
+impl Display for Flash {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Flash64K(flash_64k) => Display::fmt(flash_64k, formatter),
+            Self::Flash64KAtmel(flash_64k_atmel) => Display::fmt(flash_64k_atmel, formatter),
+            Self::Flash128K(flash_128k) => Display::fmt(flash_128k, formatter),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        program_byte, Device, Error, Flash, Flash128K, Flash64K, Flash64KAtmel, Flash64KReadOnly,
+        FlashBank, Reader128K, Reader64K, TailPolicy, UnknownDeviceID, Writer64K,
+        DEFAULT_ERASE_CONFIRMATIONS, ERASE_TIMEOUT_MARGIN, FLASH_MEMORY, PROGRAM_TIMEOUT,
+        SIZE_64KB,
+    };
+    use crate::test_util::{assert_read_sizes, assert_write_sizes, log_latency, measure, Buffer};
+    use crate::{
+        adapters::RetryWriter,
+        mmio::IME,
+        range::{FlashRegion128K, FlashRegion64K},
+        verify::PollBudget,
+        waitstate::{self, WaitstateControl},
+        InsufficientData, Progress, SaveBackend, SavePlan,
+    };
+    use claims::{
+        assert_err_eq, assert_none, assert_ok, assert_ok_eq, assert_some, assert_some_eq,
+    };
+    use core::fmt::Write as _;
+    use core::mem::MaybeUninit;
+    use core::num::{NonZeroU8, NonZeroUsize};
+    use core::time::Duration;
+    use deranged::{RangedU16, RangedU8, RangedUsize};
+    use embedded_io::{Read, Write};
+    use gba_test::test;
+
+    macro_rules! assert_flash_64k {
+        ($expr:expr) => {
+            match $expr {
+                Flash::Flash64K(flash_64k) => flash_64k,
+                flash => panic!(
+                    "assertion failed, expected Flash::Flash64K(..), got {:?}",
+                    flash
+                ),
+            }
+        };
+    }
+
+    macro_rules! assert_flash_64k_atmel {
+        ($expr:expr) => {
+            match $expr {
+                Flash::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel,
+                flash => panic!(
+                    "assertion failed, expected Flash::Flash64KAtmel(..), got {:?}",
+                    flash
+                ),
+            }
+        };
+    }
+
+    macro_rules! assert_flash_128k {
+        ($expr:expr) => {
+            match $expr {
+                Flash::Flash128K(flash_128k) => flash_128k,
+                flash => panic!(
+                    "assertion failed, expected Flash::Flash129K(..), got {:?}",
+                    flash
+                ),
+            }
+        };
+    }
+
+    /// Interrupt enable.
+    const IE: *mut u16 = 0x0400_0200 as *mut u16;
+    /// Timer 0 counter/reload.
+    const TM0CNT_L: *mut u16 = 0x0400_0100 as *mut u16;
+    /// Timer 0 control.
+    const TM0CNT_H: *mut u16 = 0x0400_0102 as *mut u16;
+
+    /// Runs `f` while timer 0 requests an interrupt every 256 cycles.
+    fn with_timer_interrupts<T>(f: impl FnOnce() -> T) -> T {
+        unsafe {
+            TM0CNT_H.write_volatile(0);
+            TM0CNT_L.write_volatile(0xff00);
+            // Enable the timer with interrupts, ticking every cycle.
+            TM0CNT_H.write_volatile(0x00c0);
+            IE.write_volatile(IE.read_volatile() | 0x0008);
+            IME.write_volatile(true);
+        }
+        let result = f();
+        unsafe {
+            IME.write_volatile(false);
+            TM0CNT_H.write_volatile(0);
+            IE.write_volatile(IE.read_volatile() & !0x0008);
+        }
+        result
+    }
+
+    #[test]
+    fn erase_confirmations_default() {
+        let flash = Flash::Flash64K(Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        });
+
+        assert_eq!(flash.erase_confirmations().get(), 4);
+    }
+
+    #[test]
+    fn set_erase_confirmations() {
+        let mut flash = Flash::Flash128K(Flash128K {
+            device: Device::MX29L010,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        });
+
+        flash.set_erase_confirmations(NonZeroU8::MIN);
+
+        assert_eq!(flash.erase_confirmations(), NonZeroU8::MIN);
+    }
+
+    #[test]
+    fn track_erase_counts_same_sectors_64k() {
+        let mut flash_64k = Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        };
+
+        assert_err_eq!(
+            flash_64k.track_erase_counts([RangedU8::new_static::<15>(); 2]),
+            Error::DuplicateLogSector { sector: 15 }
+        );
+        assert_none!(flash_64k.sector_erase_counts());
+    }
+
+    #[test]
+    fn track_erase_counts_same_sectors_128k() {
+        let mut flash_128k = Flash128K {
+            device: Device::MX29L010,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        };
+
+        assert_err_eq!(
+            flash_128k.track_erase_counts([RangedU8::new_static::<31>(); 2]),
+            Error::DuplicateLogSector { sector: 31 }
+        );
+        assert_none!(flash_128k.sector_erase_counts());
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn reset_with_timer_interrupts_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        flash.set_erase_confirmations(NonZeroU8::new(8).unwrap());
+
+        assert_ok!(with_timer_interrupts(|| flash.reset()));
+        let flash = assert_flash_64k!(flash);
+        let mut reader = assert_ok!(flash.try_reader(0..4096));
+        let mut buf = [0; 4096];
+        assert_ok_eq!(reader.read(&mut buf), 4096);
+        assert_eq!(buf, [0xff; 4096]);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn reset_with_timer_interrupts_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        flash.set_erase_confirmations(NonZeroU8::new(8).unwrap());
+
+        assert_ok!(with_timer_interrupts(|| flash.reset()));
+        let flash = assert_flash_64k_atmel!(flash);
+        let mut reader = assert_ok!(flash.try_reader(0..4096));
+        let mut buf = [0; 4096];
+        assert_ok_eq!(reader.read(&mut buf), 4096);
+        assert_eq!(buf, [0xff; 4096]);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn reset_with_timer_interrupts_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        flash.set_erase_confirmations(NonZeroU8::new(8).unwrap());
+
+        assert_ok!(with_timer_interrupts(|| flash.reset()));
+        let flash = assert_flash_128k!(flash);
+        let mut reader = assert_ok!(flash.try_reader(0..4096));
+        let mut buf = [0; 4096];
+        assert_ok_eq!(reader.read(&mut buf), 4096);
+        assert_eq!(buf, [0xff; 4096]);
+    }
+
+    #[test]
+    fn name_64k() {
+        let flash = Flash::Flash64K(Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        });
+
+        assert_eq!(flash.name(), "Flash 64KiB");
+    }
+
+    #[test]
+    fn name_64k_atmel() {
+        let flash = Flash::Flash64KAtmel(Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        });
+
+        assert_eq!(flash.name(), "Flash 64KiB (Atmel)");
+    }
+
+    #[test]
+    fn name_128k() {
+        let flash = Flash::Flash128K(Flash128K {
+            device: Device::MX29L010,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        });
+
+        assert_eq!(flash.name(), "Flash 128KiB");
+    }
+
+    #[test]
+    fn device_id_64k() {
+        let flash = Flash::Flash64K(Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        });
+
+        assert_eq!(flash.device_id(), 0x1cc2);
+        assert_eq!(flash.manufacturer_id(), 0xc2);
+        assert_eq!(flash.chip_id(), 0x1c);
+    }
+
+    #[test]
+    fn device_id_64k_swapped() {
+        let flash = Flash::Flash64K(Flash64K {
+            device: Device::MX29L512,
+            id_swapped: true,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        });
+
+        assert_eq!(flash.device_id(), 0xc21c);
+        assert_eq!(flash.manufacturer_id(), 0xc2);
+        assert_eq!(flash.chip_id(), 0x1c);
+    }
+
+    #[test]
+    fn device_id_64k_atmel() {
+        let flash = Flash::Flash64KAtmel(Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        });
+
+        assert_eq!(flash.device_id(), 0x3d1f);
+        assert_eq!(flash.manufacturer_id(), 0x1f);
+        assert_eq!(flash.chip_id(), 0x3d);
+    }
+
+    #[test]
+    fn device_id_128k() {
+        let flash = Flash::Flash128K(Flash128K {
+            device: Device::LE26FV10N1TS,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        });
+
+        assert_eq!(flash.device_id(), 0x1362);
+        assert_eq!(flash.manufacturer_id(), 0x62);
+        assert_eq!(flash.chip_id(), 0x13);
+    }
+
+    #[test]
+    fn capacity_64k() {
+        let flash = Flash::Flash64K(Flash64K {
+            device: Device::MN63F805MNP,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        });
+
+        assert_eq!(flash.capacity(), 65536);
+    }
+
+    #[test]
+    fn capacity_64k_atmel() {
+        let flash = Flash::Flash64KAtmel(Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        });
+
+        assert_eq!(flash.capacity(), 65536);
+    }
+
+    #[test]
+    fn capacity_128k() {
+        let flash = Flash::Flash128K(Flash128K {
+            device: Device::LE26FV10N1TS,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        });
+
+        assert_eq!(flash.capacity(), 131072);
+    }
+
+    #[test]
+    fn display_64k() {
+        let mut buffer = Buffer::<64>::new();
+        let flash = Flash::Flash64K(Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        });
+
+        assert_ok!(write!(buffer, "{}", flash));
+        assert_eq!(buffer.as_str(), "Flash 64KiB (Macronix MX29L512)");
+    }
+
+    #[test]
+    fn display_64k_atmel() {
+        let mut buffer = Buffer::<64>::new();
+        let flash = Flash::Flash64KAtmel(Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        });
+
+        assert_ok!(write!(buffer, "{}", flash));
+        assert_eq!(buffer.as_str(), "Flash 64KiB (Atmel AT29LV512)");
+    }
+
+    #[test]
+    fn display_128k() {
+        let mut buffer = Buffer::<64>::new();
+        let flash = Flash::Flash128K(Flash128K {
+            device: Device::LE26FV10N1TS,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        });
+
+        assert_ok!(write!(buffer, "{}", flash));
+        assert_eq!(buffer.as_str(), "Flash 128KiB (Sanyo LE26FV10N1TS)");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn self_test_64k() {
+        let mut flash_64k = assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+
+        let report = flash_64k.self_test(RangedU8::new_static::<15>());
+
+        assert!(report.write_ok);
+        assert!(report.verify_ok);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn verify_present_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+
+        assert_ok!(flash.verify_present());
+        assert_flash_64k!(flash);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn new_64k() {
+        assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+    }
+
+    #[test]
+    fn read_empty_buffer_64k() {
+        let flash = Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        };
+
+        assert_ok_eq!(flash.reader(..).read(&mut []), 0);
+    }
+
+    #[test]
+    fn write_empty_buffer_64k() {
+        let mut flash = Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        };
+
+        assert_ok_eq!(flash.writer(..).write(&[]), 0);
+    }
+
+    #[test]
+    fn read_empty_buffer_64k_atmel() {
+        let flash = Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        };
+
+        assert_ok_eq!(flash.reader(..).read(&mut []), 0);
+    }
+
+    #[test]
+    fn write_empty_buffer_64k_atmel() {
+        let mut flash = Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        };
+
+        assert_ok_eq!(flash.writer(..).write(&[]), 0);
+    }
+
+    #[test]
+    fn info_128k() {
+        let flash = Flash::Flash128K(Flash128K {
+            device: Device::MX29L010,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        });
+
+        assert_eq!(flash.info(), Device::MX29L010.info());
+    }
+
+    #[test]
+    fn info_128k_dual_die() {
+        let flash = Flash128K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: true,
+            erase_counts: None,
+        };
+
+        assert_eq!(flash.info().name, "Macronix MX29L512");
+        assert_eq!(flash.info().erase_blocks, 32);
+    }
+
+    #[test]
+    fn read_empty_buffer_128k() {
+        let flash = Flash128K {
+            device: Device::MX29L010,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        };
+
+        assert_ok_eq!(flash.reader(..).read(&mut []), 0);
+    }
+
+    #[test]
+    fn write_empty_buffer_128k() {
+        let mut flash = Flash128K {
+            device: Device::MX29L010,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        };
+
+        assert_ok_eq!(flash.writer(..).write(&[]), 0);
+    }
+
+    #[test]
+    fn write_all_strict_exhausted_64k() {
+        let mut flash = Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        };
+
+        assert_err_eq!(
+            flash
+                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
+                .write_all_strict(b"hello, world!"),
+            Error::Truncated {
+                written: 0,
+                requested: 13
+            }
+        );
+    }
+
+    #[test]
+    fn commit_buffer_too_large_64k() {
+        let mut flash = Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        };
+
+        assert_err_eq!(
+            flash
+                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<12>())
+                .commit(b"hello, world!"),
+            Error::BufferTooLarge {
+                needed: 13,
+                available: 12
+            }
+        );
+    }
+
+    #[test]
+    fn commit_buffer_too_large_64k_atmel() {
+        let mut flash = Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        };
+
+        assert_err_eq!(
+            flash
+                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<12>())
+                .commit(b"hello, world!"),
+            Error::BufferTooLarge {
+                needed: 13,
+                available: 12
+            }
+        );
+    }
+
+    #[test]
+    fn commit_buffer_too_large_128k() {
+        let mut flash = Flash128K {
+            device: Device::MX29L010,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        };
+
+        assert_err_eq!(
+            flash
+                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<12>())
+                .commit(b"hello, world!"),
+            Error::BufferTooLarge {
+                needed: 13,
+                available: 12
+            }
+        );
+    }
+
+    #[test]
+    fn read_exact_into_insufficient_data_64k() {
+        let flash = Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        };
+        let mut buf = [1; 13];
+
+        assert_err_eq!(
+            flash
+                .reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<12>())
+                .read_exact_into(&mut buf),
+            InsufficientData {
+                needed: 13,
+                available: 12
+            }
+        );
+        assert_eq!(buf, [1; 13]);
+    }
+
+    #[test]
+    fn read_exact_into_insufficient_data_128k() {
+        let flash = Flash128K {
+            device: Device::MX29L010,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
+        };
+        let mut buf = [1; 13];
+
+        assert_err_eq!(
+            flash
+                .reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<12>())
+                .read_exact_into(&mut buf),
+            InsufficientData {
+                needed: 13,
+                available: 12
+            }
+        );
+        assert_eq!(buf, [1; 13]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "range is out of bounds for a 64KiB flash device")]
+    fn reader_out_of_bounds_64k() {
+        let _ = unsafe { Reader64K::new_unchecked(FLASH_MEMORY.add(SIZE_64KB), 1) };
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "range is out of bounds for a 64KiB flash device")]
+    fn writer_out_of_bounds_64k() {
+        let _ = unsafe {
+            Writer64K::new_unchecked(FLASH_MEMORY.add(SIZE_64KB - 1), 2, Device::MX29L512)
+        };
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "range is out of bounds for a 128KiB flash device")]
+    fn reader_out_of_bounds_128k() {
+        let _ = unsafe { Reader128K::new_unchecked(FLASH_MEMORY.add(SIZE_64KB), SIZE_64KB + 1) };
+    }
+
+    #[test]
+    fn read_only_new_does_not_modify_waitstate() {
+        unsafe { waitstate::write(WaitstateControl::from_bits(0x4010)) };
+        let _flash = unsafe { Flash64KReadOnly::new() };
+
+        assert_eq!(waitstate::read(), WaitstateControl::from_bits(0x4010));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn read_only_reader_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer =
+            flash_64k.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<55>());
+        assert_ok!(writer.write_all(b"hello, world!"));
+        assert_ok!(flash_64k.wait_ready(Duration::from_millis(20)));
+
+        let flash_read_only = unsafe { Flash64KReadOnly::new() };
+        let mut reader = flash_read_only
+            .reader(RangedUsize::new_static::<42>()..RangedUsize::new_static::<55>());
+        let mut buf = [0; 13];
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf, b"hello, world!");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn write_with_timer_interrupts_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k!(flash);
+        let mut data = [0; 4096];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+
+        with_timer_interrupts(|| {
+            let mut writer = assert_ok!(flash.try_writer(0..4096));
+            assert_ok!(writer.write_all(&data));
+            assert_ok!(writer.flush());
+            // Interrupts are reenabled after every command.
+            assert!(unsafe { IME.read_volatile() });
+        });
+
+        let mut reader = assert_ok!(flash.try_reader(0..4096));
+        let mut buf = [0; 4096];
+        assert_ok_eq!(reader.read(&mut buf), 4096);
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn write_with_timer_interrupts_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k_atmel!(flash);
+        let mut data = [0; 4096];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+
+        with_timer_interrupts(|| {
+            let mut writer = assert_ok!(flash.try_writer(0..4096));
+            assert_ok!(writer.write_all(&data));
+            assert_ok!(writer.flush());
+            // Interrupts are reenabled after every command.
+            assert!(unsafe { IME.read_volatile() });
+        });
+
+        let mut reader = assert_ok!(flash.try_reader(0..4096));
+        let mut buf = [0; 4096];
+        assert_ok_eq!(reader.read(&mut buf), 4096);
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn write_with_timer_interrupts_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_128k!(flash);
+        let mut data = [0; 4096];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+
+        with_timer_interrupts(|| {
+            let mut writer = assert_ok!(flash.try_writer(63488..67584));
+            assert_ok!(writer.write_all(&data));
+            assert_ok!(writer.flush());
+            // Interrupts are reenabled after every command.
+            assert!(unsafe { IME.read_volatile() });
+        });
+
+        let mut reader = assert_ok!(flash.try_reader(63488..67584));
+        let mut buf = [0; 4096];
+        assert_ok_eq!(reader.read(&mut buf), 4096);
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn is_erased_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k!(flash);
+        let mut writer = assert_ok!(flash.try_writer(200..205));
+        assert_ok!(writer.write_all(b"hello"));
+        assert_ok!(writer.flush());
+
+        assert!(flash.is_erased(FlashRegion64K::new(0, 200)));
+        assert!(flash.is_erased(FlashRegion64K::new(205, 65331)));
+        assert!(!flash.is_erased(FlashRegion64K::new(199, 2)));
+        assert!(!flash.is_erased(FlashRegion64K::new(204, 1)));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn first_programmed_byte_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k!(flash);
+        let mut writer = assert_ok!(flash.try_writer(200..205));
+        assert_ok!(writer.write_all(b"hello"));
+        assert_ok!(writer.flush());
+
+        assert_none!(flash.first_programmed_byte(FlashRegion64K::new(0, 200)));
+        assert_some_eq!(
+            flash.first_programmed_byte(FlashRegion64K::new(0, 201)),
+            200
+        );
+        assert_some_eq!(
+            flash.first_programmed_byte(FlashRegion64K::new(150, 100)),
+            200
+        );
+        assert_some_eq!(
+            flash.first_programmed_byte(FlashRegion64K::new(204, 100)),
+            204
+        );
+        assert_none!(flash.first_programmed_byte(FlashRegion64K::new(205, 65331)));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn plan_write_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k!(flash);
+        let mut writer = assert_ok!(flash.try_writer(5000..5005));
+        assert_ok!(writer.write_all(b"hello"));
+        assert_ok!(writer.flush());
+
+        let plan = flash.plan_write(FlashRegion64K::new(4000, 8000), 5000);
+
+        assert_eq!(
+            plan,
+            SavePlan {
+                backend: SaveBackend::Flash64K,
+                bytes: 5000,
+                erases: 1,
+            }
+        );
+        assert_eq!(
+            flash.estimate_duration(&plan),
+            flash.info().byte_program_time * 5000 + flash.info().sector_erase_time
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn is_erased_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k_atmel!(flash);
+        let mut writer = assert_ok!(flash.try_writer(200..205));
+        assert_ok!(writer.write_all(b"hello"));
+        assert_ok!(writer.flush());
+        drop(writer);
+
+        assert!(flash.is_erased(FlashRegion64K::new(0, 200)));
+        assert!(flash.is_erased(FlashRegion64K::new(205, 65331)));
+        assert!(!flash.is_erased(FlashRegion64K::new(199, 2)));
+        assert!(!flash.is_erased(FlashRegion64K::new(204, 1)));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn plan_write_64k_atmel() {
+        let flash = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+
+        // Spans a page boundary.
+        let plan = flash.plan_write(FlashRegion64K::new(120, 100), 13);
+
+        assert_eq!(
+            plan,
+            SavePlan {
+                backend: SaveBackend::Flash64KAtmel,
+                bytes: 13,
+                erases: 2,
+            }
+        );
+        assert_eq!(flash.estimate_duration(&plan), Duration::from_millis(20));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn first_programmed_byte_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k_atmel!(flash);
+        let mut writer = assert_ok!(flash.try_writer(200..205));
+        assert_ok!(writer.write_all(b"hello"));
+        assert_ok!(writer.flush());
+        drop(writer);
+
+        assert_none!(flash.first_programmed_byte(FlashRegion64K::new(0, 200)));
+        assert_some_eq!(
+            flash.first_programmed_byte(FlashRegion64K::new(0, 201)),
+            200
+        );
+        assert_some_eq!(
+            flash.first_programmed_byte(FlashRegion64K::new(150, 100)),
+            200
+        );
+        assert_some_eq!(
+            flash.first_programmed_byte(FlashRegion64K::new(204, 100)),
+            204
+        );
+        assert_none!(flash.first_programmed_byte(FlashRegion64K::new(205, 65331)));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn is_erased_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_128k!(flash);
+        let mut writer = assert_ok!(flash.try_writer(65539..65544));
+        assert_ok!(writer.write_all(b"hello"));
+
+        assert!(flash.is_erased(FlashRegion128K::new(0, 65539)));
+        assert!(flash.is_erased(FlashRegion128K::new(65544, 65528)));
+        // The range starts in bank 0, but the programmed bytes are in bank 1.
+        assert!(!flash.is_erased(FlashRegion128K::new(65500, 100)));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn first_programmed_byte_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_128k!(flash);
+        let mut writer = assert_ok!(flash.try_writer(65535..65536));
+        assert_ok!(writer.write_all(b"!"));
+        let mut writer = assert_ok!(flash.try_writer(65539..65544));
+        assert_ok!(writer.write_all(b"hello"));
+
+        assert_none!(flash.first_programmed_byte(FlashRegion128K::new(0, 65535)));
+        assert_some_eq!(
+            flash.first_programmed_byte(FlashRegion128K::new(65500, 100)),
+            65535
+        );
+        assert_some_eq!(
+            flash.first_programmed_byte(FlashRegion128K::new(65536, 100)),
+            65539
+        );
+        assert_none!(flash.first_programmed_byte(FlashRegion128K::new(65536, 3)));
+        assert_none!(flash.first_programmed_byte(FlashRegion128K::new(65544, 65528)));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn sector_summary_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k!(flash);
+        let mut writer = assert_ok!(flash.try_writer(57444..57445));
+        assert_ok!(writer.write_all(&[0x42]));
+
+        let mut count = 0;
+        for state in flash.sector_summary() {
+            assert_eq!(state.index, count);
+            if state.index == 14 {
+                assert!(!state.erased);
+                assert_eq!(state.first_used_offset, Some(100));
+            } else {
+                assert!(state.erased, "sector {} is not erased", state.index);
+                assert_eq!(state.first_used_offset, None);
+            }
+            count += 1;
+        }
+        assert_eq!(count, 16);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn sector_summary_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_128k!(flash);
+        let mut writer = assert_ok!(flash.try_writer(122980..122981));
+        assert_ok!(writer.write_all(&[0x42]));
+
+        let mut count = 0;
+        for state in flash.sector_summary() {
+            assert_eq!(state.index, count);
+            if state.index == 30 {
+                assert!(!state.erased);
+                assert_eq!(state.first_used_offset, Some(100));
+            } else {
+                assert!(state.erased, "sector {} is not erased", state.index);
+                assert_eq!(state.first_used_offset, None);
+            }
+            count += 1;
+        }
+        assert_eq!(count, 32);
+    }
+
+    #[test]
+    fn read_sizes_aligned_64k() {
+        let flash = unsafe { Flash64KReadOnly::new() };
+
+        assert_read_sizes!(assert_ok!(flash.try_reader(0..16)), 16);
+    }
+
+    #[test]
+    fn read_sizes_unaligned_64k() {
+        let flash = unsafe { Flash64KReadOnly::new() };
+
+        assert_read_sizes!(assert_ok!(flash.try_reader(3..16)), 13);
+    }
+
+    #[test]
+    fn read_sizes_end_of_64k() {
+        let flash = unsafe { Flash64KReadOnly::new() };
+
+        assert_read_sizes!(assert_ok!(flash.try_reader(65529..65536)), 7);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn read_sizes_aligned_128k() {
+        let flash = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+
+        assert_read_sizes!(assert_ok!(flash.try_reader(0..16)), 16);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn read_sizes_unaligned_128k() {
+        let flash = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+
+        assert_read_sizes!(assert_ok!(flash.try_reader(3..16)), 13);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn read_sizes_across_banks_128k() {
+        let flash = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+
+        assert_read_sizes!(assert_ok!(flash.try_reader(65529..65542)), 13);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn write_sizes_aligned_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k!(flash);
+
+        assert_write_sizes!(assert_ok!(flash.try_writer(0..16)), 16);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn write_sizes_unaligned_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k!(flash);
+
+        assert_write_sizes!(assert_ok!(flash.try_writer(3..16)), 13);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn write_sizes_end_of_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k!(flash);
+
+        assert_write_sizes!(assert_ok!(flash.try_writer(65529..65536)), 7);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn write_sizes_aligned_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k_atmel!(flash);
+
+        assert_write_sizes!(assert_ok!(flash.try_writer(0..128)), 128);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn write_sizes_unaligned_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_64k_atmel!(flash);
+
+        assert_write_sizes!(assert_ok!(flash.try_writer(125..138)), 13);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn write_sizes_aligned_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_128k!(flash);
+
+        assert_write_sizes!(assert_ok!(flash.try_writer(0..16)), 16);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn write_sizes_unaligned_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_128k!(flash);
+
+        assert_write_sizes!(assert_ok!(flash.try_writer(3..16)), 13);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn write_sizes_across_banks_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash = assert_flash_128k!(flash);
+
+        assert_write_sizes!(assert_ok!(flash.try_writer(65529..65542)), 13);
+    }
+
+    #[test]
+    fn write_empty_buffer_exhausted_64k() {
+        let mut flash = Flash64K {
+            device: Device::MX29L512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            erase_counts: None,
+        };
+
+        assert_err_eq!(
+            flash
+                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
+                .write(&[]),
+            Error::EndOfWriter
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn empty_range_read_64k() {
+        let flash = assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+        let mut buffer = [1, 2, 3, 4];
+
+        assert_ok_eq!(
+            flash
+                .reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
+                .read(&mut buffer),
+            0
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn empty_range_write_64k() {
+        let mut flash = assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+
+        assert_err_eq!(
+            flash
+                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
+                .write(&[1, 2, 3, 4]),
+            Error::EndOfWriter
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn full_range_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer = flash_64k.writer(..);
+
+        for i in 0..16384 {
+            assert_ok_eq!(
+                writer.write(&[
+                    0u8.wrapping_add(i as u8),
+                    1u8.wrapping_add(i as u8),
+                    2u8.wrapping_add(i as u8),
+                    3u8.wrapping_add(i as u8)
+                ]),
+                4
+            );
+        }
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_64k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_64k.reader(..);
+        let mut buf = [0, 0, 0, 0];
+
+        for i in 0..16384 {
+            assert_ok_eq!(reader.read(&mut buf), 4);
+            assert_eq!(
+                buf,
+                [
+                    0u8.wrapping_add(i as u8),
+                    1u8.wrapping_add(i as u8),
+                    2u8.wrapping_add(i as u8),
+                    3u8.wrapping_add(i as u8)
+                ],
+            );
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn commit_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer =
+            flash_64k.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<55>());
+
+        assert_ok!(writer.commit(b"hello, world!"));
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_64k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader =
+            flash_64k.reader(RangedUsize::new_static::<42>()..RangedUsize::new_static::<55>());
+        let mut buf = [0; 13];
+
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf, b"hello, world!");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn write_verified_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+
+        assert_ok!(flash_64k
+            .writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<55>())
+            .write_verified(b"hello, world!"));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn timed_out_returns_to_read_mode_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        assert_ok!(flash_64k
+            .writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<55>())
+            .write_all(b"hello, world!"));
+        let mut reader =
+            flash_64k.reader(RangedUsize::new_static::<42>()..RangedUsize::new_static::<55>());
+
+        // With no time to complete, programming times out while the device is still busy.
+        assert_err_eq!(
+            program_byte(
+                unsafe { FLASH_MEMORY.add(0x1000) },
+                0,
+                0x1000,
+                flash_64k.device,
+                &mut PollBudget::new(Duration::ZERO),
+            ),
+            Error::OperationTimedOut
+        );
+
+        let mut buf = [0; 13];
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf, b"hello, world!");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn validate_presence_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        assert_ok!(flash_64k
+            .writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<55>())
+            .write_all(b"hello, world!"));
+
+        assert_ok!(flash_64k
+            .reader(RangedUsize::new_static::<42>()..RangedUsize::new_static::<55>())
+            .validate_presence());
+        assert_ok!(flash_64k.reader(..).validate_presence());
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn validate_presence_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        assert_ok!(assert_ok!(flash_128k.try_writer(65529..65542)).write_all(b"hello, world!"));
+
+        assert_ok!(assert_ok!(flash_128k.try_reader(65529..65542)).validate_presence());
+        assert_ok!(flash_128k.reader(..).validate_presence());
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn write_verified_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
+
+        // Spans a page boundary.
+        assert_ok!(flash_64k_atmel
+            .writer(RangedUsize::new_static::<120>()..RangedUsize::new_static::<133>())
+            .write_verified(b"hello, world!"));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn write_verified_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65530>()..RangedUsize::new_static::<65556>());
+
+        // Spans both banks.
+        assert_ok!(writer.write_verified(b"hello, world!"));
+        // Writing continues correctly after data has been read back.
+        assert_ok!(writer.write_verified(b"hello, world!"));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn byte_program_latency_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer = flash_64k.writer(..);
+        let mut worst = Duration::ZERO;
+
+        for i in 0..256 {
+            let (result, latency) = measure(|| writer.write(&[i as u8]));
+            assert_ok_eq!(result, 1);
+            worst = worst.max(latency);
+        }
+
+        log_latency("Flash byte program", worst);
+        // Spinning may take up to twice as long as estimated.
+        assert!(worst <= PROGRAM_TIMEOUT * 2, "took {worst:?}");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn sector_erase_latency_64k() {
+        let mut flash_64k = assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+        let timeout = flash_64k.info().sector_erase_time * ERASE_TIMEOUT_MARGIN;
+
+        let (result, latency) = measure(|| flash_64k.erase_sectors(..=RangedU8::new_static::<0>()));
+
+        assert_ok!(result);
+        log_latency("Flash sector erase", latency);
+        // Spinning may take up to twice as long as estimated.
+        assert!(latency <= timeout * 2, "took {latency:?}");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn chip_erase_latency_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        let timeout = flash.info().chip_erase_time * ERASE_TIMEOUT_MARGIN;
+
+        let (result, latency) = measure(|| flash.reset());
+
+        assert_ok!(result);
+        log_latency("Flash chip erase", latency);
+        // Spinning may take up to twice as long as estimated.
+        assert!(latency <= timeout * 2, "took {latency:?}");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn cloned_readers_interleaved_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer = flash_64k.writer(..RangedUsize::new_static::<6>());
+        assert_ok_eq!(writer.write(b"header"), 6);
+        assert_ok!(flash_64k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_64k.reader(..RangedUsize::new_static::<6>());
+        let mut buf = [0; 3];
+        assert_ok_eq!(reader.read(&mut buf[..1]), 1);
+        let mut clone = reader.clone();
+
+        assert_ok_eq!(reader.read(&mut buf), 3);
+        assert_eq!(&buf, b"ead");
+        assert_ok_eq!(clone.read(&mut buf[..2]), 2);
+        assert_eq!(&buf[..2], b"ea");
+        assert_ok_eq!(reader.read(&mut buf), 2);
+        assert_eq!(&buf[..2], b"er");
+        assert_ok_eq!(clone.read(&mut buf), 3);
+        assert_eq!(&buf, b"der");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn partial_range_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer =
+            flash_64k.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+
+        assert_ok_eq!(writer.write(&[b'a'; 100]), 58);
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_64k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader =
+            flash_64k.reader(RangedUsize::new_static::<51>()..RangedUsize::new_static::<60>());
+        let mut buf = [0; 20];
+
+        assert_ok_eq!(reader.read(&mut buf), 9);
+        assert_eq!(
+            buf,
+            [
+                b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn not_erased_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer = flash_64k.writer(..RangedUsize::new_static::<13>());
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+
+        let mut writer =
+            flash_64k.writer(RangedUsize::new_static::<4>()..RangedUsize::new_static::<13>());
+
+        assert_err_eq!(writer.write(b"p"), Error::NotErased { address: 4 });
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn not_erased_partial_write_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer = flash_64k.writer(..RangedUsize::new_static::<13>());
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+
+        let mut writer =
+            flash_64k.writer(RangedUsize::new_static::<3>()..RangedUsize::new_static::<13>());
+
+        // The first byte is written, and the failing byte is reported on the next write.
+        assert_ok_eq!(writer.write(b"lp"), 1);
+        assert_err_eq!(writer.write(b"p"), Error::NotErased { address: 4 });
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn writer_into_64k() {
+        let mut flash_64k = assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+        let erased = assert_ok!(
+            flash_64k.erase_sectors(RangedU8::new_static::<1>()..RangedU8::new_static::<2>())
+        );
+        assert_eq!(erased.sectors(), 1..2);
+
+        let mut writer = assert_ok!(flash_64k.writer_into(
+            &erased,
+            RangedUsize::new_static::<0x1000>()..RangedUsize::new_static::<0x100d>()
+        ));
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+
+        let mut reader = flash_64k
+            .reader(RangedUsize::new_static::<0x1000>()..RangedUsize::new_static::<0x100d>());
+        let mut buf = [0; 13];
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf, b"hello, world!");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn writer_into_not_erased_64k() {
+        let mut flash_64k = assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+        let erased = assert_ok!(
+            flash_64k.erase_sectors(RangedU8::new_static::<1>()..RangedU8::new_static::<2>())
+        );
+
+        assert_err_eq!(
+            flash_64k
+                .writer_into(
+                    &erased,
+                    RangedUsize::new_static::<0x1ff0>()..RangedUsize::new_static::<0x2010>()
+                )
+                .map(|_| ()),
+            Error::NotErased { address: 0x2000 }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn write_erased_runs_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let data = [0xff, 0xff, 1, 2, 0xff, 0xff, 0xff, 3, 0xff];
+        let mut writer =
+            flash_64k.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<51>());
+
+        assert_ok_eq!(writer.write(&data), 9);
+
+        let mut reader =
+            flash_64k.reader(RangedUsize::new_static::<42>()..RangedUsize::new_static::<51>());
+        let mut buf = [0; 9];
+        assert_ok_eq!(reader.read(&mut buf), 9);
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn rewrite_same_data_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer =
+            flash_64k.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<51>());
+        assert_ok_eq!(writer.write(b"hello, wo"), 9);
+
+        let mut writer =
+            flash_64k.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<51>());
+
+        assert_ok_eq!(writer.write(b"hello, wo"), 9);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn read_uninit_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer = flash_64k.writer(..RangedUsize::new_static::<13>());
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+
+        let mut reader = flash_64k.reader(..RangedUsize::new_static::<13>());
+        let mut buf = [MaybeUninit::uninit(); 20];
+
+        assert_eq!(assert_ok!(reader.read_uninit(&mut buf)), b"hello, world!");
+        assert_eq!(assert_ok!(reader.read_uninit(&mut buf)), b"");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn erase_one_sector_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        // Write some data to it.
+        let mut writer = flash_64k.writer(..RangedUsize::new_static::<13>());
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+
+        assert_ok!(
+            flash_64k.erase_sectors(RangedU8::new_static::<0>()..RangedU8::new_static::<1>())
+        );
+
+        let mut reader =
+            flash_64k.reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<13>());
+        let mut buf = [0; 13];
+
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(buf, [0xff; 13],);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn erase_all_sectors_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        // Write some data to it.
+        let mut writer = flash_64k.writer(..);
+        for i in 0..16384 {
+            assert_ok_eq!(
+                writer.write(&[
+                    0u8.wrapping_add(i as u8),
+                    1u8.wrapping_add(i as u8),
+                    2u8.wrapping_add(i as u8),
+                    3u8.wrapping_add(i as u8)
+                ]),
+                4
+            );
+        }
+
+        assert_ok!(flash_64k.erase_sectors(..));
+
+        let mut reader = flash_64k.reader(..);
+        let mut buf = [0; 4];
+
+        for _ in 0..16384 {
+            assert_ok_eq!(reader.read(&mut buf), 4);
+            assert_eq!(buf, [0xff; 4],);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn erase_sectors_with_progress_64k() {
+        let mut flash_64k = assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+        let mut calls = 0;
+
+        assert_ok!(flash_64k.erase_sectors_with_progress(
+            RangedU8::new_static::<2>()..RangedU8::new_static::<6>(),
+            |progress| {
+                calls += 1;
+                assert_eq!(
+                    progress,
+                    Progress {
+                        completed: calls,
+                        total: 4
+                    }
+                );
+            }
+        ));
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn erase_iter_partial_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        for sector in 2..6 {
+            let offset = sector * 4096;
+            let mut writer = assert_ok!(flash_64k.try_writer(offset..offset + 4));
+            assert_ok!(writer.write_all(&[1, 2, 3, 4]));
+        }
+
+        {
+            let mut erase =
+                flash_64k.erase_iter(RangedU8::new_static::<2>()..RangedU8::new_static::<6>());
+            assert_eq!(erase.len(), 4);
+            for sector in 2..4 {
+                assert_eq!(assert_ok!(assert_some!(erase.next())).get(), sector);
+            }
+            assert_eq!(erase.erased().sectors(), 2..4);
+        }
+
+        for sector in 2..6 {
+            let offset = sector * 4096;
+            let mut buf = [0; 4];
+            assert_ok!(assert_ok!(flash_64k.try_reader(offset..offset + 4)).read_exact(&mut buf));
+            if sector < 4 {
+                assert_eq!(buf, [0xff; 4], "sector {sector} was not erased");
+            } else {
+                assert_eq!(buf, [1, 2, 3, 4], "sector {sector} was modified");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn erase_counts_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let log = [RangedU8::new_static::<14>(), RangedU8::new_static::<15>()];
+        assert_none!(flash_64k.sector_erase_counts());
+        assert_ok!(flash_64k.track_erase_counts(log));
+        assert_eq!(assert_some!(flash_64k.sector_erase_counts()), [0; 16]);
+
+        assert_ok!(
+            flash_64k.erase_sectors(RangedU8::new_static::<2>()..=RangedU8::new_static::<3>())
+        );
+        {
+            let mut erase =
+                flash_64k.erase_iter(RangedU8::new_static::<2>()..=RangedU8::new_static::<2>());
+            assert_ok!(assert_some!(erase.next()));
+        }
+
+        let mut expected = [0; 16];
+        expected[2] = 2;
+        expected[3] = 1;
+        assert_some_eq!(flash_64k.sector_erase_counts(), expected);
+
+        // The counts persist when the device is created again.
+        let mut flash_64k = assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+        assert_ok!(flash_64k.track_erase_counts(log));
+        assert_some_eq!(flash_64k.sector_erase_counts(), expected);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn erase_counts_alternate_sectors_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let log = [RangedU8::new_static::<14>(), RangedU8::new_static::<15>()];
+        assert_ok!(flash_64k.track_erase_counts(log));
+
+        // Each sector of the log holds 58 records, so this fills the first sector.
+        for _ in 0..60 {
+            assert_ok!(
+                flash_64k.erase_sectors(RangedU8::new_static::<2>()..=RangedU8::new_static::<2>())
+            );
+        }
+
+        let mut expected = [0; 16];
+        expected[2] = 60;
+        expected[15] = 1;
+        assert_some_eq!(flash_64k.sector_erase_counts(), expected);
+
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        assert_ok!(flash_64k.track_erase_counts(log));
+        // The log was erased by the reset, so counting starts again.
+        assert_some_eq!(flash_64k.sector_erase_counts(), [0; 16]);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn erase_counts_reset_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        assert_ok!(flash_64k
+            .track_erase_counts([RangedU8::new_static::<14>(), RangedU8::new_static::<15>()]));
+        let mut flash = Flash::Flash64K(flash_64k);
+
+        assert_ok!(flash.reset());
+
+        let flash_64k = assert_flash_64k!(flash);
+        assert_some_eq!(flash_64k.sector_erase_counts(), [1; 16]);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn reset_with_progress_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        let mut calls = 0;
+
+        assert_ok!(flash.reset_with_progress(|progress| {
+            assert_eq!(
+                progress,
+                Progress {
+                    completed: calls,
+                    total: 1
+                }
+            );
+            calls += 1;
+        }));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn erase_chip_64k_atmel() {
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+        let mut writer = flash_64k_atmel
+            .writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<300>());
+        assert_ok_eq!(writer.write(&[b'a'; 258]), 258);
+        drop(writer);
+
+        assert_ok!(flash_64k_atmel.erase_chip());
+
+        let mut reader = flash_64k_atmel.reader(..);
+        let mut buf = [0; 128];
+        for _ in 0..512 {
+            assert_ok_eq!(reader.read(&mut buf), 128);
+            assert_eq!(buf, [0xff; 128]);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn fill_64k_atmel() {
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+        assert_ok!(flash_64k_atmel.erase_chip());
+
+        assert_ok!(flash_64k_atmel.fill(
+            RangedUsize::new_static::<42>()..RangedUsize::new_static::<300>(),
+            b'a'
+        ));
+
+        let mut reader = flash_64k_atmel
+            .reader(RangedUsize::new_static::<40>()..RangedUsize::new_static::<302>());
+        let mut buf = [0; 262];
+        assert_ok_eq!(reader.read(&mut buf), 262);
+        assert_eq!(buf[..2], [0xff; 2]);
+        assert_eq!(buf[2..260], [b'a'; 258]);
+        assert_eq!(buf[260..], [0xff; 2]);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn self_test_64k_atmel() {
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+
+        let report = flash_64k_atmel.self_test(RangedU16::new_static::<511>());
+
+        assert!(report.write_ok);
+        assert!(report.verify_ok);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn verify_present_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+
+        assert_ok!(flash.verify_present());
+        assert_flash_64k_atmel!(flash);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn new_64k_atmel() {
+        assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn empty_range_read_64k_atmel() {
+        let flash = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+        let mut buffer = [1, 2, 3, 4];
+
+        assert_ok_eq!(
+            flash
+                .reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
+                .read(&mut buffer),
+            0
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn empty_range_write_64k_atmel() {
+        let mut flash = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+
+        assert_err_eq!(
+            flash
+                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
+                .write(&[1, 2, 3, 4]),
+            Error::EndOfWriter
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn full_range_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
+        let mut writer = flash_64k_atmel.writer(..);
+
+        for i in 0..16384 {
+            assert_ok_eq!(
+                writer.write(&[
+                    0u8.wrapping_add(i as u8),
+                    1u8.wrapping_add(i as u8),
+                    2u8.wrapping_add(i as u8),
+                    3u8.wrapping_add(i as u8)
+                ]),
+                4
+            );
+        }
+        drop(writer);
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_64k_atmel.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_64k_atmel.reader(..);
+        let mut buf = [0, 0, 0, 0];
+
+        for i in 0..16384 {
+            assert_ok_eq!(reader.read(&mut buf), 4);
+            assert_eq!(
+                buf,
+                [
+                    0u8.wrapping_add(i as u8),
+                    1u8.wrapping_add(i as u8),
+                    2u8.wrapping_add(i as u8),
+                    3u8.wrapping_add(i as u8)
+                ],
+                "i: {}",
+                i,
+            );
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn partial_range_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
+        let mut writer = flash_64k_atmel
+            .writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<130>());
+
+        assert_ok_eq!(writer.write(&[b'a'; 100]), 88);
+        drop(writer);
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_64k_atmel.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_64k_atmel
+            .reader(RangedUsize::new_static::<121>()..RangedUsize::new_static::<130>());
+        let mut buf = [0; 20];
+
+        assert_ok_eq!(reader.read(&mut buf), 9);
+        assert_eq!(
+            buf,
+            [
+                b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn flush_repeated_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
+        #[cfg(feature = "stats")]
+        crate::stats::reset();
+        let mut writer = flash_64k_atmel.writer(RangedUsize::new_static::<42>()..);
+
+        // Nothing has been written, so nothing is programmed.
+        assert_ok!(writer.flush());
+        assert_ok_eq!(writer.write(b"abc"), 3);
+        assert_ok!(writer.flush());
+        assert_ok!(writer.flush());
+        assert_ok_eq!(writer.write(b"def"), 3);
+        assert_ok!(writer.flush());
+        assert_ok!(writer.flush());
+        drop(writer);
+
+        #[cfg(feature = "stats")]
+        assert_eq!(crate::stats::snapshot().pages_programmed, 2);
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_64k_atmel.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_64k_atmel
+            .reader(RangedUsize::new_static::<40>()..RangedUsize::new_static::<50>());
+        let mut buf = [0; 10];
+
+        assert_ok_eq!(reader.read(&mut buf), 10);
+        assert_eq!(&buf, b"\xff\xffabcdef\xff\xff");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn page_flush_latency_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
+        let mut writer = flash_64k_atmel.writer(..);
+        let mut worst = Duration::ZERO;
+
+        for page in 0..8 {
+            assert_ok_eq!(writer.write(&[page; 100]), 100);
+            let (result, latency) = measure(|| writer.flush());
+            assert_ok!(result);
+            worst = worst.max(latency);
+            assert_ok_eq!(writer.write(&[page; 28]), 28);
+        }
+
+        log_latency("Atmel page flush", worst);
+        // Spinning may take up to twice as long as estimated.
+        assert!(worst <= PROGRAM_TIMEOUT * 2, "took {worst:?}");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn tail_preserve_existing_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
+        assert_ok!(flash_64k_atmel.writer(..).write_all(&[b'x'; 128]));
+        assert_ok!(flash_64k_atmel.wait_ready(Duration::from_millis(20)));
+        let mut writer = flash_64k_atmel.writer(RangedUsize::new_static::<42>()..);
+
+        assert_ok_eq!(writer.write(b"abc"), 3);
+        assert_ok!(writer.flush());
+        drop(writer);
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_64k_atmel.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_64k_atmel
+            .reader(RangedUsize::new_static::<40>()..RangedUsize::new_static::<50>());
+        let mut buf = [0; 10];
+
+        assert_ok_eq!(reader.read(&mut buf), 10);
+        assert_eq!(&buf, b"xxabcxxxxx");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn tail_fill_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
+        assert_ok!(flash_64k_atmel.writer(..).write_all(&[b'x'; 128]));
+        assert_ok!(flash_64k_atmel.wait_ready(Duration::from_millis(20)));
+        let mut writer = flash_64k_atmel.writer(RangedUsize::new_static::<42>()..);
+        writer.set_tail_fill(TailPolicy::Fill(0xff));
+        assert_ok_eq!(writer.write(b"abc"), 3);
+        assert_ok!(writer.flush());
+        drop(writer);
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_64k_atmel.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_64k_atmel
+            .reader(RangedUsize::new_static::<40>()..RangedUsize::new_static::<50>());
+        let mut buf = [0; 10];
+
+        assert_ok_eq!(reader.read(&mut buf), 10);
+        assert_eq!(&buf, b"xxabc\xff\xff\xff\xff\xff");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn tail_caller_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
+        assert_ok!(flash_64k_atmel.writer(..).write_all(&[b'x'; 128]));
+        assert_ok!(flash_64k_atmel.wait_ready(Duration::from_millis(20)));
+        let mut writer = flash_64k_atmel.writer(RangedUsize::new_static::<42>()..);
+        writer.set_tail_fill(TailPolicy::Caller(b"12"));
+        assert_ok_eq!(writer.write(b"abc"), 3);
+        assert_ok!(writer.flush());
+        drop(writer);
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_64k_atmel.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_64k_atmel
+            .reader(RangedUsize::new_static::<40>()..RangedUsize::new_static::<50>());
+        let mut buf = [0; 10];
+
+        assert_ok_eq!(reader.read(&mut buf), 10);
+        assert_eq!(&buf, b"xxabc12\xff\xff\xff");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn final_page_needs_read_64k_atmel() {
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+
+        assert!(!flash_64k_atmel
+            .writer(..RangedUsize::new_static::<128>())
+            .final_page_needs_read());
+        assert!(!flash_64k_atmel
+            .writer(RangedUsize::new_static::<42>()..)
+            .final_page_needs_read());
+        assert!(flash_64k_atmel
+            .writer(..RangedUsize::new_static::<100>())
+            .final_page_needs_read());
+        let mut writer = flash_64k_atmel.writer(..RangedUsize::new_static::<100>());
+        writer.set_tail_fill(TailPolicy::Fill(0xff));
+        assert!(!writer.final_page_needs_read());
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn self_test_128k() {
+        let mut flash_128k = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+
+        let report = flash_128k.self_test(RangedU8::new_static::<31>());
+
+        assert!(report.write_ok);
+        assert!(report.verify_ok);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn verify_present_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+
+        assert_ok!(flash.verify_present());
+        assert_flash_128k!(flash);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn new_128k() {
+        assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn empty_range_read_128k() {
+        let flash = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+        let mut buffer = [1, 2, 3, 4];
+
+        assert_ok_eq!(
+            flash
+                .reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
+                .read(&mut buffer),
+            0
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn empty_range_write_128k() {
+        let mut flash = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+
+        assert_err_eq!(
+            flash
+                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
+                .write(&[1, 2, 3, 4]),
+            Error::EndOfWriter
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn full_range_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        let mut writer = flash_128k.writer(..);
+
+        for i in 0..32768 {
+            assert_ok_eq!(
+                writer.write(&[
+                    0u8.wrapping_add(i as u8),
+                    1u8.wrapping_add(i as u8),
+                    2u8.wrapping_add(i as u8),
+                    3u8.wrapping_add(i as u8)
+                ]),
+                4
+            );
+        }
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_128k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_128k.reader(..);
+        let mut buf = [0, 0, 0, 0];
+
+        for i in 0..32768 {
+            assert_ok_eq!(reader.read(&mut buf), 4);
+            assert_eq!(
+                buf,
+                [
+                    0u8.wrapping_add(i as u8),
+                    1u8.wrapping_add(i as u8),
+                    2u8.wrapping_add(i as u8),
+                    3u8.wrapping_add(i as u8)
+                ],
+            );
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn partial_range_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        let mut writer =
+            flash_128k.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+
+        assert_ok_eq!(writer.write(&[b'a'; 100]), 58);
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_128k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader =
+            flash_128k.reader(RangedUsize::new_static::<51>()..RangedUsize::new_static::<60>());
+        let mut buf = [0; 20];
+
+        assert_ok_eq!(reader.read(&mut buf), 9);
+        assert_eq!(
+            buf,
+            [
+                b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn concurrent_readers_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        let mut writer = flash_128k.writer(..RangedUsize::new_static::<4>());
+        assert_ok_eq!(writer.write(b"bank"), 4);
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65536>()..RangedUsize::new_static::<65540>());
+        assert_ok_eq!(writer.write(b"BANK"), 4);
+        assert_ok!(flash_128k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader_0 = flash_128k.reader(..RangedUsize::new_static::<4>());
+        let mut reader_1 = flash_128k
+            .reader(RangedUsize::new_static::<65536>()..RangedUsize::new_static::<65540>());
+        let mut buf_0 = [0; 2];
+        let mut buf_1 = [0; 2];
+
+        assert_ok_eq!(reader_0.read(&mut buf_0), 2);
+        assert_ok_eq!(reader_1.read(&mut buf_1), 2);
+        assert_eq!(&buf_0, b"ba");
+        assert_eq!(&buf_1, b"BA");
+        assert_ok_eq!(reader_0.read(&mut buf_0), 2);
+        assert_ok_eq!(reader_1.read(&mut buf_1), 2);
+        assert_eq!(&buf_0, b"nk");
+        assert_eq!(&buf_1, b"NK");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn cloned_readers_across_banks_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65534>()..RangedUsize::new_static::<65538>());
+        assert_ok_eq!(writer.write(b"abcd"), 4);
+        assert_ok!(flash_128k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_128k
+            .reader(RangedUsize::new_static::<65534>()..RangedUsize::new_static::<65538>());
+        let mut clone = reader.clone();
+        let mut buf = [0; 3];
+
+        // The original crosses into bank 1, while the clone remains in bank 0.
+        assert_ok_eq!(reader.read(&mut buf), 3);
+        assert_eq!(&buf, b"abc");
+        assert_ok_eq!(clone.read(&mut buf[..2]), 2);
+        assert_eq!(&buf[..2], b"ab");
+        assert_ok_eq!(reader.read(&mut buf), 1);
+        assert_eq!(buf[0], b'd');
+        assert_ok_eq!(clone.read(&mut buf), 2);
+        assert_eq!(&buf[..2], b"cd");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn last_byte_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut writer = flash_64k
+            .writer(RangedUsize::new_static::<65535>()..=RangedUsize::new_static::<65535>());
+
+        assert_ok_eq!(writer.write(b"zz"), 1);
+        assert_err_eq!(writer.write(b"z"), Error::EndOfWriter);
+        assert_ok!(flash_64k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_64k.reader(RangedUsize::new_static::<65535>()..);
+        let mut buf = [0; 2];
+
+        assert_ok_eq!(reader.read(&mut buf), 1);
+        assert_eq!(buf[0], b'z');
+        assert_ok_eq!(reader.read(&mut buf), 0);
+        // The byte before the last is untouched.
+        assert!(flash_64k.is_erased(..RangedUsize::new_static::<65535>()));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn inclusive_range_to_max_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let flash_64k = assert_flash_64k!(flash);
+        let mut reader = flash_64k.reader(..=RangedUsize::new_static::<65535>());
+        let mut buf = [0; 256];
+        let mut read = 0;
+
+        while let Ok(count @ 1..) = reader.read(&mut buf) {
+            assert!(buf[..count].iter().all(|&byte| byte == 0xff));
+            read += count;
+        }
+        assert_eq!(read, 65536);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn last_byte_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
+        let mut writer = flash_64k_atmel
+            .writer(RangedUsize::new_static::<65535>()..=RangedUsize::new_static::<65535>());
+
+        assert_ok_eq!(writer.write(b"zz"), 1);
+        assert_err_eq!(writer.write(b"z"), Error::EndOfWriter);
+        drop(writer);
+        assert_ok!(flash_64k_atmel.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_64k_atmel.reader(RangedUsize::new_static::<65535>()..);
+        let mut buf = [0; 2];
+
+        assert_ok_eq!(reader.read(&mut buf), 1);
+        assert_eq!(buf[0], b'z');
+        assert_ok_eq!(reader.read(&mut buf), 0);
+        // The byte before the last is untouched.
+        assert!(flash_64k_atmel.is_erased(..RangedUsize::new_static::<65535>()));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn inclusive_range_to_max_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let flash_64k_atmel = assert_flash_64k_atmel!(flash);
+        let mut reader = flash_64k_atmel.reader(..=RangedUsize::new_static::<65535>());
+        let mut buf = [0; 256];
+        let mut read = 0;
+
+        while let Ok(count @ 1..) = reader.read(&mut buf) {
+            assert!(buf[..count].iter().all(|&byte| byte == 0xff));
+            read += count;
+        }
+        assert_eq!(read, 65536);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn last_byte_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<131071>()..=RangedUsize::new_static::<131071>());
+
+        assert_ok_eq!(writer.write(b"zz"), 1);
+        assert_err_eq!(writer.write(b"z"), Error::EndOfWriter);
+        assert_ok!(flash_128k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_128k.reader(RangedUsize::new_static::<131071>()..);
+        let mut buf = [0; 2];
+
+        assert_ok_eq!(reader.read(&mut buf), 1);
+        assert_eq!(buf[0], b'z');
+        assert_ok_eq!(reader.read(&mut buf), 0);
+        // The byte before the last is untouched.
+        assert!(flash_128k.is_erased(..RangedUsize::new_static::<131071>()));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn inclusive_range_to_max_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let flash_128k = assert_flash_128k!(flash);
+        let mut reader = flash_128k.reader(..=RangedUsize::new_static::<131071>());
+        let mut buf = [0; 256];
+        let mut read = 0;
+
+        while let Ok(count @ 1..) = reader.read(&mut buf) {
+            assert!(buf[..count].iter().all(|&byte| byte == 0xff));
+            read += count;
+        }
+        assert_eq!(read, 131072);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn bank_reader_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        let mut writer =
+            flash_128k.writer(RangedUsize::new_static::<10>()..RangedUsize::new_static::<14>());
+        assert_ok_eq!(writer.write(b"bank"), 4);
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65546>()..RangedUsize::new_static::<65550>());
+        assert_ok_eq!(writer.write(b"BANK"), 4);
+        assert_ok!(flash_128k.wait_ready(Duration::from_millis(20)));
+        let mut buf = [0; 4];
 
-        send_command(Command::EnterIDMode);
-        wait(Duration::from_millis(20));
+        let mut reader = flash_128k.bank_reader(
+            FlashBank::_1,
+            RangedUsize::new_static::<10>()..RangedUsize::new_static::<14>(),
+        );
+        assert_ok_eq!(reader.read(&mut buf), 4);
+        assert_eq!(&buf, b"BANK");
 
-        // Read u16 from memory.
-        let device = u16::from_ne_bytes(unsafe {
-            [
-                FLASH_MEMORY.read_volatile(),
-                FLASH_MEMORY.add(1).read_volatile(),
-            ]
-        })
-        .try_into()?;
+        let mut reader = flash_128k.bank_reader(
+            FlashBank::_0,
+            RangedUsize::new_static::<10>()..RangedUsize::new_static::<14>(),
+        );
+        assert_ok_eq!(reader.read(&mut buf), 4);
+        assert_eq!(&buf, b"bank");
+    }
 
-        send_command(Command::TerminateMode);
-        wait(Duration::from_millis(20));
-        // Sanyo 128K device needs to have `TerminateMode` command sent twice.
-        if matches!(device, Device::LE26FV10N1TS) {
-            send_command(Command::TerminateMode);
-            wait(Duration::from_millis(20));
-        }
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn bank_reader_full_bank_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
 
-        match device {
-            Device::AT29LV512 => Ok(Self::Flash64KAtmel(Flash64KAtmel)),
-            Device::MX29L010 | Device::LE26FV10N1TS => Ok(Self::Flash128K(Flash128K)),
-            _ => Ok(Self::Flash64K(Flash64K)),
+        let mut reader = flash_128k.bank_reader(FlashBank::_1, ..);
+        let mut buf = [0; 256];
+        let mut total = 0;
+        loop {
+            let count = assert_ok!(reader.read(&mut buf));
+            if count == 0 {
+                break;
+            }
+            assert!(buf[..count].iter().all(|&byte| byte == 0xff));
+            total += count;
         }
+
+        assert_eq!(total, 65536);
     }
 
-    /// Erase the entirety of the flash backup memory.
-    pub fn reset(&mut self) -> Result<(), Error> {
-        send_command(Command::Erase);
-        send_command(Command::EraseChip);
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn split_interleaved_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let (mut bank_0, mut bank_1) = assert_flash_128k!(flash).split();
+        // Leave bank 1 selected.
+        assert_ok!(bank_1.erase_sectors(RangedU8::new_static::<0>()..=RangedU8::new_static::<0>()));
 
-        // Verify.
-        verify_byte(FLASH_MEMORY, ERASED, Duration::from_millis(20))
-    }
-}
+        let mut writer_0 =
+            bank_0.writer(RangedUsize::new_static::<10>()..RangedUsize::new_static::<15>());
+        let mut writer_1 =
+            bank_1.writer(RangedUsize::new_static::<10>()..RangedUsize::new_static::<15>());
+        for (&byte_0, &byte_1) in b"hello".iter().zip(b"world") {
+            assert_ok_eq!(writer_0.write(&[byte_0]), 1);
+            assert_ok_eq!(writer_1.write(&[byte_1]), 1);
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::{wait, Error, Flash, UnknownDeviceID};
-    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
-    use core::time::Duration;
-    use deranged::{RangedU8, RangedUsize};
-    use embedded_io::{Read, Write};
-    use gba_test::test;
+        let mut reader_0 =
+            bank_0.reader(RangedUsize::new_static::<10>()..RangedUsize::new_static::<15>());
+        let mut reader_1 =
+            bank_1.reader(RangedUsize::new_static::<10>()..RangedUsize::new_static::<15>());
+        let mut buf_0 = [0; 5];
+        let mut buf_1 = [0; 5];
+        for index in 0..5 {
+            assert_ok_eq!(reader_1.read(&mut buf_1[index..=index]), 1);
+            assert_ok_eq!(reader_0.read(&mut buf_0[index..=index]), 1);
+        }
+        assert_eq!(&buf_0, b"hello");
+        assert_eq!(&buf_1, b"world");
 
-    macro_rules! assert_flash_64k {
-        ($expr:expr) => {
-            match $expr {
-                Flash::Flash64K(flash_64k) => flash_64k,
-                flash => panic!(
-                    "assertion failed, expected Flash::Flash64K(..), got {:?}",
-                    flash
-                ),
-            }
-        };
+        // Erasing one bank leaves the other intact.
+        assert_ok!(bank_0.erase_sectors(RangedU8::new_static::<0>()..=RangedU8::new_static::<0>()));
+        let flash_128k = Flash128K::unsplit(bank_0, bank_1);
+        let mut reader = assert_ok!(flash_128k.try_reader(0..131072));
+        let mut buf = [0; 15];
+        assert_ok_eq!(reader.read(&mut buf), 15);
+        assert_eq!(buf, [0xff; 15]);
+        let mut reader = assert_ok!(flash_128k.try_reader(65546..65551));
+        assert_ok_eq!(reader.read(&mut buf[..5]), 5);
+        assert_eq!(&buf[..5], b"world");
     }
 
-    macro_rules! assert_flash_64k_atmel {
-        ($expr:expr) => {
-            match $expr {
-                Flash::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel,
-                flash => panic!(
-                    "assertion failed, expected Flash::Flash64KAtmel(..), got {:?}",
-                    flash
-                ),
-            }
+    #[test]
+    fn split_banks() {
+        let flash_128k = Flash128K {
+            device: Device::MX29L010,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
         };
+
+        let (bank_0, bank_1) = flash_128k.split();
+
+        assert_eq!(bank_0.bank(), FlashBank::_0);
+        assert_eq!(bank_0.name(), "Flash 128KiB bank 0");
+        assert_eq!(bank_1.bank(), FlashBank::_1);
+        assert_eq!(bank_1.name(), "Flash 128KiB bank 1");
+        assert_eq!(bank_1.capacity(), 65536);
     }
 
-    macro_rules! assert_flash_128k {
-        ($expr:expr) => {
-            match $expr {
-                Flash::Flash128K(flash_128k) => flash_128k,
-                flash => panic!(
-                    "assertion failed, expected Flash::Flash129K(..), got {:?}",
-                    flash
-                ),
-            }
+    #[test]
+    #[should_panic(expected = "banks must be bank 0 followed by bank 1")]
+    fn unsplit_swapped_banks() {
+        let flash_128k = Flash128K {
+            device: Device::MX29L010,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+            dual_die: false,
+            erase_counts: None,
         };
+        let (bank_0, bank_1) = flash_128k.split();
+
+        Flash128K::unsplit(bank_1, bank_0);
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_64k),
-        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
     )]
-    fn new_64k() {
-        assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+    fn not_erased_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65536>()..RangedUsize::new_static::<65549>());
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65540>()..RangedUsize::new_static::<65549>());
+
+        assert_err_eq!(writer.write(b"p"), Error::NotErased { address: 65540 });
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_64k),
-        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
     )]
-    fn empty_range_read_64k() {
-        let mut flash = assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
-        let mut buffer = [1, 2, 3, 4];
+    fn writer_into_128k() {
+        let mut flash_128k = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+        let erased =
+            assert_ok!(flash_128k
+                .erase_sectors(RangedU8::new_static::<15>()..RangedU8::new_static::<17>()));
+        assert_eq!(erased.sectors(), 15..17);
 
-        assert_ok_eq!(
-            flash
-                .reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
-                .read(&mut buffer),
-            0
-        );
+        let mut writer = assert_ok!(flash_128k.writer_into(
+            &erased,
+            RangedUsize::new_static::<0xfffa>()..RangedUsize::new_static::<0x10007>()
+        ));
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+
+        let mut reader = flash_128k
+            .reader(RangedUsize::new_static::<0xfffa>()..RangedUsize::new_static::<0x10007>());
+        let mut buf = [0; 13];
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf, b"hello, world!");
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_64k),
-        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
     )]
-    fn empty_range_write_64k() {
-        let mut flash = assert_flash_64k!(assert_ok!(unsafe { Flash::new() }));
+    fn writer_into_not_erased_128k() {
+        let mut flash_128k = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+        let erased =
+            assert_ok!(flash_128k
+                .erase_sectors(RangedU8::new_static::<17>()..RangedU8::new_static::<18>()));
 
         assert_err_eq!(
-            flash
-                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
-                .write(&[1, 2, 3, 4]),
-            Error::EndOfWriter
+            flash_128k
+                .writer_into(
+                    &erased,
+                    RangedUsize::new_static::<0x10ff0>()..RangedUsize::new_static::<0x11010>()
+                )
+                .map(|_| ()),
+            Error::NotErased { address: 0x10ff0 }
         );
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_64k),
-        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
     )]
-    fn full_range_64k() {
+    fn write_erased_runs_128k() {
         let mut flash = assert_ok!(unsafe { Flash::new() });
         assert_ok!(flash.reset());
-        let mut flash_64k = assert_flash_64k!(flash);
-        let mut writer = flash_64k.writer(..);
-
-        for i in 0..16384 {
-            assert_ok_eq!(
-                writer.write(&[
-                    0u8.wrapping_add(i as u8),
-                    1u8.wrapping_add(i as u8),
-                    2u8.wrapping_add(i as u8),
-                    3u8.wrapping_add(i as u8)
-                ]),
-                4
-            );
-        }
-
-        // Wait for the data to be available.
-        wait(Duration::from_millis(1));
+        let mut flash_128k = assert_flash_128k!(flash);
+        let data = [0xff, 0xff, 1, 2, 0xff, 0xff, 0xff, 3, 0xff];
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65530>()..RangedUsize::new_static::<65539>());
 
-        let mut reader = flash_64k.reader(..);
-        let mut buf = [0, 0, 0, 0];
+        assert_ok_eq!(writer.write(&data), 9);
 
-        for i in 0..16384 {
-            assert_ok_eq!(reader.read(&mut buf), 4);
-            assert_eq!(
-                buf,
-                [
-                    0u8.wrapping_add(i as u8),
-                    1u8.wrapping_add(i as u8),
-                    2u8.wrapping_add(i as u8),
-                    3u8.wrapping_add(i as u8)
-                ],
-            );
-        }
+        let mut reader = flash_128k
+            .reader(RangedUsize::new_static::<65530>()..RangedUsize::new_static::<65539>());
+        let mut buf = [0; 9];
+        assert_ok_eq!(reader.read(&mut buf), 9);
+        assert_eq!(buf, data);
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_64k),
-        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
     )]
-    fn partial_range_64k() {
+    fn rewrite_same_data_128k() {
         let mut flash = assert_ok!(unsafe { Flash::new() });
         assert_ok!(flash.reset());
-        let mut flash_64k = assert_flash_64k!(flash);
-        let mut writer =
-            flash_64k.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+        let mut flash_128k = assert_flash_128k!(flash);
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65530>()..RangedUsize::new_static::<65539>());
+        assert_ok_eq!(writer.write(b"hello, wo"), 9);
 
-        assert_ok_eq!(writer.write(&[b'a'; 100]), 58);
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65530>()..RangedUsize::new_static::<65539>());
 
-        // Wait for the data to be available.
-        wait(Duration::from_millis(1));
+        assert_ok_eq!(writer.write(b"hello, wo"), 9);
+    }
 
-        let mut reader =
-            flash_64k.reader(RangedUsize::new_static::<51>()..RangedUsize::new_static::<60>());
-        let mut buf = [0; 20];
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn read_uninit_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65530>()..RangedUsize::new_static::<65543>());
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
 
-        assert_ok_eq!(reader.read(&mut buf), 9);
-        assert_eq!(
-            buf,
-            [
-                b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0
-            ]
-        );
+        let mut reader = flash_128k
+            .reader(RangedUsize::new_static::<65530>()..RangedUsize::new_static::<65543>());
+        let mut buf = [MaybeUninit::uninit(); 20];
+
+        assert_eq!(assert_ok!(reader.read_uninit(&mut buf)), b"hello, world!");
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_64k),
-        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
     )]
-    fn erase_one_sector_64k() {
+    fn erase_one_sector_128k() {
         let mut flash = assert_ok!(unsafe { Flash::new() });
         assert_ok!(flash.reset());
-        let mut flash_64k = assert_flash_64k!(flash);
+        let mut flash_128k = assert_flash_128k!(flash);
         // Write some data to it.
-        let mut writer = flash_64k.writer(..RangedUsize::new_static::<13>());
+        let mut writer = flash_128k.writer(..RangedUsize::new_static::<13>());
         assert_ok_eq!(writer.write(b"hello, world!"), 13);
 
         assert_ok!(
-            flash_64k.erase_sectors(RangedU8::new_static::<0>()..RangedU8::new_static::<1>())
+            flash_128k.erase_sectors(RangedU8::new_static::<0>()..RangedU8::new_static::<1>())
         );
 
         let mut reader =
-            flash_64k.reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<13>());
+            flash_128k.reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<13>());
         let mut buf = [0; 13];
 
         assert_ok_eq!(reader.read(&mut buf), 13);
@@ -620,16 +4884,16 @@ mod tests {
 
     #[test]
     #[cfg_attr(
-        not(flash_64k),
-        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
     )]
-    fn erase_all_sectors_64k() {
+    fn erase_all_sectors_128k() {
         let mut flash = assert_ok!(unsafe { Flash::new() });
         assert_ok!(flash.reset());
-        let mut flash_64k = assert_flash_64k!(flash);
+        let mut flash_128k = assert_flash_128k!(flash);
         // Write some data to it.
-        let mut writer = flash_64k.writer(..);
-        for i in 0..16384 {
+        let mut writer = flash_128k.writer(..);
+        for i in 0..32768 {
             assert_ok_eq!(
                 writer.write(&[
                     0u8.wrapping_add(i as u8),
@@ -641,12 +4905,12 @@ mod tests {
             );
         }
 
-        assert_ok!(flash_64k.erase_sectors(..));
+        assert_ok!(flash_128k.erase_sectors(..));
 
-        let mut reader = flash_64k.reader(..);
+        let mut reader = flash_128k.reader(..);
         let mut buf = [0; 4];
 
-        for _ in 0..16384 {
+        for _ in 0..32768 {
             assert_ok_eq!(reader.read(&mut buf), 4);
             assert_eq!(buf, [0xff; 4],);
         }
@@ -654,263 +4918,391 @@ mod tests {
 
     #[test]
     #[cfg_attr(
-        not(flash_64k_atmel),
-        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
     )]
-    fn new_64k_atmel() {
-        assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+    fn erase_sectors_with_progress_128k() {
+        let mut flash_128k = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+        let mut calls = 0;
+
+        // Spans both banks.
+        assert_ok!(flash_128k.erase_sectors_with_progress(
+            RangedU8::new_static::<14>()..RangedU8::new_static::<18>(),
+            |progress| {
+                calls += 1;
+                assert_eq!(
+                    progress,
+                    Progress {
+                        completed: calls,
+                        total: 4
+                    }
+                );
+            }
+        ));
+        assert_eq!(calls, 4);
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_64k_atmel),
-        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
     )]
-    fn empty_range_read_64k_atmel() {
-        let mut flash = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
-        let mut buffer = [1, 2, 3, 4];
+    fn erase_iter_partial_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        for sector in 14..18 {
+            let offset = sector * 4096;
+            let mut writer = assert_ok!(flash_128k.try_writer(offset..offset + 4));
+            assert_ok!(writer.write_all(&[1, 2, 3, 4]));
+        }
 
-        assert_ok_eq!(
-            flash
-                .reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
-                .read(&mut buffer),
-            0
+        {
+            let mut erase =
+                flash_128k.erase_iter(RangedU8::new_static::<14>()..RangedU8::new_static::<18>());
+            assert_eq!(erase.len(), 4);
+            for sector in 14..17 {
+                assert_eq!(assert_ok!(assert_some!(erase.next())).get(), sector);
+            }
+            assert_eq!(erase.erased().sectors(), 14..17);
+        }
+
+        for sector in 14..18 {
+            let offset = sector * 4096;
+            let mut buf = [0; 4];
+            assert_ok!(assert_ok!(flash_128k.try_reader(offset..offset + 4)).read_exact(&mut buf));
+            if sector < 17 {
+                assert_eq!(buf, [0xff; 4], "sector {sector} was not erased");
+            } else {
+                assert_eq!(buf, [1, 2, 3, 4], "sector {sector} was modified");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn erase_counts_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+        assert_ok!(flash.reset());
+        let mut flash_128k = assert_flash_128k!(flash);
+        // Place the log in both banks.
+        let log = [RangedU8::new_static::<15>(), RangedU8::new_static::<16>()];
+        assert_ok!(flash_128k.track_erase_counts(log));
+
+        assert_ok!(
+            flash_128k.erase_sectors(RangedU8::new_static::<2>()..=RangedU8::new_static::<2>())
+        );
+        assert_ok!(
+            flash_128k.erase_sectors(RangedU8::new_static::<20>()..=RangedU8::new_static::<20>())
         );
+
+        let mut expected = [0; 32];
+        expected[2] = 1;
+        expected[20] = 1;
+        assert_some_eq!(flash_128k.sector_erase_counts(), expected);
+
+        let mut flash_128k = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+        assert_ok!(flash_128k.track_erase_counts(log));
+        assert_some_eq!(flash_128k.sector_erase_counts(), expected);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k_dual_die),
+        ignore = "This test requires a dual-die Flash 128KiB cart. Ensure a dual-die Flash 128KiB cart is configured and pass `--cfg flash_128k_dual_die` to enable."
+    )]
+    fn assume_128k_dual_die() {
+        let flash_128k = assert_ok!(unsafe { Flash::assume_128k_dual_die() });
+
+        assert!(flash_128k.is_dual_die());
+        assert_eq!(flash_128k.capacity(), 131072);
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_64k_atmel),
-        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+        not(flash_128k_dual_die),
+        ignore = "This test requires a dual-die Flash 128KiB cart. Ensure a dual-die Flash 128KiB cart is configured and pass `--cfg flash_128k_dual_die` to enable."
     )]
-    fn empty_range_write_64k_atmel() {
-        let mut flash = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+    fn erase_and_write_both_banks_128k_dual_die() {
+        let mut flash_128k = assert_ok!(unsafe { Flash::assume_128k_dual_die() });
 
-        assert_err_eq!(
-            flash
-                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
-                .write(&[1, 2, 3, 4]),
-            Error::EndOfWriter
-        );
+        // Spans both banks.
+        let erased =
+            assert_ok!(flash_128k
+                .erase_sectors(RangedU8::new_static::<15>()..RangedU8::new_static::<17>()));
+        let mut writer = assert_ok!(flash_128k.writer_into(
+            &erased,
+            RangedUsize::new_static::<65534>()..RangedUsize::new_static::<65538>()
+        ));
+        assert_ok_eq!(writer.write(&[1, 2, 3, 4]), 4);
+
+        // Wait for the device to finish writing.
+        assert_ok!(flash_128k.wait_ready(Duration::from_millis(20)));
+
+        let mut reader = flash_128k
+            .reader(RangedUsize::new_static::<65534>()..RangedUsize::new_static::<65538>());
+        let mut buf = [0; 4];
+        assert_ok_eq!(reader.read(&mut buf), 4);
+        assert_eq!(buf, [1, 2, 3, 4]);
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_64k_atmel),
-        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+        not(flash_swapped_id),
+        ignore = "This test requires a flash cart reporting its ID bytes swapped. Ensure such a cart is configured and pass `--cfg flash_swapped_id` to enable."
     )]
-    fn full_range_64k_atmel() {
+    fn new_swapped_id() {
         let mut flash = assert_ok!(unsafe { Flash::new() });
-        assert_ok!(flash.reset());
-        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
-        let mut writer = flash_64k_atmel.writer(..);
+        let info = flash.info();
 
-        for i in 0..16384 {
-            assert_ok_eq!(
-                writer.write(&[
-                    0u8.wrapping_add(i as u8),
-                    1u8.wrapping_add(i as u8),
-                    2u8.wrapping_add(i as u8),
-                    3u8.wrapping_add(i as u8)
-                ]),
-                4
-            );
+        assert!(info.id_swapped);
+        assert_eq!(flash.device_id(), info.id);
+        assert!(Device::try_from(info.id).is_err());
+        match &mut flash {
+            Flash::Flash64K(flash_64k) => assert_ok!(flash_64k.verify_present()),
+            Flash::Flash64KAtmel(flash_64k_atmel) => assert_ok!(flash_64k_atmel.verify_present()),
+            Flash::Flash128K(flash_128k) => assert_ok!(flash_128k.verify_present()),
         }
-        drop(writer);
+    }
 
-        // Wait for the data to be available.
-        wait(Duration::from_millis(1));
+    #[test]
+    #[cfg_attr(
+        not(flash_128k_dual_die),
+        ignore = "This test requires a dual-die Flash 128KiB cart. Ensure a dual-die Flash 128KiB cart is configured and pass `--cfg flash_128k_dual_die` to enable."
+    )]
+    fn reset_128k_dual_die() {
+        let mut flash = Flash::Flash128K(assert_ok!(unsafe { Flash::assume_128k_dual_die() }));
+        assert_ok!(flash.reset());
+        let flash_128k = assert_flash_128k!(flash);
 
-        let mut reader = flash_64k_atmel.reader(..);
-        let mut buf = [0, 0, 0, 0];
+        let mut reader = flash_128k.reader(..);
+        let mut buf = [0; 4];
 
-        for i in 0..16384 {
+        for _ in 0..32768 {
             assert_ok_eq!(reader.read(&mut buf), 4);
-            assert_eq!(
-                buf,
-                [
-                    0u8.wrapping_add(i as u8),
-                    1u8.wrapping_add(i as u8),
-                    2u8.wrapping_add(i as u8),
-                    3u8.wrapping_add(i as u8)
-                ],
-                "i: {}",
-                i,
-            );
+            assert_eq!(buf, [0xff; 4]);
         }
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_64k_atmel),
-        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+        any(flash_64k, flash_64k_atmel, flash_128k),
+        ignore = "This test cannot be run with a Flash chip. Ensure Flash is not configured and do not pass `--cfg flash_64k`, `--cfg flash_64k_atmel`, or `--cfg flash_128k` to enable."
     )]
-    fn partial_range_64k_atmel() {
-        let mut flash = assert_ok!(unsafe { Flash::new() });
-        assert_ok!(flash.reset());
-        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
-        let mut writer = flash_64k_atmel
-            .writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<130>());
+    fn new_unknown() {
+        assert_err_eq!(unsafe { Flash::new() }, UnknownDeviceID(0xffff));
+    }
 
-        assert_ok_eq!(writer.write(&[b'a'; 100]), 88);
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    #[cfg_attr(
+        any(flash_64k, flash_64k_atmel, flash_128k),
+        ignore = "This test cannot be run with a Flash chip. Ensure Flash is not configured and do not pass `--cfg flash_64k`, `--cfg flash_64k_atmel`, or `--cfg flash_128k` to enable."
+    )]
+    fn drop_flush_failure_64k_atmel() {
+        let mut flash_64k_atmel = Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        };
+        let mut writer = flash_64k_atmel.writer(..);
+
+        assert_ok_eq!(writer.write(b"a"), 1);
+        // The failed flush is ignored.
         drop(writer);
+    }
 
-        // Wait for the data to be available.
-        wait(Duration::from_millis(1));
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    #[cfg_attr(
+        any(flash_64k, flash_64k_atmel, flash_128k),
+        ignore = "This test cannot be run with a Flash chip. Ensure Flash is not configured and do not pass `--cfg flash_64k`, `--cfg flash_64k_atmel`, or `--cfg flash_128k` to enable."
+    )]
+    fn write_page_flush_failure_64k_atmel() {
+        let mut flash_64k_atmel = Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        };
+        let mut writer = flash_64k_atmel.writer(..);
+        let mut buffer = Buffer::<128>::new();
 
-        let mut reader = flash_64k_atmel
-            .reader(RangedUsize::new_static::<121>()..RangedUsize::new_static::<130>());
-        let mut buf = [0; 20];
+        assert_err_eq!(writer.write(&[0; 200]), Error::OperationTimedOut);
 
-        assert_ok_eq!(reader.read(&mut buf), 9);
+        // Nothing was consumed.
+        write!(buffer, "{writer:?}").unwrap();
         assert_eq!(
-            buf,
-            [
-                b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0
-            ]
+            buffer.as_str(),
+            "Flash::Writer64KAtmel { range: 0..65536, position: 0, remaining: 65536 }"
         );
+        assert_eq!(writer.stats().bytes_written, 0);
+        assert_eq!(writer.stats().failures, 1);
     }
 
     #[test]
+    #[cfg(not(feature = "strict"))]
     #[cfg_attr(
-        not(flash_128k),
-        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+        any(flash_64k, flash_64k_atmel, flash_128k),
+        ignore = "This test cannot be run with a Flash chip. Ensure Flash is not configured and do not pass `--cfg flash_64k`, `--cfg flash_64k_atmel`, or `--cfg flash_128k` to enable."
     )]
-    fn new_128k() {
-        assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
-    }
+    fn write_page_flush_failure_keeps_buffered_bytes_64k_atmel() {
+        let mut flash_64k_atmel = Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        };
+        let mut writer = flash_64k_atmel.writer(..);
+        let mut buffer = Buffer::<128>::new();
 
-    #[test]
-    #[cfg_attr(
-        not(flash_128k),
-        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
-    )]
-    fn empty_range_read_128k() {
-        let mut flash = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
-        let mut buffer = [1, 2, 3, 4];
+        assert_ok_eq!(writer.write(&[0; 100]), 100);
+        assert_err_eq!(writer.write(&[1; 100]), Error::OperationTimedOut);
 
-        assert_ok_eq!(
-            flash
-                .reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
-                .read(&mut buffer),
-            0
+        // The position returns to the first byte of the failed write.
+        write!(buffer, "{writer:?}").unwrap();
+        assert_eq!(
+            buffer.as_str(),
+            "Flash::Writer64KAtmel { range: 0..65536, position: 100, remaining: 65436 }"
         );
+        assert_eq!(writer.stats().bytes_written, 100);
     }
 
     #[test]
+    #[cfg(not(feature = "strict"))]
     #[cfg_attr(
-        not(flash_128k),
-        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+        any(flash_64k, flash_64k_atmel, flash_128k),
+        ignore = "This test cannot be run with a Flash chip. Ensure Flash is not configured and do not pass `--cfg flash_64k`, `--cfg flash_64k_atmel`, or `--cfg flash_128k` to enable."
     )]
-    fn empty_range_write_128k() {
-        let mut flash = assert_flash_128k!(assert_ok!(unsafe { Flash::new() }));
+    fn retry_writer_page_flush_failure_64k_atmel() {
+        use crate::adapters::RetryError;
+
+        let mut flash_64k_atmel = Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        };
+        let mut writer = RetryWriter::new(flash_64k_atmel.writer(..), 2);
+        let mut buffer = Buffer::<128>::new();
 
         assert_err_eq!(
-            flash
-                .writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
-                .write(&[1, 2, 3, 4]),
-            Error::EndOfWriter
+            writer.write(&[0; 200]),
+            RetryError {
+                error: Error::OperationTimedOut,
+                attempts: 3,
+            }
+        );
+
+        // Each retry rewrote the same page, rather than advancing past it.
+        let writer = writer.into_inner();
+        write!(buffer, "{writer:?}").unwrap();
+        assert_eq!(
+            buffer.as_str(),
+            "Flash::Writer64KAtmel { range: 0..65536, position: 0, remaining: 65536 }"
         );
+        assert_eq!(writer.stats().failures, 3);
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_128k),
-        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
     )]
-    fn full_range_128k() {
+    fn retry_writer_64k_atmel() {
         let mut flash = assert_ok!(unsafe { Flash::new() });
         assert_ok!(flash.reset());
-        let mut flash_128k = assert_flash_128k!(flash);
-        let mut writer = flash_128k.writer(..);
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(flash);
+        let mut writer = RetryWriter::new(
+            flash_64k_atmel
+                .writer(RangedUsize::new_static::<120>()..RangedUsize::new_static::<133>()),
+            2,
+        );
 
-        for i in 0..32768 {
-            assert_ok_eq!(
-                writer.write(&[
-                    0u8.wrapping_add(i as u8),
-                    1u8.wrapping_add(i as u8),
-                    2u8.wrapping_add(i as u8),
-                    3u8.wrapping_add(i as u8)
-                ]),
-                4
-            );
-        }
+        // Spans a page boundary.
+        assert_ok!(writer.write_all(b"hello, world!"));
+        assert_ok!(writer.flush());
+        drop(writer);
 
-        // Wait for the data to be available.
-        wait(Duration::from_millis(1));
+        let mut buf = [0; 13];
+        assert_ok!(flash_64k_atmel
+            .reader(RangedUsize::new_static::<120>()..RangedUsize::new_static::<133>())
+            .read_exact_into(&mut buf));
+        assert_eq!(&buf, b"hello, world!");
+    }
 
-        let mut reader = flash_128k.reader(..);
-        let mut buf = [0, 0, 0, 0];
+    #[test]
+    #[cfg(feature = "strict")]
+    #[should_panic(expected = "failed to flush Atmel flash writer on drop")]
+    #[cfg_attr(
+        any(flash_64k, flash_64k_atmel, flash_128k),
+        ignore = "This test cannot be run with a Flash chip. Ensure Flash is not configured and do not pass `--cfg flash_64k`, `--cfg flash_64k_atmel`, or `--cfg flash_128k` to enable."
+    )]
+    fn drop_flush_failure_strict_64k_atmel() {
+        let mut flash_64k_atmel = Flash64KAtmel {
+            device: Device::AT29LV512,
+            id_swapped: false,
+            halt: None,
+            erase_confirmations: DEFAULT_ERASE_CONFIRMATIONS,
+        };
+        let mut writer = flash_64k_atmel.writer(..);
 
-        for i in 0..32768 {
-            assert_ok_eq!(reader.read(&mut buf), 4);
-            assert_eq!(
-                buf,
-                [
-                    0u8.wrapping_add(i as u8),
-                    1u8.wrapping_add(i as u8),
-                    2u8.wrapping_add(i as u8),
-                    3u8.wrapping_add(i as u8)
-                ],
-            );
-        }
+        assert_ok_eq!(writer.write(b"a"), 1);
+        drop(writer);
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_128k),
-        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
     )]
-    fn partial_range_128k() {
+    fn cursor_debug_64k() {
         let mut flash = assert_ok!(unsafe { Flash::new() });
         assert_ok!(flash.reset());
-        let mut flash_128k = assert_flash_128k!(flash);
-        let mut writer =
-            flash_128k.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
-
-        assert_ok_eq!(writer.write(&[b'a'; 100]), 58);
-
-        // Wait for the data to be available.
-        wait(Duration::from_millis(1));
+        let mut flash_64k = assert_flash_64k!(flash);
+        let mut buffer = Buffer::<128>::new();
 
-        let mut reader =
-            flash_128k.reader(RangedUsize::new_static::<51>()..RangedUsize::new_static::<60>());
-        let mut buf = [0; 20];
+        let mut writer =
+            flash_64k.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+        assert_ok!(writer.write_all(&[0; 16]));
+        write!(buffer, "{writer:?}").unwrap();
+        assert_eq!(
+            buffer.as_str(),
+            "Flash::Writer64K { range: 42..100, position: 58, remaining: 42 }"
+        );
 
-        assert_ok_eq!(reader.read(&mut buf), 9);
+        let reader =
+            flash_64k.reader(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+        let mut buffer = Buffer::<128>::new();
+        write!(buffer, "{reader:?}").unwrap();
         assert_eq!(
-            buf,
-            [
-                b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0
-            ]
+            buffer.as_str(),
+            "Flash::Reader64K { range: 42..100, position: 42, remaining: 58 }"
         );
     }
 
     #[test]
     #[cfg_attr(
-        not(flash_128k),
-        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
     )]
-    fn erase_one_sector_128k() {
-        let mut flash = assert_ok!(unsafe { Flash::new() });
-        assert_ok!(flash.reset());
-        let mut flash_128k = assert_flash_128k!(flash);
-        // Write some data to it.
-        let mut writer = flash_128k.writer(..RangedUsize::new_static::<13>());
-        assert_ok_eq!(writer.write(b"hello, world!"), 13);
-
-        assert_ok!(
-            flash_128k.erase_sectors(RangedU8::new_static::<0>()..RangedU8::new_static::<1>())
-        );
+    fn cursor_debug_64k_atmel() {
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+        let mut writer = flash_64k_atmel
+            .writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+        assert_ok!(writer.write_all(&[0; 16]));
+        let mut buffer = Buffer::<128>::new();
 
-        let mut reader =
-            flash_128k.reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<13>());
-        let mut buf = [0; 13];
+        write!(buffer, "{writer:?}").unwrap();
 
-        assert_ok_eq!(reader.read(&mut buf), 13);
-        assert_eq!(buf, [0xff; 13],);
+        assert_eq!(
+            buffer.as_str(),
+            "Flash::Writer64KAtmel { range: 42..100, position: 58, remaining: 42 }"
+        );
     }
 
     #[test]
@@ -918,42 +5310,41 @@ mod tests {
         not(flash_128k),
         ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
     )]
-    fn erase_all_sectors_128k() {
+    fn cursor_display_128k() {
         let mut flash = assert_ok!(unsafe { Flash::new() });
         assert_ok!(flash.reset());
         let mut flash_128k = assert_flash_128k!(flash);
-        // Write some data to it.
-        let mut writer = flash_128k.writer(..);
-        for i in 0..32768 {
-            assert_ok_eq!(
-                writer.write(&[
-                    0u8.wrapping_add(i as u8),
-                    1u8.wrapping_add(i as u8),
-                    2u8.wrapping_add(i as u8),
-                    3u8.wrapping_add(i as u8)
-                ]),
-                4
-            );
-        }
+        let mut buffer = Buffer::<128>::new();
 
-        assert_ok!(flash_128k.erase_sectors(..));
-
-        let mut reader = flash_128k.reader(..);
-        let mut buf = [0; 4];
+        let mut writer = flash_128k
+            .writer(RangedUsize::new_static::<65530>()..RangedUsize::new_static::<65540>());
+        assert_ok!(writer.write_all(&[0; 8]));
+        write!(buffer, "{writer}").unwrap();
+        assert_eq!(buffer.as_str(), "Flash 128KiB 65530..65540 at 65538");
 
-        for _ in 0..32768 {
-            assert_ok_eq!(reader.read(&mut buf), 4);
-            assert_eq!(buf, [0xff; 4],);
-        }
+        let reader = flash_128k
+            .reader(RangedUsize::new_static::<65530>()..RangedUsize::new_static::<65540>());
+        let mut buffer = Buffer::<128>::new();
+        write!(buffer, "{reader}").unwrap();
+        assert_eq!(buffer.as_str(), "Flash 128KiB 65530..65540 at 65530");
     }
 
     #[test]
     #[cfg_attr(
-        any(flash_64k, flash_64k_atmel, flash_128k),
-        ignore = "This test cannot be run with a Flash chip. Ensure Flash is not configured and do not pass `--cfg flash_64k`, `--cfg flash_64k_atmel`, or `--cfg flash_128k` to enable."
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
     )]
-    fn new_unknown() {
-        assert_err_eq!(unsafe { Flash::new() }, UnknownDeviceID(0xffff));
+    fn throttled_64k_atmel() {
+        let mut flash_64k_atmel = assert_flash_64k_atmel!(assert_ok!(unsafe { Flash::new() }));
+        let mut writer = flash_64k_atmel
+            .writer(RangedUsize::new_static::<100>()..RangedUsize::new_static::<612>())
+            .throttled(NonZeroUsize::new(200).unwrap());
+
+        assert_ok_eq!(writer.write(&[1; 512]), 156);
+        assert_ok_eq!(writer.write(&[2; 356]), 128);
+        assert_ok_eq!(writer.write(&[3; 228]), 128);
+        assert_ok_eq!(writer.write(&[4; 100]), 100);
+        assert_ok!(writer.flush());
     }
 
     // #[test]
@@ -0,0 +1,140 @@
+//! Raw access to flash device commands.
+//!
+//! This module is an escape hatch for carts that need command sequences not supported by the
+//! rest of this crate, such as non-standard unlock sequences or vendor-specific commands. Nothing
+//! here checks that the commands sent make sense for the installed device; you're on your own.
+//!
+//! These functions use the same addresses as the rest of this crate. Each command sequence is
+//! sent with interrupts disabled, just like the sequences sent by the safe API. Readers and
+//! writers on 128KiB devices select their bank before accessing the device, so switching banks
+//! using raw commands does not affect them, as long as no writer is alive at the time.
+//!
+//! WAITCNT must already be configured for flash access. Creating a device using [`Flash::new()`]
+//! or one of its variants does this.
+//!
+//! # Example
+//! A custom unlock sequence for entering ID mode can be sent like so:
+//!
+//! ``` no_run
+//! use gba_save::flash::{raw, Flash};
+//!
+//! // Configures WAITCNT for flash access.
+//! let _flash = unsafe { Flash::new() };
+//! let id = unsafe {
+//!     raw::send_command_bytes(&[(0x5555, 0xaa), (0x2aaa, 0x55), (0x5555, 0x90)]);
+//!     let id = u16::from_le_bytes([raw::read_byte(0), raw::read_byte(1)]);
+//!     raw::exit_id_mode();
+//!     id
+//! };
+//! ```
+//!
+//! [`Flash::new()`]: crate::flash::Flash::new()
+
+use crate::{
+    flash::{command::CommandSequence, FLASH_MEMORY},
+    mmio::without_interrupts,
+    time,
+};
+use core::time::Duration;
+
+/// Writes each `(offset, byte)` pair to the device, in order.
+///
+/// Offsets are measured from the start of the flash memory region. The whole sequence is sent
+/// with interrupts disabled, so it should be kept short.
+///
+/// # Safety
+/// Must have exclusive ownership of flash memory, and no flash writer may be alive. The sequence
+/// must leave the device in a state that the rest of this crate can work with, such as by
+/// following any mode change with a command to exit that mode.
+pub unsafe fn send_command_bytes(sequence: &[(u16, u8)]) {
+    without_interrupts(|| {
+        for &(offset, byte) in sequence {
+            unsafe { FLASH_MEMORY.add(offset as usize).write_volatile(byte) };
+        }
+    });
+}
+
+/// Reads the byte at the given offset from the start of the flash memory region.
+///
+/// # Safety
+/// Must have exclusive ownership of flash memory.
+pub unsafe fn read_byte(offset: u16) -> u8 {
+    unsafe { FLASH_MEMORY.add(offset as usize).read_volatile() }
+}
+
+/// Reads the device's status byte.
+///
+/// While a program or erase operation is in progress, most devices toggle bit 6 on every read.
+/// Once the operation has completed, this reads the byte at the start of the flash memory
+/// region.
+///
+/// # Safety
+/// Must have exclusive ownership of flash memory.
+pub unsafe fn read_status() -> u8 {
+    unsafe { read_byte(0) }
+}
+
+/// Enters ID mode using the standard command sequence, returning the device ID.
+///
+/// The low byte is the manufacturer ID and the high byte is the chip ID. The device remains in ID
+/// mode until [`exit_id_mode()`] is called.
+///
+/// # Safety
+/// Must have exclusive ownership of flash memory, and no flash writer may be alive.
+pub unsafe fn enter_id_mode() -> u16 {
+    super::enter_id_mode(None)
+}
+
+/// Exits ID mode using the standard command sequence.
+///
+/// Some devices, such as the Sanyo LE26FV10N1TS, only exit ID mode once this sequence has been
+/// sent twice.
+///
+/// # Safety
+/// Must have exclusive ownership of flash memory, and no flash writer may be alive.
+pub unsafe fn exit_id_mode() {
+    CommandSequence::begin().terminate_mode().execute();
+    time::spin_for(Duration::from_millis(20));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enter_id_mode, exit_id_mode, read_byte, send_command_bytes};
+    use crate::flash::Flash;
+    use claims::assert_ok;
+    use gba_test::test;
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn enter_id_mode_matches_device_id() {
+        let flash = assert_ok!(unsafe { Flash::new() });
+
+        let id = unsafe { enter_id_mode() };
+        unsafe { exit_id_mode() };
+
+        assert_eq!(id, flash.device_id());
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn send_command_bytes_id_mode() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+
+        let id = unsafe {
+            send_command_bytes(&[(0x5555, 0xaa), (0x2aaa, 0x55), (0x5555, 0x90)]);
+            let id = u16::from_le_bytes([read_byte(0), read_byte(1)]);
+            exit_id_mode();
+            id
+        };
+
+        assert_eq!(id, flash.device_id());
+        // The crate can still use the device afterward.
+        assert_ok!(flash.verify_present());
+    }
+}
@@ -1,62 +1,265 @@
-use crate::flash::{switch_bank, Bank, FLASH_MEMORY, SIZE_64KB};
-use core::{cmp::min, convert::Infallible, marker::PhantomData, ptr};
+#[cfg(feature = "log")]
+use crate::read_log::{ReadLog, ReadLogSlot};
+use crate::{
+    cursor::Cursor,
+    flash::{switch_bank, Bank, Error, FLASH_MEMORY, SIZE_64KB},
+    InsufficientData,
+};
+use core::{
+    cmp::min,
+    convert::Infallible,
+    fmt::{self, Debug, Display, Formatter},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr, slice,
+};
 use embedded_io::{ErrorType, Read};
+use static_assertions::assert_not_impl_any;
+
+/// Addresses in ROM read between presence probes, chosen to hold different values.
+///
+/// These are the first byte of the Nintendo logo and the fixed value in the cartridge header.
+const ROM_PROBES: [*const u8; 2] = [0x0800_0004 as *const u8, 0x0800_00b2 as *const u8];
+
+/// Returns whether the byte at `address` reads the same while the cartridge bus is used for other
+/// reads.
+///
+/// With no chip to drive the bus, a read returns whatever value was last left on the bus, which
+/// the ROM reads in between change.
+fn reads_consistently(address: *const u8) -> bool {
+    let first = unsafe { address.read_volatile() };
+    ROM_PROBES.iter().all(|&rom| {
+        unsafe { rom.read_volatile() };
+        let value = unsafe { address.read_volatile() };
+        value == first
+    })
+}
 
 /// A reader on a 64KiB flash device.
 ///
-/// This type allows reading data over the range specified upon creation.
-#[derive(Debug)]
+/// This type allows reading data over the range specified upon creation. Reading into an empty
+/// buffer returns `Ok(0)` without accessing the device.
+///
+/// Cloning a reader creates an independent cursor at the same position. A 64KiB device has no
+/// state that reading depends on, so clones can read in any interleaving. This also holds for
+/// readers created by [`Flash128K::bank_reader()`], since no other reader can switch banks while
+/// they are alive. Readers created by a [`FlashBank64K`] select their bank at the start of every
+/// read, since the other half of the device may have switched banks.
+///
+/// [`Flash128K::bank_reader()`]: crate::flash::Flash128K::bank_reader()
+/// [`FlashBank64K`]: crate::flash::FlashBank64K
+#[must_use]
+#[derive(Clone)]
 pub struct Reader64K<'a> {
     address: *mut u8,
     len: usize,
+    start: usize,
+    bank: Option<Bank>,
+    #[cfg(feature = "log")]
+    log: ReadLogSlot,
     lifetime: PhantomData<&'a ()>,
 }
 
+assert_not_impl_any!(Reader64K<'static>: Send, Sync);
+
 impl Reader64K<'_> {
     pub(crate) unsafe fn new_unchecked(address: *mut u8, len: usize) -> Self {
+        debug_assert!(
+            address >= FLASH_MEMORY && address as usize + len <= FLASH_MEMORY as usize + SIZE_64KB,
+            "range is out of bounds for a 64KiB flash device"
+        );
         Self {
             address,
             len,
+            start: unsafe { address.offset_from(FLASH_MEMORY) as usize },
+            bank: None,
+            #[cfg(feature = "log")]
+            log: ReadLogSlot::default(),
             lifetime: PhantomData,
         }
     }
-}
 
-impl ErrorType for Reader64K<'_> {
-    type Error = Infallible;
-}
+    /// Selects `bank` at the start of every read, for reading a bank whose selection is shared.
+    pub(super) fn in_bank(mut self, bank: Bank) -> Self {
+        self.bank = Some(bank);
+        self
+    }
 
-impl Read for Reader64K<'_> {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+    /// Logs the creation of this reader, and its completion once it is exhausted. Clones of the
+    /// reader do not log their completion, so the read is only logged once.
+    ///
+    /// This does nothing unless the `log` feature is enabled.
+    #[cfg_attr(not(feature = "log"), allow(unused_mut, unused_variables))]
+    pub(crate) fn logged(mut self, device: &'static str) -> Self {
+        #[cfg(feature = "log")]
+        {
+            self.log = ReadLogSlot::new(ReadLog::new(device, self.start, self.len));
+        }
+        self
+    }
+
+    /// Fills `buf` entirely with data from the reader.
+    ///
+    /// If fewer than `buf.len()` bytes remain in the reader, [`InsufficientData`] is returned
+    /// before anything is read, leaving `buf` untouched.
+    pub fn read_exact_into(&mut self, buf: &mut [u8]) -> Result<(), InsufficientData> {
+        if buf.len() > self.len {
+            return Err(InsufficientData {
+                needed: buf.len(),
+                available: self.len,
+            });
+        }
+        let Ok(_) = self.read(buf);
+        Ok(())
+    }
+
+    /// Checks that reads are returning data from a flash chip.
+    ///
+    /// If no chip is present, such as when the cartridge has been removed or the device was never
+    /// detected, reads return whatever value was last left on the cartridge bus, which looks like
+    /// data. This reads the first and last bytes of the reader's range several times, reading
+    /// from ROM in between to change the value on the bus. A chip returns the same stored value
+    /// each time, while an empty bus does not, in which case [`Error::NotPresent`] is returned.
+    ///
+    /// This is a heuristic: an empty bus may happen to read consistently, so a successful check
+    /// does not guarantee that a chip is present. It is intended to be called before trusting
+    /// loaded data, alongside a checksum, so that a removed cartridge is reported as such rather
+    /// than as corrupted data.
+    ///
+    /// # Example
+    /// ``` no_run
+    /// use embedded_io::Read;
+    /// use gba_save::flash::Flash;
+    ///
+    /// let Flash::Flash64K(flash_64k) = unsafe { Flash::new() }.unwrap() else {
+    ///     panic!("unsupported flash type");
+    /// };
+    /// let mut reader = flash_64k.reader(..);
+    /// reader.validate_presence().expect("flash is not present");
+    /// let mut buf = [0; 64];
+    /// reader.read_exact(&mut buf).unwrap();
+    /// // Verify the checksum of `buf` before using it.
+    /// ```
+    pub fn validate_presence(&self) -> Result<(), Error> {
+        if let Some(bank) = self.bank {
+            switch_bank(bank);
+        }
+        let first = unsafe { FLASH_MEMORY.add(self.start) };
+        let last = unsafe { self.address.add(self.len) }
+            .wrapping_sub(1)
+            .max(first);
+        if reads_consistently(first) && reads_consistently(last) {
+            Ok(())
+        } else {
+            Err(Error::NotPresent)
+        }
+    }
+
+    /// Reads bytes into a possibly uninitialized buffer, returning the initialized prefix.
+    ///
+    /// This behaves the same as [`Read::read()`], but does not require `buf` to be initialized,
+    /// avoiding the cost of zeroing large buffers before reading into them.
+    pub fn read_uninit<'buf>(
+        &mut self,
+        buf: &'buf mut [MaybeUninit<u8>],
+    ) -> Result<&'buf mut [u8], Infallible> {
+        if buf.is_empty() {
+            return Ok(&mut []);
+        }
+        if let Some(bank) = self.bank {
+            // The other half of the device may have switched banks since the last read.
+            switch_bank(bank);
+        }
         let mut read_count = 0;
         loop {
             if read_count >= min(buf.len(), self.len) {
                 self.address = unsafe { self.address.add(read_count) };
                 self.len -= read_count;
-                return Ok(read_count);
+                record_stat!(bytes_read, read_count);
+                #[cfg(feature = "log")]
+                if self.len == 0 {
+                    self.log.finish();
+                }
+                // SAFETY: The first `read_count` bytes of `buf` have been initialized.
+                return Ok(unsafe {
+                    slice::from_raw_parts_mut(buf.as_mut_ptr().cast(), read_count)
+                });
             }
 
             unsafe {
-                *buf.get_unchecked_mut(read_count) = self.address.add(read_count).read_volatile();
+                buf.get_unchecked_mut(read_count)
+                    .write(self.address.add(read_count).read_volatile());
             }
             read_count += 1;
         }
     }
 }
 
+impl Reader64K<'_> {
+    fn cursor(&self) -> Cursor {
+        let position = unsafe { self.address.offset_from(FLASH_MEMORY) as usize };
+        Cursor {
+            start: self.start,
+            position,
+            end: position + self.len,
+        }
+    }
+}
+
+impl Debug for Reader64K<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().debug(formatter, "Flash::Reader64K")
+    }
+}
+
+impl Display for Reader64K<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().display(formatter, "Flash 64KiB")
+    }
+}
+
+impl ErrorType for Reader64K<'_> {
+    type Error = Infallible;
+}
+
+impl Read for Reader64K<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        // SAFETY: Initialized bytes are valid `MaybeUninit<u8>`s, and only initialized bytes are
+        // written to the buffer.
+        self.read_uninit(unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) })
+            .map(|buf| buf.len())
+    }
+}
+
 /// A reader on a 128KiB flash device.
 ///
-/// This type allows reading data over the range specified upon creation.
-#[derive(Debug)]
+/// This type allows reading data over the range specified upon creation. Reading into an empty
+/// buffer returns `Ok(0)` without accessing the device.
+///
+/// Cloning a reader creates an independent cursor at the same position. Each reader tracks the
+/// bank it needs and selects it at the start of every read, so clones can read in any
+/// interleaving even when they are in different banks.
+#[must_use]
+#[derive(Clone)]
 pub struct Reader128K<'a> {
     address: *mut u8,
     len: usize,
+    start: usize,
     bank: Bank,
+    #[cfg(feature = "log")]
+    log: ReadLogSlot,
     lifetime: PhantomData<&'a ()>,
 }
 
+assert_not_impl_any!(Reader128K<'static>: Send, Sync);
+
 impl Reader128K<'_> {
     pub(crate) unsafe fn new_unchecked(address: *mut u8, len: usize) -> Self {
+        debug_assert!(
+            address >= FLASH_MEMORY
+                && address as usize + len <= FLASH_MEMORY as usize + 2 * SIZE_64KB,
+            "range is out of bounds for a 128KiB flash device"
+        );
         let bank = if address < unsafe { FLASH_MEMORY.add(SIZE_64KB) } {
             Bank::_0
         } else {
@@ -67,41 +270,192 @@ impl Reader128K<'_> {
         Self {
             address,
             len,
+            start: unsafe { address.offset_from(FLASH_MEMORY) as usize },
             bank,
+            #[cfg(feature = "log")]
+            log: ReadLogSlot::default(),
             lifetime: PhantomData,
         }
     }
-}
 
-impl ErrorType for Reader128K<'_> {
-    type Error = Infallible;
-}
+    /// Logs the creation of this reader, and its completion once it is exhausted. Clones of the
+    /// reader do not log their completion, so the read is only logged once.
+    ///
+    /// This does nothing unless the `log` feature is enabled.
+    #[cfg_attr(not(feature = "log"), allow(unused_mut, unused_variables))]
+    pub(crate) fn logged(mut self, device: &'static str) -> Self {
+        #[cfg(feature = "log")]
+        {
+            self.log = ReadLogSlot::new(ReadLog::new(device, self.start, self.len));
+        }
+        self
+    }
+
+    /// Fills `buf` entirely with data from the reader.
+    ///
+    /// If fewer than `buf.len()` bytes remain in the reader, [`InsufficientData`] is returned
+    /// before anything is read, leaving `buf` untouched.
+    pub fn read_exact_into(&mut self, buf: &mut [u8]) -> Result<(), InsufficientData> {
+        if buf.len() > self.len {
+            return Err(InsufficientData {
+                needed: buf.len(),
+                available: self.len,
+            });
+        }
+        let Ok(_) = self.read(buf);
+        Ok(())
+    }
+
+    /// Checks that reads are returning data from a flash chip.
+    ///
+    /// This behaves the same as [`Reader64K::validate_presence()`]. The first and last bytes of
+    /// the reader's range are each read from the bank they are in.
+    ///
+    /// # Example
+    /// ``` no_run
+    /// use embedded_io::Read;
+    /// use gba_save::flash::Flash;
+    ///
+    /// let Flash::Flash128K(flash_128k) = unsafe { Flash::new() }.unwrap() else {
+    ///     panic!("unsupported flash type");
+    /// };
+    /// let mut reader = flash_128k.reader(..);
+    /// reader.validate_presence().expect("flash is not present");
+    /// let mut buf = [0; 64];
+    /// reader.read_exact(&mut buf).unwrap();
+    /// // Verify the checksum of `buf` before using it.
+    /// ```
+    pub fn validate_presence(&self) -> Result<(), Error> {
+        let end = unsafe { self.address.offset_from(FLASH_MEMORY) as usize } + self.len;
+        let last = end.saturating_sub(1).max(self.start);
+        // Reads switch to the bank they need, so the bank does not need to be restored.
+        let present = [self.start, last].into_iter().all(|offset| {
+            let (bank, offset) = if offset < SIZE_64KB {
+                (Bank::_0, offset)
+            } else {
+                (Bank::_1, offset - SIZE_64KB)
+            };
+            switch_bank(bank);
+            reads_consistently(unsafe { FLASH_MEMORY.add(offset) })
+        });
+        if present {
+            Ok(())
+        } else {
+            Err(Error::NotPresent)
+        }
+    }
+
+    /// Reads bytes into a possibly uninitialized buffer, returning the initialized prefix.
+    ///
+    /// This behaves the same as [`Read::read()`], but does not require `buf` to be initialized,
+    /// avoiding the cost of zeroing large buffers before reading into them.
+    pub fn read_uninit<'buf>(
+        &mut self,
+        buf: &'buf mut [MaybeUninit<u8>],
+    ) -> Result<&'buf mut [u8], Infallible> {
+        if buf.is_empty() {
+            return Ok(&mut []);
+        }
+        // Another reader may have switched banks since the last read.
+        switch_bank(self.bank);
 
-impl Read for Reader128K<'_> {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
         let mut read_count = 0;
         loop {
             if read_count >= min(buf.len(), self.len) {
                 self.address = unsafe { self.address.add(read_count) };
                 self.len -= read_count;
-                return Ok(read_count);
+                record_stat!(bytes_read, read_count);
+                #[cfg(feature = "log")]
+                if self.len == 0 {
+                    self.log.finish();
+                }
+                // SAFETY: The first `read_count` bytes of `buf` have been initialized.
+                return Ok(unsafe {
+                    slice::from_raw_parts_mut(buf.as_mut_ptr().cast(), read_count)
+                });
             }
 
             let mut address = unsafe { self.address.add(read_count) };
-            if matches!(self.bank, Bank::_0) {
-                if ptr::eq(address, unsafe { FLASH_MEMORY.add(SIZE_64KB) }) {
-                    self.bank = Bank::_1;
-                    switch_bank(self.bank);
-                }
+            if matches!(self.bank, Bank::_0)
+                && ptr::eq(address, unsafe { FLASH_MEMORY.add(SIZE_64KB) })
+            {
+                self.bank = Bank::_1;
+                switch_bank(self.bank);
             }
             if matches!(self.bank, Bank::_1) {
                 address = unsafe { address.sub(SIZE_64KB) };
             }
 
             unsafe {
-                *buf.get_unchecked_mut(read_count) = address.read_volatile();
+                buf.get_unchecked_mut(read_count)
+                    .write(address.read_volatile());
             }
             read_count += 1;
         }
     }
 }
+
+impl Reader128K<'_> {
+    fn cursor(&self) -> Cursor {
+        let position = unsafe { self.address.offset_from(FLASH_MEMORY) as usize };
+        Cursor {
+            start: self.start,
+            position,
+            end: position + self.len,
+        }
+    }
+}
+
+impl Debug for Reader128K<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().debug(formatter, "Flash::Reader128K")
+    }
+}
+
+impl Display for Reader128K<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().display(formatter, "Flash 128KiB")
+    }
+}
+
+impl ErrorType for Reader128K<'_> {
+    type Error = Infallible;
+}
+
+impl Read for Reader128K<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        // SAFETY: Initialized bytes are valid `MaybeUninit<u8>`s, and only initialized bytes are
+        // written to the buffer.
+        self.read_uninit(unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) })
+            .map(|buf| buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reads_consistently;
+    use core::ptr;
+    use gba_test::test;
+
+    /// Timer 0's counter, which changes on every cycle while the timer is running.
+    const TM0CNT_L: *const u8 = 0x0400_0100 as *const u8;
+    /// Timer 0's control register.
+    const TM0CNT_H: *mut u16 = 0x0400_0102 as *mut u16;
+
+    #[test]
+    fn reads_consistently_stable() {
+        let value = 42u8;
+
+        assert!(reads_consistently(ptr::addr_of!(value)));
+    }
+
+    #[test]
+    fn reads_consistently_unstable() {
+        // Start the timer with a prescaler of 1, so that its counter never reads the same twice.
+        unsafe { TM0CNT_H.write_volatile(0x0080) };
+        let consistent = reads_consistently(TM0CNT_L);
+        unsafe { TM0CNT_H.write_volatile(0) };
+
+        assert!(!consistent);
+    }
+}
@@ -0,0 +1,84 @@
+//! Typical latencies of flash operations.
+//!
+//! Each constant is the slowest typical value among the devices supported by this crate, taken
+//! from the manufacturers' datasheets. They can be used to budget how long a save may block, such
+//! as when scheduling audio around it. The values for a specific device are available from
+//! [`Flash::info()`].
+//!
+//! These are typical values, not upper bounds. Programming blocks for at most [`PROGRAM_TIMEOUT`]
+//! before failing, while an erase blocks for at most [`ERASE_TIMEOUT_MARGIN`] times the device's
+//! typical erase time.
+//!
+//! [`Flash::info()`]: crate::flash::Flash::info()
+//! [`PROGRAM_TIMEOUT`]: crate::flash::PROGRAM_TIMEOUT
+//! [`ERASE_TIMEOUT_MARGIN`]: crate::flash::ERASE_TIMEOUT_MARGIN
+
+use core::time::Duration;
+
+/// The time taken to program a single byte on a device that programs bytes individually.
+pub const BYTE_PROGRAM: Duration = Duration::from_micros(30);
+
+/// The time taken to program a 128-byte page on an Atmel device.
+///
+/// This is how long flushing a [`Writer64KAtmel`](crate::flash::Writer64KAtmel) blocks for each
+/// page written.
+pub const PAGE_PROGRAM: Duration = Duration::from_millis(10);
+
+/// The time taken to erase a single 4KiB sector.
+pub const SECTOR_ERASE: Duration = Duration::from_millis(1000);
+
+/// The time taken to erase an entire device.
+pub const CHIP_ERASE: Duration = Duration::from_millis(3000);
+
+#[cfg(test)]
+mod tests {
+    use super::{BYTE_PROGRAM, CHIP_ERASE, PAGE_PROGRAM, SECTOR_ERASE};
+    use crate::flash::Device;
+    use gba_test::test;
+
+    const DEVICES: [Device; 6] = [
+        Device::MX29L010,
+        Device::LE26FV10N1TS,
+        Device::MN63F805MNP,
+        Device::MX29L512,
+        Device::AT29LV512,
+        Device::LE39FW512,
+    ];
+
+    #[test]
+    fn byte_program_is_slowest() {
+        let slowest = DEVICES
+            .into_iter()
+            .filter(|&device| device != Device::AT29LV512)
+            .map(|device| device.info().byte_program_time)
+            .max();
+
+        assert_eq!(slowest, Some(BYTE_PROGRAM));
+    }
+
+    #[test]
+    fn page_program_matches_atmel() {
+        assert_eq!(Device::AT29LV512.info().byte_program_time, PAGE_PROGRAM);
+    }
+
+    #[test]
+    fn sector_erase_is_slowest() {
+        let slowest = DEVICES
+            .into_iter()
+            .filter(|&device| device != Device::AT29LV512)
+            .map(|device| device.info().sector_erase_time)
+            .max();
+
+        assert_eq!(slowest, Some(SECTOR_ERASE));
+    }
+
+    #[test]
+    fn chip_erase_is_slowest() {
+        let slowest = DEVICES
+            .into_iter()
+            .map(|device| device.info().chip_erase_time)
+            .max();
+
+        assert_eq!(slowest, Some(CHIP_ERASE));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::flash::DeviceInfo;
+use embedded_io::{Read, ReadExactError, Write};
+
+/// An estimate of the remaining endurance of a flash device.
+///
+/// Each erase block of a flash device can only be erased a limited number of times before it may
+/// wear out. This type tracks the total number of blocks erased over the lifetime of the device,
+/// and estimates how many more erases the device can sustain, assuming erases are spread evenly
+/// across its blocks.
+///
+/// The lifetime count must be persisted by the application, such as in a reserved part of the
+/// save, using [`write_lifetime_erases()`](Self::write_lifetime_erases()) and
+/// [`read_lifetime_erases()`](Self::read_lifetime_erases()).
+///
+/// ``` no_run
+/// use gba_save::flash::{Flash, WearEstimator};
+///
+/// let flash = unsafe { Flash::new() }.expect("flash not available");
+/// // Read from the save.
+/// let lifetime_erases = 1234;
+/// let mut estimator = WearEstimator::new(flash.info(), lifetime_erases);
+///
+/// // After erasing some sectors.
+/// estimator.add_erases(4);
+/// if estimator.remaining_percent() < 10 {
+///     // Save less often.
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct WearEstimator {
+    info: DeviceInfo,
+    lifetime_erases: u32,
+}
+
+impl WearEstimator {
+    /// The number of bytes used to store the lifetime erase count.
+    pub const LIFETIME_SIZE: usize = 4;
+
+    /// Creates an estimator for the described device, which has previously had `lifetime_erases`
+    /// blocks erased.
+    pub fn new(info: DeviceInfo, lifetime_erases: u32) -> Self {
+        Self {
+            info,
+            lifetime_erases,
+        }
+    }
+
+    /// Records that `erases` more blocks have been erased.
+    pub fn add_erases(&mut self, erases: u32) {
+        self.lifetime_erases = self.lifetime_erases.saturating_add(erases);
+    }
+
+    /// Records the erases counted by the statistics counters.
+    ///
+    /// Sectors erased are counted for most devices. For Atmel devices, which erase each page as it
+    /// is programmed, pages programmed are counted instead. The counters should be reset after
+    /// calling this, so the same erases are not recorded twice.
+    #[cfg(feature = "stats")]
+    pub fn add_session(&mut self, stats: &crate::stats::Stats) {
+        self.add_erases(if self.info.erase_block_size == 128 {
+            stats.pages_programmed
+        } else {
+            stats.sectors_erased
+        });
+    }
+
+    /// Returns the total number of blocks erased over the lifetime of the device.
+    pub fn lifetime_erases(&self) -> u32 {
+        self.lifetime_erases
+    }
+
+    /// Returns the estimated number of block erases remaining before the device may wear out.
+    pub fn remaining_erases(&self) -> u64 {
+        (self.info.endurance as u64 * self.info.erase_blocks as u64)
+            .saturating_sub(self.lifetime_erases as u64)
+    }
+
+    /// Returns the estimated percentage of the device's endurance remaining.
+    pub fn remaining_percent(&self) -> u8 {
+        let total = self.info.endurance as u64 * self.info.erase_blocks as u64;
+        if total == 0 {
+            return 0;
+        }
+        (self.remaining_erases() * 100 / total) as u8
+    }
+
+    /// Reads a lifetime erase count stored as little-endian bytes.
+    pub fn read_lifetime_erases<R>(reader: &mut R) -> Result<u32, ReadExactError<R::Error>>
+    where
+        R: Read,
+    {
+        let mut bytes = [0; Self::LIFETIME_SIZE];
+        reader.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// Writes the lifetime erase count as little-endian bytes.
+    pub fn write_lifetime_erases<W>(&self, writer: &mut W) -> Result<(), W::Error>
+    where
+        W: Write,
+    {
+        writer.write_all(&self.lifetime_erases.to_le_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WearEstimator;
+    use crate::flash::Device;
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use embedded_io::ReadExactError;
+    use gba_test::test;
+
+    #[test]
+    fn new() {
+        let estimator = WearEstimator::new(Device::MX29L512.info(), 42);
+
+        assert_eq!(estimator.lifetime_erases(), 42);
+        assert_eq!(estimator.remaining_erases(), 1_599_958);
+    }
+
+    #[test]
+    fn add_erases() {
+        let mut estimator = WearEstimator::new(Device::MX29L512.info(), 42);
+        estimator.add_erases(8);
+
+        assert_eq!(estimator.lifetime_erases(), 50);
+    }
+
+    #[test]
+    fn add_erases_saturates() {
+        let mut estimator = WearEstimator::new(Device::MX29L512.info(), u32::MAX - 1);
+        estimator.add_erases(8);
+
+        assert_eq!(estimator.lifetime_erases(), u32::MAX);
+    }
+
+    #[test]
+    fn remaining_erases_worn_out() {
+        let estimator = WearEstimator::new(Device::AT29LV512.info(), 5_120_001);
+
+        assert_eq!(estimator.remaining_erases(), 0);
+        assert_eq!(estimator.remaining_percent(), 0);
+    }
+
+    #[test]
+    fn remaining_percent_new() {
+        let estimator = WearEstimator::new(Device::MX29L010.info(), 0);
+
+        assert_eq!(estimator.remaining_percent(), 100);
+    }
+
+    #[test]
+    fn remaining_percent_half() {
+        let estimator = WearEstimator::new(Device::MX29L512.info(), 800_000);
+
+        assert_eq!(estimator.remaining_percent(), 50);
+    }
+
+    #[test]
+    #[cfg(feature = "stats")]
+    fn add_session_sectors() {
+        let mut estimator = WearEstimator::new(Device::MX29L512.info(), 0);
+        estimator.add_session(&crate::stats::Stats {
+            sectors_erased: 3,
+            pages_programmed: 100,
+            ..Default::default()
+        });
+
+        assert_eq!(estimator.lifetime_erases(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "stats")]
+    fn add_session_pages() {
+        let mut estimator = WearEstimator::new(Device::AT29LV512.info(), 0);
+        estimator.add_session(&crate::stats::Stats {
+            sectors_erased: 3,
+            pages_programmed: 100,
+            ..Default::default()
+        });
+
+        assert_eq!(estimator.lifetime_erases(), 100);
+    }
+
+    #[test]
+    fn write_lifetime_erases() {
+        let estimator = WearEstimator::new(Device::MX29L512.info(), 0x1234_5678);
+        let mut bytes = [0; 4];
+
+        assert_ok!(estimator.write_lifetime_erases(&mut &mut bytes[..]));
+        assert_eq!(bytes, [0x78, 0x56, 0x34, 0x12]);
+    }
+
+    #[test]
+    fn read_lifetime_erases() {
+        assert_ok_eq!(
+            WearEstimator::read_lifetime_erases(&mut &[0x78, 0x56, 0x34, 0x12][..]),
+            0x1234_5678
+        );
+    }
+
+    #[test]
+    fn read_lifetime_erases_unexpected_eof() {
+        assert_err_eq!(
+            WearEstimator::read_lifetime_erases(&mut &[0x78, 0x56][..]),
+            ReadExactError::UnexpectedEof
+        );
+    }
+}
@@ -1,33 +1,222 @@
 use crate::{
+    adapters::Throttled,
+    cursor::Cursor,
     flash::{
-        send_command, switch_bank, verify_byte, verify_bytes, Bank, Command, Error, Reader64K,
-        FLASH_MEMORY, SIZE_64KB,
+        command::CommandSequence, switch_bank, timed_out, Bank, Device, Error, Reader128K,
+        Reader64K, FLASH_MEMORY, PROGRAM_TIMEOUT, SIZE_64KB,
     },
-    mmio::IME,
+    verify::{find_mismatch, PollBudget},
+    write_all::{write_all, write_all_strict},
+    write_stats::WriteTracker,
+    WriteStats,
+};
+use core::{
+    cmp::min,
+    fmt::{self, Debug, Display, Formatter},
+    marker::PhantomData,
+    num::NonZeroUsize,
+    ptr,
+    time::Duration,
 };
-use core::{cmp::min, marker::PhantomData, ptr, time::Duration};
 use embedded_io::{ErrorType, Read, Write};
+use static_assertions::assert_not_impl_any;
+
+/// The number of bytes programmed under a single verification timeout.
+pub(super) const BATCH_SIZE: usize = 32;
+/// The verification timeout shared by each batch of programmed bytes.
+pub(super) const BATCH_TIMEOUT: Duration = PROGRAM_TIMEOUT;
+
+/// Programs `byte` at `address`, spending polls from `budget` while waiting for it to complete.
+///
+/// If the current value already equals `byte`, programming is skipped. This is always the case
+/// when writing `0xff` to erased memory.
+///
+/// Programming can only clear bits. If any bit set in `byte` is cleared in the current value, the
+/// program operation cannot succeed, so this fails fast rather than waiting for verification to
+/// time out.
+///
+/// Completion is detected using the toggle bit on devices that support it, which is faster than
+/// waiting for the programmed value to be read back and allows a failed program to be reported
+/// immediately as [`Error::ProgramFailed`]. Other devices are polled until the programmed value is
+/// read back.
+pub(super) fn program_byte(
+    address: *mut u8,
+    byte: u8,
+    offset: usize,
+    device: Device,
+    budget: &mut PollBudget,
+) -> Result<(), Error> {
+    let current = unsafe { address.read_volatile() };
+    if current == byte {
+        return Ok(());
+    }
+    if current & byte != byte {
+        return Err(Error::NotErased { address: offset });
+    }
+
+    CommandSequence::begin()
+        .program(address as usize - FLASH_MEMORY as usize, byte)
+        .execute();
+    // The device ignores commands until programming completes, so each byte must be polled before
+    // the next can be programmed. Only the timeout is shared across the batch.
+    if !device.has_toggle_bit() {
+        return budget.verify_byte(address, byte).map_err(timed_out);
+    }
+    budget.wait_toggle_stopped(address).map_err(timed_out)?;
+    // Programming has completed, so a mismatch will not resolve itself by waiting longer.
+    if unsafe { address.read_volatile() } != byte {
+        record_stat!(verification_failures, 1);
+        return Err(Error::ProgramFailed { address: offset });
+    }
+    Ok(())
+}
+
+/// Programs a full 128-byte page on an Atmel device, spending polls from `budget` while waiting
+/// for it to complete.
+pub(super) fn program_page(
+    address: *mut u8,
+    page: &[u8; 128],
+    budget: &mut PollBudget,
+) -> Result<(), Error> {
+    // The whole page is sent with interrupts disabled, as GBATEK recommends for Atmel devices.
+    CommandSequence::begin()
+        .program_page(address as usize - FLASH_MEMORY as usize, page)
+        .execute();
+    record_stat!(pages_programmed, 1);
+
+    for (i, &byte) in page.iter().enumerate() {
+        budget
+            .verify_byte(unsafe { address.add(i) }, byte)
+            .map_err(timed_out)?;
+    }
+    Ok(())
+}
 
 /// A writer on a 64KiB flash device.
 ///
-/// This type allows writing data on the range specified upon creation.
+/// This type allows writing data on the range specified upon creation. Writing an empty buffer
+/// returns `Ok(0)` without accessing the device, unless the writer is exhausted.
 ///
-/// If the memory being written to has been written to previously without being erased, the writes
-/// will not succeed.
+/// Bytes that already hold the value being written are not reprogrammed, so writing `0xff` to
+/// erased memory is cheap. If the memory being written to has been written to previously without
+/// being erased, the writes will fail with [`Error::NotErased`].
+#[must_use]
 pub struct Writer64K<'a> {
     address: *mut u8,
     len: usize,
+    device: Device,
+    bank: Option<Bank>,
+    stats: WriteTracker,
     lifetime: PhantomData<&'a ()>,
 }
 
+assert_not_impl_any!(Writer64K<'static>: Send, Sync);
+
 impl Writer64K<'_> {
-    pub(crate) unsafe fn new_unchecked(address: *mut u8, len: usize) -> Self {
+    pub(crate) unsafe fn new_unchecked(address: *mut u8, len: usize, device: Device) -> Self {
+        debug_assert!(
+            address >= FLASH_MEMORY && address as usize + len <= FLASH_MEMORY as usize + SIZE_64KB,
+            "range is out of bounds for a 64KiB flash device"
+        );
         Self {
             address,
             len,
+            device,
+            bank: None,
+            stats: WriteTracker::new(
+                "Flash 64KiB",
+                unsafe { address.offset_from(FLASH_MEMORY) as usize },
+                len,
+            ),
             lifetime: PhantomData,
         }
     }
+
+    /// Selects `bank` at the start of every write, for writing a bank whose selection is shared.
+    pub(super) fn in_bank(mut self, bank: Bank) -> Self {
+        self.bank = Some(bank);
+        self
+    }
+
+    /// Returns statistics about the writes performed by this writer.
+    pub fn stats(&self) -> WriteStats {
+        self.stats.snapshot()
+    }
+
+    /// Writes all of `buf` and then flushes the writer.
+    ///
+    /// If `buf` is larger than the space remaining in the writer, [`Error::BufferTooLarge`] is
+    /// returned before anything is written, so a save either fits entirely or is not started.
+    pub fn commit(&mut self, buf: &[u8]) -> Result<(), Error> {
+        if buf.len() > self.len {
+            return Err(Error::BufferTooLarge {
+                needed: buf.len(),
+                available: self.len,
+            });
+        }
+        write_all(self, buf, Error::EndOfWriter)?;
+        self.flush()
+    }
+
+    /// Writes all of `buf`, returning an error if it does not fit in the space remaining.
+    ///
+    /// [`write()`](Write::write()) writes as much of `buf` as fits and returns the number of bytes
+    /// written, which is easy to ignore by mistake. This instead returns [`Error::Truncated`] if
+    /// the writer runs out of space, after writing as much of `buf` as fit. To write nothing at all
+    /// when `buf` does not fit, use [`commit()`](Self::commit()).
+    pub fn write_all_strict(&mut self, buf: &[u8]) -> Result<(), Error> {
+        write_all_strict(
+            self,
+            buf,
+            |error| matches!(error, Error::EndOfWriter),
+            |written, requested| Error::Truncated { written, requested },
+        )
+    }
+
+    /// Writes all of `buf`, flushes the writer, and then reads the data back to check it.
+    ///
+    /// The data is read back through a [`Reader64K`], the same path used when loading. Each byte is
+    /// verified as it is programmed, so programming has completed by the time the data is read
+    /// back. If any byte does not match `buf`, [`Error::VerifyMismatch`] is returned with the
+    /// offset of the first mismatching byte. Otherwise, this behaves the same as
+    /// [`commit()`](Self::commit()).
+    pub fn write_verified(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let start = self.address;
+        self.commit(buf)?;
+        let mut reader = unsafe { Reader64K::new_unchecked(start, buf.len()) };
+        if let Some(bank) = self.bank {
+            reader = reader.in_bank(bank);
+        }
+        let mismatch = find_mismatch(&mut reader, buf);
+        match mismatch {
+            Some(index) => Err(Error::VerifyMismatch {
+                address: unsafe { start.offset_from(FLASH_MEMORY) as usize } + index,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Writer64K<'_> {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            start: self.stats.start,
+            position: unsafe { self.address.offset_from(FLASH_MEMORY) as usize },
+            end: self.stats.start + self.stats.len,
+        }
+    }
+}
+
+impl Debug for Writer64K<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().debug(formatter, "Flash::Writer64K")
+    }
+}
+
+impl Display for Writer64K<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().display(formatter, "Flash 64KiB")
+    }
 }
 
 impl ErrorType for Writer64K<'_> {
@@ -36,49 +225,89 @@ impl ErrorType for Writer64K<'_> {
 
 impl Write for Writer64K<'_> {
     fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return if self.len == 0 {
+                Err(Error::EndOfWriter)
+            } else {
+                Ok(0)
+            };
+        }
+        if let Some(bank) = self.bank {
+            // The other half of the device may have switched banks since the last write.
+            switch_bank(bank);
+        }
         let mut write_count = 0;
-        loop {
+        let mut budget = PollBudget::new(BATCH_TIMEOUT);
+        let result = loop {
             if write_count >= min(buf.len(), self.len) {
                 if self.len == 0 {
                     return Err(Error::EndOfWriter);
                 }
-                self.address = unsafe { self.address.add(write_count) };
-                self.len -= write_count;
-                return Ok(write_count);
+                break Ok(());
+            }
+            if write_count > 0 && write_count.is_multiple_of(BATCH_SIZE) {
+                budget = PollBudget::new(BATCH_TIMEOUT);
             }
 
             let address = unsafe { self.address.add(write_count) };
             let byte = unsafe { *buf.get_unchecked(write_count) };
-            send_command(Command::Write);
-            unsafe {
-                address.write_volatile(byte);
+            let offset = unsafe { address.offset_from(FLASH_MEMORY) as usize };
+            if let Err(error) = program_byte(address, byte, offset, self.device, &mut budget) {
+                break Err(error);
             }
-            verify_byte(address, byte, Duration::from_millis(20))?;
 
             write_count += 1;
+        };
+
+        // If some bytes were written before a failure, report them, leaving the failing byte as
+        // the first byte of the next write.
+        if let Err(error) = result {
+            if write_count == 0 {
+                self.stats.failures += 1;
+                return Err(error);
+            }
         }
+        self.address = unsafe { self.address.add(write_count) };
+        self.len -= write_count;
+        record_stat!(bytes_written, write_count);
+        self.stats.bytes_written += write_count;
+        Ok(write_count)
     }
 
     fn flush(&mut self) -> Result<(), Self::Error> {
+        #[cfg(feature = "log")]
+        self.stats.log_summary();
         Ok(())
     }
 }
 
 /// A writer on a 128KiB flash device.
 ///
-/// This type allows writing data on the range specified upon creation.
+/// This type allows writing data on the range specified upon creation. Writing an empty buffer
+/// returns `Ok(0)` without accessing the device, unless the writer is exhausted.
 ///
-/// If the memory being written to has been written to previously without being erased, the writes
-/// will not succeed.
+/// Bytes that already hold the value being written are not reprogrammed, so writing `0xff` to
+/// erased memory is cheap. If the memory being written to has been written to previously without
+/// being erased, the writes will fail with [`Error::NotErased`].
+#[must_use]
 pub struct Writer128K<'a> {
     address: *mut u8,
     len: usize,
     bank: Bank,
+    device: Device,
+    stats: WriteTracker,
     lifetime: PhantomData<&'a ()>,
 }
 
+assert_not_impl_any!(Writer128K<'static>: Send, Sync);
+
 impl Writer128K<'_> {
-    pub(crate) unsafe fn new_unchecked(address: *mut u8, len: usize) -> Self {
+    pub(crate) unsafe fn new_unchecked(address: *mut u8, len: usize, device: Device) -> Self {
+        debug_assert!(
+            address >= FLASH_MEMORY
+                && address as usize + len <= FLASH_MEMORY as usize + 2 * SIZE_64KB,
+            "range is out of bounds for a 128KiB flash device"
+        );
         let bank = if address < unsafe { FLASH_MEMORY.add(SIZE_64KB) } {
             Bank::_0
         } else {
@@ -90,9 +319,94 @@ impl Writer128K<'_> {
             address,
             len,
             bank,
+            device,
+            stats: WriteTracker::new(
+                "Flash 128KiB",
+                unsafe { address.offset_from(FLASH_MEMORY) as usize },
+                len,
+            ),
             lifetime: PhantomData,
         }
     }
+
+    /// Returns statistics about the writes performed by this writer.
+    pub fn stats(&self) -> WriteStats {
+        self.stats.snapshot()
+    }
+
+    /// Writes all of `buf` and then flushes the writer.
+    ///
+    /// If `buf` is larger than the space remaining in the writer, [`Error::BufferTooLarge`] is
+    /// returned before anything is written, so a save either fits entirely or is not started.
+    pub fn commit(&mut self, buf: &[u8]) -> Result<(), Error> {
+        if buf.len() > self.len {
+            return Err(Error::BufferTooLarge {
+                needed: buf.len(),
+                available: self.len,
+            });
+        }
+        write_all(self, buf, Error::EndOfWriter)?;
+        self.flush()
+    }
+
+    /// Writes all of `buf`, returning an error if it does not fit in the space remaining.
+    ///
+    /// [`write()`](Write::write()) writes as much of `buf` as fits and returns the number of bytes
+    /// written, which is easy to ignore by mistake. This instead returns [`Error::Truncated`] if
+    /// the writer runs out of space, after writing as much of `buf` as fit. To write nothing at all
+    /// when `buf` does not fit, use [`commit()`](Self::commit()).
+    pub fn write_all_strict(&mut self, buf: &[u8]) -> Result<(), Error> {
+        write_all_strict(
+            self,
+            buf,
+            |error| matches!(error, Error::EndOfWriter),
+            |written, requested| Error::Truncated { written, requested },
+        )
+    }
+
+    /// Writes all of `buf`, flushes the writer, and then reads the data back to check it.
+    ///
+    /// The data is read back through a [`Reader128K`], the same path used when loading. Each byte
+    /// is verified as it is programmed, so programming has completed by the time the data is read
+    /// back. If any byte does not match `buf`, [`Error::VerifyMismatch`] is returned with the
+    /// offset of the first mismatching byte. Otherwise, this behaves the same as
+    /// [`commit()`](Self::commit()).
+    pub fn write_verified(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let start = self.address;
+        self.commit(buf)?;
+        let mut reader = unsafe { Reader128K::new_unchecked(start, buf.len()) };
+        let mismatch = find_mismatch(&mut reader, buf);
+        // Reading may have switched banks.
+        switch_bank(self.bank);
+        match mismatch {
+            Some(index) => Err(Error::VerifyMismatch {
+                address: unsafe { start.offset_from(FLASH_MEMORY) as usize } + index,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Writer128K<'_> {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            start: self.stats.start,
+            position: unsafe { self.address.offset_from(FLASH_MEMORY) as usize },
+            end: self.stats.start + self.stats.len,
+        }
+    }
+}
+
+impl Debug for Writer128K<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().debug(formatter, "Flash::Writer128K")
+    }
+}
+
+impl Display for Writer128K<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().display(formatter, "Flash 128KiB")
+    }
 }
 
 impl ErrorType for Writer128K<'_> {
@@ -101,63 +415,134 @@ impl ErrorType for Writer128K<'_> {
 
 impl Write for Writer128K<'_> {
     fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return if self.len == 0 {
+                Err(Error::EndOfWriter)
+            } else {
+                Ok(0)
+            };
+        }
         let mut write_count = 0;
-        loop {
+        let mut budget = PollBudget::new(BATCH_TIMEOUT);
+        let result = loop {
             if write_count >= min(buf.len(), self.len) {
                 if self.len == 0 {
                     return Err(Error::EndOfWriter);
                 }
-                self.address = unsafe { self.address.add(write_count) };
-                self.len -= write_count;
-                return Ok(write_count);
+                break Ok(());
+            }
+            if write_count > 0 && write_count.is_multiple_of(BATCH_SIZE) {
+                budget = PollBudget::new(BATCH_TIMEOUT);
             }
 
             let mut address = unsafe { self.address.add(write_count) };
-            if matches!(self.bank, Bank::_0) {
-                if ptr::eq(address, unsafe { FLASH_MEMORY.add(SIZE_64KB) }) {
-                    self.bank = Bank::_1;
-                    switch_bank(self.bank);
-                }
+            let offset = unsafe { address.offset_from(FLASH_MEMORY) as usize };
+            if matches!(self.bank, Bank::_0)
+                && ptr::eq(address, unsafe { FLASH_MEMORY.add(SIZE_64KB) })
+            {
+                self.bank = Bank::_1;
+                switch_bank(self.bank);
             }
             if matches!(self.bank, Bank::_1) {
                 address = unsafe { address.sub(SIZE_64KB) };
             }
 
             let byte = unsafe { *buf.get_unchecked(write_count) };
-            send_command(Command::Write);
-            unsafe {
-                address.write_volatile(byte);
+            if let Err(error) = program_byte(address, byte, offset, self.device, &mut budget) {
+                break Err(error);
             }
-            verify_byte(address, byte, Duration::from_millis(20))?;
 
             write_count += 1;
+        };
+
+        // If some bytes were written before a failure, report them, leaving the failing byte as
+        // the first byte of the next write.
+        if let Err(error) = result {
+            if write_count == 0 {
+                self.stats.failures += 1;
+                return Err(error);
+            }
         }
+        self.address = unsafe { self.address.add(write_count) };
+        self.len -= write_count;
+        record_stat!(bytes_written, write_count);
+        self.stats.bytes_written += write_count;
+        Ok(write_count)
     }
 
     fn flush(&mut self) -> Result<(), Self::Error> {
+        #[cfg(feature = "log")]
+        self.stats.log_summary();
         Ok(())
     }
 }
 
+/// How a [`Writer64KAtmel`] fills the rest of a partially written page when flushing.
+///
+/// Atmel devices program a whole 128-byte page at once, so any bytes of the page after the last
+/// byte written must be supplied when the page is flushed.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum TailPolicy<'a> {
+    /// Reads the rest of the page back from the device, preserving its existing contents.
+    #[default]
+    PreserveExisting,
+    /// Fills the rest of the page with the given byte.
+    ///
+    /// Filling with `0xff` deliberately erases the rest of the page.
+    Fill(u8),
+    /// Fills the rest of the page with the given bytes.
+    ///
+    /// The first byte is placed directly after the last byte written. Any bytes beyond the end of
+    /// the slice are filled with `0xff`, and any bytes beyond the end of the page are ignored.
+    Caller(&'a [u8]),
+}
+
 /// A writer on a 64KiB Atmel flash device.
 ///
-/// This type allows writing data on the range specified upon creation.
-#[derive(Debug)]
+/// This type allows writing data on the range specified upon creation. Writing an empty buffer
+/// returns `Ok(0)` without accessing the device, unless the writer is exhausted.
+///
+/// Writes are buffered and programmed a 128-byte page at a time. Calling `flush()` programs the
+/// current page only if it has been written to since it was last programmed, so it can be called
+/// repeatedly without reprogramming the device. When a page is flushed partway through, the rest
+/// of it is filled according to the writer's [`TailPolicy`], which by default preserves the
+/// existing contents.
+///
+/// If programming a page fails partway through a call to `write()`, only the bytes of the pages
+/// programmed before it are reported as written, and the error is returned if there are none. The
+/// writer's position is left at the first unreported byte, so the rest of the data can be retried
+/// by writing it again, the same as with the other writers.
+///
+/// Dropping the writer flushes it. Any error from that flush is ignored, unless the `strict`
+/// feature is enabled, in which case it causes a panic. Call `flush()` explicitly to handle the
+/// error instead.
+#[must_use]
 pub struct Writer64KAtmel<'a> {
     address: *mut u8,
     len: usize,
     buf: [u8; 128],
     flushed: bool,
+    tail: TailPolicy<'a>,
+    stats: WriteTracker,
     lifetime: PhantomData<&'a ()>,
 }
 
-impl Writer64KAtmel<'_> {
+assert_not_impl_any!(Writer64KAtmel<'static>: Send, Sync);
+
+/// The size of a page programmed by a [`Writer64KAtmel`].
+const PAGE_SIZE: NonZeroUsize = NonZeroUsize::new(128).unwrap();
+
+impl<'a> Writer64KAtmel<'a> {
     pub(crate) unsafe fn new_unchecked(address: *mut u8, len: usize) -> Self {
+        debug_assert!(
+            address >= FLASH_MEMORY && address as usize + len <= FLASH_MEMORY as usize + SIZE_64KB,
+            "range is out of bounds for a 64KiB flash device"
+        );
         let mut buf = [0xff; 128];
-        let mut flushed = true;
 
-        // Read data in case of unalignment.
-        let offset = address.align_offset(128);
+        // Read the start of the page in case of unalignment. Nothing has been written yet, so the
+        // page does not need to be flushed until it is.
+        let offset = address as usize % 128;
         if offset != 0 {
             let mut reader = unsafe { Reader64K::new_unchecked(address.sub(offset), offset) };
             unsafe {
@@ -165,17 +550,162 @@ impl Writer64KAtmel<'_> {
                     .read_exact(buf.get_unchecked_mut(..offset))
                     .unwrap_unchecked()
             };
-            flushed = false;
         }
 
         Self {
             address,
             len,
             buf,
-            flushed,
+            flushed: true,
+            tail: TailPolicy::PreserveExisting,
+            stats: WriteTracker::new(
+                "Flash 64KiB (Atmel)",
+                unsafe { address.offset_from(FLASH_MEMORY) as usize },
+                len,
+            ),
             lifetime: PhantomData,
         }
     }
+
+    /// Returns statistics about the writes performed by this writer.
+    pub fn stats(&self) -> WriteStats {
+        self.stats.snapshot()
+    }
+
+    /// Sets how the rest of a partially written page is filled when flushing.
+    ///
+    /// By default, the rest of the page is read back from the device and preserved. Layouts that
+    /// always write whole pages can avoid that read, or can overwrite stale bytes rather than
+    /// preserving them, by choosing a different policy.
+    pub fn set_tail_fill(&mut self, tail: TailPolicy<'a>) {
+        self.tail = tail;
+    }
+
+    /// Returns whether flushing at the end of the writer's range will read the rest of the final
+    /// page back from the device.
+    ///
+    /// This is `false` when the range ends on a page boundary, or when the tail policy does not
+    /// preserve existing contents.
+    pub fn final_page_needs_read(&self) -> bool {
+        self.tail == TailPolicy::PreserveExisting
+            && !(self.address as usize + self.len).is_multiple_of(128)
+    }
+
+    /// Writes all of `buf` and then flushes the writer.
+    ///
+    /// If `buf` is larger than the space remaining in the writer, [`Error::BufferTooLarge`] is
+    /// returned before anything is written, so a save either fits entirely or is not started.
+    pub fn commit(&mut self, buf: &[u8]) -> Result<(), Error> {
+        if buf.len() > self.len {
+            return Err(Error::BufferTooLarge {
+                needed: buf.len(),
+                available: self.len,
+            });
+        }
+        write_all(self, buf, Error::EndOfWriter)?;
+        self.flush()
+    }
+
+    /// Wraps this writer in a [`Throttled`] writer that writes whole pages.
+    ///
+    /// Each call to `write()` on the returned writer ends on a page boundary and writes at most
+    /// `bytes_per_call` bytes, rounded down to whole pages, or a single page if `bytes_per_call` is
+    /// smaller than a page. This way each call programs at most the pages it is budgeted for.
+    pub fn throttled(self, bytes_per_call: NonZeroUsize) -> Throttled<Self> {
+        let offset = self.address as usize % 128;
+        Throttled::with_blocks(self, bytes_per_call, PAGE_SIZE, offset)
+    }
+
+    /// Writes all of `buf`, returning an error if it does not fit in the space remaining.
+    ///
+    /// [`write()`](Write::write()) writes as much of `buf` as fits and returns the number of bytes
+    /// written, which is easy to ignore by mistake. This instead returns [`Error::Truncated`] if
+    /// the writer runs out of space, after writing as much of `buf` as fit. To write nothing at all
+    /// when `buf` does not fit, use [`commit()`](Self::commit()).
+    pub fn write_all_strict(&mut self, buf: &[u8]) -> Result<(), Error> {
+        write_all_strict(
+            self,
+            buf,
+            |error| matches!(error, Error::EndOfWriter),
+            |written, requested| Error::Truncated { written, requested },
+        )
+    }
+
+    /// Writes all of `buf`, flushes the writer, and then reads the data back to check it.
+    ///
+    /// The data is read back through a [`Reader64K`], the same path used when loading. The final
+    /// page is programmed by the flush before the data is read back. If any byte does not match
+    /// `buf`, [`Error::VerifyMismatch`] is returned with the offset of the first mismatching byte.
+    /// Otherwise, this behaves the same as [`commit()`](Self::commit()).
+    pub fn write_verified(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let start = self.address;
+        self.commit(buf)?;
+        let mut reader = unsafe { Reader64K::new_unchecked(start, buf.len()) };
+        let mismatch = find_mismatch(&mut reader, buf);
+        match mismatch {
+            Some(index) => Err(Error::VerifyMismatch {
+                address: unsafe { start.offset_from(FLASH_MEMORY) as usize } + index,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn flush_page(&mut self) -> Result<(), Error> {
+        if self.flushed {
+            return Ok(());
+        }
+        // Fill any remaining bytes at the back of the buffer.
+        let offset = self.address as usize % 128;
+        if offset != 0 {
+            let tail = unsafe { self.buf.get_unchecked_mut(offset..) };
+            match self.tail {
+                TailPolicy::PreserveExisting => {
+                    let mut reader =
+                        unsafe { Reader64K::new_unchecked(self.address, 128 - offset) };
+                    unsafe { reader.read_exact(tail).unwrap_unchecked() };
+                }
+                TailPolicy::Fill(byte) => tail.fill(byte),
+                TailPolicy::Caller(bytes) => {
+                    let count = min(bytes.len(), tail.len());
+                    tail[..count].copy_from_slice(&bytes[..count]);
+                    tail[count..].fill(0xff);
+                }
+            }
+        }
+
+        let offset_address = unsafe { self.address.sub(if offset == 0 { 128 } else { offset }) };
+
+        program_page(
+            offset_address,
+            &self.buf,
+            &mut PollBudget::new(PROGRAM_TIMEOUT),
+        )?;
+
+        self.flushed = true;
+        Ok(())
+    }
+}
+
+impl Writer64KAtmel<'_> {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            start: self.stats.start,
+            position: unsafe { self.address.offset_from(FLASH_MEMORY) as usize },
+            end: self.stats.start + self.stats.len,
+        }
+    }
+}
+
+impl Debug for Writer64KAtmel<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().debug(formatter, "Flash::Writer64KAtmel")
+    }
+}
+
+impl Display for Writer64KAtmel<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().display(formatter, "Flash 64KiB (Atmel)")
+    }
 }
 
 impl ErrorType for Writer64KAtmel<'_> {
@@ -184,14 +714,21 @@ impl ErrorType for Writer64KAtmel<'_> {
 
 impl Write for Writer64KAtmel<'_> {
     fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return if self.len == 0 {
+                Err(Error::EndOfWriter)
+            } else {
+                Ok(0)
+            };
+        }
+        let start = self.address;
         let mut write_count = 0;
         loop {
             if write_count >= min(buf.len(), self.len) {
                 if self.len == 0 {
                     return Err(Error::EndOfWriter);
                 }
-                self.len -= write_count;
-                return Ok(write_count);
+                break;
             }
 
             unsafe {
@@ -202,61 +739,55 @@ impl Write for Writer64KAtmel<'_> {
 
             unsafe { self.address = self.address.add(1) };
 
-            if self.address as usize % 128 == 0 {
-                self.flush()?;
+            if (self.address as usize).is_multiple_of(128) {
+                if let Err(error) = self.flush_page() {
+                    // Only the pages programmed before the failed one are reported as written.
+                    // The position returns to the first byte of the failed page written by this
+                    // call, so retrying the rest of `buf` rewrites the page from there. Anything
+                    // buffered for the page by earlier calls is kept.
+                    let page = unsafe { self.address.sub(128) };
+                    self.address = if start > page { start } else { page };
+                    if (self.address as usize).is_multiple_of(128) {
+                        // Nothing written before this call is buffered for the failed page.
+                        self.flushed = true;
+                    }
+                    write_count = unsafe { self.address.offset_from(start) as usize };
+                    if write_count == 0 {
+                        self.stats.failures += 1;
+                        return Err(error);
+                    }
+                    break;
+                }
             }
 
             write_count += 1;
         }
+        self.len -= write_count;
+        record_stat!(bytes_written, write_count);
+        self.stats.bytes_written += write_count;
+        Ok(write_count)
     }
 
     fn flush(&mut self) -> Result<(), Self::Error> {
-        if self.flushed {
-            return Ok(());
-        }
-
-        // Read any remaining bytes at the back of the buffer.
-        let offset = self.address as usize % 128;
-        if offset != 0 {
-            let mut reader = unsafe { Reader64K::new_unchecked(self.address, 128 - offset) };
-            unsafe {
-                reader
-                    .read_exact(self.buf.get_unchecked_mut(offset..))
-                    .unwrap_unchecked()
-            };
-        }
-
-        let offset_address = unsafe { self.address.sub(if offset == 0 { 128 } else { offset }) };
-
-        // Disable interrupts, storing the previous value.
-        //
-        // This prevents anything from interrupting during writes to memory. GBATEK recommends
-        // disabling interrupts on writes to Atmel devices.
-        let previous_ime = unsafe { IME.read_volatile() };
-        // SAFETY: This is guaranteed to be a valid write.
-        unsafe { IME.write_volatile(false) };
-
-        send_command(Command::Write);
-        for (i, &byte) in self.buf.iter().enumerate() {
-            unsafe { offset_address.add(i).write_volatile(byte) };
-        }
-
-        // Restore previous interrupt enable value.
-        // SAFETY: This is guaranteed to be a valid write.
-        unsafe {
-            IME.write_volatile(previous_ime);
+        let result = self.flush_page();
+        if result.is_err() {
+            self.stats.failures += 1;
         }
-
-        verify_bytes(offset_address, &self.buf, Duration::from_millis(20))?;
-
-        self.flushed = true;
-        Ok(())
+        #[cfg(feature = "log")]
+        self.stats.log_summary();
+        result
     }
 }
 
 impl Drop for Writer64KAtmel<'_> {
     fn drop(&mut self) {
+        let result = self.flush();
+        #[cfg(feature = "strict")]
+        if let Err(error) = result {
+            panic!("failed to flush Atmel flash writer on drop: {error:?}");
+        }
         // This will swallow any errors.
-        let _ignored_result = self.flush();
+        #[cfg(not(feature = "strict"))]
+        let _ignored_result = result;
     }
 }
@@ -0,0 +1,111 @@
+//! Halting the CPU during long waits.
+//!
+//! Some flash operations, such as erasing sectors or entering and exiting ID mode, involve waits
+//! lasting up to tens of milliseconds. By default, the CPU spins while waiting. Creating a flash
+//! device with a [`Halt`] instead halts the CPU between polls of the device using the BIOS `Halt`
+//! function, which saves power.
+//!
+//! A halted CPU only resumes when an interrupt enabled in `IE` is requested, so the application
+//! must ensure such an interrupt occurs regularly, such as by enabling a timer interrupt. Note
+//! that `IME` does not need to be enabled for the CPU to resume.
+//!
+//! Only waits of at least a millisecond halt the CPU. Waiting for individual bytes to be
+//! programmed always spins, as those waits are much shorter.
+
+#[cfg(target_arch = "arm")]
+use core::arch::asm;
+use core::time::Duration;
+
+/// Configuration for halting the CPU during long waits.
+///
+/// See the [module documentation](self) for details.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Halt {
+    period: Duration,
+}
+
+impl Halt {
+    /// Creates a configuration that halts the CPU, assuming an interrupt occurs at least once
+    /// every `period`.
+    ///
+    /// Waits are measured by counting halts, so a wait of a given duration will halt the CPU that
+    /// duration divided by `period` times, rounded up.
+    ///
+    /// # Safety
+    /// An interrupt enabled in `IE` must be requested at least once every `period` for as long as
+    /// any device created with this configuration is in use. Otherwise, the CPU may halt
+    /// indefinitely.
+    pub unsafe fn new(period: Duration) -> Self {
+        Self { period }
+    }
+
+    /// Returns the maximum period between interrupts.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Returns the number of halts needed to wait at least `duration`.
+    pub(crate) fn count(self, duration: Duration) -> u128 {
+        duration
+            .as_micros()
+            .div_ceil(self.period.as_micros().max(1))
+    }
+
+    /// Halts the CPU until the next interrupt.
+    pub(crate) fn halt(self) {
+        // SAFETY: The BIOS `Halt` function only clobbers the argument registers.
+        #[cfg(target_arch = "arm")]
+        unsafe {
+            asm!(
+                "swi #0x02",
+                out("r0") _,
+                out("r1") _,
+                out("r2") _,
+                out("r3") _,
+                options(nostack, preserves_flags)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Halt;
+    use core::time::Duration;
+    use gba_test::test;
+
+    #[test]
+    fn period() {
+        let halt = unsafe { Halt::new(Duration::from_micros(500)) };
+
+        assert_eq!(halt.period(), Duration::from_micros(500));
+    }
+
+    #[test]
+    fn count_exact() {
+        let halt = unsafe { Halt::new(Duration::from_millis(1)) };
+
+        assert_eq!(halt.count(Duration::from_millis(20)), 20);
+    }
+
+    #[test]
+    fn count_rounds_up() {
+        let halt = unsafe { Halt::new(Duration::from_millis(3)) };
+
+        assert_eq!(halt.count(Duration::from_millis(20)), 7);
+    }
+
+    #[test]
+    fn count_zero_duration() {
+        let halt = unsafe { Halt::new(Duration::from_millis(1)) };
+
+        assert_eq!(halt.count(Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn count_zero_period() {
+        let halt = unsafe { Halt::new(Duration::ZERO) };
+
+        assert_eq!(halt.count(Duration::from_millis(1)), 1000);
+    }
+}
@@ -0,0 +1,38 @@
+use crate::error_kind::Class;
+use embedded_io::ErrorKind;
+
+/// An error returned when a reader does not have enough data remaining to fill a buffer.
+///
+/// This is returned by the `read_exact_into()` method on each reader, before any data is read.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct InsufficientData {
+    /// The number of bytes requested.
+    pub needed: usize,
+    /// The number of bytes remaining in the reader.
+    pub available: usize,
+}
+
+impl embedded_io::Error for InsufficientData {
+    fn kind(&self) -> ErrorKind {
+        Class::InvalidInput.kind()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InsufficientData;
+    use embedded_io::{Error as _, ErrorKind};
+    use gba_test::test;
+
+    #[test]
+    fn kind() {
+        assert_eq!(
+            InsufficientData {
+                needed: 43,
+                available: 42
+            }
+            .kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+}
@@ -4,10 +4,92 @@
 #![cfg_attr(test, test_runner(gba_test::runner))]
 #![cfg_attr(test, reexport_test_harness_main = "test_harness")]
 
+/// Records an amount to one of the statistics counters.
+///
+/// This compiles to nothing unless the `stats` feature is enabled.
+macro_rules! record_stat {
+    ($field:ident, $amount:expr) => {
+        #[cfg(feature = "stats")]
+        $crate::stats::record(|stats| stats.$field = stats.$field.wrapping_add($amount as u32));
+    };
+}
+
+pub mod adapters;
+#[cfg(feature = "agb")]
+pub mod agb;
+pub mod boot;
+pub mod crashdump;
+pub mod diagnostics;
 pub mod flash;
+pub mod halt;
+pub mod migrate;
+pub mod prelude;
+pub mod range;
+pub mod ring_log;
+pub mod seq;
+#[cfg(feature = "testing")]
+pub mod soak;
 pub mod sram;
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod waitstate;
 
+mod backend;
+mod backup;
+mod cursor;
+mod error;
+mod error_kind;
+mod insufficient_data;
 mod mmio;
+mod plan;
+mod progress;
+#[cfg(feature = "log")]
+mod read_log;
+mod report;
+#[cfg(test)]
+mod test_util;
+mod time;
+mod verify;
+mod write_all;
+mod write_stats;
+
+pub use backend::DefaultBackupError;
+#[cfg(any(
+    all(feature = "backend-sram", not(feature = "backend-flash-128k")),
+    all(feature = "backend-flash-128k", not(feature = "backend-sram")),
+))]
+pub use backend::{default_backup, DefaultBackup};
+pub use backup::{dump, load_all, restore, save_all, Backup, TransferError, TRANSFER_CHUNK_SIZE};
+pub use deranged;
+pub use error::Error;
+pub use insufficient_data::InsufficientData;
+pub use plan::{SaveBackend, SavePlan};
+pub use progress::Progress;
+pub use report::{report, BackupKind, BackupReport};
+pub use time::set_clock;
+pub use write_stats::WriteStats;
+
+/// An allocator that always fails.
+///
+/// `agb_save` depends on `alloc`, which requires a global allocator to be defined. None of this
+/// crate's tests allocate.
+#[cfg(all(test, feature = "agb"))]
+struct NoAllocator;
+
+#[cfg(all(test, feature = "agb"))]
+unsafe impl core::alloc::GlobalAlloc for NoAllocator {
+    unsafe fn alloc(&self, _layout: core::alloc::Layout) -> *mut u8 {
+        core::ptr::null_mut()
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: core::alloc::Layout) {}
+}
+
+#[cfg(all(test, feature = "agb"))]
+#[global_allocator]
+static ALLOCATOR: NoAllocator = NoAllocator;
 
 #[cfg(test)]
 #[no_mangle]
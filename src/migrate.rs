@@ -0,0 +1,336 @@
+//! Relocating data when a save layout changes.
+//!
+//! When an update changes where data is stored, the old layout can be converted to the new one in
+//! place using a table of [`Move`]s. Each move copies a range of bytes to a new offset through a
+//! small bounce buffer, so no more RAM is needed than for any other read or write. Moves are
+//! performed in order, and a move whose source and destination overlap is copied in whichever
+//! direction preserves its data.
+//!
+//! ``` no_run
+//! use gba_save::{migrate::{self, Move}, sram::Sram};
+//!
+//! const MOVES: [Move; 2] = [
+//!     // The inventory grew, so the world data moves later.
+//!     Move { from: 1024, to: 2048, len: 8192 },
+//!     Move { from: 64, to: 128, len: 960 },
+//! ];
+//!
+//! let mut sram = unsafe { Sram::new() };
+//! migrate::relocate(&mut sram, &MOVES).expect("could not migrate save");
+//! ```
+//!
+//! A move table can be checked without touching any data using [`validate()`].
+
+use crate::{
+    range::SramRegion,
+    sram::{self, Sram},
+};
+use embedded_io::{Read, Write};
+
+/// The size of the buffer used to copy data.
+const BOUNCE_SIZE: usize = 32;
+
+/// A relocation of a range of bytes.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Move {
+    /// The offset the bytes are moved from.
+    pub from: usize,
+    /// The offset the bytes are moved to.
+    pub to: usize,
+    /// The number of bytes moved.
+    pub len: usize,
+}
+
+/// An error that can occur while migrating data.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Error {
+    /// A move's source or destination extends beyond the end of the backup memory.
+    OutOfBounds {
+        /// The index of the move.
+        index: usize,
+    },
+
+    /// Two moves write to overlapping destinations.
+    DestinationsOverlap {
+        /// The index of the earlier move.
+        first: usize,
+        /// The index of the later move.
+        second: usize,
+    },
+
+    /// A move's source is overwritten by an earlier move before it is read.
+    SourceOverwritten {
+        /// The index of the move whose source is overwritten.
+        index: usize,
+        /// The index of the earlier move that overwrites it.
+        by: usize,
+    },
+
+    /// Writing to SRAM failed.
+    Sram(sram::Error),
+}
+
+fn overlaps(a_start: usize, a_len: usize, b_start: usize, b_len: usize) -> bool {
+    a_len != 0 && b_len != 0 && a_start < b_start + b_len && b_start < a_start + a_len
+}
+
+/// Checks that a table of moves can be performed on a device with the given capacity.
+///
+/// Every source and destination must lie within the device, no two destinations may overlap,
+/// and no move may overwrite the source of a later move. No data is read or written.
+pub fn validate(moves: &[Move], capacity: usize) -> Result<(), Error> {
+    for (index, m) in moves.iter().enumerate() {
+        let in_bounds = |start: usize| start.checked_add(m.len).is_some_and(|end| end <= capacity);
+        if !in_bounds(m.from) || !in_bounds(m.to) {
+            return Err(Error::OutOfBounds { index });
+        }
+    }
+    for (first, earlier) in moves.iter().enumerate() {
+        for (second, later) in moves.iter().enumerate().skip(first + 1) {
+            if overlaps(earlier.to, earlier.len, later.to, later.len) {
+                return Err(Error::DestinationsOverlap { first, second });
+            }
+            if overlaps(earlier.to, earlier.len, later.from, later.len) {
+                return Err(Error::SourceOverwritten {
+                    index: second,
+                    by: first,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copies `len` bytes from offset `from` to offset `to` through `bounce`.
+fn copy_chunk(
+    sram: &mut Sram,
+    from: usize,
+    to: usize,
+    len: usize,
+    bounce: &mut [u8; BOUNCE_SIZE],
+) -> Result<(), Error> {
+    let Ok(_) = sram
+        .reader(SramRegion::new(from, len))
+        .read(&mut bounce[..len]);
+    sram.writer(SramRegion::new(to, len))
+        .write_all(&bounce[..len])
+        .map_err(Error::Sram)
+}
+
+/// Performs a table of moves on SRAM.
+///
+/// The table is first checked using [`validate()`], and nothing is moved if it is invalid. Moves
+/// are then performed in order.
+pub fn relocate(sram: &mut Sram, moves: &[Move]) -> Result<(), Error> {
+    validate(moves, sram.capacity())?;
+
+    let mut bounce = [0; BOUNCE_SIZE];
+    for m in moves {
+        if m.to <= m.from {
+            // Copy forward, so the start of the source is read before it is overwritten.
+            let mut start = 0;
+            while start < m.len {
+                let len = BOUNCE_SIZE.min(m.len - start);
+                copy_chunk(sram, m.from + start, m.to + start, len, &mut bounce)?;
+                start += len;
+            }
+        } else {
+            // Copy backward, so the end of the source is read before it is overwritten.
+            let mut end = m.len;
+            while end > 0 {
+                let len = BOUNCE_SIZE.min(end);
+                end -= len;
+                copy_chunk(sram, m.from + end, m.to + end, len, &mut bounce)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{relocate, validate, Error, Move};
+    use crate::sram::Sram;
+    use claims::{assert_err_eq, assert_ok};
+    use deranged::RangedUsize;
+    use embedded_io::{Read, Write};
+    use gba_test::test;
+
+    #[test]
+    fn validate_ok() {
+        assert_ok!(validate(
+            &[
+                Move {
+                    from: 1024,
+                    to: 2048,
+                    len: 8192
+                },
+                Move {
+                    from: 64,
+                    to: 128,
+                    len: 960
+                },
+            ],
+            32768
+        ));
+    }
+
+    #[test]
+    fn validate_out_of_bounds_source() {
+        assert_err_eq!(
+            validate(
+                &[Move {
+                    from: 32700,
+                    to: 0,
+                    len: 100
+                }],
+                32768
+            ),
+            Error::OutOfBounds { index: 0 }
+        );
+    }
+
+    #[test]
+    fn validate_out_of_bounds_destination() {
+        assert_err_eq!(
+            validate(
+                &[
+                    Move {
+                        from: 0,
+                        to: 100,
+                        len: 10
+                    },
+                    Move {
+                        from: 0,
+                        to: usize::MAX,
+                        len: 10
+                    }
+                ],
+                32768
+            ),
+            Error::OutOfBounds { index: 1 }
+        );
+    }
+
+    #[test]
+    fn validate_destinations_overlap() {
+        assert_err_eq!(
+            validate(
+                &[
+                    Move {
+                        from: 0,
+                        to: 100,
+                        len: 10
+                    },
+                    Move {
+                        from: 200,
+                        to: 105,
+                        len: 10
+                    }
+                ],
+                32768
+            ),
+            Error::DestinationsOverlap {
+                first: 0,
+                second: 1
+            }
+        );
+    }
+
+    #[test]
+    fn validate_source_overwritten() {
+        assert_err_eq!(
+            validate(
+                &[
+                    Move {
+                        from: 64,
+                        to: 128,
+                        len: 960
+                    },
+                    Move {
+                        from: 1024,
+                        to: 2048,
+                        len: 8192
+                    }
+                ],
+                32768
+            ),
+            Error::SourceOverwritten { index: 1, by: 0 }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn relocate_overlapping_forward() {
+        let mut sram = unsafe { Sram::new() };
+        let mut data = [0; 100];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let mut writer = sram.writer(RangedUsize::new_static::<50>()..);
+        assert_ok!(writer.write_all(&data));
+
+        assert_ok!(relocate(
+            &mut sram,
+            &[Move {
+                from: 50,
+                to: 10,
+                len: 100
+            }]
+        ));
+
+        let mut reader = sram.reader(RangedUsize::new_static::<10>()..);
+        let mut buf = [0; 100];
+        assert_ok!(reader.read_exact(&mut buf));
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn relocate_overlapping_backward() {
+        let mut sram = unsafe { Sram::new() };
+        let mut data = [0; 100];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let mut writer = sram.writer(RangedUsize::new_static::<10>()..);
+        assert_ok!(writer.write_all(&data));
+
+        assert_ok!(relocate(
+            &mut sram,
+            &[Move {
+                from: 10,
+                to: 50,
+                len: 100
+            }]
+        ));
+
+        let mut reader = sram.reader(RangedUsize::new_static::<50>()..);
+        let mut buf = [0; 100];
+        assert_ok!(reader.read_exact(&mut buf));
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    fn relocate_invalid() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            relocate(
+                &mut sram,
+                &[Move {
+                    from: 0,
+                    to: 32768,
+                    len: 1
+                }]
+            ),
+            Error::OutOfBounds { index: 0 }
+        );
+    }
+}
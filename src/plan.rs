@@ -0,0 +1,234 @@
+use crate::flash::{DeviceInfo, SECTOR_SIZE};
+use core::time::Duration;
+
+/// The time taken to write a single byte to SRAM, including the overhead of the write loop.
+const SRAM_BYTE_WRITE: Duration = Duration::from_micros(1);
+
+/// The type of backup device a [`SavePlan`] was made for.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SaveBackend {
+    /// SRAM.
+    Sram,
+    /// 64KiB flash memory.
+    Flash64K,
+    /// 64KiB Atmel flash memory.
+    Flash64KAtmel,
+    /// 128KiB flash memory.
+    Flash128K,
+}
+
+/// A description of the work a save will perform.
+///
+/// This is created by `plan_write()` on each type of backup device, and passed to that device's
+/// `estimate_duration()` to estimate how long the save will take before starting it. This allows
+/// deciding, for example, whether to show a progress bar or to simply block for a frame.
+///
+/// # Example
+/// ``` no_run
+/// use deranged::RangedUsize;
+/// use gba_save::flash::Flash;
+///
+/// if let Ok(Flash::Flash64K(flash_64k)) = unsafe { Flash::new() } {
+///     let range = RangedUsize::new_static::<0>()..RangedUsize::new_static::<8192>();
+///     let plan = flash_64k.plan_write(range, 8192);
+///     if flash_64k.estimate_duration(&plan).as_millis() > 16 {
+///         // Show a progress bar.
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SavePlan {
+    /// The type of backup device the save is for.
+    pub backend: SaveBackend,
+    /// The number of bytes to be written.
+    pub bytes: usize,
+    /// The number of erase blocks that must be erased before writing.
+    ///
+    /// This is always zero for SRAM. Atmel devices erase each 128-byte page as part of programming
+    /// it, so for those this is the number of pages that will be programmed.
+    pub erases: u32,
+}
+
+impl SavePlan {
+    /// Creates a plan for writing `bytes` bytes to SRAM.
+    pub(crate) fn sram(bytes: usize) -> Self {
+        Self {
+            backend: SaveBackend::Sram,
+            bytes,
+            erases: 0,
+        }
+    }
+
+    /// Creates a plan for writing `bytes` bytes at `offset` to a flash device erased a sector at a
+    /// time.
+    ///
+    /// `programmed` is called with the start and end offsets of the part of each covered sector
+    /// that will be written, and returns whether any of those bytes are not erased. Only such
+    /// sectors must be erased.
+    pub(crate) fn flash<F>(
+        backend: SaveBackend,
+        offset: usize,
+        bytes: usize,
+        mut programmed: F,
+    ) -> Self
+    where
+        F: FnMut(usize, usize) -> bool,
+    {
+        let end = offset + bytes;
+        let erases = (offset / SECTOR_SIZE..end.div_ceil(SECTOR_SIZE))
+            .filter(|sector| {
+                let start = offset.max(sector * SECTOR_SIZE);
+                let end = end.min((sector + 1) * SECTOR_SIZE);
+                programmed(start, end)
+            })
+            .count() as u32;
+        Self {
+            backend,
+            bytes,
+            erases,
+        }
+    }
+
+    /// Creates a plan for writing `bytes` bytes at `offset` to an Atmel device.
+    pub(crate) fn atmel(offset: usize, bytes: usize, page_size: usize) -> Self {
+        let pages = if bytes == 0 {
+            0
+        } else {
+            (offset + bytes).div_ceil(page_size) - offset / page_size
+        };
+        Self {
+            backend: SaveBackend::Flash64KAtmel,
+            bytes,
+            erases: pages as u32,
+        }
+    }
+
+    /// Estimates how long the plan takes on SRAM.
+    pub(crate) fn sram_duration(&self) -> Duration {
+        SRAM_BYTE_WRITE.saturating_mul(self.bytes as u32)
+    }
+
+    /// Estimates how long the plan takes on a flash device erased a sector at a time, using the
+    /// timings in `info`.
+    pub(crate) fn flash_duration(&self, info: DeviceInfo) -> Duration {
+        info.byte_program_time
+            .saturating_mul(self.bytes as u32)
+            .saturating_add(info.sector_erase_time.saturating_mul(self.erases))
+    }
+
+    /// Estimates how long the plan takes on an Atmel device, using the timings in `info`.
+    pub(crate) fn atmel_duration(&self, info: DeviceInfo) -> Duration {
+        // Each page is erased and programmed at once.
+        info.byte_program_time.saturating_mul(self.erases)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SaveBackend, SavePlan};
+    use crate::flash::typical::{BYTE_PROGRAM, PAGE_PROGRAM, SECTOR_ERASE};
+    use crate::flash::DeviceInfo;
+    use core::time::Duration;
+    use gba_test::test;
+
+    const INFO: DeviceInfo = DeviceInfo {
+        name: "test",
+        id: 0,
+        id_swapped: false,
+        erase_block_size: 4096,
+        erase_blocks: 16,
+        endurance: 100_000,
+        byte_program_time: BYTE_PROGRAM,
+        sector_erase_time: SECTOR_ERASE,
+        chip_erase_time: Duration::from_millis(3000),
+    };
+
+    #[test]
+    fn flash_erases_only_programmed_sectors() {
+        // Covers the end of sector 0, all of sector 1, and the start of sector 2.
+        let plan = SavePlan::flash(SaveBackend::Flash64K, 4000, 5000, |start, _| start == 4096);
+
+        assert_eq!(
+            plan,
+            SavePlan {
+                backend: SaveBackend::Flash64K,
+                bytes: 5000,
+                erases: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn flash_checks_covered_part_of_each_sector() {
+        let mut ranges = [(0, 0); 3];
+        let mut count = 0;
+
+        SavePlan::flash(SaveBackend::Flash64K, 4000, 5000, |start, end| {
+            ranges[count] = (start, end);
+            count += 1;
+            false
+        });
+
+        assert_eq!(count, 3);
+        assert_eq!(ranges, [(4000, 4096), (4096, 8192), (8192, 9000)]);
+    }
+
+    #[test]
+    fn flash_empty() {
+        let plan = SavePlan::flash(SaveBackend::Flash128K, 4000, 0, |_, _| true);
+
+        assert_eq!(plan.erases, 0);
+    }
+
+    #[test]
+    fn atmel_pages() {
+        // Spans a page boundary.
+        assert_eq!(SavePlan::atmel(120, 13, 128).erases, 2);
+        assert_eq!(SavePlan::atmel(128, 128, 128).erases, 1);
+        assert_eq!(SavePlan::atmel(128, 0, 128).erases, 0);
+    }
+
+    #[test]
+    fn sram_duration() {
+        assert_eq!(
+            SavePlan::sram(2000).sram_duration(),
+            Duration::from_millis(2)
+        );
+    }
+
+    #[test]
+    fn flash_duration() {
+        let plan = SavePlan {
+            backend: SaveBackend::Flash64K,
+            bytes: 100,
+            erases: 2,
+        };
+
+        assert_eq!(
+            plan.flash_duration(INFO),
+            Duration::from_micros(3000) + Duration::from_millis(2000)
+        );
+    }
+
+    #[test]
+    fn atmel_duration() {
+        let plan = SavePlan::atmel(0, 1024, 128);
+
+        assert_eq!(
+            plan.atmel_duration(DeviceInfo {
+                byte_program_time: PAGE_PROGRAM,
+                ..INFO
+            }),
+            Duration::from_millis(80)
+        );
+    }
+
+    #[test]
+    fn flash_slot_rewrite_slower_than_sram_save() {
+        let sram = SavePlan::sram(2000).sram_duration();
+        let flash =
+            SavePlan::flash(SaveBackend::Flash64K, 0, 8192, |_, _| true).flash_duration(INFO);
+
+        assert!(flash > sram * 100);
+    }
+}
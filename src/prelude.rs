@@ -0,0 +1,40 @@
+//! Commonly used traits and types.
+//!
+//! Importing everything from this module brings into scope the [`Read`] and [`Write`] traits
+//! needed to use this crate's readers and writers, the range types used to specify their ranges,
+//! and the backup device types.
+//!
+//! ``` no_run
+//! use gba_save::prelude::*;
+//!
+//! let mut sram = unsafe { Sram::new() };
+//! let mut writer = sram.writer(..RangedUsize::new_static::<13>());
+//! writer.write_all_strict(b"hello, world!").expect("could not write to SRAM");
+//! ```
+
+pub use crate::{
+    flash::{Flash, Flash128K, Flash64K, Flash64KAtmel, Flash64KReadOnly, FlashBank64K},
+    range::{
+        FlashAddress128K, FlashAddress64K, FlashSector128K, FlashSector64K, RangedU8, RangedUsize,
+        SramAddress,
+    },
+    sram::{Sram, SramPart, SramReadOnly},
+    Progress,
+};
+pub use embedded_io::{Read, Write};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::assert_ok_eq;
+    use gba_test::test;
+
+    #[test]
+    fn traits_in_scope() {
+        let mut reader: &[u8] = b"hello";
+        let mut buf = [0; 5];
+
+        assert_ok_eq!(reader.read(&mut buf), 5);
+        assert_eq!(RangedUsize::<0, 32767>::new_static::<5>().get(), buf.len());
+    }
+}
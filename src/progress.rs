@@ -0,0 +1,64 @@
+/// Progress of a long-running operation.
+///
+/// This is reported to the callbacks passed to the `_with_progress` variants of slow operations,
+/// such as [`Flash128K::erase_sectors_with_progress()`]. Callbacks are always invoked between
+/// device operations with interrupts enabled, so it is safe to perform rendering work within
+/// them.
+///
+/// [`Flash128K::erase_sectors_with_progress()`]: crate::flash::Flash128K::erase_sectors_with_progress()
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Progress {
+    /// The number of units of work that have been completed.
+    pub completed: usize,
+    /// The total number of units of work in the operation.
+    pub total: usize,
+}
+
+impl Progress {
+    /// Returns whether the operation has completed.
+    pub fn is_complete(&self) -> bool {
+        self.completed >= self.total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Progress;
+    use gba_test::test;
+
+    #[test]
+    fn is_complete_not_started() {
+        assert!(!Progress {
+            completed: 0,
+            total: 16
+        }
+        .is_complete());
+    }
+
+    #[test]
+    fn is_complete_partial() {
+        assert!(!Progress {
+            completed: 15,
+            total: 16
+        }
+        .is_complete());
+    }
+
+    #[test]
+    fn is_complete_finished() {
+        assert!(Progress {
+            completed: 16,
+            total: 16
+        }
+        .is_complete());
+    }
+
+    #[test]
+    fn is_complete_empty() {
+        assert!(Progress {
+            completed: 0,
+            total: 0
+        }
+        .is_complete());
+    }
+}
@@ -0,0 +1,628 @@
+//! Address and sector types used to specify ranges of backup memory.
+//!
+//! Readers, writers, and erase operations take ranges of bounded integers, ensuring at compile
+//! time that the ranges lie within the backup memory. The aliases defined here name the bounded
+//! integer type for each kind of backup memory. The underlying [`deranged`] types are also
+//! re-exported, so downstream crates do not need to depend on a matching version of `deranged`.
+//!
+//! ``` no_run
+//! use gba_save::{range::SramAddress, sram::Sram};
+//!
+//! const SETTINGS: core::ops::Range<SramAddress> =
+//!     SramAddress::new_static::<0>()..SramAddress::new_static::<64>();
+//!
+//! let sram = unsafe { Sram::new() };
+//! let reader = sram.reader(SETTINGS);
+//! ```
+//!
+//! Ranges computed at runtime can be converted using [`bounded_range()`], or passed directly to
+//! the `try_reader()` and `try_writer()` methods on each device.
+//!
+//! ``` no_run
+//! use gba_save::sram::Sram;
+//!
+//! # fn f(offset: usize, len: usize) -> Result<(), gba_save::range::RangeError> {
+//! let sram = unsafe { Sram::new() };
+//! let reader = sram.try_reader(offset..offset + len)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Layouts can also be described with [`Region`]s, which are built by `const fn`s and can be
+//! checked for overlap at compile time. A [`Region`] is accepted anywhere a range of addresses is.
+//!
+//! ``` no_run
+//! use gba_save::{range::SramRegion, sram::Sram};
+//!
+//! const SETTINGS: SramRegion = SramRegion::new(0, 64);
+//! const INVENTORY: SramRegion = SramRegion::new(64, 960);
+//! const _: () = assert!(!SETTINGS.overlaps(INVENTORY));
+//!
+//! let sram = unsafe { Sram::new() };
+//! let reader = sram.reader(SETTINGS);
+//! ```
+//!
+//! The [`save_layout!`] macro defines a set of named regions at once, checking at compile time
+//! that none of them overlap.
+//!
+//! [`save_layout!`]: crate::save_layout
+
+pub use deranged::{RangedU16, RangedU8, RangedUsize};
+
+use core::ops::{self, Bound, RangeBounds};
+
+/// An address within SRAM.
+pub type SramAddress = RangedUsize<0, 32767>;
+
+/// An address within a 64KiB flash device.
+pub type FlashAddress64K = RangedUsize<0, 65535>;
+
+/// An address within a 128KiB flash device.
+pub type FlashAddress128K = RangedUsize<0, 131071>;
+
+/// A sector index within a 64KiB flash device.
+pub type FlashSector64K = RangedU8<0, 15>;
+
+/// A sector index within a 128KiB flash device.
+pub type FlashSector128K = RangedU8<0, 31>;
+
+/// A 128-byte page index within a 64KiB Atmel flash device.
+pub type FlashPage64KAtmel = RangedU16<0, 511>;
+
+/// A region of SRAM.
+pub type SramRegion = Region<32767>;
+
+/// A region of a 64KiB flash device.
+pub type FlashRegion64K = Region<65535>;
+
+/// A region of a 128KiB flash device.
+pub type FlashRegion128K = Region<131071>;
+
+/// A pair of bounds over bounded addresses.
+///
+/// This is returned by [`bounded_range()`], and can be used anywhere a range of addresses is
+/// accepted.
+pub type Bounds<const MAX: usize> = (Bound<RangedUsize<0, MAX>>, Bound<RangedUsize<0, MAX>>);
+
+/// An error converting a runtime range into a range of bounded addresses.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RangeError {
+    /// A bound of the range is beyond the end of the backup memory.
+    OutOfBounds {
+        /// The offending bound.
+        value: usize,
+        /// The largest permitted exclusive end bound, equal to the size of the backup memory.
+        max: usize,
+    },
+
+    /// The start of the range is after its end.
+    Inverted {
+        /// The start of the range.
+        start: usize,
+        /// The end of the range.
+        end: usize,
+    },
+
+    /// Two regions in a list of regions overlap.
+    Overlapping {
+        /// The index of the first overlapping region.
+        first: usize,
+        /// The index of the second overlapping region.
+        second: usize,
+    },
+}
+
+/// A contiguous region of backup memory with addresses in `0..=MAX`.
+///
+/// Unlike ranges of bounded addresses, a `Region` can be constructed in `const` contexts from
+/// plain offsets. Constructing a region that does not lie within the backup memory panics, which
+/// is a compile error when done in a `const` item.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Region<const MAX: usize> {
+    offset: usize,
+    len: usize,
+}
+
+impl<const MAX: usize> Region<MAX> {
+    /// Creates a region of `len` bytes starting at `offset`.
+    ///
+    /// # Panics
+    /// Panics if the region extends beyond the end of the backup memory.
+    pub const fn new(offset: usize, len: usize) -> Self {
+        assert!(
+            offset <= MAX + 1 && len <= MAX + 1 - offset,
+            "region extends beyond the end of the backup memory"
+        );
+        Self { offset, len }
+    }
+
+    /// Creates a region covering the offsets `start..end`.
+    ///
+    /// # Panics
+    /// Panics if `start` is after `end`, or if the region extends beyond the end of the backup
+    /// memory.
+    pub const fn from_offsets(start: usize, end: usize) -> Self {
+        assert!(start <= end, "region start is after its end");
+        Self::new(start, end - start)
+    }
+
+    /// Creates a region covering the entire backup memory.
+    pub const fn full() -> Self {
+        Self {
+            offset: 0,
+            len: MAX + 1,
+        }
+    }
+
+    /// Creates a region covering the given range of bounded addresses.
+    ///
+    /// A range whose start is after its end, such as `10..5`, creates an empty region at its
+    /// start. Unlike [`from_offsets()`](Self::from_offsets()), this never panics, as it is used
+    /// when creating every reader and writer.
+    pub fn from_range<Range>(range: Range) -> Self
+    where
+        Range: RangeBounds<RangedUsize<0, MAX>>,
+    {
+        let offset = match range.start_bound() {
+            Bound::Included(start) => start.get(),
+            Bound::Excluded(start) => start.get() + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.get() + 1,
+            Bound::Excluded(end) => end.get(),
+            Bound::Unbounded => MAX + 1,
+        };
+        Self {
+            offset,
+            len: end.saturating_sub(offset),
+        }
+    }
+
+    /// Returns the offset of the start of the region.
+    pub const fn offset(self) -> usize {
+        self.offset
+    }
+
+    /// Returns the number of bytes in the region.
+    pub const fn len(self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the region contains no bytes.
+    pub const fn is_empty(self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the offset one past the end of the region.
+    pub const fn end(self) -> usize {
+        self.offset + self.len
+    }
+
+    /// Returns whether this region shares any bytes with `other`.
+    pub const fn overlaps(self, other: Self) -> bool {
+        !self.is_empty()
+            && !other.is_empty()
+            && self.offset < other.end()
+            && other.offset < self.end()
+    }
+
+    /// Returns whether no two of the given regions overlap.
+    pub const fn are_disjoint(regions: &[Self]) -> bool {
+        let mut i = 0;
+        while i < regions.len() {
+            let mut j = i + 1;
+            while j < regions.len() {
+                if regions[i].overlaps(regions[j]) {
+                    return false;
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        true
+    }
+}
+
+/// Defines a set of named, non-overlapping regions of backup memory.
+///
+/// The region type for the backup memory is given first, followed by a list of named ranges of
+/// offsets. Each name becomes a constant of the region type, which can be passed directly to
+/// `reader()` and `writer()`.
+///
+/// ``` no_run
+/// use gba_save::{range::SramRegion, save_layout, sram::Sram};
+///
+/// save_layout! {
+///     SramRegion {
+///         pub SETTINGS: 0..64,
+///         pub INVENTORY: 64..1024,
+///         pub WORLD: 1024..32768,
+///     }
+/// }
+///
+/// let sram = unsafe { Sram::new() };
+/// let reader = sram.reader(INVENTORY);
+/// ```
+///
+/// It is a compile error for a region to extend beyond the end of the backup memory, or for any two
+/// regions to overlap.
+#[macro_export]
+macro_rules! save_layout {
+    ($region:ty { $($vis:vis $name:ident: $start:tt..$end:tt),* $(,)? }) => {
+        $(
+            $vis const $name: $region = <$region>::from_offsets($start, $end);
+        )*
+        const _: () = assert!(
+            <$region>::are_disjoint(&[$($name),*]),
+            "save layout regions overlap"
+        );
+    };
+}
+
+/// A type that describes a region of backup memory with addresses in `0..=MAX`.
+///
+/// This is implemented for both [`Region`] and ranges of bounded addresses, and is accepted by
+/// every method that creates a reader or writer.
+pub trait IntoRegion<const MAX: usize> {
+    /// Converts into a [`Region`].
+    fn into_region(self) -> Region<MAX>;
+}
+
+impl<const MAX: usize> IntoRegion<MAX> for Region<MAX> {
+    fn into_region(self) -> Region<MAX> {
+        self
+    }
+}
+
+impl<const MAX: usize, Range> IntoRegion<MAX> for Range
+where
+    Range: RangeBounds<RangedUsize<0, MAX>>,
+{
+    fn into_region(self) -> Region<MAX> {
+        Region::from_range(self)
+    }
+}
+
+/// Checks that no two of the given regions overlap.
+pub(crate) fn check_disjoint<const MAX: usize>(regions: &[Region<MAX>]) -> Result<(), RangeError> {
+    for (first, region) in regions.iter().enumerate() {
+        if let Some(second) = regions[first + 1..]
+            .iter()
+            .position(|other| region.overlaps(*other))
+        {
+            return Err(RangeError::Overlapping {
+                first,
+                second: first + 1 + second,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Converts a range of offsets into bounds over addresses in `0..=MAX`.
+///
+/// The end of `range` may be `MAX + 1`, indicating that the range extends to the end of the
+/// backup memory.
+pub fn bounded_range<const MAX: usize>(
+    range: ops::Range<usize>,
+) -> Result<Bounds<MAX>, RangeError> {
+    if range.start > range.end {
+        return Err(RangeError::Inverted {
+            start: range.start,
+            end: range.end,
+        });
+    }
+    let out_of_bounds = |value| RangeError::OutOfBounds {
+        value,
+        max: MAX + 1,
+    };
+    if range.end > MAX + 1 {
+        return Err(out_of_bounds(range.end));
+    }
+
+    if range.start == MAX + 1 {
+        // The range is empty and located at the very end of the backup memory.
+        let max = RangedUsize::new_static::<MAX>();
+        return Ok((Bound::Excluded(max), Bound::Included(max)));
+    }
+    let start = RangedUsize::new(range.start).ok_or(out_of_bounds(range.start))?;
+    let end = match RangedUsize::new(range.end) {
+        Some(end) => Bound::Excluded(end),
+        None => Bound::Unbounded,
+    };
+    Ok((Bound::Included(start), end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bounded_range, check_disjoint, RangeError, RangedUsize, Region, SramRegion};
+    use crate::test_util::hash;
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use core::ops::Bound;
+    use gba_test::test;
+
+    #[test]
+    fn range_error_copy_eq_hash() {
+        let error = RangeError::OutOfBounds {
+            value: 32769,
+            max: 32768,
+        };
+        let copy = error;
+
+        assert_eq!(copy, error);
+        assert_eq!(hash(&copy), hash(&error));
+        assert_ne!(
+            hash(&error),
+            hash(&RangeError::Inverted {
+                start: 32769,
+                end: 32768
+            })
+        );
+    }
+
+    #[test]
+    fn bounded_range_empty() {
+        assert_ok_eq!(
+            bounded_range::<32767>(42..42),
+            (
+                Bound::Included(RangedUsize::new_static::<42>()),
+                Bound::Excluded(RangedUsize::new_static::<42>())
+            )
+        );
+    }
+
+    #[test]
+    fn bounded_range_partial() {
+        assert_ok_eq!(
+            bounded_range::<32767>(42..100),
+            (
+                Bound::Included(RangedUsize::new_static::<42>()),
+                Bound::Excluded(RangedUsize::new_static::<100>())
+            )
+        );
+    }
+
+    #[test]
+    fn bounded_range_full() {
+        assert_ok_eq!(
+            bounded_range::<32767>(0..32768),
+            (
+                Bound::Included(RangedUsize::new_static::<0>()),
+                Bound::Unbounded
+            )
+        );
+    }
+
+    #[test]
+    fn bounded_range_last_byte() {
+        assert_ok_eq!(
+            bounded_range::<32767>(32767..32768),
+            (
+                Bound::Included(RangedUsize::new_static::<32767>()),
+                Bound::Unbounded
+            )
+        );
+    }
+
+    #[test]
+    fn bounded_range_empty_at_end() {
+        assert_ok_eq!(
+            bounded_range::<32767>(32768..32768),
+            (
+                Bound::Excluded(RangedUsize::new_static::<32767>()),
+                Bound::Included(RangedUsize::new_static::<32767>())
+            )
+        );
+    }
+
+    #[test]
+    fn bounded_range_end_out_of_bounds() {
+        assert_err_eq!(
+            bounded_range::<32767>(0..32769),
+            RangeError::OutOfBounds {
+                value: 32769,
+                max: 32768
+            }
+        );
+    }
+
+    #[test]
+    fn bounded_range_start_out_of_bounds() {
+        assert_err_eq!(
+            bounded_range::<32767>(40000..50000),
+            RangeError::OutOfBounds {
+                value: 50000,
+                max: 32768
+            }
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn bounded_range_inverted() {
+        assert_err_eq!(
+            bounded_range::<32767>(100..42),
+            RangeError::Inverted {
+                start: 100,
+                end: 42
+            }
+        );
+    }
+
+    #[test]
+    fn region_from_range_inclusive_max() {
+        let region = Region::<32767>::from_range(..=RangedUsize::new_static::<32767>());
+        assert_eq!(region.offset(), 0);
+        assert_eq!(region.len(), 32768);
+    }
+
+    #[test]
+    fn region_from_range_last_byte() {
+        let region = Region::<131071>::from_range(
+            RangedUsize::new_static::<131071>()..=RangedUsize::new_static::<131071>(),
+        );
+        assert_eq!(region.offset(), 131071);
+        assert_eq!(region.len(), 1);
+        assert_eq!(region.end(), 131072);
+    }
+
+    #[test]
+    fn region_from_range_last_byte_unbounded() {
+        let region = Region::<65535>::from_range(RangedUsize::new_static::<65535>()..);
+        assert_eq!(region.offset(), 65535);
+        assert_eq!(region.len(), 1);
+    }
+
+    #[test]
+    fn region_new() {
+        let region = SramRegion::new(42, 100);
+        assert_eq!(region.offset(), 42);
+        assert_eq!(region.len(), 100);
+        assert_eq!(region.end(), 142);
+    }
+
+    #[test]
+    fn region_new_at_end() {
+        assert!(SramRegion::new(32768, 0).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "region extends beyond the end of the backup memory")]
+    fn region_new_out_of_bounds() {
+        SramRegion::new(32760, 9);
+    }
+
+    #[test]
+    fn region_from_offsets() {
+        assert_eq!(SramRegion::from_offsets(42, 100), SramRegion::new(42, 58));
+    }
+
+    #[test]
+    #[should_panic(expected = "region start is after its end")]
+    fn region_from_offsets_inverted() {
+        SramRegion::from_offsets(100, 42);
+    }
+
+    #[test]
+    fn region_full() {
+        assert_eq!(SramRegion::full(), SramRegion::new(0, 32768));
+    }
+
+    #[test]
+    fn region_from_range() {
+        assert_eq!(
+            Region::<32767>::from_range(
+                RangedUsize::new_static::<42>()..=RangedUsize::new_static::<100>()
+            ),
+            SramRegion::new(42, 59)
+        );
+    }
+
+    #[test]
+    fn region_from_range_inverted() {
+        let region = Region::<32767>::from_range(
+            RangedUsize::new_static::<10>()..RangedUsize::new_static::<5>(),
+        );
+        assert_eq!(region.offset(), 10);
+        assert!(region.is_empty());
+    }
+
+    #[test]
+    fn region_from_range_inverted_inclusive() {
+        assert!(Region::<32767>::from_range(
+            RangedUsize::new_static::<10>()..=RangedUsize::new_static::<8>()
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn region_from_range_unbounded() {
+        assert_eq!(Region::<32767>::from_range(..), SramRegion::full());
+    }
+
+    #[test]
+    fn region_overlaps() {
+        assert!(SramRegion::new(0, 64).overlaps(SramRegion::new(63, 1)));
+    }
+
+    #[test]
+    fn region_overlaps_adjacent() {
+        assert!(!SramRegion::new(0, 64).overlaps(SramRegion::new(64, 64)));
+    }
+
+    #[test]
+    fn region_overlaps_empty() {
+        assert!(!SramRegion::new(0, 64).overlaps(SramRegion::new(32, 0)));
+    }
+
+    #[test]
+    fn region_are_disjoint() {
+        assert!(SramRegion::are_disjoint(&[
+            SramRegion::new(0, 64),
+            SramRegion::new(64, 960),
+            SramRegion::new(1024, 31744),
+        ]));
+    }
+
+    #[test]
+    fn region_are_disjoint_overlapping() {
+        assert!(!SramRegion::are_disjoint(&[
+            SramRegion::new(0, 64),
+            SramRegion::new(1024, 31744),
+            SramRegion::new(63, 1),
+        ]));
+    }
+
+    #[test]
+    fn region_are_disjoint_empty() {
+        assert!(SramRegion::are_disjoint(&[]));
+    }
+
+    #[test]
+    fn check_disjoint_ok() {
+        assert_ok!(check_disjoint(&[
+            SramRegion::new(64, 960),
+            SramRegion::new(0, 64),
+        ]));
+    }
+
+    #[test]
+    fn check_disjoint_overlapping() {
+        assert_err_eq!(
+            check_disjoint(&[
+                SramRegion::new(0, 64),
+                SramRegion::new(1024, 31744),
+                SramRegion::new(2048, 1),
+            ]),
+            RangeError::Overlapping {
+                first: 1,
+                second: 2
+            }
+        );
+    }
+
+    #[test]
+    fn save_layout() {
+        save_layout! {
+            SramRegion {
+                SETTINGS: 0..64,
+                INVENTORY: 64..1024,
+                WORLD: 1024..32768,
+            }
+        }
+
+        assert_eq!(SETTINGS, SramRegion::new(0, 64));
+        assert_eq!(INVENTORY, SramRegion::new(64, 960));
+        assert_eq!(WORLD, SramRegion::new(1024, 31744));
+    }
+
+    #[test]
+    fn region_in_const() {
+        const SETTINGS: SramRegion = SramRegion::new(0, 64);
+        const INVENTORY: SramRegion = SramRegion::from_offsets(64, 1024);
+        const _: () = assert!(!SETTINGS.overlaps(INVENTORY));
+
+        assert_eq!(INVENTORY.len(), 960);
+    }
+}
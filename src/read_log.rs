@@ -0,0 +1,92 @@
+//! Logging of reads, enabled by the `log` feature.
+
+use crate::time::Instant;
+
+/// The state needed to log a reader's completion.
+#[derive(Debug)]
+pub(crate) struct ReadLog {
+    device: &'static str,
+    start: usize,
+    len: usize,
+    started: Option<Instant>,
+}
+
+impl ReadLog {
+    /// Logs the creation of a reader over `len` bytes at offset `start` of the named device.
+    pub(crate) fn new(device: &'static str, start: usize, len: usize) -> Self {
+        log::debug!("{} read {}..{} started", device, start, start + len);
+        Self {
+            device,
+            start,
+            len,
+            started: Instant::now(),
+        }
+    }
+
+    /// Logs the completion of the reader, once all of its bytes have been read.
+    pub(crate) fn finish(&self) {
+        match self.started.and_then(Instant::elapsed) {
+            Some(elapsed) => log::debug!(
+                "{} read {}..{}: {} bytes read in {:?}",
+                self.device,
+                self.start,
+                self.start + self.len,
+                self.len,
+                elapsed,
+            ),
+            None => log::debug!(
+                "{} read {}..{}: {} bytes read",
+                self.device,
+                self.start,
+                self.start + self.len,
+                self.len,
+            ),
+        }
+    }
+}
+
+/// The log of a reader, held only by the reader that was originally created.
+///
+/// Cloning a reader does not clone its log, so a read is logged as complete once, when the
+/// original reader is exhausted, no matter how many times the reader is cloned.
+#[derive(Debug, Default)]
+pub(crate) struct ReadLogSlot(Option<ReadLog>);
+
+impl ReadLogSlot {
+    /// Holds `log` until the reader is exhausted.
+    pub(crate) fn new(log: ReadLog) -> Self {
+        Self(Some(log))
+    }
+
+    /// Logs the completion of the reader, if it is the original and has not already done so.
+    pub(crate) fn finish(&mut self) {
+        if let Some(log) = self.0.take() {
+            log.finish();
+        }
+    }
+}
+
+impl Clone for ReadLogSlot {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReadLog, ReadLogSlot};
+    use gba_test::test;
+
+    #[test]
+    fn slot_not_cloned() {
+        let slot = ReadLogSlot::new(ReadLog {
+            device: "SRAM",
+            start: 0,
+            len: 42,
+            started: None,
+        });
+
+        assert!(slot.0.is_some());
+        assert!(slot.clone().0.is_none());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{
+    flash::{self, Flash},
+    sram::Sram,
+    waitstate,
+};
+use core::fmt::{self, Display, Formatter};
+
+/// The start of the backup memory region.
+const BACKUP_MEMORY: *mut u8 = 0x0e00_0000 as *mut u8;
+/// The offsets written to by the flash ID mode command sequence.
+const COMMAND_OFFSETS: [usize; 2] = [0x5555, 0x2aaa];
+
+/// The type of backup memory found by [`report()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BackupKind {
+    /// No backup memory was detected.
+    None,
+    /// SRAM.
+    Sram {
+        /// Whether SRAM is mirrored across the upper 32KiB of the SRAM address space.
+        mirrored: bool,
+    },
+    /// 64KiB flash memory.
+    Flash64K,
+    /// 64KiB Atmel flash memory.
+    Flash64KAtmel,
+    /// 128KiB flash memory.
+    Flash128K,
+}
+
+/// A summary of the cartridge's backup memory, for inclusion in bug reports.
+///
+/// This is created by [`report()`]. Its [`Display`] implementation fits on the GBA's screen using
+/// an 8x8 font, with no line longer than 30 characters.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BackupReport {
+    /// The type of backup memory found.
+    pub kind: BackupKind,
+    /// The ID reported by the flash device, if one was found.
+    pub flash_id: Option<u16>,
+    /// The manufacturer and part number of the flash device, if one was found.
+    pub flash_chip: Option<&'static str>,
+    /// The value of WAITCNT when the report was created.
+    pub waitcnt: u16,
+}
+
+impl Display for BackupReport {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self.kind {
+            BackupKind::None => writeln!(formatter, "Backup: none detected")?,
+            BackupKind::Sram { mirrored } => {
+                writeln!(formatter, "Backup: SRAM")?;
+                writeln!(
+                    formatter,
+                    "Mirrored: {}",
+                    if mirrored { "yes" } else { "no" }
+                )?;
+            }
+            BackupKind::Flash64K | BackupKind::Flash64KAtmel => {
+                writeln!(formatter, "Backup: Flash 64KiB")?
+            }
+            BackupKind::Flash128K => writeln!(formatter, "Backup: Flash 128KiB")?,
+        }
+        if let Some(chip) = self.flash_chip {
+            writeln!(formatter, "Chip: {chip}")?;
+        }
+        if let Some(id) = self.flash_id {
+            writeln!(formatter, "ID: {id:#06x}")?;
+        }
+        write!(formatter, "WAITCNT: {:#06x}", self.waitcnt)
+    }
+}
+
+/// Detects the cartridge's backup memory, returning a summary for inclusion in bug reports.
+///
+/// Flash is probed first by reading the device ID. If no known flash device is found, SRAM is
+/// probed by writing to its first byte and reading it back. The probes are non-destructive: any
+/// SRAM bytes they overwrite are restored afterward, as is WAITCNT's SRAM wait control setting.
+///
+/// # Safety
+/// Must have exclusive ownership of both backup memory and WAITCNT for the duration of the call.
+/// No backup device accessor may be in use.
+pub unsafe fn report() -> BackupReport {
+    let waitstate_control = waitstate::read();
+    unsafe { waitstate::set_backup_waitstate_8() };
+
+    // The flash ID mode command sequence overwrites these bytes on SRAM carts.
+    let scratch =
+        COMMAND_OFFSETS.map(|offset| unsafe { BACKUP_MEMORY.add(offset).read_volatile() });
+
+    let report = match unsafe { Flash::new() } {
+        Ok(flash) => BackupReport {
+            kind: match flash {
+                Flash::Flash64K(_) => BackupKind::Flash64K,
+                Flash::Flash64KAtmel(_) => BackupKind::Flash64KAtmel,
+                Flash::Flash128K(_) => BackupKind::Flash128K,
+            },
+            flash_id: Some(flash.device_id()),
+            flash_chip: Some(flash.info().name),
+            waitcnt: waitstate_control.to_bits(),
+        },
+        Err(_) => {
+            unsafe { flash::raw::exit_id_mode() };
+            for (offset, byte) in COMMAND_OFFSETS.into_iter().zip(scratch) {
+                unsafe { BACKUP_MEMORY.add(offset).write_volatile(byte) };
+            }
+
+            let mut sram = unsafe { Sram::new() };
+            BackupReport {
+                kind: match sram.verify_present() {
+                    Ok(()) => BackupKind::Sram {
+                        mirrored: sram.detect_mirroring().unwrap_or(false),
+                    },
+                    Err(_) => BackupKind::None,
+                },
+                flash_id: None,
+                flash_chip: None,
+                waitcnt: waitstate_control.to_bits(),
+            }
+        }
+    };
+
+    // Only restore the bits that were modified, in case other bits were changed in the meantime.
+    unsafe {
+        waitstate::modify(|current| {
+            current.set_backup_waitstate(waitstate_control.backup_waitstate())
+        })
+    };
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{report, BackupKind, BackupReport, BACKUP_MEMORY};
+    use crate::{
+        test_util::Buffer,
+        waitstate::{self, WaitstateControl},
+    };
+    use core::fmt::Write;
+    use gba_test::test;
+
+    #[test]
+    fn display_none() {
+        let mut buffer = Buffer::<64>::new();
+        write!(
+            buffer,
+            "{}",
+            BackupReport {
+                kind: BackupKind::None,
+                flash_id: None,
+                flash_chip: None,
+                waitcnt: 0x4317,
+            }
+        )
+        .unwrap();
+
+        assert_eq!(buffer.as_str(), "Backup: none detected\nWAITCNT: 0x4317");
+    }
+
+    #[test]
+    fn display_sram() {
+        let mut buffer = Buffer::<64>::new();
+        write!(
+            buffer,
+            "{}",
+            BackupReport {
+                kind: BackupKind::Sram { mirrored: true },
+                flash_id: None,
+                flash_chip: None,
+                waitcnt: 0x4317,
+            }
+        )
+        .unwrap();
+
+        assert_eq!(
+            buffer.as_str(),
+            "Backup: SRAM\nMirrored: yes\nWAITCNT: 0x4317"
+        );
+    }
+
+    #[test]
+    fn display_flash_fits_screen() {
+        let mut buffer = Buffer::<128>::new();
+        write!(
+            buffer,
+            "{}",
+            BackupReport {
+                kind: BackupKind::Flash128K,
+                flash_id: Some(0x1362),
+                flash_chip: Some("Sanyo LE26FV10N1TS"),
+                waitcnt: 0x4317,
+            }
+        )
+        .unwrap();
+
+        assert_eq!(
+            buffer.as_str(),
+            "Backup: Flash 128KiB\nChip: Sanyo LE26FV10N1TS\nID: 0x1362\nWAITCNT: 0x4317"
+        );
+        assert!(buffer.as_str().lines().all(|line| line.len() <= 30));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn report_sram() {
+        unsafe { waitstate::write(WaitstateControl::from_bits(0x4317)) };
+        let scratch =
+            [0x5555, 0x2aaa].map(|offset| unsafe { BACKUP_MEMORY.add(offset).read_volatile() });
+
+        let report = unsafe { report() };
+
+        assert!(matches!(report.kind, BackupKind::Sram { .. }));
+        assert_eq!(report.flash_id, None);
+        assert_eq!(report.waitcnt, 0x4317);
+        assert_eq!(waitstate::read(), WaitstateControl::from_bits(0x4317));
+        assert_eq!(
+            [0x5555, 0x2aaa].map(|offset| unsafe { BACKUP_MEMORY.add(offset).read_volatile() }),
+            scratch
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn report_flash_64k() {
+        unsafe { waitstate::write(WaitstateControl::from_bits(0x4317)) };
+
+        let report = unsafe { report() };
+
+        assert_eq!(report.kind, BackupKind::Flash64K);
+        assert!(report.flash_id.is_some());
+        assert!(report.flash_chip.is_some());
+        assert_eq!(waitstate::read(), WaitstateControl::from_bits(0x4317));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn report_flash_128k() {
+        let report = unsafe { report() };
+
+        assert_eq!(report.kind, BackupKind::Flash128K);
+        assert!(report.flash_id.is_some());
+    }
+}
@@ -0,0 +1,407 @@
+//! A rolling log kept in SRAM.
+//!
+//! [`RingLog`] stores bytes in a fixed region of SRAM, overwriting the oldest bytes once the region
+//! is full. This is suited to keeping the most recent debug messages in the save file, so that
+//! save states sent by testers include recent events. Combined with
+//! [`FmtWriter`](crate::adapters::FmtWriter), formatted messages can be logged directly:
+//!
+//! ``` no_run
+//! use core::fmt::Write as _;
+//! use gba_save::{adapters::FmtWriter, range::SramRegion, ring_log::RingLog, sram::Sram};
+//!
+//! const LOG: SramRegion = SramRegion::new(31744, 1024);
+//!
+//! let mut sram = unsafe { Sram::new() };
+//! let mut log = FmtWriter::new(RingLog::open(&mut sram, LOG));
+//! writeln!(log, "entered level {}", 3).expect("could not log");
+//! ```
+//!
+//! The log does not track message boundaries; messages should be separated, such as by newlines,
+//! by the application.
+//!
+//! # Layout
+//! The first 8 bytes of the region hold two 4-byte header slots, each recording the position at
+//! which the next byte will be written. The rest of the region holds the logged bytes.
+//!
+//! Each write stores its bytes first and then records the new position in the older of the two
+//! slots, along with a sequence number and a check byte. If power is lost while a slot is being
+//! written, the slot fails its check and the position recorded in the other slot is used instead.
+//! The log therefore always reopens in a consistent state, at worst losing the bytes of the
+//! interrupted write and any bytes it had already overwritten.
+
+use crate::{
+    range::SramRegion,
+    sram::{Error, Reader, Sram},
+    write_all::write_all,
+};
+use core::{
+    cmp::min,
+    fmt::{self, Debug, Formatter},
+    ops,
+};
+use embedded_io::{ErrorType, Read, Write};
+
+/// The length of a single header slot.
+const SLOT_LEN: usize = 4;
+/// The length of both header slots.
+const HEADER_LEN: usize = 2 * SLOT_LEN;
+/// Set in a recorded position once the log has wrapped around.
+const WRAPPED: u16 = 0x8000;
+/// Mixed into the check byte, so that slots of all zeros or all ones are invalid.
+const CHECK: u8 = 0xa5;
+
+/// A position recorded in a header slot.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Slot {
+    sequence: u8,
+    head: usize,
+    wrapped: bool,
+}
+
+impl Slot {
+    fn to_bytes(self) -> [u8; SLOT_LEN] {
+        let [low, high] = (self.head as u16 | if self.wrapped { WRAPPED } else { 0 }).to_le_bytes();
+        [self.sequence, low, high, self.sequence ^ low ^ high ^ CHECK]
+    }
+
+    /// Parses a slot, returning `None` if it is torn or its position is beyond `capacity`.
+    fn from_bytes([sequence, low, high, check]: [u8; SLOT_LEN], capacity: usize) -> Option<Self> {
+        if check != sequence ^ low ^ high ^ CHECK {
+            return None;
+        }
+        let position = u16::from_le_bytes([low, high]);
+        let head = (position & !WRAPPED) as usize;
+        (head < capacity).then_some(Self {
+            sequence,
+            head,
+            wrapped: position & WRAPPED != 0,
+        })
+    }
+}
+
+/// A rolling log stored in a region of SRAM.
+///
+/// Bytes are written through [`embedded_io::Write`]. Once the region is full, the oldest bytes are
+/// overwritten. The position of the log is persisted after every write, so the log can be
+/// reopened with [`open()`](Self::open()) after a reset. See the [module documentation](self) for
+/// details.
+pub struct RingLog<'a> {
+    sram: &'a mut Sram,
+    region: SramRegion,
+    slot: Slot,
+}
+
+impl<'a> RingLog<'a> {
+    /// Opens the log stored in `region`.
+    ///
+    /// If `region` does not contain a valid log, such as when it has never been written, an empty
+    /// log is opened. Nothing is written until the log is first written to.
+    ///
+    /// # Panics
+    /// Panics if `region` is not larger than the log's 8-byte header.
+    pub fn open(sram: &'a mut Sram, region: SramRegion) -> Self {
+        assert!(
+            region.len() > HEADER_LEN,
+            "region is too small to hold a ring log"
+        );
+        let capacity = region.len() - HEADER_LEN;
+        let mut header = [0; HEADER_LEN];
+        let Ok(_) = sram
+            .reader(SramRegion::new(region.offset(), HEADER_LEN))
+            .read(&mut header);
+        let [a0, a1, a2, a3, b0, b1, b2, b3] = header;
+        let slot = match (
+            Slot::from_bytes([a0, a1, a2, a3], capacity),
+            Slot::from_bytes([b0, b1, b2, b3], capacity),
+        ) {
+            (Some(a), Some(b)) if b.sequence == a.sequence.wrapping_add(1) => b,
+            (Some(a), _) => a,
+            (None, Some(b)) => b,
+            // Start the sequence so that the first write goes to the first slot.
+            (None, None) => Slot {
+                sequence: u8::MAX,
+                head: 0,
+                wrapped: false,
+            },
+        };
+        Self { sram, region, slot }
+    }
+
+    /// Returns the number of bytes the log can hold.
+    pub fn capacity(&self) -> usize {
+        self.region.len() - HEADER_LEN
+    }
+
+    /// Returns the number of bytes currently in the log.
+    pub fn len(&self) -> usize {
+        if self.slot.wrapped {
+            self.capacity()
+        } else {
+            self.slot.head
+        }
+    }
+
+    /// Returns whether the log contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns readers over the contents of the log, from oldest to newest.
+    ///
+    /// The contents are split in two where the log wraps around. Reading the first reader and then
+    /// the second yields the bytes in the order they were written.
+    pub fn readers(&self) -> (Reader<'_>, Reader<'_>) {
+        let (first, second) = if self.slot.wrapped {
+            (self.slot.head..self.capacity(), 0..self.slot.head)
+        } else {
+            (0..self.slot.head, 0..0)
+        };
+        (
+            self.sram.reader(self.data_region(first)),
+            self.sram.reader(self.data_region(second)),
+        )
+    }
+
+    /// Empties the log.
+    pub fn clear(&mut self) -> Result<(), Error> {
+        self.commit(0, false)
+    }
+
+    fn data_region(&self, range: ops::Range<usize>) -> SramRegion {
+        SramRegion::new(self.region.offset() + HEADER_LEN + range.start, range.len())
+    }
+
+    /// Records a new position in the older header slot.
+    fn commit(&mut self, head: usize, wrapped: bool) -> Result<(), Error> {
+        let slot = Slot {
+            sequence: self.slot.sequence.wrapping_add(1),
+            head,
+            wrapped,
+        };
+        let offset = self.region.offset() + (slot.sequence as usize % 2) * SLOT_LEN;
+        write_all(
+            &mut self.sram.writer(SramRegion::new(offset, SLOT_LEN)),
+            &slot.to_bytes(),
+            Error::EndOfWriter,
+        )?;
+        self.slot = slot;
+        Ok(())
+    }
+}
+
+impl Debug for RingLog<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("RingLog")
+            .field("region", &self.region)
+            .field("head", &self.slot.head)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl ErrorType for RingLog<'_> {
+    type Error = Error;
+}
+
+impl Write for RingLog<'_> {
+    /// Appends all of `buf` to the log, overwriting the oldest bytes if needed.
+    ///
+    /// If `buf` is larger than the log, only its last [`capacity()`](RingLog::capacity()) bytes
+    /// are kept.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let capacity = self.capacity();
+        let kept = buf.get(buf.len().saturating_sub(capacity)..).unwrap_or(&[]);
+        let head = self.slot.head;
+        let (before_end, after_wrap) = kept.split_at(min(kept.len(), capacity - head));
+        write_all(
+            &mut self
+                .sram
+                .writer(self.data_region(head..head + before_end.len())),
+            before_end,
+            Error::EndOfWriter,
+        )?;
+        write_all(
+            &mut self.sram.writer(self.data_region(0..after_wrap.len())),
+            after_wrap,
+            Error::EndOfWriter,
+        )?;
+
+        let end = head + kept.len();
+        self.commit(end % capacity, self.slot.wrapped || end >= capacity)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RingLog, Slot};
+    use crate::{range::SramRegion, sram::Sram};
+    use claims::{assert_none, assert_ok, assert_some_eq};
+    use embedded_io::{Read, Write};
+    use gba_test::test;
+
+    const REGION: SramRegion = SramRegion::new(32000, 24);
+
+    /// Reads the whole contents of `log` into `buf`.
+    fn contents<'buf>(log: &RingLog, buf: &'buf mut [u8]) -> &'buf [u8] {
+        let (mut first, mut second) = log.readers();
+        let len = log.len();
+        let Ok(first_len) = first.read(&mut buf[..len]);
+        let Ok(_) = second.read(&mut buf[first_len..len]);
+        &buf[..len]
+    }
+
+    fn reset(sram: &mut Sram) {
+        assert_ok!(sram.writer(REGION).write_all(&[0xff; 24]));
+    }
+
+    #[test]
+    fn slot_round_trip() {
+        let slot = Slot {
+            sequence: 7,
+            head: 12,
+            wrapped: true,
+        };
+
+        assert_some_eq!(Slot::from_bytes(slot.to_bytes(), 16), slot);
+    }
+
+    #[test]
+    fn slot_torn() {
+        let mut bytes = Slot {
+            sequence: 7,
+            head: 12,
+            wrapped: false,
+        }
+        .to_bytes();
+        bytes[2] ^= 1;
+
+        assert_none!(Slot::from_bytes(bytes, 16));
+    }
+
+    #[test]
+    fn slot_erased() {
+        assert_none!(Slot::from_bytes([0xff; 4], 16));
+        assert_none!(Slot::from_bytes([0; 4], 16));
+    }
+
+    #[test]
+    fn slot_beyond_capacity() {
+        let slot = Slot {
+            sequence: 0,
+            head: 16,
+            wrapped: false,
+        };
+
+        assert_none!(Slot::from_bytes(slot.to_bytes(), 16));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn write_and_reopen() {
+        let mut sram = unsafe { Sram::new() };
+        reset(&mut sram);
+        {
+            let mut log = RingLog::open(&mut sram, REGION);
+            assert!(log.is_empty());
+            assert_ok!(log.write_all(b"hello\n"));
+        }
+
+        let log = RingLog::open(&mut sram, REGION);
+        let mut buf = [0; 16];
+
+        assert_eq!(contents(&log, &mut buf), b"hello\n");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn wrap_around() {
+        let mut sram = unsafe { Sram::new() };
+        reset(&mut sram);
+        {
+            let mut log = RingLog::open(&mut sram, REGION);
+            assert_ok!(log.write_all(b"0123456789"));
+            assert_ok!(log.write_all(b"abcdefghij"));
+        }
+
+        let log = RingLog::open(&mut sram, REGION);
+        let mut buf = [0; 16];
+
+        assert_eq!(log.len(), 16);
+        assert_eq!(contents(&log, &mut buf), b"456789abcdefghij");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn write_larger_than_capacity() {
+        let mut sram = unsafe { Sram::new() };
+        reset(&mut sram);
+        let mut log = RingLog::open(&mut sram, REGION);
+        assert_ok!(log.write_all(b"abc"));
+
+        assert_ok!(log.write_all(b"0123456789abcdefghij"));
+
+        let mut buf = [0; 16];
+        assert_eq!(contents(&log, &mut buf), b"456789abcdefghij");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn torn_header_falls_back() {
+        let mut sram = unsafe { Sram::new() };
+        reset(&mut sram);
+        {
+            let mut log = RingLog::open(&mut sram, REGION);
+            assert_ok!(log.write_all(b"first\n"));
+            assert_ok!(log.write_all(b"second\n"));
+        }
+        // The second write recorded its position in the second slot. Tear it.
+        assert_ok!(sram
+            .writer(SramRegion::new(REGION.offset() + 6, 1))
+            .write_all(&[0]));
+
+        let log = RingLog::open(&mut sram, REGION);
+        let mut buf = [0; 16];
+
+        assert_eq!(contents(&log, &mut buf), b"first\n");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn clear() {
+        let mut sram = unsafe { Sram::new() };
+        reset(&mut sram);
+        {
+            let mut log = RingLog::open(&mut sram, REGION);
+            assert_ok!(log.write_all(b"hello\n"));
+            assert_ok!(log.clear());
+        }
+
+        let log = RingLog::open(&mut sram, REGION);
+
+        assert!(log.is_empty());
+    }
+}
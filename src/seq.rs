@@ -0,0 +1,173 @@
+//! Wrapping sequence numbers.
+//!
+//! A [`SeqNum`] is a 32-bit counter that is incremented each time data is saved. Comparing two
+//! sequence numbers determines which was saved more recently, even after the counter wraps
+//! around. This is useful for choosing the newest of several save slots.
+//!
+//! ``` no_run
+//! use gba_save::seq::SeqNum;
+//!
+//! let slot_a = SeqNum(u32::MAX);
+//! let slot_b = slot_a.next();
+//!
+//! assert!(slot_b.is_newer_than(slot_a));
+//! ```
+//!
+//! Comparison follows the serial number arithmetic of RFC 1982: a sequence number is newer than
+//! another if it is less than 2<sup>31</sup> steps ahead of it. Two sequence numbers exactly
+//! 2<sup>31</sup> apart are not comparable, and neither is newer than the other.
+
+use embedded_io::{Read, ReadExactError, Write};
+
+/// A wrapping sequence number.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SeqNum(pub u32);
+
+impl SeqNum {
+    /// The number of bytes used to store a sequence number.
+    pub const SIZE: usize = 4;
+
+    /// Returns the following sequence number, wrapping around on overflow.
+    pub fn next(self) -> Self {
+        Self(self.0.wrapping_add(1))
+    }
+
+    /// Returns whether this sequence number is newer than `other`.
+    ///
+    /// This is true if `self` is less than 2<sup>31</sup> steps ahead of `other`, accounting for
+    /// wrap-around.
+    pub fn is_newer_than(self, other: Self) -> bool {
+        let distance = self.0.wrapping_sub(other.0);
+        distance != 0 && distance < 1 << 31
+    }
+
+    /// Returns the newest of the given sequence numbers, if any are given.
+    ///
+    /// If the sequence numbers are spread across more than half of the sequence space, "newest" is
+    /// not well defined, and the result depends on the order of the sequence numbers.
+    pub fn newest<I>(seq_nums: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        seq_nums.into_iter().reduce(|newest, seq_num| {
+            if seq_num.is_newer_than(newest) {
+                seq_num
+            } else {
+                newest
+            }
+        })
+    }
+
+    /// Reads a sequence number stored as little-endian bytes.
+    pub fn read_from<R>(reader: &mut R) -> Result<Self, ReadExactError<R::Error>>
+    where
+        R: Read,
+    {
+        let mut bytes = [0; Self::SIZE];
+        reader.read_exact(&mut bytes)?;
+        Ok(Self(u32::from_le_bytes(bytes)))
+    }
+
+    /// Writes the sequence number as little-endian bytes.
+    pub fn write_to<W>(self, writer: &mut W) -> Result<(), W::Error>
+    where
+        W: Write,
+    {
+        writer.write_all(&self.0.to_le_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeqNum;
+    use claims::{assert_err_eq, assert_none, assert_ok, assert_ok_eq, assert_some_eq};
+    use embedded_io::ReadExactError;
+    use gba_test::test;
+
+    #[test]
+    fn next() {
+        assert_eq!(SeqNum(41).next(), SeqNum(42));
+    }
+
+    #[test]
+    fn next_wraps() {
+        assert_eq!(SeqNum(u32::MAX).next(), SeqNum(0));
+    }
+
+    #[test]
+    fn is_newer_than() {
+        assert!(SeqNum(42).is_newer_than(SeqNum(41)));
+    }
+
+    #[test]
+    fn is_newer_than_older() {
+        assert!(!SeqNum(41).is_newer_than(SeqNum(42)));
+    }
+
+    #[test]
+    fn is_newer_than_equal() {
+        assert!(!SeqNum(42).is_newer_than(SeqNum(42)));
+    }
+
+    #[test]
+    fn is_newer_than_wrapped() {
+        assert!(SeqNum(0).is_newer_than(SeqNum(u32::MAX)));
+        assert!(!SeqNum(u32::MAX).is_newer_than(SeqNum(0)));
+    }
+
+    #[test]
+    fn is_newer_than_far_ahead() {
+        assert!(SeqNum((1 << 31) - 1).is_newer_than(SeqNum(0)));
+        assert!(!SeqNum(0).is_newer_than(SeqNum((1 << 31) - 1)));
+    }
+
+    #[test]
+    fn is_newer_than_far_ahead_wrapped() {
+        assert!(SeqNum(100).is_newer_than(SeqNum(100 + (1 << 31) + 1)));
+        assert!(!SeqNum(100 + (1 << 31) + 1).is_newer_than(SeqNum(100)));
+    }
+
+    #[test]
+    fn is_newer_than_half_apart() {
+        assert!(!SeqNum(1 << 31).is_newer_than(SeqNum(0)));
+        assert!(!SeqNum(0).is_newer_than(SeqNum(1 << 31)));
+    }
+
+    #[test]
+    fn newest() {
+        assert_some_eq!(
+            SeqNum::newest([SeqNum(u32::MAX - 1), SeqNum(1), SeqNum(u32::MAX)]),
+            SeqNum(1)
+        );
+    }
+
+    #[test]
+    fn newest_empty() {
+        assert_none!(SeqNum::newest([]));
+    }
+
+    #[test]
+    fn write_to() {
+        let mut bytes = [0; 4];
+
+        assert_ok!(SeqNum(0x1234_5678).write_to(&mut &mut bytes[..]));
+        assert_eq!(bytes, [0x78, 0x56, 0x34, 0x12]);
+    }
+
+    #[test]
+    fn read_from() {
+        assert_ok_eq!(
+            SeqNum::read_from(&mut &[0x78, 0x56, 0x34, 0x12][..]),
+            SeqNum(0x1234_5678)
+        );
+    }
+
+    #[test]
+    fn read_from_unexpected_eof() {
+        assert_err_eq!(
+            SeqNum::read_from(&mut &[0x78, 0x56][..]),
+            ReadExactError::UnexpectedEof
+        );
+    }
+}
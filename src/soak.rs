@@ -0,0 +1,639 @@
+//! Soak testing of save hardware.
+//!
+//! [`soak_test()`] repeatedly fills the entire backup memory with a pseudo-random pattern, reads
+//! it back, and verifies it, accumulating the results into a [`SoakReport`]. Flash devices are
+//! erased at the start of every iteration, so each iteration also performs a full erase cycle.
+//! The pattern is generated by an xorshift generator keyed by the iteration number, so a failure
+//! can be reproduced by rerunning the same iteration.
+//!
+//! Each iteration is timed using a clock provided by the application, since the hardware timers
+//! belong to the application. For example, timers 2 and 3 can be cascaded to count ticks:
+//!
+//! ``` no_run
+//! use gba_save::{soak, sram::Sram};
+//!
+//! const TM2CNT_L: *mut u16 = 0x0400_0108 as *mut u16;
+//! const TM3CNT_L: *mut u16 = 0x0400_010c as *mut u16;
+//!
+//! fn ticks() -> u32 {
+//!     // Assumes timer 2 is running and timer 3 is cascaded from it.
+//!     unsafe { (TM3CNT_L.read_volatile() as u32) << 16 | TM2CNT_L.read_volatile() as u32 }
+//! }
+//!
+//! let mut sram = unsafe { Sram::new() };
+//! let report = soak::soak_test(&mut sram, 100, ticks, |progress| {
+//!     // Display progress.
+//! });
+//! if !report.is_ok() {
+//!     // Reject the cart.
+//! }
+//! ```
+//!
+//! This module is only available with the `testing` feature enabled.
+
+use crate::{
+    flash::{self, Flash},
+    range::{FlashRegion128K, FlashRegion64K, SramRegion},
+    sram::{self, Sram},
+};
+use embedded_io::{Read, Write};
+
+/// The number of bytes written and verified at once.
+const CHUNK_SIZE: usize = 256;
+/// The number of times a failed chunk write is retried before it is counted as a failure.
+const MAX_RETRIES: u32 = 3;
+/// The number of failing addresses recorded in a [`SoakReport`].
+pub const MAX_FAILED_ADDRESSES: usize = 8;
+
+/// Backup memory that can be soak tested.
+pub trait BackupMedia {
+    /// The error returned when erasing or writing fails.
+    type Error: embedded_io::Error;
+
+    /// Whether the memory must be erased before it can be rewritten.
+    const ERASABLE: bool;
+
+    /// Returns the number of bytes of memory.
+    fn capacity(&self) -> usize;
+
+    /// Erases the entire memory.
+    ///
+    /// This is only called if [`ERASABLE`](Self::ERASABLE) is `true`.
+    fn erase(&mut self) -> Result<(), Self::Error>;
+
+    /// Writes `buf` starting at `offset`.
+    ///
+    /// # Panics
+    /// May panic if the range extends beyond the end of the memory.
+    fn write_at(&mut self, offset: usize, buf: &[u8]) -> Result<(), Self::Error>;
+
+    /// Fills `buf` with the data starting at `offset`.
+    ///
+    /// # Panics
+    /// May panic if the range extends beyond the end of the memory.
+    fn read_at(&self, offset: usize, buf: &mut [u8]);
+}
+
+impl BackupMedia for Sram {
+    type Error = sram::Error;
+
+    const ERASABLE: bool = false;
+
+    fn capacity(&self) -> usize {
+        32768
+    }
+
+    fn erase(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: usize, buf: &[u8]) -> Result<(), Self::Error> {
+        self.writer(SramRegion::new(offset, buf.len()))
+            .write_all(buf)
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) {
+        let Ok(_) = self.reader(SramRegion::new(offset, buf.len())).read(buf);
+    }
+}
+
+impl BackupMedia for Flash {
+    type Error = flash::Error;
+
+    const ERASABLE: bool = true;
+
+    fn capacity(&self) -> usize {
+        Flash::capacity(self)
+    }
+
+    fn erase(&mut self) -> Result<(), Self::Error> {
+        self.reset()
+    }
+
+    fn write_at(&mut self, offset: usize, buf: &[u8]) -> Result<(), Self::Error> {
+        match self {
+            Self::Flash64K(flash_64k) => flash_64k
+                .writer(FlashRegion64K::new(offset, buf.len()))
+                .write_all(buf),
+            Self::Flash64KAtmel(flash_64k_atmel) => {
+                let mut writer = flash_64k_atmel.writer(FlashRegion64K::new(offset, buf.len()));
+                writer.write_all(buf)?;
+                writer.flush()
+            }
+            Self::Flash128K(flash_128k) => flash_128k
+                .writer(FlashRegion128K::new(offset, buf.len()))
+                .write_all(buf),
+        }
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) {
+        let Ok(_) = match self {
+            Self::Flash64K(flash_64k) => flash_64k
+                .reader(FlashRegion64K::new(offset, buf.len()))
+                .read(buf),
+            Self::Flash64KAtmel(flash_64k_atmel) => flash_64k_atmel
+                .reader(FlashRegion64K::new(offset, buf.len()))
+                .read(buf),
+            Self::Flash128K(flash_128k) => flash_128k
+                .reader(FlashRegion128K::new(offset, buf.len()))
+                .read(buf),
+        };
+    }
+}
+
+/// An xorshift pseudo-random number generator.
+struct Xorshift(u32);
+
+impl Xorshift {
+    /// Creates the generator used for the given iteration.
+    fn for_iteration(iteration: u32) -> Self {
+        Self(iteration.wrapping_add(1).wrapping_mul(0x9e37_79b9).max(1))
+    }
+
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) {
+        for bytes in buf.chunks_mut(4) {
+            let value = self.next().to_le_bytes();
+            bytes.copy_from_slice(&value[..bytes.len()]);
+        }
+    }
+}
+
+/// A histogram of durations, bucketed by powers of two.
+struct Histogram {
+    buckets: [u32; 33],
+    count: u32,
+    min: u32,
+    max: u32,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            buckets: [0; 33],
+            count: 0,
+            min: u32::MAX,
+            max: 0,
+        }
+    }
+
+    fn record(&mut self, duration: u32) {
+        self.buckets[(u32::BITS - duration.leading_zeros()) as usize] += 1;
+        self.count += 1;
+        self.min = self.min.min(duration);
+        self.max = self.max.max(duration);
+    }
+
+    /// Returns an upper bound on the given percentile of recorded durations.
+    fn percentile(&self, percent: u32) -> u32 {
+        let target = (self.count as u64 * percent as u64).div_ceil(100);
+        let mut seen = 0;
+        for (bucket, &count) in self.buckets.iter().enumerate() {
+            seen += count as u64;
+            if seen >= target && seen > 0 {
+                let upper = ((1u64 << bucket) - 1).min(u32::MAX as u64) as u32;
+                return upper.clamp(self.min, self.max);
+            }
+        }
+        0
+    }
+
+    fn timing(&self) -> Timing {
+        if self.count == 0 {
+            return Timing::default();
+        }
+        Timing {
+            min: self.min,
+            p50: self.percentile(50),
+            p90: self.percentile(90),
+            p99: self.percentile(99),
+            max: self.max,
+        }
+    }
+}
+
+/// The time taken by each iteration of a soak test, in ticks of the application's clock.
+///
+/// Percentiles are approximate: each is rounded up to one less than the next power of two, and
+/// then clamped to the range of recorded durations.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Timing {
+    /// The fastest iteration.
+    pub min: u32,
+    /// The median iteration.
+    pub p50: u32,
+    /// The 90th percentile iteration.
+    pub p90: u32,
+    /// The 99th percentile iteration.
+    pub p99: u32,
+    /// The slowest iteration.
+    pub max: u32,
+}
+
+/// Progress of a soak test, reported after every iteration.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SoakProgress {
+    /// The number of iterations completed.
+    pub completed: u32,
+    /// The total number of iterations.
+    pub total: u32,
+    /// The number of bytes that have failed verification so far.
+    pub mismatched_bytes: u32,
+}
+
+/// The results of a soak test.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SoakReport {
+    /// The number of iterations performed.
+    pub iterations: u32,
+    /// The number of full erase cycles performed. This is always zero for SRAM.
+    pub erases: u32,
+    /// The number of erases that returned an error.
+    pub erase_failures: u32,
+    /// The number of bytes written, including bytes in chunks that failed to write.
+    pub bytes_written: u64,
+    /// The number of times writing a chunk was retried after returning an error.
+    pub retries: u32,
+    /// The number of chunks that still failed to write after retrying.
+    pub write_failures: u32,
+    /// The number of bytes that did not match the written pattern when read back.
+    pub mismatched_bytes: u32,
+    /// The time taken by each iteration.
+    pub timing: Timing,
+    failed_addresses: [usize; MAX_FAILED_ADDRESSES],
+    failed_address_count: usize,
+}
+
+impl SoakReport {
+    fn new() -> Self {
+        Self {
+            iterations: 0,
+            erases: 0,
+            erase_failures: 0,
+            bytes_written: 0,
+            retries: 0,
+            write_failures: 0,
+            mismatched_bytes: 0,
+            timing: Timing::default(),
+            failed_addresses: [0; MAX_FAILED_ADDRESSES],
+            failed_address_count: 0,
+        }
+    }
+
+    /// Returns whether every erase, write, and read back succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.erase_failures == 0 && self.write_failures == 0 && self.mismatched_bytes == 0
+    }
+
+    /// Returns the distinct addresses that failed verification, in the order they were first
+    /// found.
+    ///
+    /// At most [`MAX_FAILED_ADDRESSES`] addresses are recorded.
+    pub fn failed_addresses(&self) -> &[usize] {
+        &self.failed_addresses[..self.failed_address_count]
+    }
+
+    fn record_mismatch(&mut self, address: usize) {
+        self.mismatched_bytes += 1;
+        if self.failed_address_count < MAX_FAILED_ADDRESSES
+            && !self.failed_addresses().contains(&address)
+        {
+            self.failed_addresses[self.failed_address_count] = address;
+            self.failed_address_count += 1;
+        }
+    }
+}
+
+/// Runs `iterations` iterations of writing, reading back, and verifying the entire memory.
+///
+/// `clock` returns the current time in ticks of any unit, and is used to time each iteration.
+/// `report` is called after every iteration.
+pub fn soak_test<B, C, F>(media: &mut B, iterations: u32, mut clock: C, mut report: F) -> SoakReport
+where
+    B: BackupMedia,
+    C: FnMut() -> u32,
+    F: FnMut(SoakProgress),
+{
+    let mut soak_report = SoakReport::new();
+    let mut histogram = Histogram::new();
+    let mut expected = [0; CHUNK_SIZE];
+    let mut actual = [0; CHUNK_SIZE];
+    let capacity = media.capacity();
+
+    for iteration in 0..iterations {
+        let start = clock();
+
+        if B::ERASABLE {
+            match media.erase() {
+                Ok(()) => soak_report.erases += 1,
+                Err(_) => soak_report.erase_failures += 1,
+            }
+        }
+
+        let mut rng = Xorshift::for_iteration(iteration);
+        for offset in (0..capacity).step_by(CHUNK_SIZE) {
+            let chunk = &mut expected[..CHUNK_SIZE.min(capacity - offset)];
+            rng.fill(chunk);
+            let mut attempts = 0;
+            while media.write_at(offset, chunk).is_err() {
+                if attempts == MAX_RETRIES {
+                    soak_report.write_failures += 1;
+                    break;
+                }
+                attempts += 1;
+                soak_report.retries += 1;
+            }
+            soak_report.bytes_written += chunk.len() as u64;
+        }
+
+        let mut rng = Xorshift::for_iteration(iteration);
+        for offset in (0..capacity).step_by(CHUNK_SIZE) {
+            let len = CHUNK_SIZE.min(capacity - offset);
+            rng.fill(&mut expected[..len]);
+            media.read_at(offset, &mut actual[..len]);
+            for (i, (actual, expected)) in actual[..len].iter().zip(&expected[..len]).enumerate() {
+                if actual != expected {
+                    soak_report.record_mismatch(offset + i);
+                }
+            }
+        }
+
+        histogram.record(clock().wrapping_sub(start));
+        soak_report.iterations += 1;
+        report(SoakProgress {
+            completed: iteration + 1,
+            total: iterations,
+            mismatched_bytes: soak_report.mismatched_bytes,
+        });
+    }
+
+    soak_report.timing = histogram.timing();
+    soak_report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{soak_test, BackupMedia, Histogram, SoakProgress, Timing, Xorshift};
+    use crate::{flash::Flash, sram::Sram};
+    use claims::assert_ok;
+    use embedded_io::ErrorKind;
+    use gba_test::test;
+
+    /// Memory held in RAM, with injectable faults.
+    struct FakeMedia {
+        bytes: [u8; 1000],
+        stuck: Option<usize>,
+        failing_writes: u32,
+    }
+
+    impl FakeMedia {
+        fn new() -> Self {
+            Self {
+                bytes: [0; 1000],
+                stuck: None,
+                failing_writes: 0,
+            }
+        }
+    }
+
+    impl BackupMedia for FakeMedia {
+        type Error = ErrorKind;
+
+        const ERASABLE: bool = true;
+
+        fn capacity(&self) -> usize {
+            self.bytes.len()
+        }
+
+        fn erase(&mut self) -> Result<(), Self::Error> {
+            self.bytes = [0xff; 1000];
+            Ok(())
+        }
+
+        fn write_at(&mut self, offset: usize, buf: &[u8]) -> Result<(), Self::Error> {
+            if self.failing_writes > 0 {
+                self.failing_writes -= 1;
+                return Err(ErrorKind::Other);
+            }
+            self.bytes[offset..offset + buf.len()].copy_from_slice(buf);
+            if let Some(stuck) = self.stuck {
+                self.bytes[stuck] = 0;
+            }
+            Ok(())
+        }
+
+        fn read_at(&self, offset: usize, buf: &mut [u8]) {
+            buf.copy_from_slice(&self.bytes[offset..offset + buf.len()]);
+        }
+    }
+
+    #[test]
+    fn xorshift_deterministic() {
+        let mut first = [0; 16];
+        let mut second = [0; 16];
+        Xorshift::for_iteration(5).fill(&mut first);
+        Xorshift::for_iteration(5).fill(&mut second);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn xorshift_keyed_by_iteration() {
+        let mut first = [0; 16];
+        let mut second = [0; 16];
+        Xorshift::for_iteration(5).fill(&mut first);
+        Xorshift::for_iteration(6).fill(&mut second);
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn xorshift_last_iteration() {
+        let mut buf = [0; 16];
+        Xorshift::for_iteration(u32::MAX).fill(&mut buf);
+
+        assert_ne!(buf, [0; 16]);
+    }
+
+    #[test]
+    fn histogram_percentiles() {
+        let mut histogram = Histogram::new();
+        for duration in 1..=100 {
+            histogram.record(duration);
+        }
+
+        assert_eq!(
+            histogram.timing(),
+            Timing {
+                min: 1,
+                p50: 63,
+                p90: 100,
+                p99: 100,
+                max: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn histogram_empty() {
+        assert_eq!(Histogram::new().timing(), Timing::default());
+    }
+
+    #[test]
+    fn soak_ok() {
+        let mut media = FakeMedia::new();
+        let mut progress = [None; 3];
+
+        let report = soak_test(
+            &mut media,
+            3,
+            || 0,
+            |soak_progress| progress[soak_progress.completed as usize - 1] = Some(soak_progress),
+        );
+
+        assert!(report.is_ok());
+        assert_eq!(report.iterations, 3);
+        assert_eq!(report.erases, 3);
+        assert_eq!(report.bytes_written, 3000);
+        assert_eq!(report.failed_addresses(), &[]);
+        assert_eq!(
+            progress[2],
+            Some(SoakProgress {
+                completed: 3,
+                total: 3,
+                mismatched_bytes: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn soak_stuck_byte() {
+        let mut media = FakeMedia::new();
+        media.stuck = Some(300);
+
+        let report = soak_test(&mut media, 4, || 0, |_| {});
+
+        assert!(!report.is_ok());
+        // The stuck byte may match the pattern by chance.
+        assert!(report.mismatched_bytes >= 1);
+        assert_eq!(report.failed_addresses(), &[300]);
+    }
+
+    #[test]
+    fn soak_retries() {
+        let mut media = FakeMedia::new();
+        media.failing_writes = 2;
+
+        let report = soak_test(&mut media, 1, || 0, |_| {});
+
+        assert!(report.is_ok());
+        assert_eq!(report.retries, 2);
+        assert_eq!(report.write_failures, 0);
+    }
+
+    #[test]
+    fn soak_write_failure() {
+        let mut media = FakeMedia::new();
+        media.failing_writes = 4;
+
+        let report = soak_test(&mut media, 1, || 0, |_| {});
+
+        assert!(!report.is_ok());
+        assert_eq!(report.retries, 3);
+        assert_eq!(report.write_failures, 1);
+    }
+
+    #[test]
+    fn soak_timing() {
+        let mut media = FakeMedia::new();
+        let mut ticks = 0;
+
+        let report = soak_test(
+            &mut media,
+            2,
+            || {
+                ticks += 10;
+                ticks
+            },
+            |_| {},
+        );
+
+        assert_eq!(
+            report.timing,
+            Timing {
+                min: 10,
+                p50: 10,
+                p90: 10,
+                p99: 10,
+                max: 10,
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn soak_sram() {
+        let mut sram = unsafe { Sram::new() };
+
+        let report = soak_test(&mut sram, 2, || 0, |_| {});
+
+        assert!(report.is_ok());
+        assert_eq!(report.erases, 0);
+        assert_eq!(report.bytes_written, 65536);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn soak_flash_64k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+
+        let report = soak_test(&mut flash, 2, || 0, |_| {});
+
+        assert!(report.is_ok());
+        assert_eq!(report.erases, 2);
+        assert_eq!(report.bytes_written, 131072);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k_atmel),
+        ignore = "This test requires a Flash 64KiB Atmel chip. Ensure Flash 64KiB Atmel is configured and pass `--cfg flash_64k_atmel` to enable."
+    )]
+    fn soak_flash_64k_atmel() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+
+        let report = soak_test(&mut flash, 1, || 0, |_| {});
+
+        assert!(report.is_ok());
+        assert_eq!(report.erases, 1);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_128k),
+        ignore = "This test requires a Flash 128KiB chip. Ensure Flash 128KiB is configured and pass `--cfg flash_128k` to enable."
+    )]
+    fn soak_flash_128k() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+
+        let report = soak_test(&mut flash, 2, || 0, |_| {});
+
+        assert!(report.is_ok());
+        assert_eq!(report.erases, 2);
+        assert_eq!(report.bytes_written, 262144);
+    }
+}
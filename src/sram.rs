@@ -1,58 +1,174 @@
-use crate::mmio::{Cycles, WAITCNT};
+#[cfg(feature = "log")]
+use crate::read_log::{ReadLog, ReadLogSlot};
+use crate::{
+    cursor::Cursor,
+    diagnostics::{SelfTestReport, PATTERN},
+    error_kind::Class,
+    plan::SavePlan,
+    range::{bounded_range, check_disjoint, IntoRegion, RangeError, Region, SramRegion},
+    verify::{self, find_mismatch},
+    waitstate::{self, Cycles, WaitstateControl},
+    write_all::{write_all, write_all_strict},
+    write_stats::WriteTracker,
+    InsufficientData, WriteStats,
+};
 use core::{
     cmp::min,
     convert::Infallible,
+    fmt::{self, Debug, Display, Formatter},
     marker::PhantomData,
-    ops::{Bound, RangeBounds},
+    mem::{size_of, MaybeUninit},
+    ops, slice,
+    time::Duration,
 };
-use deranged::RangedUsize;
 use embedded_io::{ErrorKind, ErrorType, Read, Write};
+use static_assertions::assert_not_impl_any;
+
+pub use view::{FromBytes, VolatileView};
 
-const SRAM_MEMORY: *mut u8 = 0x0e00_0000 as *mut u8;
+mod view;
+
+pub(crate) const SRAM_MEMORY: *mut u8 = 0x0e00_0000 as *mut u8;
+const SRAM_SIZE: usize = 0x8000;
 
 /// A reader on SRAM.
 ///
-/// This type allows reading data over the range specified upon creation.
+/// This type allows reading data over the range specified upon creation. Reading into an empty
+/// buffer returns `Ok(0)` without accessing the device.
+///
+/// Cloning a reader creates an independent cursor at the same position. SRAM is plain memory, so
+/// any number of readers can read in any interleaving.
+#[must_use]
+#[derive(Clone)]
 pub struct Reader<'a> {
     address: *mut u8,
     len: usize,
+    start: usize,
+    #[cfg(feature = "log")]
+    log: ReadLogSlot,
     lifetime: PhantomData<&'a ()>,
 }
 
+assert_not_impl_any!(Reader<'static>: Send, Sync);
+
 impl Reader<'_> {
     unsafe fn new_unchecked(address: *mut u8, len: usize) -> Self {
+        debug_assert!(
+            address >= SRAM_MEMORY && address as usize + len <= SRAM_MEMORY as usize + SRAM_SIZE,
+            "range is out of bounds for SRAM"
+        );
         Self {
             address,
             len,
+            start: unsafe { address.offset_from(SRAM_MEMORY) as usize },
+            #[cfg(feature = "log")]
+            log: ReadLogSlot::default(),
             lifetime: PhantomData,
         }
     }
-}
 
-impl ErrorType for Reader<'_> {
-    type Error = Infallible;
-}
+    /// Logs the creation of this reader, and its completion once it is exhausted. Clones of the
+    /// reader do not log their completion, so the read is only logged once.
+    ///
+    /// This does nothing unless the `log` feature is enabled.
+    #[cfg_attr(not(feature = "log"), allow(unused_mut, unused_variables))]
+    pub(crate) fn logged(mut self, device: &'static str) -> Self {
+        #[cfg(feature = "log")]
+        {
+            self.log = ReadLogSlot::new(ReadLog::new(device, self.start, self.len));
+        }
+        self
+    }
 
-impl Read for Reader<'_> {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+    /// Fills `buf` entirely with data from the reader.
+    ///
+    /// If fewer than `buf.len()` bytes remain in the reader, [`InsufficientData`] is returned
+    /// before anything is read, leaving `buf` untouched.
+    pub fn read_exact_into(&mut self, buf: &mut [u8]) -> Result<(), InsufficientData> {
+        if buf.len() > self.len {
+            return Err(InsufficientData {
+                needed: buf.len(),
+                available: self.len,
+            });
+        }
+        let Ok(_) = self.read(buf);
+        Ok(())
+    }
+
+    /// Reads bytes into a possibly uninitialized buffer, returning the initialized prefix.
+    ///
+    /// This behaves the same as [`Read::read()`], but does not require `buf` to be initialized,
+    /// avoiding the cost of zeroing large buffers before reading into them.
+    pub fn read_uninit<'buf>(
+        &mut self,
+        buf: &'buf mut [MaybeUninit<u8>],
+    ) -> Result<&'buf mut [u8], Infallible> {
+        if buf.is_empty() {
+            return Ok(&mut []);
+        }
         let mut read_count = 0;
         loop {
             if read_count >= min(buf.len(), self.len) {
                 self.address = unsafe { self.address.add(read_count) };
                 self.len -= read_count;
-                return Ok(read_count);
+                record_stat!(bytes_read, read_count);
+                #[cfg(feature = "log")]
+                if self.len == 0 {
+                    self.log.finish();
+                }
+                // SAFETY: The first `read_count` bytes of `buf` have been initialized.
+                return Ok(unsafe {
+                    slice::from_raw_parts_mut(buf.as_mut_ptr().cast(), read_count)
+                });
             }
 
             unsafe {
-                *buf.get_unchecked_mut(read_count) = self.address.add(read_count).read_volatile();
+                buf.get_unchecked_mut(read_count)
+                    .write(self.address.add(read_count).read_volatile());
             }
             read_count += 1;
         }
     }
 }
 
+impl Reader<'_> {
+    fn cursor(&self) -> Cursor {
+        let position = unsafe { self.address.offset_from(SRAM_MEMORY) as usize };
+        Cursor {
+            start: self.start,
+            position,
+            end: position + self.len,
+        }
+    }
+}
+
+impl Debug for Reader<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().debug(formatter, "Sram::Reader")
+    }
+}
+
+impl Display for Reader<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().display(formatter, "SRAM")
+    }
+}
+
+impl ErrorType for Reader<'_> {
+    type Error = Infallible;
+}
+
+impl Read for Reader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        // SAFETY: Initialized bytes are valid `MaybeUninit<u8>`s, and only initialized bytes are
+        // written to the buffer.
+        self.read_uninit(unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) })
+            .map(|buf| buf.len())
+    }
+}
+
 /// An error that can occur when writing to flash memory.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Error {
     /// Data written was unable to be verified.
     WriteFailure,
@@ -62,42 +178,193 @@ pub enum Error {
     /// This indicates that the range provided when creating the writer has been completely
     /// exhausted.
     EndOfWriter,
+
+    /// Data written was unable to be verified, even after retrying.
+    ///
+    /// This is only returned by writers created with [`Sram::writer_with_retries()`].
+    RetriesExhausted {
+        /// The total number of attempts made to write the byte, including the initial attempt.
+        attempts: u16,
+    },
+
+    /// The buffer passed to `commit()` is larger than the space remaining in the writer.
+    BufferTooLarge {
+        /// The number of bytes in the buffer.
+        needed: usize,
+        /// The number of bytes remaining in the writer.
+        available: usize,
+    },
+
+    /// Data read back by `write_verified()` did not match the data written.
+    VerifyMismatch {
+        /// The offset of the first mismatching byte from the start of SRAM.
+        address: usize,
+    },
+
+    /// The buffer passed to `write_all_strict()` did not fit in the space remaining in the writer.
+    ///
+    /// As much of the buffer as fit was written.
+    Truncated {
+        /// The number of bytes of the buffer that were written.
+        written: usize,
+        /// The number of bytes in the buffer.
+        requested: usize,
+    },
 }
 
 impl embedded_io::Error for Error {
     fn kind(&self) -> ErrorKind {
         match self {
-            Self::WriteFailure => ErrorKind::NotConnected,
-            Self::EndOfWriter => ErrorKind::WriteZero,
+            Self::WriteFailure => Class::Verification,
+            Self::EndOfWriter => Class::EndOfWriter,
+            Self::RetriesExhausted { .. } => Class::Verification,
+            Self::BufferTooLarge { .. } => Class::InvalidInput,
+            Self::VerifyMismatch { .. } => Class::Verification,
+            Self::Truncated { .. } => Class::EndOfWriter,
         }
+        .kind()
     }
 }
 
 fn verify_byte(address: *const u8, byte: u8) -> Result<(), Error> {
-    if unsafe { address.read_volatile() } == byte {
-        Ok(())
-    } else {
-        Err(Error::WriteFailure)
-    }
+    // SRAM writes complete immediately, so there is no need to wait for the byte to be written.
+    verify::verify_byte(address, byte, Duration::ZERO).map_err(|_| Error::WriteFailure)
 }
 
 /// A writer on SRAM.
 ///
-/// This type allows writing data on the range specified upon creation.
+/// This type allows writing data on the range specified upon creation. Writing an empty buffer
+/// returns `Ok(0)` without accessing the device, unless the writer is exhausted.
+#[must_use]
 pub struct Writer<'a> {
     address: *mut u8,
     len: usize,
+    retries: u8,
+    stats: WriteTracker,
     lifetime: PhantomData<&'a ()>,
 }
 
+assert_not_impl_any!(Writer<'static>: Send, Sync);
+
 impl Writer<'_> {
-    unsafe fn new_unchecked(address: *mut u8, len: usize) -> Self {
+    unsafe fn new_unchecked(address: *mut u8, len: usize, retries: u8) -> Self {
+        debug_assert!(
+            address >= SRAM_MEMORY && address as usize + len <= SRAM_MEMORY as usize + SRAM_SIZE,
+            "range is out of bounds for SRAM"
+        );
         Self {
             address,
             len,
+            retries,
+            stats: WriteTracker::new(
+                "SRAM",
+                unsafe { address.offset_from(SRAM_MEMORY) as usize },
+                len,
+            ),
             lifetime: PhantomData,
         }
     }
+
+    /// Returns statistics about the writes performed by this writer.
+    pub fn stats(&self) -> WriteStats {
+        self.stats.snapshot()
+    }
+
+    /// Writes all of `buf` and then flushes the writer.
+    ///
+    /// If `buf` is larger than the space remaining in the writer, [`Error::BufferTooLarge`] is
+    /// returned before anything is written, so a save either fits entirely or is not started.
+    pub fn commit(&mut self, buf: &[u8]) -> Result<(), Error> {
+        if buf.len() > self.len {
+            return Err(Error::BufferTooLarge {
+                needed: buf.len(),
+                available: self.len,
+            });
+        }
+        write_all(self, buf, Error::EndOfWriter)?;
+        self.flush()
+    }
+
+    /// Writes all of `buf`, returning an error if it does not fit in the space remaining.
+    ///
+    /// [`write()`](Write::write()) writes as much of `buf` as fits and returns the number of bytes
+    /// written, which is easy to ignore by mistake. This instead returns [`Error::Truncated`] if
+    /// the writer runs out of space, after writing as much of `buf` as fit. To write nothing at all
+    /// when `buf` does not fit, use [`commit()`](Self::commit()).
+    pub fn write_all_strict(&mut self, buf: &[u8]) -> Result<(), Error> {
+        write_all_strict(
+            self,
+            buf,
+            |error| matches!(error, Error::EndOfWriter),
+            |written, requested| Error::Truncated { written, requested },
+        )
+    }
+
+    /// Writes all of `buf`, flushes the writer, and then reads the data back to check it.
+    ///
+    /// The data is read back through a [`Reader`], the same path used when loading. If any byte
+    /// does not match `buf`, [`Error::VerifyMismatch`] is returned with the offset of the first
+    /// mismatching byte. Otherwise, this behaves the same as [`commit()`](Self::commit()).
+    pub fn write_verified(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let start = self.address;
+        self.commit(buf)?;
+        let mut reader = unsafe { Reader::new_unchecked(start, buf.len()) };
+        match find_mismatch(&mut reader, buf) {
+            Some(index) => Err(Error::VerifyMismatch {
+                address: unsafe { start.offset_from(SRAM_MEMORY) as usize } + index,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn write_byte(&mut self, address: *mut u8, byte: u8) -> Result<(), Error> {
+        let mut retries = 0;
+        loop {
+            unsafe {
+                address.write_volatile(byte);
+            }
+            match verify_byte(address, byte) {
+                Ok(()) => return Ok(()),
+                Err(error) => {
+                    if retries == self.retries {
+                        record_stat!(verification_failures, 1);
+                        return Err(if self.retries == 0 {
+                            error
+                        } else {
+                            Error::RetriesExhausted {
+                                attempts: u16::from(retries) + 1,
+                            }
+                        });
+                    }
+                    retries += 1;
+                    record_stat!(verify_retries, 1);
+                    self.stats.retries += 1;
+                }
+            }
+        }
+    }
+}
+
+impl Writer<'_> {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            start: self.stats.start,
+            position: unsafe { self.address.offset_from(SRAM_MEMORY) as usize },
+            end: self.stats.start + self.stats.len,
+        }
+    }
+}
+
+impl Debug for Writer<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().debug(formatter, "Sram::Writer")
+    }
+}
+
+impl Display for Writer<'_> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        self.cursor().display(formatter, "SRAM")
+    }
 }
 
 impl ErrorType for Writer<'_> {
@@ -106,6 +373,13 @@ impl ErrorType for Writer<'_> {
 
 impl Write for Writer<'_> {
     fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return if self.len == 0 {
+                Err(Error::EndOfWriter)
+            } else {
+                Ok(0)
+            };
+        }
         let mut write_count = 0;
         loop {
             if write_count >= min(buf.len(), self.len) {
@@ -114,194 +388,1412 @@ impl Write for Writer<'_> {
                 }
                 self.address = unsafe { self.address.add(write_count) };
                 self.len -= write_count;
+                record_stat!(bytes_written, write_count);
+                self.stats.bytes_written += write_count;
                 return Ok(write_count);
             }
 
             let address = unsafe { self.address.add(write_count) };
             let byte = unsafe { *buf.get_unchecked(write_count) };
-            unsafe {
-                address.write_volatile(byte);
+            if let Err(error) = self.write_byte(address, byte) {
+                self.stats.failures += 1;
+                if write_count == 0 {
+                    return Err(error);
+                }
+                // Report the bytes written before the failure. The failure will be encountered
+                // again on the next write.
+                self.address = address;
+                self.len -= write_count;
+                record_stat!(bytes_written, write_count);
+                self.stats.bytes_written += write_count;
+                return Ok(write_count);
             }
-            verify_byte(address, byte)?;
 
             write_count += 1;
         }
     }
 
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        #[cfg(feature = "log")]
+        self.stats.log_summary();
+        Ok(())
+    }
+}
+
+/// A reader over a list of SRAM regions.
+///
+/// The regions are read in order, as if they were a single contiguous range. This type is created
+/// by [`Sram::scatter_reader()`].
+pub struct ScatterReader<'a> {
+    regions: slice::Iter<'a, SramRegion>,
+    reader: Reader<'a>,
+}
+
+assert_not_impl_any!(ScatterReader<'static>: Send, Sync);
+
+impl ErrorType for ScatterReader<'_> {
+    type Error = Infallible;
+}
+
+impl Read for ScatterReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let mut read_count = 0;
+        while read_count < buf.len() {
+            if self.reader.len == 0 {
+                let Some(region) = self.regions.next() else {
+                    break;
+                };
+                let (address, len) = translate_range_to_buffer(*region);
+                self.reader = unsafe { Reader::new_unchecked(address, len) };
+                continue;
+            }
+            let Ok(count) = self.reader.read(&mut buf[read_count..]);
+            read_count += count;
+        }
+        Ok(read_count)
+    }
+}
+
+/// A writer over a list of SRAM regions.
+///
+/// The regions are written in order, as if they were a single contiguous range. This type is
+/// created by [`Sram::scatter_writer()`].
+pub struct ScatterWriter<'a> {
+    regions: slice::Iter<'a, SramRegion>,
+    writer: Writer<'a>,
+}
+
+assert_not_impl_any!(ScatterWriter<'static>: Send, Sync);
+
+impl ScatterWriter<'_> {
+    fn is_exhausted(&self) -> bool {
+        self.writer.len == 0
+            && self
+                .regions
+                .as_slice()
+                .iter()
+                .all(|region| region.is_empty())
+    }
+}
+
+impl ErrorType for ScatterWriter<'_> {
+    type Error = Error;
+}
+
+impl Write for ScatterWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let mut write_count = 0;
+        while write_count < buf.len() {
+            if self.writer.len == 0 {
+                let Some(region) = self.regions.next() else {
+                    break;
+                };
+                let (address, len) = translate_range_to_buffer(*region);
+                self.writer = unsafe { Writer::new_unchecked(address, len, 0) };
+                continue;
+            }
+            match self.writer.write(&buf[write_count..]) {
+                Ok(count) => write_count += count,
+                // Bytes already written to earlier regions must still be reported.
+                Err(_) if write_count > 0 => break,
+                Err(error) => return Err(error),
+            }
+        }
+        if write_count == 0 && self.is_exhausted() {
+            return Err(Error::EndOfWriter);
+        }
+        Ok(write_count)
+    }
+
     fn flush(&mut self) -> Result<(), Self::Error> {
         Ok(())
     }
 }
 
-fn translate_range_to_buffer<const MAX: usize, Range>(range: Range) -> (*mut u8, usize)
+const fn translate_range_to_buffer<const MAX: usize>(region: Region<MAX>) -> (*mut u8, usize) {
+    (unsafe { SRAM_MEMORY.add(region.offset()) }, region.len())
+}
+
+/// Checks that no two scatter regions overlap.
+///
+/// Overlapping regions are always a bug in the save layout, so the `strict` feature turns the
+/// error into a panic.
+fn check_scatter_regions(regions: &[SramRegion]) -> Result<(), RangeError> {
+    let result = check_disjoint(regions);
+    #[cfg(feature = "strict")]
+    if let Err(error) = result {
+        panic!("scatter regions overlap: {error:?}");
+    }
+    result
+}
+
+/// Creates a view over `range`, which must be exactly the size of `T`.
+fn view<'a, T>(range: ops::Range<usize>) -> Result<VolatileView<'a, T>, RangeError>
 where
-    Range: RangeBounds<RangedUsize<0, MAX>>,
+    T: FromBytes,
 {
-    let offset = match range.start_bound() {
-        Bound::Included(start) => start.get(),
-        Bound::Excluded(start) => start.get() + 1,
-        Bound::Unbounded => 0,
-    };
-    let address = unsafe { SRAM_MEMORY.add(offset) };
-    let len = match range.end_bound() {
-        Bound::Included(end) => end.get() + 1,
-        Bound::Excluded(end) => end.get(),
-        Bound::Unbounded => MAX + 1,
-    } - offset;
-    (address, len)
+    let region: Region<32767> = bounded_range(range)?.into_region();
+    let (address, _) = translate_range_to_buffer(region);
+    Ok(unsafe { VolatileView::new_unchecked(address) })
 }
 
 /// Access to SRAM backup.
+///
+/// Readers and writers borrow the `Sram` they are created from. A writer borrows it exclusively,
+/// so no other reader or writer can exist while it is alive. Likewise, the `Sram` cannot be dropped
+/// while a reader or writer is alive.
+///
+/// Readers and writers access SRAM through raw pointers, and are neither `Send` nor `Sync`. Moving
+/// one into an interrupt handler would allow its accesses to interleave with those made by the
+/// main program, so this is rejected at compile time.
+///
+/// To save in response to an interrupt, the interrupt handler should instead record a request
+/// that the main loop acts on:
+///
+/// ``` no_run
+/// use core::ptr;
+/// use gba_save::sram::Sram;
+///
+/// static mut SAVE_REQUESTED: bool = false;
+///
+/// // Called from the interrupt handler.
+/// fn request_save() {
+///     // A single byte is written at once, so this cannot be torn by the main loop.
+///     unsafe { ptr::addr_of_mut!(SAVE_REQUESTED).write_volatile(true) };
+/// }
+///
+/// // Called once per frame from the main loop.
+/// fn drain_save_requests(sram: &mut Sram, data: &[u8]) {
+///     if unsafe { ptr::addr_of!(SAVE_REQUESTED).read_volatile() } {
+///         // Clear the request before saving, so a request made during the save is not lost.
+///         unsafe { ptr::addr_of_mut!(SAVE_REQUESTED).write_volatile(false) };
+///         sram.writer(..).write_all_strict(data).expect("could not save");
+///     }
+/// }
+/// ```
+///
+/// # Panics
+/// Reading and writing never panic, so a save cannot be cut short partway through by a panic.
+/// This covers [`reader()`](Self::reader()), [`try_reader()`](Self::try_reader()),
+/// [`writer()`](Self::writer()), and [`try_writer()`](Self::try_writer()), along with the
+/// `read()` and `read_exact_into()` methods of the returned readers and the `write()`, `flush()`,
+/// `write_all_strict()`, `commit()`, and `write_verified()` methods of the returned writers. This
+/// is checked in CI by the `panic_audit` example, which fails to link if any of these can reach a
+/// panic in a release build.
 pub struct Sram {
-    /// As this struct maintains ownership of SRAM memory and WAITCNT's SRAM wait control setting,
-    /// we want to make sure it can only be constructed through its `unsafe` `new()` associated
-    /// function.
-    _private: (),
+    /// Whether offsets in the mirror of SRAM are accepted by `try_reader()` and `try_writer()`.
+    mirrored: bool,
 }
 
 impl Sram {
     /// Creates an accessor to the SRAM backup.
     ///
+    /// This sets WAITCNT's SRAM wait control setting (bits 0-1) to 8 cycles. No other bits of
+    /// WAITCNT are modified.
+    ///
     /// # Safety
     /// Must have exclusive ownership of both SRAM memory and WAITCNT’s SRAM wait control setting
     /// for the duration of its lifetime.
     pub unsafe fn new() -> Self {
-        let mut waitstate_control = unsafe { WAITCNT.read_volatile() };
+        unsafe { waitstate::set_backup_waitstate_8() };
+
+        Self { mirrored: false }
+    }
+
+    /// Creates an accessor to the SRAM backup, using a known value of WAITCNT.
+    ///
+    /// Rather than reading WAITCNT, the SRAM wait control setting is applied to
+    /// `waitstate_control`, which is then written to WAITCNT and returned. This allows callers
+    /// that keep their own copy of WAITCNT to keep that copy coherent.
+    ///
+    /// # Safety
+    /// Must have exclusive ownership of both SRAM memory and WAITCNT’s SRAM wait control setting
+    /// for the duration of its lifetime. `waitstate_control` must be the current value of
+    /// WAITCNT, or else the other settings in WAITCNT will be overwritten.
+    pub unsafe fn new_with_waitstate(
+        mut waitstate_control: WaitstateControl,
+    ) -> (Self, WaitstateControl) {
         waitstate_control.set_backup_waitstate(Cycles::_8);
-        unsafe { WAITCNT.write_volatile(waitstate_control) };
+        unsafe { waitstate::write(waitstate_control) };
 
-        Self { _private: () }
+        (Self { mirrored: false }, waitstate_control)
     }
 
-    /// Returns a reader over the given range.
-    pub fn reader<'a, 'b, Range>(&'a self, range: Range) -> Reader<'b>
-    where
-        Range: RangeBounds<RangedUsize<0, 32767>>,
-        'a: 'b,
-    {
-        let (address, len) = translate_range_to_buffer(range);
-        unsafe { Reader::new_unchecked(address, len) }
+    /// Returns a human-readable name for this type of backup memory.
+    pub fn name(&self) -> &'static str {
+        "SRAM"
     }
 
-    /// Returns a writer over the given range.
-    pub fn writer<'a, 'b, Range>(&'a mut self, range: Range) -> Writer<'b>
+    /// Returns the number of bytes of storage available.
+    pub fn capacity(&self) -> usize {
+        SRAM_SIZE
+    }
+
+    /// Plans writing `data_len` bytes to the start of the given range, for use with
+    /// [`estimate_duration()`](Self::estimate_duration()).
+    ///
+    /// At most the length of the range is written. SRAM never needs erasing.
+    pub fn plan_write<Range>(&self, range: Range, data_len: usize) -> SavePlan
     where
-        Range: RangeBounds<RangedUsize<0, 32767>>,
-        'a: 'b,
+        Range: IntoRegion<32767>,
     {
-        let (address, len) = translate_range_to_buffer(range);
-        unsafe { Writer::new_unchecked(address, len) }
+        SavePlan::sram(min(data_len, range.into_region().len()))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{translate_range_to_buffer, Error, Sram, SRAM_MEMORY};
-    use claims::{assert_err_eq, assert_ok_eq};
-    use deranged::RangedUsize;
-    use embedded_io::{Read, Write};
-    use gba_test::test;
-    use more_ranges::{
-        RangeFromExclusive, RangeFromExclusiveToExclusive, RangeFromExclusiveToInclusive,
-    };
+    /// Estimates how long performing `plan` takes on SRAM.
+    ///
+    /// This should only be used to judge the scale of a save, such as to decide whether to show a
+    /// progress bar.
+    pub fn estimate_duration(&self, plan: &SavePlan) -> Duration {
+        plan.sram_duration()
+    }
 
-    #[test]
-    fn translate_range_to_buffer_unbounded_unbounded() {
-        assert_eq!(
-            translate_range_to_buffer::<32767, _>(..),
-            (SRAM_MEMORY, 32768)
-        );
+    /// Checks that SRAM is present.
+    ///
+    /// This writes a probe value to the first byte of SRAM and reads it back. The byte's original
+    /// value is restored afterward. If the probe cannot be read back, such as when the cartridge
+    /// has been removed, [`Error::WriteFailure`] is returned.
+    pub fn verify_present(&mut self) -> Result<(), Error> {
+        let original = unsafe { SRAM_MEMORY.read_volatile() };
+        let probe = !original;
+        unsafe {
+            SRAM_MEMORY.write_volatile(probe);
+        }
+        let result = verify_byte(SRAM_MEMORY, probe);
+        unsafe {
+            SRAM_MEMORY.write_volatile(original);
+        }
+        result
     }
 
-    #[test]
-    fn translate_range_to_buffer_unbounded_included() {
-        assert_eq!(
-            translate_range_to_buffer::<32767, _>(..=RangedUsize::new_static::<42>()),
-            (SRAM_MEMORY, 43)
-        );
+    /// Detects whether SRAM is mirrored across the upper 32KiB of the SRAM address space.
+    ///
+    /// Most 32KiB SRAM chips are mirrored, so that writing to an offset also changes the same
+    /// offset `0x8000` bytes later. This writes probe values to the first byte of SRAM and checks
+    /// whether they appear in the mirror. The byte's original value is restored afterward. If the
+    /// probe cannot be read back, [`Error::WriteFailure`] is returned.
+    pub fn detect_mirroring(&mut self) -> Result<bool, Error> {
+        let mirror = unsafe { SRAM_MEMORY.add(SRAM_SIZE) };
+        let original = unsafe { SRAM_MEMORY.read_volatile() };
+        let probe = !original;
+        unsafe {
+            SRAM_MEMORY.write_volatile(probe);
+        }
+        let result =
+            verify_byte(SRAM_MEMORY, probe).map(|()| unsafe { mirror.read_volatile() } == probe);
+        unsafe {
+            SRAM_MEMORY.write_volatile(original);
+        }
+        // Check the original value as well, in case the mirror coincidentally held the probe.
+        result.map(|mirrored| mirrored && unsafe { mirror.read_volatile() } == original)
     }
 
-    #[test]
-    fn translate_range_to_buffer_unbounded_excluded() {
-        assert_eq!(
-            translate_range_to_buffer::<32767, _>(..RangedUsize::new_static::<42>()),
-            (SRAM_MEMORY, 42)
-        );
+    /// Sets whether SRAM is treated as mirrored.
+    ///
+    /// When mirrored, [`try_reader()`](Self::try_reader()) and
+    /// [`try_writer()`](Self::try_writer()) also accept ranges lying entirely within the mirror
+    /// at offsets `0x8000..0x10000`, normalizing them to the canonical offsets. This keeps code
+    /// written against mirror addresses going through this crate's verified writes. Whether the
+    /// cartridge actually mirrors SRAM can be checked with
+    /// [`detect_mirroring()`](Self::detect_mirroring()).
+    pub fn set_mirrored(&mut self, mirrored: bool) {
+        self.mirrored = mirrored;
     }
 
-    #[test]
-    fn translate_range_to_buffer_included_unbounded() {
-        assert_eq!(
-            translate_range_to_buffer::<32767, _>(RangedUsize::new_static::<42>()..),
-            (unsafe { SRAM_MEMORY.add(42) }, 32726)
-        );
+    /// Returns whether SRAM is treated as mirrored.
+    pub fn is_mirrored(&self) -> bool {
+        self.mirrored
     }
 
-    #[test]
-    fn translate_range_to_buffer_included_included() {
-        assert_eq!(
-            translate_range_to_buffer::<32767, _>(
-                RangedUsize::new_static::<42>()..=RangedUsize::new_static::<100>()
-            ),
-            (unsafe { SRAM_MEMORY.add(42) }, 59)
-        );
+    fn canonicalize(&self, range: ops::Range<usize>) -> ops::Range<usize> {
+        if self.mirrored && range.start >= SRAM_SIZE && range.end <= 2 * SRAM_SIZE {
+            range.start - SRAM_SIZE..range.end.saturating_sub(SRAM_SIZE)
+        } else {
+            range
+        }
     }
 
-    #[test]
-    fn translate_range_to_buffer_included_excluded() {
-        assert_eq!(
-            translate_range_to_buffer::<32767, _>(
-                RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>()
-            ),
-            (unsafe { SRAM_MEMORY.add(42) }, 58)
-        );
+    /// Tests that data can be written to and read from SRAM.
+    ///
+    /// A test pattern is written over `region` and then read back. The contents of `region` are
+    /// overwritten, so it should be reserved for diagnostics. See the
+    /// [`diagnostics`](crate::diagnostics) module for details.
+    ///
+    /// SRAM writes complete immediately and are never polled, so the report's
+    /// [`latency`](SelfTestReport::latency) is always zero rather than a measured value.
+    pub fn self_test<Range>(&mut self, region: Range) -> SelfTestReport
+    where
+        Range: IntoRegion<32767>,
+    {
+        let (address, len) = translate_range_to_buffer(region.into_region());
+        let mut writer = unsafe { Writer::new_unchecked(address, len, 0) };
+        let write_ok = (0..len).all(|i| {
+            writer
+                .write_byte(unsafe { address.add(i) }, PATTERN[i % PATTERN.len()])
+                .is_ok()
+        });
+        let verify_ok = (0..len)
+            .all(|i| unsafe { address.add(i).read_volatile() } == PATTERN[i % PATTERN.len()]);
+
+        SelfTestReport {
+            write_ok,
+            verify_ok,
+            // SRAM is never polled.
+            latency: 0,
+        }
     }
 
-    #[test]
-    fn translate_range_to_buffer_excluded_unbounded() {
-        assert_eq!(
-            translate_range_to_buffer::<32767, _>(RangeFromExclusive {
-                start: RangedUsize::new_static::<42>()
-            }),
-            (unsafe { SRAM_MEMORY.add(43) }, 32725)
-        );
+    /// Returns a reader over the given range.
+    pub fn reader<Range>(&self, range: Range) -> Reader<'_>
+    where
+        Range: IntoRegion<32767>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Reader::new_unchecked(address, len) }.logged("SRAM")
     }
 
-    #[test]
-    fn translate_range_to_buffer_excluded_included() {
-        assert_eq!(
-            translate_range_to_buffer::<32767, _>(RangeFromExclusiveToInclusive {
-                start: RangedUsize::new_static::<42>(),
-                end: RangedUsize::new_static::<100>()
-            }),
-            (unsafe { SRAM_MEMORY.add(43) }, 58)
-        );
+    /// Returns a reader over the given range of offsets, if it is within SRAM.
+    pub fn try_reader(&self, range: ops::Range<usize>) -> Result<Reader<'_>, RangeError> {
+        Ok(self.reader(bounded_range(self.canonicalize(range))?))
     }
 
-    #[test]
-    fn translate_range_to_buffer_excluded_excluded() {
-        assert_eq!(
-            translate_range_to_buffer::<32767, _>(RangeFromExclusiveToExclusive {
-                start: RangedUsize::new_static::<42>(),
-                end: RangedUsize::new_static::<100>()
-            }),
-            (unsafe { SRAM_MEMORY.add(43) }, 57)
-        );
+    /// Returns a view of the value of type `T` stored at `offset`, if it is within SRAM.
+    ///
+    /// The value is read directly from SRAM through the view, without copying it into a buffer
+    /// first. See [`VolatileView`] for details.
+    ///
+    /// Views are only offered for SRAM. Flash and EEPROM cannot be read through arbitrary memory
+    /// accesses.
+    pub fn view<T>(&self, offset: usize) -> Result<VolatileView<'_, T>, RangeError>
+    where
+        T: FromBytes,
+    {
+        view(self.canonicalize(offset..offset.saturating_add(size_of::<T>())))
     }
 
-    #[test]
+    /// Returns a writer over the given range.
+    pub fn writer<Range>(&mut self, range: Range) -> Writer<'_>
+    where
+        Range: IntoRegion<32767>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Writer::new_unchecked(address, len, 0) }
+    }
+
+    /// Returns a writer over the given range of offsets, if it is within SRAM.
+    pub fn try_writer(&mut self, range: ops::Range<usize>) -> Result<Writer<'_>, RangeError> {
+        Ok(self.writer(bounded_range(self.canonicalize(range))?))
+    }
+
+    /// Returns a reader over the given regions, read in order as a single stream.
+    ///
+    /// If any two regions overlap, [`RangeError::Overlapping`] is returned, unless the `strict`
+    /// feature is enabled, in which case it causes a panic.
+    pub fn scatter_reader<'a>(
+        &'a self,
+        regions: &'a [SramRegion],
+    ) -> Result<ScatterReader<'a>, RangeError> {
+        check_scatter_regions(regions)?;
+        Ok(ScatterReader {
+            regions: regions.iter(),
+            reader: unsafe { Reader::new_unchecked(SRAM_MEMORY, 0) },
+        })
+    }
+
+    /// Returns a writer over the given regions, written in order as a single stream.
+    ///
+    /// This allows data to be written to a fragmented layout as though it were contiguous. If any
+    /// two regions overlap, [`RangeError::Overlapping`] is returned, unless the `strict` feature is
+    /// enabled, in which case it causes a panic.
+    pub fn scatter_writer<'a>(
+        &'a mut self,
+        regions: &'a [SramRegion],
+    ) -> Result<ScatterWriter<'a>, RangeError> {
+        check_scatter_regions(regions)?;
+        Ok(ScatterWriter {
+            regions: regions.iter(),
+            writer: unsafe { Writer::new_unchecked(SRAM_MEMORY, 0, 0) },
+        })
+    }
+
+    /// Returns a writer over the given range that retries failed byte writes.
+    ///
+    /// If a written byte cannot be verified, it is written again, up to `retries` more times. If
+    /// the byte still cannot be verified, [`Error::RetriesExhausted`] is returned.
+    pub fn writer_with_retries<Range>(&mut self, range: Range, retries: u8) -> Writer<'_>
+    where
+        Range: IntoRegion<32767>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Writer::new_unchecked(address, len, retries) }
+    }
+
+    /// Divides SRAM into two disjoint parts at `offset`.
+    ///
+    /// The first part contains offsets `0..offset` and the second contains `offset..0x8000`. Each
+    /// part can create readers and writers independently, so data can be streamed from one part
+    /// into the other.
+    ///
+    /// # Panics
+    /// Panics if `offset` is greater than the capacity of SRAM.
+    ///
+    /// # Example
+    /// ``` no_run
+    /// use embedded_io::Read;
+    /// use gba_save::sram::Sram;
+    ///
+    /// let mut sram = unsafe { Sram::new() };
+    /// let (old, mut new) = sram.split_at(0x4000);
+    /// let mut buf = [0; 32];
+    /// old.reader().read_exact(&mut buf).unwrap();
+    /// new.writer().write_all_strict(&buf).unwrap();
+    /// ```
+    pub fn split_at(&mut self, offset: usize) -> (SramPart<'_>, SramPart<'_>) {
+        SramPart {
+            region: SramRegion::full(),
+            lifetime: PhantomData,
+        }
+        .split_at(offset)
+    }
+}
+
+/// A part of SRAM, created by [`Sram::split_at()`].
+///
+/// Parts never overlap, so readers and writers over different parts can be used at the same time.
+/// Offsets passed to a part are relative to the start of the part.
+pub struct SramPart<'a> {
+    region: SramRegion,
+    lifetime: PhantomData<&'a mut Sram>,
+}
+
+impl<'a> SramPart<'a> {
+    /// Returns the region of SRAM covered by this part.
+    pub fn region(&self) -> SramRegion {
+        self.region
+    }
+
+    /// Returns the number of bytes in this part.
+    pub fn len(&self) -> usize {
+        self.region.len()
+    }
+
+    /// Returns whether this part contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.region.is_empty()
+    }
+
+    /// Divides this part into two disjoint parts at `offset`.
+    ///
+    /// # Panics
+    /// Panics if `offset` is greater than the length of this part.
+    pub fn split_at(self, offset: usize) -> (SramPart<'a>, SramPart<'a>) {
+        assert!(offset <= self.len(), "split offset is out of bounds");
+        (
+            SramPart {
+                region: SramRegion::new(self.region.offset(), offset),
+                lifetime: PhantomData,
+            },
+            SramPart {
+                region: SramRegion::new(self.region.offset() + offset, self.len() - offset),
+                lifetime: PhantomData,
+            },
+        )
+    }
+
+    fn subregion(&self, range: ops::Range<usize>) -> Result<SramRegion, RangeError> {
+        if range.start > range.end {
+            return Err(RangeError::Inverted {
+                start: range.start,
+                end: range.end,
+            });
+        }
+        if range.end > self.len() {
+            return Err(RangeError::OutOfBounds {
+                value: range.end,
+                max: self.len(),
+            });
+        }
+        Ok(SramRegion::new(
+            self.region.offset() + range.start,
+            range.len(),
+        ))
+    }
+
+    /// Returns a reader over the whole part.
+    pub fn reader(&self) -> Reader<'_> {
+        let (address, len) = translate_range_to_buffer(self.region);
+        unsafe { Reader::new_unchecked(address, len) }.logged("SRAM")
+    }
+
+    /// Returns a reader over the given range of offsets, if it is within this part.
+    pub fn try_reader(&self, range: ops::Range<usize>) -> Result<Reader<'_>, RangeError> {
+        let (address, len) = translate_range_to_buffer(self.subregion(range)?);
+        Ok(unsafe { Reader::new_unchecked(address, len) }.logged("SRAM"))
+    }
+
+    /// Returns a writer over the whole part.
+    pub fn writer(&mut self) -> Writer<'_> {
+        let (address, len) = translate_range_to_buffer(self.region);
+        unsafe { Writer::new_unchecked(address, len, 0) }
+    }
+
+    /// Returns a writer over the given range of offsets, if it is within this part.
+    pub fn try_writer(&mut self, range: ops::Range<usize>) -> Result<Writer<'_>, RangeError> {
+        let (address, len) = translate_range_to_buffer(self.subregion(range)?);
+        Ok(unsafe { Writer::new_unchecked(address, len, 0) })
+    }
+}
+
+/// Read-only access to SRAM backup.
+///
+/// Unlike [`Sram`], creating this type has no side effects: WAITCNT is not modified. This makes it
+/// suitable for crash handlers and integrity checks that should disturb as little global state as
+/// possible. Only readers can be created.
+pub struct SramReadOnly {
+    /// As this struct relies on WAITCNT already being configured, we want to make sure it can only
+    /// be constructed through its `unsafe` `new()` associated function.
+    _private: (),
+}
+
+impl SramReadOnly {
+    /// Creates a read-only accessor to the SRAM backup.
+    ///
+    /// # Safety
+    /// WAITCNT's SRAM wait control setting (bits 0-1) must already be set to 8 cycles, and SRAM
+    /// memory must not be written to for the lifetime of this value.
+    pub unsafe fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Returns a reader over the given range.
+    pub fn reader<Range>(&self, range: Range) -> Reader<'_>
+    where
+        Range: IntoRegion<32767>,
+    {
+        let (address, len) = translate_range_to_buffer(range.into_region());
+        unsafe { Reader::new_unchecked(address, len) }.logged("SRAM")
+    }
+
+    /// Returns a reader over the given range of offsets, if it is within SRAM.
+    pub fn try_reader(&self, range: ops::Range<usize>) -> Result<Reader<'_>, RangeError> {
+        Ok(self.reader(bounded_range(range)?))
+    }
+
+    /// Returns a view of the value of type `T` stored at `offset`, if it is within SRAM.
+    ///
+    /// See [`Sram::view()`] for details.
+    pub fn view<T>(&self, offset: usize) -> Result<VolatileView<'_, T>, RangeError>
+    where
+        T: FromBytes,
+    {
+        view(offset..offset.saturating_add(size_of::<T>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{translate_range_to_buffer, Error, Reader, Sram, SramReadOnly, SRAM_MEMORY};
+    use crate::{
+        diagnostics::SelfTestReport,
+        range::{RangeError, Region, SramRegion},
+        test_util::{assert_read_sizes, assert_write_sizes, log_latency, measure, Buffer},
+        waitstate::{self, WaitstateControl},
+        InsufficientData, SaveBackend, SavePlan, WriteStats,
+    };
+    use claims::{assert_err, assert_err_eq, assert_ok, assert_ok_eq};
+    use core::{fmt::Write as _, mem::MaybeUninit, time::Duration};
+    use deranged::RangedUsize;
+    use embedded_io::{Read, Write};
+    use gba_test::test;
+    use more_ranges::{
+        RangeFromExclusive, RangeFromExclusiveToExclusive, RangeFromExclusiveToInclusive,
+    };
+
+    #[test]
+    fn split_at() {
+        let mut sram = unsafe { Sram::new() };
+        let (first, second) = sram.split_at(100);
+
+        assert_eq!(first.region(), SramRegion::new(0, 100));
+        assert_eq!(second.region(), SramRegion::new(100, 32668));
+    }
+
+    #[test]
+    fn split_at_end() {
+        let mut sram = unsafe { Sram::new() };
+        let (first, second) = sram.split_at(32768);
+
+        assert_eq!(first.len(), 32768);
+        assert!(second.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "split offset is out of bounds")]
+    fn split_at_out_of_bounds() {
+        let mut sram = unsafe { Sram::new() };
+        let _ = sram.split_at(32769);
+    }
+
+    #[test]
+    fn split_part() {
+        let mut sram = unsafe { Sram::new() };
+        let (_, second) = sram.split_at(100);
+        let (first, second) = second.split_at(50);
+
+        assert_eq!(first.region(), SramRegion::new(100, 50));
+        assert_eq!(second.region(), SramRegion::new(150, 32618));
+    }
+
+    #[test]
+    fn part_try_reader_out_of_bounds() {
+        let mut sram = unsafe { Sram::new() };
+        let (first, _) = sram.split_at(100);
+
+        assert_err_eq!(
+            first.try_reader(50..101).map(|_| ()),
+            RangeError::OutOfBounds {
+                value: 101,
+                max: 100
+            }
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn part_try_writer_inverted() {
+        let mut sram = unsafe { Sram::new() };
+        let (_, mut second) = sram.split_at(100);
+
+        assert_err_eq!(
+            second.try_writer(50..10).map(|_| ()),
+            RangeError::Inverted { start: 50, end: 10 }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn copy_between_parts() {
+        let mut sram = unsafe { Sram::new() };
+        let (mut first, mut second) = sram.split_at(16384);
+        assert_ok!(assert_ok!(first.try_writer(0..12)).write_all(b"hello, world"));
+
+        let mut reader = assert_ok!(first.try_reader(0..12));
+        let mut writer = assert_ok!(second.try_writer(100..112));
+        let mut buf = [0; 4];
+        for _ in 0..3 {
+            assert_ok!(reader.read_exact(&mut buf));
+            assert_ok!(writer.write_all(&buf));
+        }
+
+        let mut buf = [0; 12];
+        assert_ok!(sram
+            .reader(RangedUsize::new_static::<16484>()..RangedUsize::new_static::<16496>())
+            .read_exact(&mut buf));
+        assert_eq!(&buf, b"hello, world");
+    }
+
+    #[test]
+    fn translate_range_to_buffer_unbounded_unbounded() {
+        assert_eq!(
+            translate_range_to_buffer(Region::<32767>::from_range(..)),
+            (SRAM_MEMORY, 32768)
+        );
+    }
+
+    #[test]
+    fn translate_range_to_buffer_unbounded_included() {
+        assert_eq!(
+            translate_range_to_buffer(Region::<32767>::from_range(
+                ..=RangedUsize::new_static::<42>()
+            )),
+            (SRAM_MEMORY, 43)
+        );
+    }
+
+    #[test]
+    fn translate_range_to_buffer_unbounded_excluded() {
+        assert_eq!(
+            translate_range_to_buffer(Region::<32767>::from_range(
+                ..RangedUsize::new_static::<42>()
+            )),
+            (SRAM_MEMORY, 42)
+        );
+    }
+
+    #[test]
+    fn translate_range_to_buffer_included_unbounded() {
+        assert_eq!(
+            translate_range_to_buffer(Region::<32767>::from_range(
+                RangedUsize::new_static::<42>()..
+            )),
+            (unsafe { SRAM_MEMORY.add(42) }, 32726)
+        );
+    }
+
+    #[test]
+    fn translate_range_to_buffer_included_included() {
+        assert_eq!(
+            translate_range_to_buffer(Region::<32767>::from_range(
+                RangedUsize::new_static::<42>()..=RangedUsize::new_static::<100>()
+            )),
+            (unsafe { SRAM_MEMORY.add(42) }, 59)
+        );
+    }
+
+    #[test]
+    fn translate_range_to_buffer_included_excluded() {
+        assert_eq!(
+            translate_range_to_buffer(Region::<32767>::from_range(
+                RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>()
+            )),
+            (unsafe { SRAM_MEMORY.add(42) }, 58)
+        );
+    }
+
+    #[test]
+    fn translate_range_to_buffer_excluded_unbounded() {
+        assert_eq!(
+            translate_range_to_buffer(Region::<32767>::from_range(RangeFromExclusive {
+                start: RangedUsize::new_static::<42>()
+            })),
+            (unsafe { SRAM_MEMORY.add(43) }, 32725)
+        );
+    }
+
+    #[test]
+    fn translate_range_to_buffer_excluded_included() {
+        assert_eq!(
+            translate_range_to_buffer(Region::<32767>::from_range(RangeFromExclusiveToInclusive {
+                start: RangedUsize::new_static::<42>(),
+                end: RangedUsize::new_static::<100>()
+            })),
+            (unsafe { SRAM_MEMORY.add(43) }, 58)
+        );
+    }
+
+    #[test]
+    fn translate_range_to_buffer_excluded_excluded() {
+        assert_eq!(
+            translate_range_to_buffer(Region::<32767>::from_range(RangeFromExclusiveToExclusive {
+                start: RangedUsize::new_static::<42>(),
+                end: RangedUsize::new_static::<100>()
+            })),
+            (unsafe { SRAM_MEMORY.add(43) }, 57)
+        );
+    }
+
+    #[test]
+    fn new_with_waitstate() {
+        let (_sram, waitstate_control) =
+            unsafe { Sram::new_with_waitstate(WaitstateControl::from_bits(0x4010)) };
+
+        assert_eq!(waitstate_control, WaitstateControl::from_bits(0x4013));
+        assert_eq!(waitstate::read(), WaitstateControl::from_bits(0x4013));
+    }
+
+    #[test]
+    fn read_only_new_does_not_modify_waitstate() {
+        unsafe { waitstate::write(WaitstateControl::from_bits(0x4010)) };
+        let _sram = unsafe { SramReadOnly::new() };
+
+        assert_eq!(waitstate::read(), WaitstateControl::from_bits(0x4010));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn read_only_reader() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..RangedUsize::new_static::<13>());
+        assert_ok!(writer.write_all(b"hello, world!"));
+
+        let sram_read_only = unsafe { SramReadOnly::new() };
+        let mut reader = sram_read_only.reader(..RangedUsize::new_static::<13>());
+        let mut buf = [0; 13];
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf, b"hello, world!");
+    }
+
+    #[test]
+    fn read_only_try_reader_out_of_bounds() {
+        let sram = unsafe { SramReadOnly::new() };
+
+        assert_err_eq!(
+            sram.try_reader(0..32769).map(|_| ()),
+            RangeError::OutOfBounds {
+                value: 32769,
+                max: 32768
+            }
+        );
+    }
+
+    #[test]
+    fn name() {
+        let sram = unsafe { Sram::new() };
+
+        assert_eq!(sram.name(), "SRAM");
+    }
+
+    #[test]
+    fn capacity() {
+        let sram = unsafe { Sram::new() };
+
+        assert_eq!(sram.capacity(), 32768);
+    }
+
+    #[test]
+    fn plan_write() {
+        let sram = unsafe { Sram::new() };
+
+        let plan = sram.plan_write(SramRegion::new(32000, 768), 1000);
+
+        assert_eq!(
+            plan,
+            SavePlan {
+                backend: SaveBackend::Sram,
+                bytes: 768,
+                erases: 0,
+            }
+        );
+        assert_eq!(sram.estimate_duration(&plan), Duration::from_micros(768));
+    }
+
+    #[test]
+    fn try_reader_out_of_bounds() {
+        let sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.try_reader(32000..33000).map(|_| ()),
+            RangeError::OutOfBounds {
+                value: 33000,
+                max: 32768
+            }
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_writer_inverted() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.try_writer(100..42).map(|_| ()),
+            RangeError::Inverted {
+                start: 100,
+                end: 42
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn try_writer_full_range() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = assert_ok!(sram.try_writer(0..32768));
+
+        for _ in 0..32 {
+            assert_ok_eq!(writer.write(&[0xab; 1024]), 1024);
+        }
+        assert_err_eq!(writer.write(&[0xab]), Error::EndOfWriter);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn commit() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..RangedUsize::new_static::<13>());
+
+        assert_ok!(writer.commit(b"hello, world!"));
+
+        let mut reader = sram.reader(..RangedUsize::new_static::<13>());
+        let mut buf = [0; 13];
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf, b"hello, world!");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn write_verified() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_ok!(sram
+            .writer(RangedUsize::new_static::<100>()..RangedUsize::new_static::<113>())
+            .write_verified(b"hello, world!"));
+    }
+
+    #[test]
+    fn write_verified_buffer_too_large() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.writer(..RangedUsize::new_static::<12>())
+                .write_verified(b"hello, world!"),
+            Error::BufferTooLarge {
+                needed: 13,
+                available: 12
+            }
+        );
+    }
+
+    #[test]
+    fn commit_buffer_too_large() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.writer(..RangedUsize::new_static::<12>())
+                .commit(b"hello, world!"),
+            Error::BufferTooLarge {
+                needed: 13,
+                available: 12
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn write_all_strict() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..RangedUsize::new_static::<13>());
+
+        assert_ok!(writer.write_all_strict(b"hello, world!"));
+
+        let mut reader = sram.reader(..RangedUsize::new_static::<13>());
+        let mut buf = [0; 13];
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf, b"hello, world!");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn write_all_strict_truncated() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..RangedUsize::new_static::<12>());
+
+        assert_err_eq!(
+            writer.write_all_strict(b"hello, world!"),
+            Error::Truncated {
+                written: 12,
+                requested: 13
+            }
+        );
+
+        let mut reader = sram.reader(..RangedUsize::new_static::<12>());
+        let mut buf = [0; 12];
+        assert_ok_eq!(reader.read(&mut buf), 12);
+        assert_eq!(&buf, b"hello, world");
+    }
+
+    #[test]
+    fn write_all_strict_exhausted() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
+                .write_all_strict(b"hello, world!"),
+            Error::Truncated {
+                written: 0,
+                requested: 13
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn read_exact_into() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..RangedUsize::new_static::<13>());
+        assert_ok!(writer.write_all(b"hello, world!"));
+
+        let mut reader = sram.reader(..RangedUsize::new_static::<13>());
+        let mut buf = [0; 5];
+
+        assert_ok!(reader.read_exact_into(&mut buf));
+        assert_eq!(&buf, b"hello");
+    }
+
+    #[test]
+    fn read_exact_into_insufficient_data() {
+        let sram = unsafe { Sram::new() };
+        let mut reader = sram.reader(..RangedUsize::new_static::<12>());
+        let mut buf = [1; 13];
+
+        assert_err_eq!(
+            reader.read_exact_into(&mut buf),
+            InsufficientData {
+                needed: 13,
+                available: 12
+            }
+        );
+        assert_eq!(buf, [1; 13]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "range is out of bounds for SRAM")]
+    fn reader_out_of_bounds() {
+        let _ = unsafe { Reader::new_unchecked(SRAM_MEMORY.add(32767), 2) };
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn scatter() {
+        const REGIONS: [SramRegion; 3] = [
+            SramRegion::new(100, 5),
+            SramRegion::new(0, 2),
+            SramRegion::new(50, 6),
+        ];
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = assert_ok!(sram.scatter_writer(&REGIONS));
+
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+        assert_err_eq!(writer.write(b"!"), Error::EndOfWriter);
+
+        let mut reader = sram.reader(..RangedUsize::new_static::<2>());
+        let mut buf = [0; 2];
+        assert_ok_eq!(reader.read(&mut buf), 2);
+        assert_eq!(&buf, b", ");
+
+        let mut reader = assert_ok!(sram.scatter_reader(&REGIONS));
+        let mut buf = [0; 20];
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf[..13], b"hello, world!");
+    }
+
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    fn scatter_writer_overlapping() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.scatter_writer(&[SramRegion::new(0, 64), SramRegion::new(63, 2)])
+                .map(|_| ()),
+            RangeError::Overlapping {
+                first: 0,
+                second: 1
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    fn scatter_reader_overlapping() {
+        let sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.scatter_reader(&[SramRegion::new(0, 64), SramRegion::new(63, 2)])
+                .map(|_| ()),
+            RangeError::Overlapping {
+                first: 0,
+                second: 1
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "strict")]
+    #[should_panic(expected = "scatter regions overlap")]
+    fn scatter_writer_overlapping_strict() {
+        let mut sram = unsafe { Sram::new() };
+
+        let _ = sram.scatter_writer(&[SramRegion::new(0, 64), SramRegion::new(63, 2)]);
+    }
+
+    #[test]
+    #[cfg(feature = "strict")]
+    #[should_panic(expected = "scatter regions overlap")]
+    fn scatter_reader_overlapping_strict() {
+        let sram = unsafe { Sram::new() };
+
+        let _ = sram.scatter_reader(&[SramRegion::new(0, 64), SramRegion::new(63, 2)]);
+    }
+
+    #[test]
+    fn scatter_writer_empty() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = assert_ok!(sram.scatter_writer(&[]));
+
+        assert_err_eq!(writer.write(b"hello"), Error::EndOfWriter);
+    }
+
+    #[test]
+    fn read_sizes_aligned() {
+        let sram = unsafe { Sram::new() };
+
+        assert_read_sizes!(sram.reader(SramRegion::new(0, 16)), 16);
+    }
+
+    #[test]
+    fn read_sizes_unaligned() {
+        let sram = unsafe { Sram::new() };
+
+        assert_read_sizes!(sram.reader(SramRegion::new(3, 13)), 13);
+    }
+
+    #[test]
+    fn read_sizes_end_of_sram() {
+        let sram = unsafe { Sram::new() };
+
+        assert_read_sizes!(sram.reader(SramRegion::new(32761, 7)), 7);
+    }
+
+    #[test]
+    fn scatter_read_sizes() {
+        const REGIONS: [SramRegion; 2] = [SramRegion::new(3, 5), SramRegion::new(64, 8)];
+        let sram = unsafe { Sram::new() };
+
+        assert_read_sizes!(assert_ok!(sram.scatter_reader(&REGIONS)), 13);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn write_sizes_aligned() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_write_sizes!(sram.writer(SramRegion::new(0, 16)), 16);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn write_sizes_unaligned() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_write_sizes!(sram.writer(SramRegion::new(3, 13)), 13);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn write_sizes_end_of_sram() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_write_sizes!(sram.writer(SramRegion::new(32761, 7)), 7);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn scatter_write_sizes() {
+        const REGIONS: [SramRegion; 2] = [SramRegion::new(3, 5), SramRegion::new(64, 8)];
+        let mut sram = unsafe { Sram::new() };
+
+        assert_write_sizes!(assert_ok!(sram.scatter_writer(&REGIONS)), 13);
+    }
+
+    #[test]
+    fn read_empty_buffer() {
+        let sram = unsafe { Sram::new() };
+
+        assert_ok_eq!(sram.reader(..).read(&mut []), 0);
+    }
+
+    #[test]
+    fn write_empty_buffer() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_ok_eq!(sram.writer(..).write(&[]), 0);
+    }
+
+    #[test]
+    fn write_empty_buffer_exhausted() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>())
+                .write(&[]),
+            Error::EndOfWriter
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn empty_range_read() {
+        let sram = unsafe { Sram::new() };
+        let mut reader =
+            sram.reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>());
+
+        let mut buf = [1, 2, 3, 4];
+        assert_ok_eq!(reader.read(&mut buf), 0);
+        assert_eq!(buf, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn empty_range_write() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer =
+            sram.writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>());
+
+        assert_err_eq!(writer.write(&[0]), Error::EndOfWriter);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn full_range() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..);
+
+        for i in 0..8192 {
+            assert_ok_eq!(
+                writer.write(&[
+                    0u8.wrapping_add(i as u8),
+                    1u8.wrapping_add(i as u8),
+                    2u8.wrapping_add(i as u8),
+                    3u8.wrapping_add(i as u8)
+                ]),
+                4
+            );
+        }
+
+        let mut reader = sram.reader(..);
+        let mut buf = [0, 0, 0, 0];
+
+        for i in 0..8192 {
+            assert_ok_eq!(reader.read(&mut buf), 4);
+            assert_eq!(
+                buf,
+                [
+                    0u8.wrapping_add(i as u8),
+                    1u8.wrapping_add(i as u8),
+                    2u8.wrapping_add(i as u8),
+                    3u8.wrapping_add(i as u8)
+                ]
+            );
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn write_verified_byte_latency() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..);
+        let mut worst = Duration::ZERO;
+
+        for i in 0..256 {
+            let (result, latency) = measure(|| writer.write_verified(&[i as u8]));
+            assert_ok!(result);
+            worst = worst.max(latency);
+        }
+
+        log_latency("SRAM byte write and verify", worst);
+        assert!(worst < Duration::from_millis(1), "took {worst:?}");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn cloned_readers_interleaved() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..RangedUsize::new_static::<6>());
+        assert_ok_eq!(writer.write(b"header"), 6);
+
+        let mut reader = sram.reader(..RangedUsize::new_static::<6>());
+        let mut buf = [0; 3];
+        assert_ok_eq!(reader.read(&mut buf[..1]), 1);
+        let mut clone = reader.clone();
+
+        assert_ok_eq!(reader.read(&mut buf), 3);
+        assert_eq!(&buf, b"ead");
+        assert_ok_eq!(clone.read(&mut buf[..2]), 2);
+        assert_eq!(&buf[..2], b"ea");
+        assert_ok_eq!(reader.read(&mut buf), 2);
+        assert_eq!(&buf[..2], b"er");
+        assert_ok_eq!(clone.read(&mut buf), 3);
+        assert_eq!(&buf, b"der");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn partial_range() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer =
+            sram.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+
+        assert_ok_eq!(writer.write(&[b'a'; 100]), 58);
+
+        let mut reader =
+            sram.reader(RangedUsize::new_static::<51>()..RangedUsize::new_static::<60>());
+        let mut buf = [0; 20];
+
+        assert_ok_eq!(reader.read(&mut buf), 9);
+        assert_eq!(
+            buf,
+            [
+                b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0
+            ]
+        );
+    }
+
+    #[test]
     #[cfg_attr(
         not(sram),
         ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
     )]
-    fn empty_range_read() {
-        let sram = unsafe { Sram::new() };
-        let mut reader =
-            sram.reader(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>());
+    fn region() {
+        const REGION: SramRegion = SramRegion::new(42, 58);
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(REGION);
 
-        let mut buf = [1, 2, 3, 4];
-        assert_ok_eq!(reader.read(&mut buf), 0);
-        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_ok_eq!(writer.write(&[b'b'; 100]), 58);
+
+        let mut reader = sram.reader(REGION);
+        let mut buf = [0; 100];
+
+        assert_ok_eq!(reader.read(&mut buf), 58);
+        assert_eq!(buf[..58], [b'b'; 58]);
     }
 
     #[test]
@@ -309,12 +1801,20 @@ mod tests {
         not(sram),
         ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
     )]
-    fn empty_range_write() {
+    fn last_byte() {
         let mut sram = unsafe { Sram::new() };
         let mut writer =
-            sram.writer(RangedUsize::new_static::<0>()..RangedUsize::new_static::<0>());
+            sram.writer(RangedUsize::new_static::<32767>()..=RangedUsize::new_static::<32767>());
 
-        assert_err_eq!(writer.write(&[0]), Error::EndOfWriter);
+        assert_ok_eq!(writer.write(b"zz"), 1);
+        assert_err_eq!(writer.write(b"z"), Error::EndOfWriter);
+
+        let mut reader = sram.reader(RangedUsize::new_static::<32767>()..);
+        let mut buf = [0; 2];
+
+        assert_ok_eq!(reader.read(&mut buf), 1);
+        assert_eq!(buf[0], b'z');
+        assert_ok_eq!(reader.read(&mut buf), 0);
     }
 
     #[test]
@@ -322,37 +1822,35 @@ mod tests {
         not(sram),
         ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
     )]
-    fn full_range() {
+    fn inclusive_range_to_max() {
         let mut sram = unsafe { Sram::new() };
-        let mut writer = sram.writer(..);
+        let mut writer = sram.writer(..=RangedUsize::new_static::<32767>());
+        let mut written = 0;
+        while let Ok(count) = writer.write(&[b'm'; 256]) {
+            written += count;
+        }
+        assert_eq!(written, 32768);
 
-        for i in 0..8192 {
-            assert_ok_eq!(
-                writer.write(&[
-                    0u8.wrapping_add(i as u8),
-                    1u8.wrapping_add(i as u8),
-                    2u8.wrapping_add(i as u8),
-                    3u8.wrapping_add(i as u8)
-                ]),
-                4
-            );
+        let mut reader = sram.reader(..=RangedUsize::new_static::<32767>());
+        let mut buf = [0; 256];
+        let mut read = 0;
+        while let Ok(count @ 1..) = reader.read(&mut buf) {
+            assert_eq!(buf[..count], [b'm'; 256][..count]);
+            read += count;
         }
+        assert_eq!(read, 32768);
+    }
 
-        let mut reader = sram.reader(..);
-        let mut buf = [0, 0, 0, 0];
+    #[test]
+    #[cfg_attr(
+        sram,
+        ignore = "This test cannot be run with an SRAM chip. Ensure SRAM is not configured and do not pass `--cfg sram` to enable."
+    )]
+    fn write_failure() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..);
 
-        for i in 0..8192 {
-            assert_ok_eq!(reader.read(&mut buf), 4);
-            assert_eq!(
-                buf,
-                [
-                    0u8.wrapping_add(i as u8),
-                    1u8.wrapping_add(i as u8),
-                    2u8.wrapping_add(i as u8),
-                    3u8.wrapping_add(i as u8)
-                ]
-            );
-        }
+        assert_err_eq!(writer.write(b"hello, world!"), Error::WriteFailure);
     }
 
     #[test]
@@ -360,24 +1858,241 @@ mod tests {
         not(sram),
         ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
     )]
-    fn partial_range() {
+    fn writer_with_retries() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer_with_retries(..RangedUsize::new_static::<13>(), 3);
+
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+
+        let mut reader = sram.reader(..RangedUsize::new_static::<13>());
+        let mut buf = [0; 13];
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf, b"hello, world!");
+    }
+
+    #[test]
+    #[cfg_attr(
+        sram,
+        ignore = "This test cannot be run with an SRAM chip. Ensure SRAM is not configured and do not pass `--cfg sram` to enable."
+    )]
+    fn write_failure_retries_exhausted() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer_with_retries(.., 3);
+
+        assert_err_eq!(
+            writer.write(b"hello, world!"),
+            Error::RetriesExhausted { attempts: 4 }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        sram,
+        ignore = "This test cannot be run with an SRAM chip. Ensure SRAM is not configured and do not pass `--cfg sram` to enable."
+    )]
+    fn write_failure_max_retries_exhausted() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer_with_retries(.., u8::MAX);
+
+        assert_err_eq!(
+            writer.write(b"hello, world!"),
+            Error::RetriesExhausted { attempts: 256 }
+        );
+        assert_eq!(writer.stats().retries, 255);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn writer_stats() {
         let mut sram = unsafe { Sram::new() };
         let mut writer =
-            sram.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+            sram.writer(RangedUsize::new_static::<1>()..RangedUsize::new_static::<14>());
 
-        assert_ok_eq!(writer.write(&[b'a'; 100]), 58);
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+        assert_eq!(
+            writer.stats(),
+            WriteStats {
+                start: 1,
+                len: 13,
+                bytes_written: 13,
+                retries: 0,
+                failures: 0,
+                elapsed: None,
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        sram,
+        ignore = "This test cannot be run with an SRAM chip. Ensure SRAM is not configured and do not pass `--cfg sram` to enable."
+    )]
+    fn writer_stats_failure() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer_with_retries(.., 3);
+
+        assert_err!(writer.write(b"hello, world!"));
+        let stats = writer.stats();
+        assert_eq!(stats.retries, 3);
+        assert_eq!(stats.failures, 1);
+        assert!(!stats.is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn verify_present() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..RangedUsize::new_static::<1>());
+        assert_ok_eq!(writer.write(&[42]), 1);
+
+        assert_ok!(sram.verify_present());
+
+        let mut reader = sram.reader(..RangedUsize::new_static::<1>());
+        let mut buf = [0];
+        assert_ok_eq!(reader.read(&mut buf), 1);
+        assert_eq!(buf, [42]);
+    }
+
+    #[test]
+    #[cfg_attr(
+        sram,
+        ignore = "This test cannot be run with an SRAM chip. Ensure SRAM is not configured and do not pass `--cfg sram` to enable."
+    )]
+    fn verify_present_not_present() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_err_eq!(sram.verify_present(), Error::WriteFailure);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn detect_mirroring() {
+        let mut sram = unsafe { Sram::new() };
+        let original = unsafe { SRAM_MEMORY.read_volatile() };
+
+        assert_ok_eq!(sram.detect_mirroring(), true);
+        assert_eq!(unsafe { SRAM_MEMORY.read_volatile() }, original);
+    }
+
+    #[test]
+    #[cfg_attr(
+        sram,
+        ignore = "This test cannot be run with an SRAM chip. Ensure SRAM is not configured and do not pass `--cfg sram` to enable."
+    )]
+    fn detect_mirroring_not_present() {
+        let mut sram = unsafe { Sram::new() };
+
+        assert_err_eq!(sram.detect_mirroring(), Error::WriteFailure);
+    }
+
+    #[test]
+    fn try_reader_mirror_not_mirrored() {
+        let sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.try_reader(32810..32868).map(|_| ()),
+            RangeError::OutOfBounds {
+                value: 32810,
+                max: 32768
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn try_writer_mirrored() {
+        let mut sram = unsafe { Sram::new() };
+        sram.set_mirrored(true);
+        let mut writer = assert_ok!(sram.try_writer(32810..32823));
+
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
 
         let mut reader =
-            sram.reader(RangedUsize::new_static::<51>()..RangedUsize::new_static::<60>());
-        let mut buf = [0; 20];
+            sram.reader(RangedUsize::new_static::<42>()..RangedUsize::new_static::<55>());
+        let mut buf = [0; 13];
+        assert_ok_eq!(reader.read(&mut buf), 13);
+        assert_eq!(&buf, b"hello, world!");
+    }
+
+    #[test]
+    fn try_reader_mirrored_full() {
+        let mut sram = unsafe { Sram::new() };
+        sram.set_mirrored(true);
+
+        assert_eq!(assert_ok!(sram.try_reader(32768..65536)).len, 32768);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn read_uninit() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..RangedUsize::new_static::<13>());
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+
+        let mut reader = sram.reader(..RangedUsize::new_static::<13>());
+        let mut buf = [MaybeUninit::uninit(); 13];
+
+        assert_eq!(assert_ok!(reader.read_uninit(&mut buf)), b"hello, world!");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn read_uninit_partial() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer = sram.writer(..RangedUsize::new_static::<13>());
+        assert_ok_eq!(writer.write(b"hello, world!"), 13);
+
+        let mut reader = sram.reader(..RangedUsize::new_static::<5>());
+        let mut buf = [MaybeUninit::uninit(); 13];
+
+        assert_eq!(assert_ok!(reader.read_uninit(&mut buf)), b"hello");
+        assert_eq!(assert_ok!(reader.read_uninit(&mut buf)), b"");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn read_uninit_empty_buffer() {
+        let sram = unsafe { Sram::new() };
+        let mut reader = sram.reader(..);
+
+        assert_eq!(assert_ok!(reader.read_uninit(&mut [])), b"");
+    }
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn self_test() {
+        let mut sram = unsafe { Sram::new() };
 
-        assert_ok_eq!(reader.read(&mut buf), 9);
         assert_eq!(
-            buf,
-            [
-                b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', b'a', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0
-            ]
+            sram.self_test(RangedUsize::new_static::<32704>()..),
+            SelfTestReport {
+                write_ok: true,
+                verify_ok: true,
+                latency: 0,
+            }
         );
     }
 
@@ -386,10 +2101,71 @@ mod tests {
         sram,
         ignore = "This test cannot be run with an SRAM chip. Ensure SRAM is not configured and do not pass `--cfg sram` to enable."
     )]
-    fn write_failure() {
+    fn self_test_not_present() {
         let mut sram = unsafe { Sram::new() };
-        let mut writer = sram.writer(..);
 
-        assert_err_eq!(writer.write(b"hello, world!"), Error::WriteFailure);
+        assert!(!sram.self_test(RangedUsize::new_static::<32704>()..).is_ok());
+    }
+
+    #[test]
+    fn reader_debug() {
+        let sram = unsafe { Sram::new() };
+        let reader = sram.reader(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+        let mut buffer = Buffer::<128>::new();
+
+        write!(buffer, "{reader:?}").unwrap();
+
+        assert_eq!(
+            buffer.as_str(),
+            "Sram::Reader { range: 42..100, position: 42, remaining: 58 }"
+        );
+    }
+
+    #[test]
+    fn reader_display() {
+        let sram = unsafe { Sram::new() };
+        let reader = sram.reader(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+        let mut buffer = Buffer::<128>::new();
+
+        write!(buffer, "{reader}").unwrap();
+
+        assert_eq!(buffer.as_str(), "SRAM 42..100 at 42");
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn writer_debug_after_write() {
+        let mut sram = unsafe { Sram::new() };
+        let mut writer =
+            sram.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+        assert_ok!(writer.write_all(&[0; 16]));
+        let mut buffer = Buffer::<128>::new();
+
+        write!(buffer, "{writer:?}").unwrap();
+
+        assert_eq!(
+            buffer.as_str(),
+            "Sram::Writer { range: 42..100, position: 58, remaining: 42 }"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn reader_display_after_read() {
+        let sram = unsafe { Sram::new() };
+        let mut reader =
+            sram.reader(RangedUsize::new_static::<42>()..RangedUsize::new_static::<100>());
+        assert_ok!(reader.read_exact(&mut [0; 16]));
+        let mut buffer = Buffer::<128>::new();
+
+        write!(buffer, "{reader}").unwrap();
+
+        assert_eq!(buffer.as_str(), "SRAM 42..100 at 58");
     }
 }
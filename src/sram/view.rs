@@ -0,0 +1,312 @@
+use core::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    mem::{size_of, MaybeUninit},
+};
+
+/// Types that can be loaded from any sequence of bytes.
+///
+/// This is required by [`VolatileView`], which copies values out of SRAM byte by byte.
+///
+/// # Safety
+/// Every sequence of `size_of::<Self>()` bytes must be a valid value of `Self`. This rules out
+/// types such as `bool`, `char`, enums, references, and structs containing any of them.
+///
+/// Implementors should also be `#[repr(C)]` or `#[repr(transparent)]`, so that their layout, and
+/// therefore the meaning of the data saved in SRAM, does not change between builds.
+pub unsafe trait FromBytes: Copy {}
+
+macro_rules! impl_from_bytes {
+    ($($ty:ty),*) => {
+        $(unsafe impl FromBytes for $ty {})*
+    };
+}
+
+impl_from_bytes!(u8, u16, u32, u64, u128, usize);
+impl_from_bytes!(i8, i16, i32, i64, i128, isize);
+impl_from_bytes!(());
+
+unsafe impl<T, const N: usize> FromBytes for [T; N] where T: FromBytes {}
+
+/// A view of a value stored in SRAM.
+///
+/// This is returned by [`Sram::view()`](super::Sram::view()). Values are read directly from SRAM
+/// when requested, rather than through a reader and a staging buffer. No reference to SRAM is
+/// ever created; every access is a volatile read of a single byte, as SRAM only has an 8-bit bus.
+///
+/// SRAM cannot be written while a view exists, as the view borrows the SRAM accessor.
+pub struct VolatileView<'a, T> {
+    address: *const u8,
+    lifetime: PhantomData<&'a T>,
+}
+
+impl<T> VolatileView<'_, T>
+where
+    T: FromBytes,
+{
+    /// Creates a view of the value at `address`.
+    ///
+    /// # Safety
+    /// `address..address + size_of::<T>()` must be within SRAM.
+    pub(super) unsafe fn new_unchecked(address: *const u8) -> Self {
+        Self {
+            address,
+            lifetime: PhantomData,
+        }
+    }
+
+    /// Reads `len` bytes starting at `offset` within the view into `value`.
+    ///
+    /// # Safety
+    /// `offset + len` must be at most `size_of::<T>()`, and `value` must be valid for writes of
+    /// `len` bytes.
+    unsafe fn copy_to(&self, offset: usize, len: usize, value: *mut u8) {
+        for index in 0..len {
+            unsafe {
+                value
+                    .add(index)
+                    .write(self.address.add(offset + index).read_volatile())
+            };
+        }
+    }
+
+    /// Reads the whole value out of SRAM.
+    pub fn get(&self) -> T {
+        let mut value = MaybeUninit::<T>::uninit();
+        unsafe { self.copy_to(0, size_of::<T>(), value.as_mut_ptr().cast()) };
+        // SAFETY: All bytes of `value` were written, and `T: FromBytes` accepts any bytes.
+        unsafe { value.assume_init() }
+    }
+
+    /// Reads a single field of the value out of SRAM.
+    ///
+    /// `field` is given a pointer to the value and must return a pointer to the field within it,
+    /// usually computed with [`addr_of!`](core::ptr::addr_of). Only the bytes of the field are
+    /// read.
+    ///
+    /// # Panics
+    /// Panics if the pointer returned by `field` is not within the value.
+    ///
+    /// # Example
+    /// ``` no_run
+    /// use core::ptr::addr_of;
+    /// use gba_save::sram::{FromBytes, Sram};
+    ///
+    /// #[derive(Clone, Copy)]
+    /// #[repr(C)]
+    /// struct Save {
+    ///     score: u32,
+    ///     level: u8,
+    /// }
+    ///
+    /// unsafe impl FromBytes for Save {}
+    ///
+    /// let sram = unsafe { Sram::new() };
+    /// let view = sram.view::<Save>(0).expect("save is within SRAM");
+    /// let level = view.read_field(|save| unsafe { addr_of!((*save).level) });
+    /// ```
+    pub fn read_field<F>(&self, field: impl FnOnce(*const T) -> *const F) -> F
+    where
+        F: FromBytes,
+    {
+        let offset = (field(self.address.cast()) as usize).wrapping_sub(self.address as usize);
+        assert!(
+            offset <= size_of::<T>() && size_of::<F>() <= size_of::<T>() - offset,
+            "field is not within the viewed value"
+        );
+        let mut value = MaybeUninit::<F>::uninit();
+        unsafe { self.copy_to(offset, size_of::<F>(), value.as_mut_ptr().cast()) };
+        // SAFETY: All bytes of `value` were written, and `F: FromBytes` accepts any bytes.
+        unsafe { value.assume_init() }
+    }
+
+    /// Returns the offset of the value within SRAM.
+    pub fn offset(&self) -> usize {
+        self.address as usize - super::SRAM_MEMORY as usize
+    }
+}
+
+impl<'a, T, const N: usize> VolatileView<'a, [T; N]>
+where
+    T: FromBytes,
+{
+    /// Returns a view of the element at `index`, or `None` if `index` is out of bounds.
+    ///
+    /// This allows reading single entries of a table stored in SRAM without reading the whole
+    /// table.
+    pub fn index(&self, index: usize) -> Option<VolatileView<'a, T>> {
+        (index < N).then(|| VolatileView {
+            address: unsafe { self.address.add(index * size_of::<T>()) },
+            lifetime: PhantomData,
+        })
+    }
+}
+
+impl<T> Clone for VolatileView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for VolatileView<'_, T> {}
+
+impl<T> Debug for VolatileView<'_, T> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let start = self.address as usize - super::SRAM_MEMORY as usize;
+        formatter
+            .debug_struct("Sram::VolatileView")
+            .field("range", &(start..start + size_of::<T>()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FromBytes;
+    use crate::{range::RangeError, sram::Sram, test_util::Buffer};
+    use claims::{assert_err_eq, assert_none, assert_ok, assert_some};
+    use core::{fmt::Write as _, ptr::addr_of};
+    use deranged::RangedUsize;
+    use embedded_io::Write;
+    use gba_test::test;
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    #[repr(C)]
+    struct Entry {
+        id: u16,
+        flags: u8,
+        level: u8,
+        score: u32,
+    }
+
+    unsafe impl FromBytes for Entry {}
+
+    const ENTRY_BYTES: [u8; 8] = [0x34, 0x12, 0x01, 0x05, 0x78, 0x56, 0x34, 0x12];
+
+    fn write_entries(sram: &mut Sram) {
+        let mut writer =
+            sram.writer(RangedUsize::new_static::<42>()..RangedUsize::new_static::<58>());
+        assert_ok!(writer.write_all(&ENTRY_BYTES));
+        assert_ok!(writer.write_all(&[0xff; 8]));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn get() {
+        let mut sram = unsafe { Sram::new() };
+        write_entries(&mut sram);
+
+        let view = assert_ok!(sram.view::<Entry>(42));
+
+        assert_eq!(
+            view.get(),
+            Entry {
+                id: 0x1234,
+                flags: 1,
+                level: 5,
+                score: 0x1234_5678,
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn read_field() {
+        let mut sram = unsafe { Sram::new() };
+        write_entries(&mut sram);
+
+        let view = assert_ok!(sram.view::<Entry>(42));
+
+        assert_eq!(
+            view.read_field(|entry| unsafe { addr_of!((*entry).level) }),
+            5
+        );
+        assert_eq!(
+            view.read_field(|entry| unsafe { addr_of!((*entry).score) }),
+            0x1234_5678
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn index() {
+        let mut sram = unsafe { Sram::new() };
+        write_entries(&mut sram);
+
+        let view = assert_ok!(sram.view::<[Entry; 2]>(42));
+
+        assert_eq!(assert_some!(view.index(1)).get().id, 0xffff);
+        assert_none!(view.index(2));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn mirrored() {
+        let mut sram = unsafe { Sram::new() };
+        write_entries(&mut sram);
+        sram.set_mirrored(true);
+
+        let view = assert_ok!(sram.view::<u16>(32810));
+
+        assert_eq!(view.offset(), 42);
+        assert_eq!(view.get(), 0x1234);
+    }
+
+    #[test]
+    #[should_panic(expected = "field is not within the viewed value")]
+    fn read_field_outside_value() {
+        let sram = unsafe { Sram::new() };
+        let view = assert_ok!(sram.view::<u16>(42));
+
+        view.read_field(|value| unsafe { value.add(1) });
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.view::<[u8; 16]>(32760).map(|_| ()),
+            RangeError::OutOfBounds {
+                value: 32776,
+                max: 32768
+            }
+        );
+    }
+
+    #[test]
+    fn offset_overflow() {
+        let sram = unsafe { Sram::new() };
+
+        assert_err_eq!(
+            sram.view::<u32>(usize::MAX).map(|_| ()),
+            RangeError::OutOfBounds {
+                value: usize::MAX,
+                max: 32768
+            }
+        );
+    }
+
+    #[test]
+    fn debug() {
+        let sram = unsafe { Sram::new() };
+        let view = assert_ok!(sram.view::<Entry>(42));
+        let mut buffer = Buffer::<64>::new();
+
+        write!(buffer, "{view:?}").unwrap();
+
+        assert_eq!(buffer.as_str(), "Sram::VolatileView { range: 42..50 }");
+    }
+}
@@ -0,0 +1,134 @@
+//! Statistics counters for backup memory operations.
+//!
+//! When the `stats` feature is enabled, this crate counts the bytes read and written, the sectors
+//! erased and pages programmed, and the verification retries and failures that occur across all
+//! backup devices. These counters can be inspected at any time using [`snapshot()`], and cleared
+//! using [`reset()`].
+//!
+//! The counters are plain `u32` values that wrap on overflow. Interrupts are disabled for the
+//! duration of each access to the counters, so they remain consistent even when backup memory is
+//! accessed, or the counters are inspected or reset, from within an interrupt handler.
+//!
+//! ``` no_run
+//! use gba_save::stats;
+//!
+//! let stats = stats::snapshot();
+//! // Display `stats.bytes_written`, `stats.sectors_erased`, etc.
+//! ```
+
+use crate::mmio::without_interrupts;
+use core::ptr;
+
+/// A snapshot of the statistics counters.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Stats {
+    /// Bytes written to backup memory.
+    pub bytes_written: u32,
+    /// Bytes read from backup memory.
+    pub bytes_read: u32,
+    /// Flash sectors erased.
+    ///
+    /// Erasing an entire flash chip counts every 4KiB sector on the chip.
+    pub sectors_erased: u32,
+    /// 128-byte pages programmed on Atmel flash devices.
+    pub pages_programmed: u32,
+    /// Writes that were retried after failing verification.
+    pub verify_retries: u32,
+    /// Operations that ultimately failed verification.
+    pub verification_failures: u32,
+}
+
+impl Stats {
+    const fn new() -> Self {
+        Self {
+            bytes_written: 0,
+            bytes_read: 0,
+            sectors_erased: 0,
+            pages_programmed: 0,
+            verify_retries: 0,
+            verification_failures: 0,
+        }
+    }
+}
+
+static mut STATS: Stats = Stats::new();
+
+/// Returns the current values of the statistics counters.
+pub fn snapshot() -> Stats {
+    // SAFETY: Interrupts are disabled, so nothing else can access `STATS` during the read.
+    without_interrupts(|| unsafe { ptr::addr_of!(STATS).read() })
+}
+
+/// Resets all statistics counters to zero.
+pub fn reset() {
+    // SAFETY: Interrupts are disabled, so nothing else can access `STATS` during the write.
+    without_interrupts(|| unsafe { ptr::addr_of_mut!(STATS).write(Stats::new()) })
+}
+
+pub(crate) fn record<F>(f: F)
+where
+    F: FnOnce(&mut Stats),
+{
+    // SAFETY: Interrupts are disabled while the reference is live, so nothing else can access
+    // `STATS` until it is dropped. `f` only updates counters and cannot reach `snapshot()` or
+    // `reset()`.
+    without_interrupts(|| f(unsafe { &mut *ptr::addr_of_mut!(STATS) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{record, reset, snapshot, Stats};
+    use crate::mmio::IME;
+    use gba_test::test;
+
+    #[test]
+    fn reset_clears_counters() {
+        record(|stats| {
+            stats.bytes_written += 1;
+            stats.bytes_read += 2;
+            stats.sectors_erased += 3;
+            stats.pages_programmed += 4;
+            stats.verify_retries += 5;
+            stats.verification_failures += 6;
+        });
+        reset();
+
+        assert_eq!(snapshot(), Stats::default());
+    }
+
+    #[test]
+    fn record_increments() {
+        reset();
+        record(|stats| stats.bytes_written += 42);
+        record(|stats| stats.bytes_written += 8);
+        record(|stats| stats.sectors_erased += 1);
+
+        assert_eq!(
+            snapshot(),
+            Stats {
+                bytes_written: 50,
+                sectors_erased: 1,
+                ..Stats::default()
+            }
+        );
+    }
+
+    #[test]
+    fn record_macro_wraps() {
+        reset();
+        record(|stats| stats.bytes_read = u32::MAX);
+        record_stat!(bytes_read, 2);
+
+        assert_eq!(snapshot().bytes_read, 1);
+    }
+
+    #[test]
+    fn record_disables_interrupts() {
+        unsafe { IME.write_volatile(true) };
+        record(|_| assert!(!unsafe { IME.read_volatile() }));
+
+        // Interrupts are enabled again afterward.
+        assert!(unsafe { IME.read_volatile() });
+        unsafe { IME.write_volatile(false) };
+    }
+}
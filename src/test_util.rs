@@ -0,0 +1,184 @@
+use core::{
+    fmt,
+    fmt::Write,
+    hash::{Hash, Hasher},
+    str,
+    time::Duration,
+};
+
+/// A fixed-size buffer that can be formatted into.
+pub(crate) struct Buffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Buffer<N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+}
+
+impl<const N: usize> Write for Buffer<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// A 64-bit FNV-1a hasher, used to check that equal values hash equally.
+struct FnvHasher(u64);
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// Returns the hash of `value`.
+pub(crate) fn hash<T>(value: &T) -> u64
+where
+    T: Hash,
+{
+    let mut hasher = FnvHasher(0xcbf2_9ce4_8422_2325);
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The size of the largest buffer used by [`assert_read_sizes!`] and [`assert_write_sizes!`].
+pub(crate) const HUGE: usize = 512;
+
+/// Returns the buffer sizes exercised against a reader or writer with `remaining` bytes left.
+pub(crate) fn buffer_sizes(remaining: usize) -> [usize; 5] {
+    [0, 1, remaining, remaining + 1, HUGE]
+}
+
+/// Asserts that readers created by `$reader` read the expected number of bytes into buffers of
+/// every size in [`buffer_sizes()`], and then read the rest of their data and nothing more.
+macro_rules! assert_read_sizes {
+    ($reader:expr, $remaining:expr) => {{
+        let remaining: usize = $remaining;
+        let mut buf = [0; $crate::test_util::HUGE];
+        for size in $crate::test_util::buffer_sizes(remaining) {
+            let mut reader = $reader;
+            let read = size.min(remaining);
+            assert_eq!(
+                embedded_io::Read::read(&mut reader, &mut buf[..size]),
+                Ok(read),
+                "buffer size {size}"
+            );
+            if size > 0 {
+                assert_eq!(
+                    embedded_io::Read::read(&mut reader, &mut buf),
+                    Ok(remaining - read),
+                    "buffer size {size}"
+                );
+            }
+            assert_eq!(
+                embedded_io::Read::read(&mut reader, &mut buf[..1]),
+                Ok(if size == 0 { 1.min(remaining) } else { 0 }),
+                "buffer size {size}"
+            );
+        }
+    }};
+}
+pub(crate) use assert_read_sizes;
+
+/// Asserts that writers created by `$writer` write the expected number of bytes from buffers of
+/// every size in [`buffer_sizes()`], and then report that they are exhausted.
+///
+/// Every byte written is `0xff`, so that flash writers can write to erased memory repeatedly.
+macro_rules! assert_write_sizes {
+    ($writer:expr, $remaining:expr) => {{
+        let remaining: usize = $remaining;
+        let buf = [0xff; $crate::test_util::HUGE];
+        for size in $crate::test_util::buffer_sizes(remaining) {
+            let mut writer = $writer;
+            let written = size.min(remaining);
+            assert_eq!(
+                embedded_io::Write::write(&mut writer, &buf[..size]).ok(),
+                Some(written),
+                "buffer size {size}"
+            );
+            if remaining > written {
+                assert_eq!(
+                    embedded_io::Write::write(&mut writer, &buf).ok(),
+                    Some(remaining - written),
+                    "buffer size {size}"
+                );
+            }
+            let error = embedded_io::Write::write(&mut writer, &buf[..1]).unwrap_err();
+            assert_eq!(
+                embedded_io::Error::kind(&error),
+                embedded_io::ErrorKind::WriteZero,
+                "buffer size {size}"
+            );
+            assert!(
+                embedded_io::Write::flush(&mut writer).is_ok(),
+                "buffer size {size}"
+            );
+        }
+    }};
+}
+pub(crate) use assert_write_sizes;
+
+/// Timer 2 counter/reload.
+const TM2CNT_L: *mut u16 = 0x0400_0108 as *mut u16;
+/// Timer 2 control.
+const TM2CNT_H: *mut u16 = 0x0400_010a as *mut u16;
+/// Timer 3 counter/reload.
+const TM3CNT_L: *mut u16 = 0x0400_010c as *mut u16;
+/// Timer 3 control.
+const TM3CNT_H: *mut u16 = 0x0400_010e as *mut u16;
+
+/// Runs `f`, returning its result along with the time it took as measured by the hardware timers.
+///
+/// Timer 2 counts at 262144Hz and timer 3 counts its overflows, so durations of up to several
+/// hours can be measured with a resolution of about 4 microseconds. Timers 0 and 1 are left for
+/// tests that need them.
+pub(crate) fn measure<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    unsafe {
+        TM2CNT_H.write_volatile(0);
+        TM3CNT_H.write_volatile(0);
+        TM2CNT_L.write_volatile(0);
+        TM3CNT_L.write_volatile(0);
+        // Cascade timer 3 first, so that it counts every overflow of timer 2.
+        TM3CNT_H.write_volatile(0x0084);
+        TM2CNT_H.write_volatile(0x0081);
+    }
+    let result = f();
+    unsafe { TM2CNT_H.write_volatile(0) };
+    let ticks =
+        unsafe { (TM3CNT_L.read_volatile() as u64) << 16 | TM2CNT_L.read_volatile() as u64 };
+    unsafe { TM3CNT_H.write_volatile(0) };
+    (
+        result,
+        Duration::from_nanos(ticks * 1_000_000_000 / 262_144),
+    )
+}
+
+/// Logs the latency measured for `operation`, for comparison against documented expectations.
+///
+/// Nothing is logged unless the `log` feature is enabled.
+#[cfg_attr(not(feature = "log"), allow(unused_variables))]
+pub(crate) fn log_latency(operation: &str, latency: Duration) {
+    #[cfg(feature = "log")]
+    log::info!("{operation} took {latency:?}");
+}
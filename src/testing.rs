@@ -0,0 +1,203 @@
+//! Helpers for hardware test suites.
+//!
+//! These helpers put backup memory into a known state and write and check a deterministic byte
+//! pattern, which is the same pattern used by this crate's own full-range tests. They are intended
+//! for use in `gba_test`-based test suites, and panic on failure rather than returning errors.
+//!
+//! ``` no_run
+//! use gba_save::{sram::Sram, testing};
+//!
+//! let mut sram = unsafe { Sram::new() };
+//! testing::clear_sram(&mut sram);
+//! testing::fill_pattern(&mut sram.writer(..), 42);
+//! testing::assert_pattern(&mut sram.reader(..), 42);
+//! ```
+//!
+//! This module is only available with the `testing` feature enabled.
+
+use crate::{flash::Flash, sram::Sram};
+use core::time::Duration;
+use embedded_io::{Error as _, ErrorKind, Read, Write};
+
+/// Returns the byte at `index` in the pattern generated from `seed`.
+///
+/// Each group of four bytes counts up from the index of the group, offset by `seed`.
+pub fn pattern_byte(seed: u8, index: usize) -> u8 {
+    seed.wrapping_add((index % 4) as u8)
+        .wrapping_add((index / 4) as u8)
+}
+
+/// Sets every byte of SRAM to `0x00`.
+///
+/// # Panics
+/// Panics if any byte cannot be written.
+pub fn clear_sram(sram: &mut Sram) {
+    let mut writer = sram.writer(..);
+    let zeros = [0; 32];
+    loop {
+        match writer.write(&zeros) {
+            Ok(_) => {}
+            Err(error) if error.kind() == ErrorKind::WriteZero => break,
+            Err(error) => panic!("failed to clear SRAM: {error:?}"),
+        }
+    }
+}
+
+/// Erases the entire flash device and waits for it to become ready.
+///
+/// # Panics
+/// Panics if the device cannot be erased.
+pub fn clear_flash(flash: &mut Flash) {
+    if let Err(error) = flash.reset() {
+        panic!("failed to erase flash: {error:?}");
+    }
+    if let Err(error) = flash.wait_ready(Duration::from_millis(20)) {
+        panic!("flash did not become ready after erasing: {error:?}");
+    }
+}
+
+/// Writes the pattern generated from `seed` until `writer` is exhausted, returning the number of
+/// bytes written.
+///
+/// The writer is flushed once it is exhausted.
+///
+/// # Panics
+/// Panics if the writer reports any error other than being exhausted.
+pub fn fill_pattern<W>(writer: &mut W, seed: u8) -> usize
+where
+    W: Write,
+{
+    let mut chunk = [0; 32];
+    let mut written = 0;
+    loop {
+        for (i, byte) in chunk.iter_mut().enumerate() {
+            *byte = pattern_byte(seed, written + i);
+        }
+        match writer.write(&chunk) {
+            Ok(count) => written += count,
+            Err(error) if error.kind() == ErrorKind::WriteZero => break,
+            Err(error) => panic!("failed to write pattern at offset {written}: {error:?}"),
+        }
+    }
+    if let Err(error) = writer.flush() {
+        panic!("failed to flush pattern: {error:?}");
+    }
+    written
+}
+
+/// Reads from `reader` until it is exhausted, asserting that every byte matches the pattern
+/// generated from `seed`. Returns the number of bytes read.
+///
+/// # Panics
+/// Panics if any byte does not match the pattern, or if the reader reports an error.
+pub fn assert_pattern<R>(reader: &mut R, seed: u8) -> usize
+where
+    R: Read,
+{
+    let mut chunk = [0; 32];
+    let mut read = 0;
+    loop {
+        let count = match reader.read(&mut chunk) {
+            Ok(0) => return read,
+            Ok(count) => count,
+            Err(error) => panic!("failed to read pattern at offset {read}: {error:?}"),
+        };
+        for (i, &byte) in chunk[..count].iter().enumerate() {
+            let expected = pattern_byte(seed, read + i);
+            assert_eq!(
+                byte,
+                expected,
+                "pattern mismatch at offset {}: expected {expected:#04x}, found {byte:#04x}",
+                read + i
+            );
+        }
+        read += count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_pattern, clear_flash, clear_sram, fill_pattern, pattern_byte};
+    use crate::{flash::Flash, sram::Sram};
+    use claims::assert_ok;
+    use embedded_io::Read;
+    use gba_test::test;
+
+    #[test]
+    fn pattern_byte_matches_full_range_tests() {
+        assert_eq!(
+            [0, 1, 2, 3, 4, 5, 6, 7].map(|index| pattern_byte(0, index)),
+            [0, 1, 2, 3, 1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn pattern_byte_seed() {
+        assert_eq!(pattern_byte(255, 5), 1);
+    }
+
+    #[test]
+    fn fill_and_assert_pattern() {
+        let mut buf = [0; 100];
+
+        assert_eq!(fill_pattern(&mut &mut buf[..], 7), 100);
+        assert_eq!(assert_pattern(&mut &buf[..], 7), 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "pattern mismatch at offset 50")]
+    fn assert_pattern_mismatch() {
+        let mut buf = [0; 100];
+        fill_pattern(&mut &mut buf[..], 7);
+        buf[50] ^= 1;
+
+        assert_pattern(&mut &buf[..], 7);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn sram_pattern() {
+        let mut sram = unsafe { Sram::new() };
+        clear_sram(&mut sram);
+
+        assert_eq!(fill_pattern(&mut sram.writer(..), 42), 32768);
+        assert_eq!(assert_pattern(&mut sram.reader(..), 42), 32768);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(sram),
+        ignore = "This test requires an SRAM chip. Ensure SRAM is configured and pass `--cfg sram` to enable."
+    )]
+    fn clear_sram_zeroes() {
+        let mut sram = unsafe { Sram::new() };
+        fill_pattern(&mut sram.writer(..), 1);
+
+        clear_sram(&mut sram);
+
+        let mut reader = sram.reader(..);
+        let mut buf = [0xff; 32];
+        while let Ok(count @ 1..) = reader.read(&mut buf) {
+            assert!(buf[..count].iter().all(|&byte| byte == 0));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn clear_flash_erases() {
+        let mut flash = assert_ok!(unsafe { Flash::new() });
+
+        clear_flash(&mut flash);
+
+        let Flash::Flash64K(flash_64k) = flash else {
+            panic!("expected a Flash 64KiB device");
+        };
+        assert!(flash_64k.is_erased(..));
+    }
+}
@@ -0,0 +1,202 @@
+//! Measuring time while waiting on backup memory.
+//!
+//! The GBA's hardware timers belong to the application, so this crate cannot use them. Instead,
+//! time is measured by counting iterations of a busy loop. Each iteration is estimated to take
+//! [`CYCLES_PER_ITERATION`] cycles of the 16.78MHz CPU, which is the cost of the loop when run
+//! from ROM with the default waitstates. Code run from faster memory iterates more quickly, so
+//! waits measured this way should be treated as approximate.
+//!
+//! Both flash timeouts and waits between device commands are measured this way, so that a
+//! [`Duration`] means the same thing everywhere in this crate.
+//!
+//! Busy loops cannot measure how long an operation took, so elapsed times are only reported when
+//! the application provides a clock using [`set_clock()`]. The same clock times reads for
+//! logging and writes for [`WriteStats`](crate::WriteStats).
+
+use crate::mmio::without_interrupts;
+use core::{hint::black_box, ptr, time::Duration};
+
+/// The frequency of the CPU, in cycles per second.
+const CPU_FREQUENCY: u128 = 1 << 24;
+/// The estimated number of CPU cycles taken by each iteration of a busy loop.
+const CYCLES_PER_ITERATION: u128 = 16;
+
+/// A clock provided by the application, along with its frequency in ticks per second.
+///
+/// This is only accessed with interrupts disabled, so an interrupt handler calling
+/// [`set_clock()`] cannot race with a read of it.
+static mut CLOCK: Option<(fn() -> u32, u32)> = None;
+
+/// Sets the clock used to measure how long reads and writes take.
+///
+/// This crate does not own any hardware timers, so elapsed times are only measured if the
+/// application provides a clock. `clock` should return a free-running tick count, such as one read
+/// from a pair of cascaded hardware timers, which is allowed to wrap. `ticks_per_second` is the
+/// frequency at which it counts.
+///
+/// This may be called at any time, including from an interrupt handler. `clock` itself may be
+/// called from any context that reads or writes backup memory.
+pub fn set_clock(clock: fn() -> u32, ticks_per_second: u32) {
+    // SAFETY: Interrupts are disabled, so nothing else can access `CLOCK` during the write.
+    without_interrupts(|| unsafe {
+        ptr::addr_of_mut!(CLOCK).write(Some((clock, ticks_per_second)))
+    });
+}
+
+fn clock() -> Option<(fn() -> u32, u32)> {
+    // SAFETY: Interrupts are disabled, so nothing else can access `CLOCK` during the read.
+    without_interrupts(|| unsafe { ptr::addr_of!(CLOCK).read() })
+}
+
+/// Converts a number of clock ticks to a duration.
+fn ticks_to_duration(ticks: u32, ticks_per_second: u32) -> Duration {
+    Duration::from_nanos(ticks as u64 * 1_000_000_000 / ticks_per_second.max(1) as u64)
+}
+
+/// A point in time read from the clock set by [`set_clock()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) struct Instant {
+    ticks: u32,
+}
+
+impl Instant {
+    /// Returns the current time, or `None` if no clock has been set.
+    pub(crate) fn now() -> Option<Self> {
+        clock().map(|(clock, _)| Self { ticks: clock() })
+    }
+
+    /// Returns the time elapsed since this instant, or `None` if no clock has been set.
+    pub(crate) fn elapsed(self) -> Option<Duration> {
+        clock().map(|(clock, ticks_per_second)| {
+            ticks_to_duration(clock().wrapping_sub(self.ticks), ticks_per_second)
+        })
+    }
+}
+
+/// Returns the number of busy loop iterations taking approximately `duration`.
+fn iterations(duration: Duration) -> u32 {
+    (duration.as_nanos() * CPU_FREQUENCY / CYCLES_PER_ITERATION / 1_000_000_000)
+        .try_into()
+        .unwrap_or(u32::MAX)
+}
+
+/// A point in time after which waiting should stop.
+///
+/// A deadline is measured in checks of [`expired()`](Self::expired()), each of which counts as
+/// one busy loop iteration.
+#[derive(Debug)]
+pub(crate) struct Deadline {
+    initial: u32,
+    remaining: u32,
+}
+
+impl Deadline {
+    /// Creates a deadline that expires after `checks` checks.
+    pub(crate) fn after_checks(checks: u32) -> Self {
+        Self {
+            initial: checks,
+            remaining: checks,
+        }
+    }
+
+    /// Returns whether the deadline has passed, counting this check as one iteration.
+    pub(crate) fn expired(&mut self) -> bool {
+        black_box(());
+        if self.remaining == 0 {
+            true
+        } else {
+            self.remaining -= 1;
+            false
+        }
+    }
+
+    /// Returns the number of checks made before the deadline expired.
+    pub(crate) fn spent(&self) -> u32 {
+        self.initial - self.remaining
+    }
+}
+
+/// Returns a deadline expiring after approximately `duration`.
+pub(crate) fn deadline(duration: Duration) -> Deadline {
+    Deadline::after_checks(iterations(duration))
+}
+
+/// Spins for approximately `duration`.
+pub(crate) fn spin_for(duration: Duration) {
+    let mut deadline = deadline(duration);
+    while !deadline.expired() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deadline, iterations, spin_for, ticks_to_duration, Deadline};
+    use core::time::Duration;
+    use gba_test::test;
+
+    /// Timer 0 counter/reload.
+    const TM0CNT_L: *mut u16 = 0x0400_0100 as *mut u16;
+    /// Timer 0 control.
+    const TM0CNT_H: *mut u16 = 0x0400_0102 as *mut u16;
+
+    #[test]
+    fn iterations_zero() {
+        assert_eq!(iterations(Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn iterations_one_millisecond() {
+        assert_eq!(iterations(Duration::from_millis(1)), 1048);
+    }
+
+    #[test]
+    fn iterations_saturate() {
+        assert_eq!(iterations(Duration::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn ticks_to_duration_one_second() {
+        assert_eq!(ticks_to_duration(262_144, 262_144), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn ticks_to_duration_microseconds() {
+        assert_eq!(ticks_to_duration(1, 1_000_000), Duration::from_micros(1));
+    }
+
+    #[test]
+    fn ticks_to_duration_zero_frequency() {
+        assert_eq!(ticks_to_duration(5, 0), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn deadline_expires() {
+        let mut deadline = Deadline::after_checks(2);
+
+        assert!(!deadline.expired());
+        assert!(!deadline.expired());
+        assert!(deadline.expired());
+        assert_eq!(deadline.spent(), 2);
+    }
+
+    #[test]
+    fn deadline_zero() {
+        assert!(deadline(Duration::ZERO).expired());
+    }
+
+    #[test]
+    fn spin_for_10ms() {
+        // Run timer 0 at 65536Hz, so that 10ms is about 655 ticks.
+        unsafe {
+            TM0CNT_H.write_volatile(0);
+            TM0CNT_L.write_volatile(0);
+            TM0CNT_H.write_volatile(0x0082);
+        }
+        spin_for(Duration::from_millis(10));
+        let ticks = unsafe { TM0CNT_L.read_volatile() };
+        unsafe { TM0CNT_H.write_volatile(0) };
+
+        // The cost of each iteration depends on where the code runs from, so only a loose
+        // tolerance is checked.
+        assert!((328..=1311).contains(&ticks), "spun for {ticks} ticks");
+    }
+}
@@ -0,0 +1,339 @@
+//! Verification of data written to backup memory.
+//!
+//! Both flash and SRAM verify writes by reading the written data back. Flash devices take time to
+//! complete programming, so reads are polled until they match or the timeout elapses. SRAM writes
+//! complete immediately, so SRAM verifies with a zero timeout, which reads each byte exactly once.
+
+use crate::{
+    halt::Halt,
+    time::{self, Deadline},
+};
+use core::{convert::Infallible, num::NonZeroU8, slice, time::Duration};
+use embedded_io::Read;
+
+/// The status bit toggled by devices while an operation is in progress.
+const TOGGLE_BIT: u8 = 1 << 6;
+
+/// Verification did not succeed within the timeout.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct TimedOut;
+
+/// A number of failed polls that may be shared across several verifications.
+///
+/// This allows a single timeout to cover a batch of bytes, rather than giving each byte its own
+/// timeout.
+#[derive(Debug)]
+pub(crate) struct PollBudget {
+    deadline: Deadline,
+    halt: Option<Halt>,
+}
+
+impl PollBudget {
+    /// Creates a budget of polls lasting for `timeout`, with one poll per busy loop iteration.
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self::with_halt(timeout, None)
+    }
+
+    /// Creates a budget of polls lasting for `timeout`, halting the CPU between polls if `halt`
+    /// is provided.
+    ///
+    /// When halting, one poll is performed per halt, rather than one per busy loop iteration.
+    pub(crate) fn with_halt(timeout: Duration, halt: Option<Halt>) -> Self {
+        let deadline = match halt {
+            Some(halt) => {
+                Deadline::after_checks(halt.count(timeout).try_into().unwrap_or(u32::MAX))
+            }
+            None => time::deadline(timeout),
+        };
+        Self { deadline, halt }
+    }
+
+    /// Returns the number of failed polls spent so far.
+    pub(crate) fn spent(&self) -> u32 {
+        self.deadline.spent()
+    }
+
+    /// Polls until `done` returns `true`, spending a poll each time it returns `false`.
+    pub(crate) fn poll<F>(&mut self, mut done: F) -> Result<(), TimedOut>
+    where
+        F: FnMut() -> bool,
+    {
+        loop {
+            if done() {
+                return Ok(());
+            }
+            if self.deadline.expired() {
+                return Err(TimedOut);
+            }
+
+            if let Some(halt) = self.halt {
+                halt.halt();
+            }
+        }
+    }
+
+    /// Polls until the memory at `address` matches `byte`, spending a poll for each mismatch.
+    pub(crate) fn verify_byte(&mut self, address: *const u8, byte: u8) -> Result<(), TimedOut> {
+        self.poll(|| unsafe { address.read_volatile() } == byte)
+    }
+
+    /// Polls until bit 6 of the memory at `address` stops toggling between successive reads,
+    /// spending a poll each time it toggles.
+    ///
+    /// Devices supporting toggle-bit status toggle bit 6 on every read while a program or erase
+    /// operation is in progress.
+    pub(crate) fn wait_toggle_stopped(&mut self, address: *const u8) -> Result<(), TimedOut> {
+        self.poll(|| {
+            let first = unsafe { address.read_volatile() };
+            let second = unsafe { address.read_volatile() };
+            (first ^ second) & TOGGLE_BIT == 0
+        })
+    }
+
+    /// Polls until the memory at `address` matches `byte` on `confirmations` consecutive reads,
+    /// spending a poll each time a read does not match.
+    ///
+    /// This guards against a single spurious read being mistaken for success.
+    pub(crate) fn verify_byte_confirmed(
+        &mut self,
+        address: *const u8,
+        byte: u8,
+        confirmations: NonZeroU8,
+    ) -> Result<(), TimedOut> {
+        self.poll(|| confirmed(|| unsafe { address.read_volatile() }, byte, confirmations))
+    }
+}
+
+/// Returns whether `confirmations` consecutive calls to `read` all return `byte`.
+///
+/// Reading stops at the first mismatch.
+fn confirmed<F>(mut read: F, byte: u8, confirmations: NonZeroU8) -> bool
+where
+    F: FnMut() -> u8,
+{
+    (0..confirmations.get()).all(|_| read() == byte)
+}
+
+/// Polls until the memory at `address` matches `bytes`.
+///
+/// Verification resumes from the first byte that has not yet matched, so bytes that have already
+/// been verified are not read again. The timeout is measured in failed polls, with one poll per
+/// busy loop iteration. A zero timeout reads each byte exactly once.
+pub(crate) fn verify_bytes(
+    address: *const u8,
+    bytes: &[u8],
+    timeout: Duration,
+) -> Result<(), TimedOut> {
+    let mut budget = PollBudget::new(timeout);
+    for (i, &byte) in bytes.iter().enumerate() {
+        budget.verify_byte(unsafe { address.add(i) }, byte)?;
+    }
+    Ok(())
+}
+
+/// Polls until the memory at `address` matches `byte`.
+///
+/// This follows the same timeout semantics as [`verify_bytes()`].
+pub(crate) fn verify_byte(address: *const u8, byte: u8, timeout: Duration) -> Result<(), TimedOut> {
+    verify_bytes(address, slice::from_ref(&byte), timeout)
+}
+
+/// Reads `expected.len()` bytes from `reader`, returning the index of the first byte that does
+/// not match `expected`.
+///
+/// Bytes are read in small chunks, so no buffer the size of `expected` is needed. If `reader` runs
+/// out of data, the index of the first byte that could not be read is returned.
+pub(crate) fn find_mismatch<R>(reader: &mut R, expected: &[u8]) -> Option<usize>
+where
+    R: Read<Error = Infallible>,
+{
+    let mut chunk = [0; 32];
+    let mut checked = 0;
+    for expected in expected.chunks(chunk.len()) {
+        let chunk = &mut chunk[..expected.len()];
+        let Ok(()) = reader.read_exact(chunk) else {
+            return Some(checked);
+        };
+        if let Some(index) = chunk
+            .iter()
+            .zip(expected)
+            .position(|(read, expected)| read != expected)
+        {
+            return Some(checked + index);
+        }
+        checked += expected.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{confirmed, find_mismatch, verify_byte, verify_bytes, PollBudget, TimedOut};
+    use claims::{assert_err_eq, assert_none, assert_ok, assert_some_eq};
+    use core::{num::NonZeroU8, time::Duration};
+    use gba_test::test;
+
+    #[test]
+    fn verify_bytes_matching() {
+        let bytes = [1, 2, 3, 4];
+
+        assert_ok!(verify_bytes(bytes.as_ptr(), &[1, 2, 3, 4], Duration::ZERO));
+    }
+
+    #[test]
+    fn verify_bytes_empty() {
+        assert_ok!(verify_bytes([].as_ptr(), &[], Duration::ZERO));
+    }
+
+    #[test]
+    fn verify_bytes_mismatch_zero_timeout() {
+        let bytes = [1, 2, 3, 4];
+
+        assert_err_eq!(
+            verify_bytes(bytes.as_ptr(), &[1, 2, 3, 5], Duration::ZERO),
+            TimedOut
+        );
+    }
+
+    #[test]
+    fn verify_bytes_mismatch_times_out() {
+        let bytes = [1, 2, 3, 4];
+
+        assert_err_eq!(
+            verify_bytes(bytes.as_ptr(), &[1, 2, 3, 5], Duration::from_millis(1)),
+            TimedOut
+        );
+    }
+
+    #[test]
+    fn verify_byte_matching() {
+        let byte = 42;
+
+        assert_ok!(verify_byte(&byte, 42, Duration::ZERO));
+    }
+
+    #[test]
+    fn verify_byte_mismatch() {
+        let byte = 42;
+
+        assert_err_eq!(verify_byte(&byte, 43, Duration::from_millis(1)), TimedOut);
+    }
+
+    #[test]
+    fn poll_budget_shared() {
+        let bytes = [1, 2, 3, 4];
+        let mut budget = PollBudget::new(Duration::from_millis(1));
+
+        assert_ok!(budget.verify_byte(&bytes[0], 1));
+        assert_err_eq!(budget.verify_byte(&bytes[1], 3), TimedOut);
+        // The budget has been spent, so even a matching byte is only read once.
+        assert_ok!(budget.verify_byte(&bytes[2], 3));
+        assert_err_eq!(budget.verify_byte(&bytes[3], 5), TimedOut);
+    }
+
+    #[test]
+    fn find_mismatch_matching() {
+        let bytes = [42; 100];
+
+        assert_none!(find_mismatch(&mut &bytes[..], &[42; 100]));
+    }
+
+    #[test]
+    fn find_mismatch_later_chunk() {
+        let bytes = [42; 100];
+        let mut expected = [42; 100];
+        expected[70] = 43;
+
+        assert_some_eq!(find_mismatch(&mut &bytes[..], &expected), 70);
+    }
+
+    #[test]
+    fn find_mismatch_insufficient_data() {
+        let bytes = [42; 40];
+
+        assert_some_eq!(find_mismatch(&mut &bytes[..], &[42; 100]), 32);
+    }
+
+    #[test]
+    fn poll_budget_spent() {
+        let mut polls = 0;
+        let mut budget = PollBudget::new(Duration::from_millis(1));
+
+        assert_ok!(budget.poll(|| {
+            polls += 1;
+            polls > 3
+        }));
+        assert_eq!(budget.spent(), 3);
+    }
+
+    #[test]
+    fn confirmed_all_match() {
+        let mut reads = [0xff; 4].into_iter();
+
+        assert!(confirmed(
+            || reads.next().unwrap(),
+            0xff,
+            NonZeroU8::new(4).unwrap()
+        ));
+        assert_none!(reads.next());
+    }
+
+    #[test]
+    fn confirmed_stops_at_mismatch() {
+        let mut reads = [0xff, 0x00, 0xff, 0xff].into_iter();
+
+        assert!(!confirmed(
+            || reads.next().unwrap(),
+            0xff,
+            NonZeroU8::new(4).unwrap()
+        ));
+        assert_some_eq!(reads.next(), 0xff);
+    }
+
+    #[test]
+    fn verify_byte_confirmed_ignores_spurious_match() {
+        // The first read spuriously matches, and only the reads after the glitch are stable.
+        let mut reads = [0xff, 0x00, 0xff, 0xff, 0xff, 0xff].into_iter();
+        let mut polls = 0;
+        let mut budget = PollBudget::new(Duration::from_millis(1));
+
+        assert_ok!(budget.poll(|| {
+            polls += 1;
+            confirmed(|| reads.next().unwrap(), 0xff, NonZeroU8::new(4).unwrap())
+        }));
+        assert_eq!(polls, 2);
+        assert_eq!(budget.spent(), 1);
+    }
+
+    #[test]
+    fn wait_toggle_stopped_stable() {
+        let byte = 0x40;
+
+        assert_ok!(PollBudget::new(Duration::ZERO).wait_toggle_stopped(&byte));
+    }
+
+    #[test]
+    fn verify_byte_confirmed_matching() {
+        let byte = 0xff;
+
+        assert_ok!(PollBudget::new(Duration::ZERO).verify_byte_confirmed(
+            &byte,
+            0xff,
+            NonZeroU8::new(4).unwrap()
+        ));
+    }
+
+    #[test]
+    fn verify_byte_confirmed_mismatch() {
+        let byte = 0x00;
+
+        assert_err_eq!(
+            PollBudget::new(Duration::ZERO).verify_byte_confirmed(
+                &byte,
+                0xff,
+                NonZeroU8::new(4).unwrap()
+            ),
+            TimedOut
+        );
+    }
+}
@@ -0,0 +1,504 @@
+//! Waitstate configuration.
+//!
+//! The GBA's `WAITCNT` register controls the number of cycles used when accessing cartridge
+//! memory, including backup memory. This module provides a complete model of the register,
+//! allowing other code that configures waitstates (such as ROM prefetch configuration) to
+//! coordinate with this crate.
+//!
+//! # Owned Bits
+//! The only bits of `WAITCNT` this crate ever writes are the SRAM wait control bits (bits 0-1),
+//! which set the waitstate of all backup memory except EEPROM. Constructing [`Sram`] or [`Flash`]
+//! sets them to 8 cycles, and [`report()`](crate::report()) restores their previous value when it
+//! is done. The ROM waitstates (bits 2-10, which include the EEPROM waitstate), the PHI terminal
+//! output, and the prefetch buffer are never modified, so they can be changed freely by other
+//! code, including from interrupt handlers.
+//!
+//! Each update is a read-modify-write performed with interrupts disabled, so an interrupt handler
+//! that changes other bits between the read and the write cannot have its change overwritten. The
+//! exception is [`Sram::new_with_waitstate()`] and [`Flash::new_with_waitstate()`], which write
+//! the whole value passed to them.
+//!
+//! ``` no_run
+//! use gba_save::waitstate;
+//!
+//! // Enable the prefetch buffer without clobbering the backup waitstate.
+//! unsafe {
+//!     waitstate::modify(|waitstate_control| waitstate_control.set_prefetch(true));
+//! }
+//! ```
+//!
+//! # Interoperating with the `gba` crate
+//! Code that keeps its own mirror of `WAITCNT`, such as code using the `gba` crate's register
+//! types, can use [`Sram::new_with_waitstate()`] and [`Flash::new_with_waitstate()`]. These take
+//! the caller's current value of the register and return the value that was written, so the
+//! caller's mirror stays coherent. With the `gba` feature enabled, [`WaitstateControl`] converts
+//! to and from the `gba` crate's `WaitstateControl` type.
+//!
+//! ``` ignore
+//! use gba::mmio::WAITCNT;
+//! use gba_save::sram::Sram;
+//!
+//! let (sram, waitstate_control) = unsafe { Sram::new_with_waitstate(WAITCNT.read().into()) };
+//! // `WAITCNT` now contains `waitstate_control`. Update any mirrors of the register.
+//! let waitcnt: gba::interrupts::WaitstateControl = waitstate_control.into();
+//! ```
+//!
+//! This crate only ever writes to `IME` while sending a command sequence to a flash device, and it
+//! always restores the previous value immediately afterward. No other interrupt state is modified.
+//!
+//! [`Sram`]: crate::sram::Sram
+//! [`Flash`]: crate::flash::Flash
+//! [`Sram::new_with_waitstate()`]: crate::sram::Sram::new_with_waitstate()
+//! [`Flash::new_with_waitstate()`]: crate::flash::Flash::new_with_waitstate()
+
+use crate::mmio::{without_interrupts, WAITCNT};
+
+/// A number of wait cycles.
+///
+/// This is used for the SRAM wait control and the first access of each ROM waitstate region.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u8)]
+pub enum Cycles {
+    /// 4 cycles.
+    _4 = 0,
+    /// 3 cycles.
+    _3 = 1,
+    /// 2 cycles.
+    _2 = 2,
+    /// 8 cycles.
+    _8 = 3,
+}
+
+impl Cycles {
+    fn from_bits(bits: u16) -> Self {
+        match bits & 0b11 {
+            0 => Self::_4,
+            1 => Self::_3,
+            2 => Self::_2,
+            _ => Self::_8,
+        }
+    }
+}
+
+/// The contents of the `WAITCNT` register.
+///
+/// The register is laid out as follows:
+///
+/// | Bits  | Field                                        |
+/// |-------|----------------------------------------------|
+/// | 0-1   | SRAM wait control (backup memory)            |
+/// | 2-3   | Waitstate 0 first access                     |
+/// | 4     | Waitstate 0 second access                    |
+/// | 5-6   | Waitstate 1 first access                     |
+/// | 7     | Waitstate 1 second access                    |
+/// | 8-9   | Waitstate 2 first access (includes EEPROM)   |
+/// | 10    | Waitstate 2 second access (includes EEPROM)  |
+/// | 11-12 | PHI terminal output                          |
+/// | 14    | Game Pak prefetch buffer                     |
+/// | 15    | Game Pak type flag (read-only)               |
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct WaitstateControl(u16);
+
+impl WaitstateControl {
+    /// Creates a value from the raw register bits.
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw register bits.
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    fn first_access(self, shift: u16) -> Cycles {
+        Cycles::from_bits(self.0 >> shift)
+    }
+
+    fn set_first_access(&mut self, shift: u16, cycles: Cycles) {
+        self.0 &= !(0b11 << shift);
+        self.0 |= (cycles as u16) << shift;
+    }
+
+    fn flag(self, bit: u16) -> bool {
+        self.0 & (1 << bit) != 0
+    }
+
+    fn set_flag(&mut self, bit: u16, value: bool) {
+        self.0 &= !(1 << bit);
+        self.0 |= (value as u16) << bit;
+    }
+
+    /// Returns the SRAM wait control setting, used for all backup memory except EEPROM.
+    pub fn backup_waitstate(self) -> Cycles {
+        self.first_access(0)
+    }
+
+    /// Sets the SRAM wait control setting, used for all backup memory except EEPROM.
+    pub fn set_backup_waitstate(&mut self, cycles: Cycles) {
+        self.set_first_access(0, cycles);
+    }
+
+    /// Returns the first access setting for waitstate 0.
+    pub fn waitstate_0_first_access(self) -> Cycles {
+        self.first_access(2)
+    }
+
+    /// Sets the first access setting for waitstate 0.
+    pub fn set_waitstate_0_first_access(&mut self, cycles: Cycles) {
+        self.set_first_access(2, cycles);
+    }
+
+    /// Returns whether waitstate 0 uses a short (1 cycle) second access instead of 2 cycles.
+    pub fn waitstate_0_second_access_short(self) -> bool {
+        self.flag(4)
+    }
+
+    /// Sets whether waitstate 0 uses a short (1 cycle) second access instead of 2 cycles.
+    pub fn set_waitstate_0_second_access_short(&mut self, short: bool) {
+        self.set_flag(4, short);
+    }
+
+    /// Returns the first access setting for waitstate 1.
+    pub fn waitstate_1_first_access(self) -> Cycles {
+        self.first_access(5)
+    }
+
+    /// Sets the first access setting for waitstate 1.
+    pub fn set_waitstate_1_first_access(&mut self, cycles: Cycles) {
+        self.set_first_access(5, cycles);
+    }
+
+    /// Returns whether waitstate 1 uses a short (1 cycle) second access instead of 4 cycles.
+    pub fn waitstate_1_second_access_short(self) -> bool {
+        self.flag(7)
+    }
+
+    /// Sets whether waitstate 1 uses a short (1 cycle) second access instead of 4 cycles.
+    pub fn set_waitstate_1_second_access_short(&mut self, short: bool) {
+        self.set_flag(7, short);
+    }
+
+    /// Returns the first access setting for waitstate 2, which is also used for EEPROM.
+    pub fn waitstate_2_first_access(self) -> Cycles {
+        self.first_access(8)
+    }
+
+    /// Sets the first access setting for waitstate 2, which is also used for EEPROM.
+    pub fn set_waitstate_2_first_access(&mut self, cycles: Cycles) {
+        self.set_first_access(8, cycles);
+    }
+
+    /// Returns whether waitstate 2 uses a short (1 cycle) second access instead of 8 cycles.
+    pub fn waitstate_2_second_access_short(self) -> bool {
+        self.flag(10)
+    }
+
+    /// Sets whether waitstate 2 uses a short (1 cycle) second access instead of 8 cycles.
+    pub fn set_waitstate_2_second_access_short(&mut self, short: bool) {
+        self.set_flag(10, short);
+    }
+
+    /// Returns whether the Game Pak prefetch buffer is enabled.
+    pub fn prefetch(self) -> bool {
+        self.flag(14)
+    }
+
+    /// Sets whether the Game Pak prefetch buffer is enabled.
+    pub fn set_prefetch(&mut self, prefetch: bool) {
+        self.set_flag(14, prefetch);
+    }
+}
+
+#[cfg(feature = "gba")]
+impl From<gba::interrupts::WaitstateControl> for WaitstateControl {
+    fn from(waitstate_control: gba::interrupts::WaitstateControl) -> Self {
+        Self(waitstate_control.0)
+    }
+}
+
+#[cfg(feature = "gba")]
+impl From<WaitstateControl> for gba::interrupts::WaitstateControl {
+    fn from(waitstate_control: WaitstateControl) -> Self {
+        Self(waitstate_control.0)
+    }
+}
+
+/// Reads the current value of the `WAITCNT` register.
+pub fn read() -> WaitstateControl {
+    // SAFETY: Reading `WAITCNT` has no side effects.
+    unsafe { WAITCNT.read_volatile() }
+}
+
+/// Writes a value to the `WAITCNT` register.
+///
+/// # Safety
+/// Must have ownership of all of the `WAITCNT` settings that are changed by this write. Changing
+/// the waitstate of memory currently being accessed by other code can cause that code to read
+/// invalid data.
+pub unsafe fn write(waitstate_control: WaitstateControl) {
+    unsafe { WAITCNT.write_volatile(waitstate_control) }
+}
+
+/// Modifies the `WAITCNT` register using the given function.
+///
+/// The register is read, passed to `f`, and the result is written back. Only the fields changed
+/// by `f` are modified. Interrupts are disabled for the duration, so changes made to other fields
+/// by interrupt handlers are not lost; `f` should therefore be short.
+///
+/// # Safety
+/// Must have ownership of all of the `WAITCNT` settings that are changed by `f`.
+pub unsafe fn modify<F>(f: F)
+where
+    F: FnOnce(&mut WaitstateControl),
+{
+    without_interrupts(|| {
+        let mut waitstate_control = read();
+        f(&mut waitstate_control);
+        unsafe { write(waitstate_control) };
+    })
+}
+
+/// Sets the SRAM wait control setting to 8 cycles, as required by all backup devices.
+///
+/// No other bits are modified. The value written is returned.
+///
+/// # Safety
+/// Must have ownership of the SRAM wait control setting.
+pub(crate) unsafe fn set_backup_waitstate_8() -> WaitstateControl {
+    without_interrupts(|| {
+        let mut waitstate_control = read();
+        waitstate_control.set_backup_waitstate(Cycles::_8);
+        unsafe { write(waitstate_control) };
+        waitstate_control
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{modify, read, write, Cycles, WaitstateControl};
+    use crate::{flash::Flash, sram::Sram};
+    use claims::assert_ok;
+    use gba_test::test;
+
+    /// Every writable bit not owned by this crate.
+    const OTHER_BITS: u16 = 0b0101_1111_1111_1100;
+    /// Alternating writable bits not owned by this crate.
+    const ALTERNATING_BITS: u16 = 0b0001_0101_0101_0100;
+
+    /// Runs `f` with `WAITCNT` set to `bits`, restoring its previous value afterward.
+    fn with_waitcnt(bits: u16, f: impl FnOnce()) {
+        let previous = read();
+        unsafe { write(WaitstateControl(bits)) };
+        f();
+        unsafe { write(previous) };
+    }
+
+    #[test]
+    fn set_backup_waitstate_4() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_backup_waitstate(Cycles::_4);
+
+        assert_eq!(waitstate.0, 0);
+    }
+
+    #[test]
+    fn set_backup_waitstate_3() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_backup_waitstate(Cycles::_3);
+
+        assert_eq!(waitstate.0, 1);
+    }
+
+    #[test]
+    fn set_backup_waitstate_2() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_backup_waitstate(Cycles::_2);
+
+        assert_eq!(waitstate.0, 2);
+    }
+
+    #[test]
+    fn set_backup_waitstate_8() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_backup_waitstate(Cycles::_8);
+
+        assert_eq!(waitstate.0, 3);
+    }
+
+    #[test]
+    fn set_backup_waitstate_with_preexisting_value() {
+        let mut waitstate = WaitstateControl(3);
+        waitstate.set_backup_waitstate(Cycles::_4);
+
+        assert_eq!(waitstate.0, 0);
+    }
+
+    #[test]
+    fn set_backup_waitstate_preserves_other_bits() {
+        let mut waitstate = WaitstateControl(0xfffc);
+        waitstate.set_backup_waitstate(Cycles::_8);
+
+        assert_eq!(waitstate.0, 0xffff);
+    }
+
+    #[test]
+    fn backup_waitstate() {
+        assert_eq!(WaitstateControl(0).backup_waitstate(), Cycles::_4);
+        assert_eq!(WaitstateControl(1).backup_waitstate(), Cycles::_3);
+        assert_eq!(WaitstateControl(2).backup_waitstate(), Cycles::_2);
+        assert_eq!(WaitstateControl(3).backup_waitstate(), Cycles::_8);
+        assert_eq!(WaitstateControl(0xfffe).backup_waitstate(), Cycles::_2);
+    }
+
+    #[test]
+    fn set_waitstate_0_first_access() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_waitstate_0_first_access(Cycles::_8);
+
+        assert_eq!(waitstate.0, 0b0000_0000_0000_1100);
+        assert_eq!(waitstate.waitstate_0_first_access(), Cycles::_8);
+    }
+
+    #[test]
+    fn set_waitstate_0_second_access_short() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_waitstate_0_second_access_short(true);
+
+        assert_eq!(waitstate.0, 0b0000_0000_0001_0000);
+        assert!(waitstate.waitstate_0_second_access_short());
+    }
+
+    #[test]
+    fn set_waitstate_1_first_access() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_waitstate_1_first_access(Cycles::_2);
+
+        assert_eq!(waitstate.0, 0b0000_0000_0100_0000);
+        assert_eq!(waitstate.waitstate_1_first_access(), Cycles::_2);
+    }
+
+    #[test]
+    fn set_waitstate_1_second_access_short() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_waitstate_1_second_access_short(true);
+
+        assert_eq!(waitstate.0, 0b0000_0000_1000_0000);
+        assert!(waitstate.waitstate_1_second_access_short());
+    }
+
+    #[test]
+    fn set_waitstate_2_first_access() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_waitstate_2_first_access(Cycles::_3);
+
+        assert_eq!(waitstate.0, 0b0000_0001_0000_0000);
+        assert_eq!(waitstate.waitstate_2_first_access(), Cycles::_3);
+    }
+
+    #[test]
+    fn set_waitstate_2_second_access_short() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_waitstate_2_second_access_short(true);
+
+        assert_eq!(waitstate.0, 0b0000_0100_0000_0000);
+        assert!(waitstate.waitstate_2_second_access_short());
+    }
+
+    #[test]
+    fn set_prefetch() {
+        let mut waitstate = WaitstateControl(0);
+        waitstate.set_prefetch(true);
+
+        assert_eq!(waitstate.0, 0b0100_0000_0000_0000);
+        assert!(waitstate.prefetch());
+    }
+
+    #[test]
+    fn clear_flags_preserves_other_bits() {
+        let mut waitstate = WaitstateControl(0xffff);
+        waitstate.set_waitstate_0_second_access_short(false);
+        waitstate.set_waitstate_1_second_access_short(false);
+        waitstate.set_waitstate_2_second_access_short(false);
+        waitstate.set_prefetch(false);
+
+        assert_eq!(waitstate.0, 0b1011_1011_0110_1111);
+    }
+
+    #[test]
+    fn set_first_access_preserves_other_bits() {
+        let mut waitstate = WaitstateControl(0xffff);
+        waitstate.set_waitstate_0_first_access(Cycles::_4);
+        waitstate.set_waitstate_1_first_access(Cycles::_4);
+        waitstate.set_waitstate_2_first_access(Cycles::_4);
+
+        assert_eq!(waitstate.0, 0b1111_1100_1001_0011);
+    }
+
+    #[test]
+    fn modify_preserves_other_bits() {
+        with_waitcnt(OTHER_BITS, || {
+            unsafe { modify(|waitstate| waitstate.set_backup_waitstate(Cycles::_2)) };
+
+            assert_eq!(read(), WaitstateControl(OTHER_BITS | 0b10));
+        });
+    }
+
+    #[test]
+    fn sram_new_modifies_only_owned_bits() {
+        for bits in [0, OTHER_BITS, ALTERNATING_BITS] {
+            with_waitcnt(bits, || {
+                let _sram = unsafe { Sram::new() };
+
+                assert_eq!(read(), WaitstateControl(bits | 0b11));
+            });
+        }
+    }
+
+    #[test]
+    fn sram_new_overwrites_previous_backup_waitstate() {
+        with_waitcnt(OTHER_BITS | 0b01, || {
+            let _sram = unsafe { Sram::new() };
+
+            assert_eq!(read(), WaitstateControl(OTHER_BITS | 0b11));
+        });
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(flash_64k),
+        ignore = "This test requires a Flash 64KiB chip. Ensure Flash 64KiB is configured and pass `--cfg flash_64k` to enable."
+    )]
+    fn flash_new_modifies_only_owned_bits() {
+        for bits in [0, OTHER_BITS, ALTERNATING_BITS] {
+            with_waitcnt(bits, || {
+                let _flash = assert_ok!(unsafe { Flash::new() });
+
+                assert_eq!(read(), WaitstateControl(bits | 0b11));
+            });
+        }
+    }
+
+    #[test]
+    fn bits_round_trip() {
+        assert_eq!(WaitstateControl::from_bits(0x4317).to_bits(), 0x4317);
+    }
+
+    #[cfg(feature = "gba")]
+    #[test]
+    fn from_gba() {
+        assert_eq!(
+            WaitstateControl::from(gba::interrupts::WaitstateControl(0x4317)),
+            WaitstateControl(0x4317)
+        );
+    }
+
+    #[cfg(feature = "gba")]
+    #[test]
+    fn into_gba() {
+        assert_eq!(
+            gba::interrupts::WaitstateControl::from(WaitstateControl(0x4317)).0,
+            0x4317
+        );
+    }
+}
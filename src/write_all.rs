@@ -0,0 +1,201 @@
+use embedded_io::Write;
+
+/// Writes all of `buf` to `writer`, without panicking.
+///
+/// This behaves the same as [`Write::write_all()`], except that a write returning `Ok(0)` is
+/// reported as `end_of_writer` rather than causing a panic. This keeps a panic path out of every
+/// function using it.
+pub(crate) fn write_all<W>(
+    writer: &mut W,
+    mut buf: &[u8],
+    end_of_writer: W::Error,
+) -> Result<(), W::Error>
+where
+    W: Write,
+{
+    while !buf.is_empty() {
+        match writer.write(buf)? {
+            0 => return Err(end_of_writer),
+            count => buf = buf.get(count..).unwrap_or(&[]),
+        }
+    }
+    Ok(())
+}
+
+/// Writes all of `buf` to `writer`, reporting how much was written if it does not fit.
+///
+/// If `writer` runs out of space, either by returning `Ok(0)` or by returning an error for which
+/// `is_end_of_writer` returns `true`, the error created by `truncated` from the number of bytes
+/// written and the length of `buf` is returned instead. Like [`write_all()`], this never panics.
+pub(crate) fn write_all_strict<W>(
+    writer: &mut W,
+    buf: &[u8],
+    is_end_of_writer: fn(&W::Error) -> bool,
+    truncated: fn(usize, usize) -> W::Error,
+) -> Result<(), W::Error>
+where
+    W: Write,
+{
+    let mut written = 0;
+    while let Some(remaining @ [_, ..]) = buf.get(written..) {
+        match writer.write(remaining) {
+            Ok(0) => return Err(truncated(written, buf.len())),
+            Ok(count) => written += count,
+            Err(error) if is_end_of_writer(&error) => return Err(truncated(written, buf.len())),
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_all, write_all_strict};
+    use claims::{assert_err_eq, assert_ok};
+    use embedded_io::{ErrorType, SliceWriteError, Write};
+    use gba_test::test;
+
+    /// A writer that accepts at most two bytes per write, returning `Ok(0)` once full.
+    struct ChunkedWriter {
+        data: [u8; 4],
+        len: usize,
+    }
+
+    impl ErrorType for ChunkedWriter {
+        type Error = SliceWriteError;
+    }
+
+    impl Write for ChunkedWriter {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            let count = buf.len().min(2).min(self.data.len() - self.len);
+            self.data[self.len..self.len + count].copy_from_slice(&buf[..count]);
+            self.len += count;
+            Ok(count)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writes_in_chunks() {
+        let mut writer = ChunkedWriter {
+            data: [0; 4],
+            len: 0,
+        };
+
+        assert_ok!(write_all(&mut writer, &[1, 2, 3, 4], SliceWriteError::Full));
+        assert_eq!(writer.data, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn zero_length_write_is_error() {
+        let mut writer = ChunkedWriter {
+            data: [0; 4],
+            len: 0,
+        };
+
+        assert_err_eq!(
+            write_all(&mut writer, &[1, 2, 3, 4, 5], SliceWriteError::Full),
+            SliceWriteError::Full
+        );
+        assert_eq!(writer.data, [1, 2, 3, 4]);
+    }
+
+    #[derive(Debug, Eq, PartialEq)]
+    enum StrictError {
+        Full,
+        Truncated { written: usize, requested: usize },
+    }
+
+    impl embedded_io::Error for StrictError {
+        fn kind(&self) -> embedded_io::ErrorKind {
+            embedded_io::ErrorKind::WriteZero
+        }
+    }
+
+    /// A writer that accepts up to four bytes, returning `StrictError::Full` once full.
+    struct FullWriter {
+        len: usize,
+    }
+
+    impl ErrorType for FullWriter {
+        type Error = StrictError;
+    }
+
+    impl Write for FullWriter {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            if self.len == 4 {
+                return Err(StrictError::Full);
+            }
+            let count = buf.len().min(4 - self.len);
+            self.len += count;
+            Ok(count)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    fn is_full(error: &StrictError) -> bool {
+        *error == StrictError::Full
+    }
+
+    fn truncated(written: usize, requested: usize) -> StrictError {
+        StrictError::Truncated { written, requested }
+    }
+
+    #[test]
+    fn strict_fits() {
+        let mut writer = FullWriter { len: 0 };
+
+        assert_ok!(write_all_strict(
+            &mut writer,
+            &[1, 2, 3, 4],
+            is_full,
+            truncated
+        ));
+        assert_eq!(writer.len, 4);
+    }
+
+    #[test]
+    fn strict_empty() {
+        let mut writer = FullWriter { len: 4 };
+
+        assert_ok!(write_all_strict(&mut writer, &[], is_full, truncated));
+    }
+
+    #[test]
+    fn strict_end_of_writer_is_truncated() {
+        let mut writer = FullWriter { len: 1 };
+
+        assert_err_eq!(
+            write_all_strict(&mut writer, &[1, 2, 3, 4, 5], is_full, truncated),
+            StrictError::Truncated {
+                written: 3,
+                requested: 5
+            }
+        );
+    }
+
+    #[test]
+    fn strict_zero_length_write_is_truncated() {
+        let mut writer = ChunkedWriter {
+            data: [0; 4],
+            len: 0,
+        };
+
+        assert_err_eq!(
+            write_all_strict(
+                &mut writer,
+                &[1, 2, 3, 4, 5],
+                |_| false,
+                |_, _| SliceWriteError::Full
+            ),
+            SliceWriteError::Full
+        );
+        assert_eq!(writer.data, [1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::time::Instant;
+use core::{
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
+
+/// Statistics about the writes performed by a single writer.
+///
+/// These are returned by the `stats()` method on each writer, allowing them to be displayed
+/// in-game. When the `log` feature is enabled, a summary of these statistics is also logged at
+/// the info level whenever a writer is flushed, and when a writer that has written since it was
+/// last flushed is dropped.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct WriteStats {
+    /// The offset of the start of the writer's range from the start of the device.
+    pub start: usize,
+    /// The length of the writer's range, in bytes.
+    pub len: usize,
+    /// The number of bytes successfully written.
+    pub bytes_written: usize,
+    /// The number of times writing a byte was retried after failing verification.
+    pub retries: u32,
+    /// The number of write or flush operations that returned an error.
+    pub failures: u32,
+    /// The time elapsed since the writer was created.
+    ///
+    /// This is only measured if a clock has been provided using [`set_clock()`]. Otherwise, it is
+    /// `None`.
+    ///
+    /// [`set_clock()`]: crate::set_clock()
+    pub elapsed: Option<Duration>,
+}
+
+impl WriteStats {
+    pub(crate) fn new(start: usize, len: usize) -> Self {
+        Self {
+            start,
+            len,
+            bytes_written: 0,
+            retries: 0,
+            failures: 0,
+            elapsed: None,
+        }
+    }
+
+    /// Returns whether every write and flush operation succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.failures == 0
+    }
+
+    /// Logs a one-line summary of the statistics for the named device.
+    #[cfg(feature = "log")]
+    fn log_summary(&self, device: &str) {
+        match self.elapsed {
+            Some(elapsed) => log::info!(
+                "{} write {}..{}: {} bytes written in {:?}, {} retries, {}",
+                device,
+                self.start,
+                self.start + self.len,
+                self.bytes_written,
+                elapsed,
+                self.retries,
+                if self.is_ok() { "ok" } else { "failed" },
+            ),
+            None => log::info!(
+                "{} write {}..{}: {} bytes written, {} retries, {}",
+                device,
+                self.start,
+                self.start + self.len,
+                self.bytes_written,
+                self.retries,
+                if self.is_ok() { "ok" } else { "failed" },
+            ),
+        }
+    }
+}
+
+/// The statistics of a writer, along with when it was created.
+///
+/// This dereferences to the [`WriteStats`] being counted. When the `log` feature is enabled, any
+/// change to them since they were last logged causes a summary to be logged when this is dropped,
+/// so a writer that is never flushed is still logged.
+#[derive(Debug)]
+pub(crate) struct WriteTracker {
+    stats: WriteStats,
+    started: Option<Instant>,
+    #[cfg(feature = "log")]
+    device: &'static str,
+    #[cfg(feature = "log")]
+    logged: bool,
+}
+
+impl WriteTracker {
+    /// Starts tracking a writer over `len` bytes at offset `start` of the named device.
+    #[cfg_attr(not(feature = "log"), allow(unused_variables))]
+    pub(crate) fn new(device: &'static str, start: usize, len: usize) -> Self {
+        Self {
+            stats: WriteStats::new(start, len),
+            started: Instant::now(),
+            #[cfg(feature = "log")]
+            device,
+            #[cfg(feature = "log")]
+            logged: true,
+        }
+    }
+
+    /// Returns the statistics, including the time elapsed so far.
+    pub(crate) fn snapshot(&self) -> WriteStats {
+        WriteStats {
+            elapsed: self.started.and_then(Instant::elapsed),
+            ..self.stats
+        }
+    }
+
+    /// Logs a one-line summary of the statistics.
+    #[cfg(feature = "log")]
+    pub(crate) fn log_summary(&mut self) {
+        self.snapshot().log_summary(self.device);
+        self.logged = true;
+    }
+}
+
+impl Deref for WriteTracker {
+    type Target = WriteStats;
+
+    fn deref(&self) -> &WriteStats {
+        &self.stats
+    }
+}
+
+impl DerefMut for WriteTracker {
+    fn deref_mut(&mut self) -> &mut WriteStats {
+        #[cfg(feature = "log")]
+        {
+            self.logged = false;
+        }
+        &mut self.stats
+    }
+}
+
+#[cfg(feature = "log")]
+impl Drop for WriteTracker {
+    fn drop(&mut self) {
+        if !self.logged {
+            self.log_summary();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WriteStats, WriteTracker};
+    use gba_test::test;
+
+    #[test]
+    fn new() {
+        assert_eq!(
+            WriteStats::new(42, 100),
+            WriteStats {
+                start: 42,
+                len: 100,
+                bytes_written: 0,
+                retries: 0,
+                failures: 0,
+                elapsed: None,
+            }
+        );
+    }
+
+    #[test]
+    fn is_ok() {
+        assert!(WriteStats::new(0, 1).is_ok());
+    }
+
+    #[test]
+    fn is_ok_failed() {
+        let mut stats = WriteStats::new(0, 1);
+        stats.failures = 1;
+
+        assert!(!stats.is_ok());
+    }
+
+    #[test]
+    fn tracker_counts() {
+        let mut tracker = WriteTracker::new("SRAM", 42, 100);
+        tracker.bytes_written += 10;
+        tracker.failures += 1;
+
+        let stats = tracker.snapshot();
+        assert_eq!(stats.start, 42);
+        assert_eq!(stats.bytes_written, 10);
+        assert_eq!(stats.failures, 1);
+    }
+}